@@ -21,9 +21,54 @@ pub const BET_SEED: &[u8] = b"bet";
 /// Seed for oracle PDA
 pub const ORACLE_SEED: &[u8] = b"oracle";
 
+/// Seed for an oracle's bond vault PDA
+pub const ORACLE_BOND_VAULT_SEED: &[u8] = b"oracle_bond_vault";
+
 /// Seed for license PDA
 pub const LICENSE_SEED: &[u8] = b"license";
 
+/// Seed for per-category config PDA
+pub const CATEGORY_CONFIG_SEED: &[u8] = b"category_config";
+
+/// Seed for a multi-collateral market's per-mint vault PDA
+pub const COLLATERAL_VAULT_SEED: &[u8] = b"collateral_vault";
+
+/// Seed for parlay PDA
+pub const PARLAY_SEED: &[u8] = b"parlay";
+
+/// Seed for the per-mint parlay vault PDA
+pub const PARLAY_VAULT_SEED: &[u8] = b"parlay_vault";
+
+/// Seed for the per-mint PDA tracking what open parlays are owed
+pub const PARLAY_BOOK_SEED: &[u8] = b"parlay_book";
+
+/// Seed for a market's leaderboard PDA
+pub const LEADERBOARD_SEED: &[u8] = b"leaderboard";
+
+/// Seed for a market's odds history PDA
+pub const ODDS_HISTORY_SEED: &[u8] = b"odds_history";
+
+/// Seed for the PDA indexing markets by their oracle event ID
+pub const EVENT_INDEX_SEED: &[u8] = b"event_index";
+
+/// Seed for a wallet's self-exclusion PDA
+pub const SELF_EXCLUSION_SEED: &[u8] = b"self_exclusion";
+
+/// Seed for a wallet's bettor profile PDA
+pub const BETTOR_PROFILE_SEED: &[u8] = b"bettor_profile";
+
+/// Seed for a pending bet reservation PDA
+pub const RESERVATION_SEED: &[u8] = b"reservation";
+
+/// Seed for the per-mint escrow vault holding reserved stakes
+pub const RESERVATION_VAULT_SEED: &[u8] = b"reservation_vault";
+
+/// Seed for a bettor's escrow of time-locked winnings
+pub const CLAIM_ESCROW_SEED: &[u8] = b"claim_escrow";
+
+/// Seed for the per-mint vault holding time-locked winnings
+pub const CLAIM_ESCROW_VAULT_SEED: &[u8] = b"claim_escrow_vault";
+
 /// Maximum allowed domains for a license
 pub const MAX_LICENSE_DOMAINS: usize = 5;
 
@@ -45,5 +90,56 @@ pub const DEFAULT_POOL_FEE_BPS: u16 = 500;
 /// Maximum total fee (10% = 1000 basis points)
 pub const MAX_TOTAL_FEE_BPS: u16 = 1000;
 
+/// Maximum number of protocol fee rebate tiers
+pub const MAX_REBATE_TIERS: usize = 5;
+
+/// Maximum number of pool-size fee taper tiers
+pub const MAX_FEE_TAPER_TIERS: usize = 5;
+
+/// Maximum number of betting phases on a market
+pub const MAX_BETTING_PHASES: usize = 4;
+
+/// Maximum number of allowed betting mints per category
+pub const MAX_CATEGORY_MINTS: usize = 8;
+
+/// Maximum number of collateral mints a multi-collateral market accepts
+pub const MAX_COLLATERAL_MINTS: usize = 4;
+
+/// Collateral price scale meaning one common unit per token unit
+pub const PRICE_SCALE_ONE: u64 = 1_000_000;
+
+/// Reserved bytes allocated on a market account for future use
+pub const MARKET_RESERVED_LEN: usize = 32;
+
+/// Maximum length of a bet's public memo
+pub const MAX_BET_MEMO_LEN: usize = 140;
+
+/// Prefix of the memo written alongside payouts on markets with payout memos
+pub const PAYOUT_MEMO_PREFIX: &str = "Fortuna payout market";
+
+/// Maximum number of markets indexed under one oracle event ID
+pub const MAX_EVENT_INDEX_MARKETS: usize = 8;
+
+/// Number of bettors tracked on a market leaderboard
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Number of odds snapshots kept in a market's history ring buffer
+pub const ODDS_HISTORY_LEN: usize = 32;
+
+/// Minimum number of legs in a parlay
+pub const MIN_PARLAY_LEGS: usize = 2;
+
+/// Maximum number of legs in a parlay
+pub const MAX_PARLAY_LEGS: usize = 4;
+
+/// Maximum share of a stake that loss protection can cover (50% = 5000 basis points)
+pub const MAX_LOSS_PROTECTION_BPS: u16 = 5000;
+
+/// Default time a bet reservation stays confirmable (5 minutes)
+pub const DEFAULT_RESERVATION_WINDOW_SECS: i64 = 300;
+
+/// Discriminator of randomness accounts written by the randomness program
+pub const RANDOMNESS_ACCOUNT_DISCRIMINATOR: [u8; 8] = [45, 94, 173, 50, 141, 108, 112, 51];
+
 /// Basis points denominator
 pub const BPS_DENOMINATOR: u16 = 10000;
@@ -139,4 +139,307 @@ pub enum FortunaError {
 
     #[msg("License already exists for this key")]
     LicenseAlreadyExists,
+
+    #[msg("Invalid rebate tier configuration")]
+    InvalidRebateTiers,
+
+    #[msg("Market has already been resolved")]
+    MarketAlreadyResolved,
+
+    #[msg("Market has been cancelled")]
+    MarketIsCancelled,
+
+    #[msg("Invalid loss protection configuration")]
+    InvalidLossProtection,
+
+    #[msg("Loss protection is not available")]
+    LossProtectionUnavailable,
+
+    #[msg("Bet has no loss protection")]
+    NoLossProtection,
+
+    #[msg("Market is paused")]
+    MarketPaused,
+
+    #[msg("Oracle must be enabled for at least one category")]
+    OracleHasNoCategories,
+
+    #[msg("Earliest bet on an outcome cannot be withdrawn in winner-take-all markets")]
+    LeadingBetLocked,
+
+    #[msg("Protocol fees are not configured")]
+    ProtocolFeesNotConfigured,
+
+    #[msg("Oracle must be deactivated before it can be closed")]
+    OracleStillActive,
+
+    #[msg("Instruction not available for this market's resolution mode")]
+    WrongResolutionMode,
+
+    #[msg("Resolution voting is not open")]
+    VotingNotOpen,
+
+    #[msg("Bet has already voted")]
+    AlreadyVoted,
+
+    #[msg("Resolution voting has not ended")]
+    VotingNotEnded,
+
+    #[msg("No resolution votes were cast")]
+    NoResolutionVotes,
+
+    #[msg("Market pool is not stranded")]
+    PoolNotStranded,
+
+    #[msg("Stranded pool has already been swept")]
+    StrandedPoolAlreadySwept,
+
+    #[msg("Token mint is not allowed for this category")]
+    TokenNotAllowedForCategory,
+
+    #[msg("Too many allowed mints for a category")]
+    TooManyCategoryMints,
+
+    #[msg("Randomness has already been requested")]
+    RandomnessAlreadyRequested,
+
+    #[msg("Randomness has not been requested")]
+    RandomnessNotRequested,
+
+    #[msg("Outcomes can't be reordered once bets have been placed")]
+    OutcomesLocked,
+
+    #[msg("Outcome order must be a permutation of the current indices")]
+    InvalidOutcomePermutation,
+
+    #[msg("Creator resolution is disabled by the protocol")]
+    CreatorResolutionDisabled,
+
+    #[msg("Instruction does not match the market's denomination")]
+    WrongMarketDenomination,
+
+    #[msg("Parlay needs between 2 and 4 legs on distinct markets")]
+    InvalidParlayLegs,
+
+    #[msg("Parlay leg accounts do not match")]
+    ParlayLegMismatch,
+
+    #[msg("Oracle already exists for this ID")]
+    OracleAlreadyExists,
+
+    #[msg("Claim delay for this market's category has not elapsed")]
+    ClaimDelayNotElapsed,
+
+    #[msg("Bet memo too long")]
+    MemoTooLong,
+
+    #[msg("Market is busy processing another instruction")]
+    MarketBusy,
+
+    #[msg("Invalid multi-collateral configuration")]
+    InvalidCollateralConfig,
+
+    #[msg("Collateral mint is not accepted by this market")]
+    CollateralNotAccepted,
+
+    #[msg("Result commitment must be set exactly for commit-reveal markets")]
+    InvalidResultCommitment,
+
+    #[msg("Revealed outcome and salt do not match the result commitment")]
+    CommitmentMismatch,
+
+    #[msg("Market reserved space is in use and cannot be released")]
+    ReservedSpaceInUse,
+
+    #[msg("Claims on this market must go to the claimer's associated token account")]
+    ClaimAccountNotAta,
+
+    #[msg("No protocol stats recompute is in progress")]
+    StatsRecomputeNotStarted,
+
+    #[msg("Markets must be supplied in strictly ascending market ID order")]
+    StatsMarketsOutOfOrder,
+
+    #[msg("Account is not a market of this protocol")]
+    InvalidStatsMarket,
+
+    #[msg("Fee mint must be the protocol's configured fee mint and differ from the betting mint")]
+    InvalidFeeMint,
+
+    #[msg("Fee-mint token accounts are required for this market")]
+    FeeTokenAccountRequired,
+
+    #[msg("Creator fee wallet can't be the treasury, the market or one of its vaults")]
+    InvalidCreatorFeeWallet,
+
+    #[msg("Bet would exceed the stake cap for this outcome")]
+    OutcomeCapReached,
+
+    #[msg("Market has no escrowed creator fees to release")]
+    NoEscrowedCreatorFees,
+
+    #[msg("Market has reached its maximum number of bets")]
+    MarketBetLimitReached,
+
+    #[msg("Payout multipliers must give every outcome a positive rate and only apply to fixed-multiplier SPL markets")]
+    InvalidPayoutMultipliers,
+
+    #[msg("Market liquidity cannot cover the committed multiplier payouts")]
+    InsufficientLiquidity,
+
+    #[msg("Market liquidity has already been withdrawn")]
+    LiquidityAlreadyWithdrawn,
+
+    #[msg("Operation does not apply to this market's payout mode")]
+    WrongPayoutMode,
+
+    #[msg("Claimed winnings do not cover the target market's bet amount")]
+    InsufficientWinnings,
+
+    #[msg("Outcome bounds must stay within the global range with min <= max")]
+    InvalidOutcomeBounds,
+
+    #[msg("Bet is still within the market's minimum hold period")]
+    BetStillLocked,
+
+    #[msg("Bet reservation has expired and can only be refunded")]
+    ReservationExpired,
+
+    #[msg("Only the bettor can cancel a reservation before it expires")]
+    ReservationNotExpired,
+
+    #[msg("Market still owes winnings, refunds, creator fees, protected stakes or open parlays")]
+    MarketHasUnclaimedFunds,
+
+    #[msg("Wallet has excluded itself from betting")]
+    SelfExcluded,
+
+    #[msg("A self-exclusion can only be extended, never shortened")]
+    SelfExclusionShortened,
+
+    #[msg("Oracle registration requires the bond accounts and the oracle authority's signature")]
+    OracleBondRequired,
+
+    #[msg("Oracle bond stays locked until the oracle is deactivated and the cooldown has passed")]
+    OracleBondLocked,
+
+    #[msg("Slash amount must be positive and no more than the oracle's bond")]
+    InvalidSlashAmount,
+
+    #[msg("Oracle still holds a bond")]
+    OracleBondOutstanding,
+
+    #[msg("Markets with an oracle event ID must pass its event index account")]
+    EventIndexRequired,
+
+    #[msg("Event index already lists the maximum number of markets")]
+    EventIndexFull,
+
+    #[msg("Oracle is already assigned the maximum number of open markets")]
+    OracleAssignmentLimitReached,
+
+    #[msg("Market missed its bettor threshold; creator fees go to the bonus pool")]
+    CreatorFeesForfeited,
+
+    #[msg("Market met its bettor threshold; creator fees stay with the creator")]
+    CreatorFeesNotForfeited,
+
+    #[msg("Forfeited creator fees must be redirected to the bonus pool before claiming")]
+    CreatorFeeRedirectPending,
+
+    #[msg("Invalid fee taper schedule")]
+    InvalidFeeTaperSchedule,
+
+    #[msg("Account holds no lamports above its rent-exempt minimum")]
+    NoExcessLamports,
+
+    #[msg("Claim expiry cannot be negative")]
+    InvalidClaimExpiry,
+
+    #[msg("Market's claim window has not expired")]
+    ClaimsNotExpired,
+
+    #[msg("Expired claim sweeps take this market's writable winning bets")]
+    InvalidExpiredClaim,
+
+    #[msg("Bettor profile must predate this market")]
+    WalletTooNew,
+
+    #[msg("Odds-tracking markets must pass their odds history account")]
+    OddsHistoryRequired,
+
+    #[msg("Winnings in this category settle into the claimer's escrow, which must be passed")]
+    ClaimEscrowRequired,
+
+    #[msg("No escrowed winnings to release")]
+    NoEscrowedWinnings,
+
+    #[msg("Escrowed winnings are still time-locked")]
+    ClaimEscrowLocked,
+
+    #[msg("Vault mint does not match the market's token mint")]
+    VaultMintMismatch,
+
+    #[msg("Resolution can only be requested after the betting deadline")]
+    ResolutionRequestTooEarly,
+
+    #[msg("Bettor has already requested resolution")]
+    ResolutionAlreadyRequested,
+
+    #[msg("Betting phases must end in order within the betting period and respect the fee cap")]
+    InvalidBettingPhases,
+
+    #[msg("Outcome is closed to new bets")]
+    OutcomeClosed,
+
+    #[msg("Memo program account is required for this market")]
+    MemoProgramRequired,
+
+    #[msg("Creator LP share needs a rate of at most 100% and a positive window")]
+    InvalidCreatorLpShare,
+
+    #[msg("Batch accounts must be writable Fortuna markets")]
+    InvalidBatchMarket,
+
+    #[msg("Market vaults hold less than the winnings still owed")]
+    VaultUndercollateralized,
+
+    #[msg("License has created the most markets allowed in the current window")]
+    LicenseRateLimited,
+
+    #[msg("License rate limit needs both a cap and a positive window, or neither")]
+    InvalidLicenseRateLimit,
+
+    #[msg("Recovery authority and dormancy period must be set together, to a key other than the authority")]
+    InvalidRecoveryAuthority,
+
+    #[msg("Protocol authority has not been dormant long enough to be claimed")]
+    AuthorityNotDormant,
+
+    #[msg("Creator token account is required to seed the bonus pool")]
+    CreatorTokenAccountRequired,
+
+    #[msg("Randomness account is not owned by the randomness program or has the wrong layout")]
+    InvalidRandomnessAccount,
+
+    #[msg("Randomness account has already revealed its value")]
+    RandomnessAlreadyRevealed,
+
+    #[msg("Randomness account has not revealed its value yet")]
+    RandomnessNotRevealed,
+
+    #[msg("Randomness account is not the one bound to this market's request")]
+    RandomnessAccountMismatch,
+
+    #[msg("Parlay reserve can't cover this parlay's payout alongside the open parlays")]
+    ParlayReserveInsufficient,
+
+    #[msg("Parlay has no losing leg, or a leg is still open")]
+    ParlayNotLost,
+
+    #[msg("Market has resolution votes; finalize it instead")]
+    ResolutionVotesCast,
+
+    #[msg("Collateral vaults or claim accounts don't match the market's accepted mints")]
+    CollateralAccountMismatch,
 }
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use crate::state::LicenseType;
+
+/// Emitted when a license is issued
+#[event]
+pub struct LicenseIssued {
+    pub license_key: [u8; 32],
+    pub holder: Pubkey,
+    pub license_type: LicenseType,
+    pub issued_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when a license is revoked
+#[event]
+pub struct LicenseRevoked {
+    pub license_key: [u8; 32],
+    pub holder: Pubkey,
+    pub license_type: LicenseType,
+    pub timestamp: i64,
+}
+
+/// Emitted when a license moves to a new holder
+#[event]
+pub struct LicenseTransferred {
+    pub license_key: [u8; 32],
+    pub previous_holder: Pubkey,
+    pub holder: Pubkey,
+    pub license_type: LicenseType,
+    pub timestamp: i64,
+}
+
+/// Emitted when a license's settings or active state change
+#[event]
+pub struct LicenseUpdated {
+    pub license_key: [u8; 32],
+    pub holder: Pubkey,
+    pub license_type: LicenseType,
+    pub is_active: bool,
+    pub max_markets: u32,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a winning bet is paid out
+#[event]
+pub struct WinningsClaimed {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub amount: u64,
+    pub winning_outcome: u8,
+    pub winning_outcome_label: String,
+    pub timestamp: i64,
+}
+
+/// Emitted when a bettor asks for a market past its betting deadline to be resolved
+#[event]
+pub struct ResolutionRequested {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub resolution_requests: u32,
+    pub resolution_deadline: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the recovery authority takes over a dormant protocol authority
+#[event]
+pub struct DormantAuthorityClaimed {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
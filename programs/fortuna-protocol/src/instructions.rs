@@ -1,15 +1,28 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Transfer};
+use anchor_lang::system_program;
+use anchor_spl::memo;
+use anchor_spl::token::{self, spl_token, CloseAccount, Transfer};
 
 use crate::state::*;
 use crate::errors::*;
 use crate::constants::*;
+use crate::events::*;
 use crate::{
-    InitializeProtocol, RegisterOracle, UpdateOracle, CreateMarket, AssignOracle,
-    PlaceBet, ResolveMarket, OracleResolveMarket, ClaimWinnings, CancelMarket,
-    ClaimRefund, WithdrawBet, UpdateProtocol,
+    InitializeProtocol, RegisterOracle, UpdateOracle, CloseOracle, CreateMarket, AssignOracle,
+    SlashOracle, WithdrawOracleBond, SetOracleBond, SetDefaultTokenMint, ReleaseOracleAssignment,
+    PlaceBet, ResolveMarket, OracleResolveMarket, ClaimWinnings, CancelMarket, ExpireMarket,
+    ClaimRefund, ClaimLossProtection, WithdrawBet, UpdateProtocol, SetMarketFeeExempt,
+    UpdateCreatorFeeWallet, ReorderOutcomes, DisableOutcome, SetMarketPaused, SetVisibilityFlags, CompactMarket, CastResolutionVote, FinalizeCommunityResolution,
+    SweepStrandedPool, SetCategoryAllowedMints, RequestRandomness, FulfillRandomness,
+    RevealResolution, RequestResolution,
     IssueLicense, RevokeLicense, TransferLicense, UpdateLicense,
-    ModifyLicenseWallets, ModifyLicenseDomains,
+    ModifyLicenseWallets, ModifyLicenseDomains, LicenseStatusView,
+    PlaceBetSol, ClaimWinningsSol, ClaimRefundSol, WithdrawBetSol,
+    PlaceCollateralBet, ClaimCollateral, CreateParlay, ClaimParlay, SettleLostParlay, FundParlayReserve,
+    ClaimCreatorFees, ClaimCreatorFeesSol, DepositMarketLiquidity, WithdrawMarketLiquidity,
+    ClaimAndBet, ReserveBet, ConfirmBet, CancelReservation, CloseMarket, SelfExclude,
+    RedirectCreatorFees, RedirectCreatorFeesSol, RecoverExcessLamports,
+    SweepExpiredClaims, CancelMarketsBatch, ClaimDormantAuthority, CreateBettorProfile, OpenClaimEscrow, ReleaseClaimEscrow,
 };
 
 /// Initialize the protocol with treasury and fee settings
@@ -29,13 +42,41 @@ pub fn initialize_protocol(
     protocol_state.protocol_fee_bps = protocol_fee_bps;
     protocol_state.creator_fee_bps = creator_fee_bps;
     protocol_state.pool_fee_bps = pool_fee_bps;
+    protocol_state.protocol_fee_bps_per_category = [0; 12];
     protocol_state.total_markets = 0;
     protocol_state.total_volume = 0;
     protocol_state.total_oracles = 0;
     protocol_state.total_licenses = 0;
     protocol_state.require_license = false;
+    protocol_state.rebate_tiers = vec![];
+    protocol_state.loss_protection_premium_bps = 0;
+    protocol_state.stranded_pool_policy = StrandedPoolPolicy::Void;
+    protocol_state.randomness_program = Pubkey::default();
+    protocol_state.creator_resolution_allowed = true;
+    protocol_state.min_bet_amount = 0;
+    protocol_state.max_bet_amount = 0;
+    protocol_state.claim_delay_per_category = [0; 12];
+    protocol_state.max_future_deadline_secs = 0;
+    protocol_state.fee_mint = Pubkey::default();
+    protocol_state.default_token_mint = Pubkey::default();
+    protocol_state.flat_protocol_fee = 0;
+    protocol_state.min_outcomes_per_category = [0; 12];
+    protocol_state.max_outcomes_per_category = [0; 12];
+    protocol_state.redirect_creator_fees_to_treasury = false;
+    protocol_state.reservation_window_secs = DEFAULT_RESERVATION_WINDOW_SECS;
+    protocol_state.oracle_bond_mint = Pubkey::default();
+    protocol_state.oracle_bond_amount = 0;
+    protocol_state.oracle_bond_cooldown_secs = 0;
+    protocol_state.max_markets_per_oracle = 0;
+    protocol_state.license_rate_limit = 0;
+    protocol_state.license_rate_window_secs = 0;
+    protocol_state.fee_taper_schedule = vec![];
+    protocol_state.escrow_claims = [false; 12];
+    protocol_state.stats_recompute = StatsRecompute::default();
+    protocol_state.recovery_authority = Pubkey::default();
+    protocol_state.authority_dormancy_secs = 0;
+    protocol_state.last_authority_activity = Clock::get()?.unix_timestamp;
     protocol_state.bump = ctx.bumps.protocol_state;
-    protocol_state.reserved = vec![];
 
     msg!("Protocol initialized with fees: pool={}bps, creator={}bps, protocol={}bps",
         pool_fee_bps, creator_fee_bps, protocol_fee_bps);
@@ -51,13 +92,18 @@ pub fn register_oracle(
     categories: [bool; 12],
     data_source: String,
 ) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
     require!(name.len() <= MAX_ORACLE_NAME_LEN, FortunaError::OracleNameTooLong);
     require!(data_source.len() <= MAX_DATA_SOURCE_LEN, FortunaError::DataSourceTooLong);
+    require!(categories.iter().any(|c| *c), FortunaError::OracleHasNoCategories);
 
     let clock = Clock::get()?;
     let oracle = &mut ctx.accounts.oracle;
     let protocol_state = &mut ctx.accounts.protocol_state;
 
+    // A freshly created oracle account has never been registered
+    require!(oracle.registered_at == 0, FortunaError::OracleAlreadyExists);
+
     oracle.oracle_id = oracle_id;
     oracle.authority = ctx.accounts.oracle_authority.key();
     oracle.name = name.clone();
@@ -67,8 +113,29 @@ pub fn register_oracle(
     oracle.markets_resolved = 0;
     oracle.registered_at = clock.unix_timestamp;
     oracle.last_resolution_at = 0;
+    oracle.bond_amount = protocol_state.oracle_bond_amount;
+    oracle.assigned_open_markets = 0;
     oracle.bump = ctx.bumps.oracle;
-    oracle.reserved = vec![];
+
+    // The oracle authority escrows its own bond so it has stake in honest resolutions
+    if protocol_state.oracle_bond_amount > 0 {
+        let (Some(bond_vault), Some(bond_source), Some(token_program)) = (
+            ctx.accounts.bond_vault.as_ref(),
+            ctx.accounts.oracle_bond_source.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        ) else {
+            return err!(FortunaError::OracleBondRequired);
+        };
+        require!(ctx.accounts.oracle_authority.is_signer, FortunaError::OracleBondRequired);
+
+        let cpi_accounts = Transfer {
+            from: bond_source.to_account_info(),
+            to: bond_vault.to_account_info(),
+            authority: ctx.accounts.oracle_authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, protocol_state.oracle_bond_amount)?;
+    }
 
     protocol_state.total_oracles = protocol_state.total_oracles.checked_add(1)
         .ok_or(FortunaError::Overflow)?;
@@ -78,6 +145,56 @@ pub fn register_oracle(
     Ok(())
 }
 
+/// Move part of an oracle's bond to the treasury after a bad resolution (admin only)
+pub fn slash_oracle(ctx: Context<SlashOracle>, amount: u64) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let oracle = &mut ctx.accounts.oracle;
+    require!(amount > 0 && amount <= oracle.bond_amount, FortunaError::InvalidSlashAmount);
+    oracle.bond_amount -= amount;
+
+    let seeds = &[PROTOCOL_SEED, &[ctx.accounts.protocol_state.bump]];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.bond_vault.to_account_info(),
+        to: ctx.accounts.treasury_token_account.to_account_info(),
+        authority: ctx.accounts.protocol_state.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)?;
+
+    msg!("Oracle {} slashed: {} ({} bond remaining)", oracle.oracle_id, amount, oracle.bond_amount);
+
+    Ok(())
+}
+
+/// Return a deactivated oracle's remaining bond once the slashing cooldown has passed
+pub fn withdraw_oracle_bond(ctx: Context<WithdrawOracleBond>) -> Result<()> {
+    let clock = Clock::get()?;
+    let oracle = &mut ctx.accounts.oracle;
+    require!(
+        oracle.bond_unlocked_at(clock.unix_timestamp, ctx.accounts.protocol_state.oracle_bond_cooldown_secs),
+        FortunaError::OracleBondLocked
+    );
+    let amount = oracle.bond_amount;
+    oracle.bond_amount = 0;
+
+    let seeds = &[PROTOCOL_SEED, &[ctx.accounts.protocol_state.bump]];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.bond_vault.to_account_info(),
+        to: ctx.accounts.oracle_token_account.to_account_info(),
+        authority: ctx.accounts.protocol_state.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)?;
+
+    msg!("Oracle {} bond withdrawn: {}", oracle.oracle_id, amount);
+
+    Ok(())
+}
+
 /// Update oracle settings
 pub fn update_oracle(
     ctx: Context<UpdateOracle>,
@@ -86,6 +203,7 @@ pub fn update_oracle(
     data_source: Option<String>,
     is_active: Option<bool>,
 ) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
     let oracle = &mut ctx.accounts.oracle;
 
     if let Some(new_name) = name {
@@ -94,6 +212,7 @@ pub fn update_oracle(
     }
 
     if let Some(new_categories) = categories {
+        require!(new_categories.iter().any(|c| *c), FortunaError::OracleHasNoCategories);
         oracle.categories = new_categories;
     }
 
@@ -111,6 +230,20 @@ pub fn update_oracle(
     Ok(())
 }
 
+/// Close a deactivated oracle, returning its rent to the protocol authority.
+/// Deactivation already blocks oracle resolution, so markets still pointing at
+/// the closed oracle remain resolvable by their creator.
+pub fn close_oracle(ctx: Context<CloseOracle>) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+
+    protocol_state.total_oracles = protocol_state.total_oracles.saturating_sub(1);
+
+    msg!("Oracle closed: {} (ID: {})", ctx.accounts.oracle.name, ctx.accounts.oracle.oracle_id);
+
+    Ok(())
+}
+
 /// Create a new prediction market with category
 pub fn create_market(
     ctx: Context<CreateMarket>,
@@ -123,13 +256,22 @@ pub fn create_market(
     betting_deadline: i64,
     outcomes: Vec<String>,
     oracle_event_id: String,
+    options: MarketOptions,
 ) -> Result<()> {
     let protocol_state = &ctx.accounts.protocol_state;
     let clock = Clock::get()?;
     let current_time = clock.unix_timestamp;
 
+    // House markets belong to the protocol authority and bypass licensing
+    if options.is_house_market {
+        require!(
+            ctx.accounts.creator.key() == protocol_state.authority,
+            FortunaError::Unauthorized
+        );
+    }
+
     // Check license if required
-    if protocol_state.require_license {
+    if protocol_state.require_license && !options.is_house_market {
         let license = ctx.accounts.license.as_mut()
             .ok_or(FortunaError::LicenseRequired)?;
 
@@ -146,6 +288,11 @@ pub fn create_market(
         // Validate license can create markets
         require!(license.can_create_market(), FortunaError::LicenseMarketLimitReached);
         require!(license.features.can_create_markets, FortunaError::FeatureNotEnabled);
+        license.record_rate_limited_creation(
+            current_time,
+            protocol_state.license_rate_limit,
+            protocol_state.license_rate_window_secs,
+        )?;
 
         // Update license usage
         license.markets_created = license.markets_created.checked_add(1)
@@ -156,31 +303,161 @@ pub fn create_market(
     // Validate inputs
     require!(title.len() <= MAX_TITLE_LEN, FortunaError::TitleTooLong);
     require!(description.len() <= MAX_DESCRIPTION_LEN, FortunaError::DescriptionTooLong);
-    require!(outcomes.len() >= 2, FortunaError::TooFewOutcomes);
-    require!(outcomes.len() <= MAX_OUTCOMES, FortunaError::TooManyOutcomes);
     require!(bet_amount > 0, FortunaError::InvalidBetAmount);
+    require!(protocol_state.is_bet_amount_allowed(bet_amount), FortunaError::InvalidBetAmount);
     require!(oracle_event_id.len() <= 64, FortunaError::OracleEventIdTooLong);
 
+    // Guard against running markets on an unconfigured (zero-fee) protocol
+    require!(protocol_state.total_fee_bps() > 0, FortunaError::ProtocolFeesNotConfigured);
+
     // Validate category
     let market_category = MarketCategory::from_u8(category)
         .ok_or(FortunaError::InvalidCategory)?;
 
+    // Outcome count must fall within the category's bounds (global bounds unless configured)
+    let (min_outcomes, max_outcomes) = protocol_state.outcome_bounds(market_category);
+    require!(outcomes.len() >= min_outcomes, FortunaError::TooFewOutcomes);
+    require!(outcomes.len() <= max_outcomes, FortunaError::TooManyOutcomes);
+
     require!(betting_deadline > current_time, FortunaError::InvalidDeadline);
+    require!(
+        protocol_state.is_deadline_within_horizon(betting_deadline, current_time),
+        FortunaError::InvalidDeadline
+    );
     require!(resolution_deadline >= betting_deadline, FortunaError::InvalidDeadline);
+    require!(options.min_hold_secs >= 0, FortunaError::InvalidDeadline);
 
     // Validate outcome labels
     for outcome in &outcomes {
         require!(outcome.len() <= MAX_OUTCOME_LEN, FortunaError::OutcomeLabelTooLong);
     }
 
+    // Native SOL markets record the wrapped SOL mint; their vaults stay empty
+    if options.native_sol {
+        require!(
+            ctx.accounts.token_mint.key() == spl_token::native_mint::ID,
+            FortunaError::WrongMarketDenomination
+        );
+    }
+
+    // Only commit-reveal markets carry a result commitment, and they must
+    require!(
+        (options.resolution_mode == ResolutionMode::CommitReveal)
+            == (options.result_commitment != [0u8; 32]),
+        FortunaError::InvalidResultCommitment
+    );
+
+    // Multi-collateral markets list every accepted mint with a nonzero, distinct price
+    if options.multi_collateral {
+        let mints = &options.collateral_mints;
+        require!(
+            !options.native_sol
+                && !mints.is_empty()
+                && mints.len() <= MAX_COLLATERAL_MINTS
+                && mints.iter().all(|c| c.price_scale > 0)
+                && mints.iter().enumerate().all(|(i, c)| mints[..i].iter().all(|o| o.mint != c.mint)),
+            FortunaError::InvalidCollateralConfig
+        );
+    } else {
+        require!(options.collateral_mints.is_empty(), FortunaError::InvalidCollateralConfig);
+    }
+
+    // Fee-token markets pay the protocol's flat fee in its configured fee mint
+    if options.fee_mint != Pubkey::default() {
+        require!(
+            options.fee_mint == protocol_state.fee_mint
+                && options.fee_mint != ctx.accounts.token_mint.key()
+                && !options.native_sol
+                && !options.multi_collateral,
+            FortunaError::InvalidFeeMint
+        );
+    }
+
+    // Fixed-multiplier markets set a positive rate for every outcome and are paid from one SPL vault
+    if options.payout_mode == PayoutMode::FixedMultiplier {
+        require!(
+            options.payout_multipliers.len() == outcomes.len()
+                && options.payout_multipliers.iter().all(|m| *m > 0)
+                && !options.native_sol
+                && !options.multi_collateral,
+            FortunaError::InvalidPayoutMultipliers
+        );
+    } else {
+        require!(options.payout_multipliers.is_empty(), FortunaError::InvalidPayoutMultipliers);
+    }
+
+    require!(options.claim_expiry_secs >= 0, FortunaError::InvalidClaimExpiry);
+    require!(
+        options.creator_lp_share_bps <= BPS_DENOMINATOR
+            && options.creator_lp_share_window_secs >= 0
+            && (options.creator_lp_share_bps == 0) == (options.creator_lp_share_window_secs == 0),
+        FortunaError::InvalidCreatorLpShare
+    );
+
+    // Phases must end in order within the betting period, each charging no more than the
+    // protocol allows; fee-token markets take no percentage fees to vary
+    let phases = &options.betting_phases;
+    if !phases.is_empty() {
+        let protocol_fee_bps = protocol_state.protocol_fee_bps_for(market_category);
+        require!(
+            phases.len() <= MAX_BETTING_PHASES
+                && options.fee_mint == Pubkey::default()
+                && phases[0].phase_end > current_time
+                && phases.windows(2).all(|w| w[0].phase_end < w[1].phase_end)
+                && phases.iter().all(|p| {
+                    p.phase_end <= betting_deadline
+                        && protocol_fee_bps as u32 + p.pool_fee_bps as u32 + p.creator_fee_bps as u32
+                            <= MAX_TOTAL_FEE_BPS as u32
+                }),
+            FortunaError::InvalidBettingPhases
+        );
+    }
+
+    // Wallet age and odds history are handled by place_bet and payout memos by claim_winnings,
+    // the entry points for single-mint SPL markets; seeded bonuses go to the SPL pool vault
+    require!(
+        !(options.require_established_wallet
+            || options.track_odds
+            || options.payout_memo
+            || options.seed_bonus > 0)
+            || (!options.native_sol && !options.multi_collateral),
+        FortunaError::WrongMarketDenomination
+    );
+
+    // Validate the betting mints against the category's allow-list, if configured
+    let category_config = &ctx.accounts.category_config;
+    if !category_config.data_is_empty() {
+        let config = CategoryConfig::try_deserialize(&mut &category_config.data.borrow()[..])?;
+        require!(
+            config.allows_mint(&ctx.accounts.token_mint.key())
+                && options.collateral_mints.iter().all(|c| config.allows_mint(&c.mint)),
+            FortunaError::TokenNotAllowedForCategory
+        );
+    }
+
+    // House markets route creator fees to the treasury by design
+    if !options.is_house_market {
+        require_clean_creator_fee_wallet(
+            &ctx.accounts.creator_fee_wallet.key(),
+            &protocol_state.treasury,
+            &ctx.accounts.market.key(),
+            &[ctx.accounts.market_vault.key(), ctx.accounts.pool_vault.key()],
+        )?;
+    }
+
     let market = &mut ctx.accounts.market;
 
     market.market_id = market_id;
     market.creator = ctx.accounts.creator.key();
-    market.creator_fee_wallet = ctx.accounts.creator_fee_wallet.key();
+    market.creator_fee_wallet = if options.is_house_market {
+        protocol_state.treasury
+    } else {
+        ctx.accounts.creator_fee_wallet.key()
+    };
     market.token_mint = ctx.accounts.token_mint.key();
     market.category = market_category;
     market.oracle = Pubkey::default(); // No oracle assigned initially
+    market.authorized_at_assignment = false;
     market.oracle_event_id = oracle_event_id;
     market.title = title.clone();
     market.description = description;
@@ -190,14 +467,103 @@ pub fn create_market(
     market.status = MarketStatus::Open;
     market.winning_outcome = 0;
     market.total_pool = 0;
-    market.bonus_pool = 0;
+    // Paid into the pool vault below, once the market is set up
+    market.bonus_pool = options.seed_bonus;
+    market.escrowed_creator_fees = 0;
     market.created_at = current_time;
     market.resolved_at = 0;
     market.resolved_by_oracle = false;
+    market.fee_exempt = false;
+    market.fee_waiver_count = options.fee_waiver_count;
+    market.two_sided_fees = options.two_sided_fees;
+    market.min_bettors_for_creator_fee = options.min_bettors_for_creator_fee;
+    market.claim_expiry_secs = options.claim_expiry_secs;
+    market.require_established_wallet = options.require_established_wallet;
+    market.track_odds = options.track_odds;
+    market.payout_memo = options.payout_memo;
+    market.creator_lp_share_bps = options.creator_lp_share_bps;
+    market.creator_lp_share_window_secs = options.creator_lp_share_window_secs;
+    market.betting_phases = options.betting_phases;
+    market.paused = false;
+    market.visibility_flags = options.visibility_flags;
+    market.payout_mode = options.payout_mode;
+    market.winning_bet = Pubkey::default();
+    market.resolution_mode = options.resolution_mode;
+    market.stranded_pool_policy = protocol_state.stranded_pool_policy;
+    market.stranded_swept = false;
+    market.max_per_outcome = options.max_per_outcome;
+    market.max_bets = options.max_bets;
+    market.bet_count = 0;
+    market.resolution_requests = 0;
+    market.min_hold_secs = options.min_hold_secs;
+    market.randomness_requested_at = 0;
+    market.randomness_account = Pubkey::default();
+    market.randomness_seed_slot = 0;
+    market.randomness = [0u8; 32];
+    market.result_commitment = options.result_commitment;
+    market.require_ata = options.require_ata;
+    market.allow_early_resolution = options.allow_early_resolution;
+    market.fee_mint = options.fee_mint;
+    market.is_house_market = options.is_house_market;
+    market.winning_bets_total = 0;
+    market.winning_bets_claimed = 0;
+    market.pool_paid = 0;
+    market.bonus_paid = 0;
+    market.native_sol = options.native_sol;
+    market.cancel_reason = CancelReason::None;
+    market.processing = false;
+    market.multi_collateral = options.multi_collateral;
+    market.collateral_mints = options.collateral_mints;
+    market.payout_multipliers = options.payout_multipliers;
+    market.liquidity = 0;
+    market.liquidity_withdrawn = false;
     market.vault_bump = ctx.bumps.market_vault;
     market.pool_vault_bump = ctx.bumps.pool_vault;
     market.bump = ctx.bumps.market;
-    market.reserved = vec![];
+
+    // Start the market's leaderboard empty
+    let leaderboard = &mut ctx.accounts.leaderboard;
+    leaderboard.market = ctx.accounts.market.key();
+    leaderboard.entries = vec![];
+    leaderboard.bump = ctx.bumps.leaderboard;
+
+    // List the market under its external event ID so keepers can find it without scanning
+    if !ctx.accounts.market.oracle_event_id.is_empty() {
+        let market_key = ctx.accounts.market.key();
+        let event_id_hash = EventIndex::event_id_hash(&ctx.accounts.market.oracle_event_id);
+        let event_index = ctx.accounts.event_index.as_mut()
+            .ok_or(FortunaError::EventIndexRequired)?;
+        require!(event_index.markets.len() < MAX_EVENT_INDEX_MARKETS, FortunaError::EventIndexFull);
+        event_index.event_id_hash = event_id_hash;
+        event_index.markets.push(market_key);
+        event_index.bump = ctx.bumps.event_index;
+    }
+
+    // Odds-tracking markets start with an empty history
+    if ctx.accounts.market.track_odds {
+        let market_key = ctx.accounts.market.key();
+        let odds_history = ctx.accounts.odds_history.as_mut()
+            .ok_or(FortunaError::OddsHistoryRequired)?;
+        odds_history.market = market_key;
+        odds_history.next_index = 0;
+        odds_history.snapshots = vec![];
+        odds_history.bump = ctx.bumps.odds_history;
+    }
+
+    // A seeded bonus is paid in up front and shared by the winners along with the pool fees
+    let seed_bonus = ctx.accounts.market.bonus_pool;
+    if seed_bonus > 0 {
+        let creator_token_account = ctx.accounts.creator_token_account.as_ref()
+            .ok_or(FortunaError::CreatorTokenAccountRequired)?;
+        let cpi_accounts = Transfer {
+            from: creator_token_account.to_account_info(),
+            to: ctx.accounts.pool_vault.to_account_info(),
+            authority: ctx.accounts.creator.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, seed_bonus)?;
+    }
+    let market = &mut ctx.accounts.market;
 
     // Initialize outcomes
     market.outcomes = outcomes
@@ -206,6 +572,10 @@ pub fn create_market(
             label: label.clone(),
             total_amount: 0,
             bettor_count: 0,
+            protected_amount: 0,
+            first_bet: Pubkey::default(),
+            vote_weight: 0,
+            is_open: true,
         })
         .collect();
 
@@ -217,27 +587,201 @@ pub fn create_market(
 
 /// Assign an oracle to a market for automated resolution
 pub fn assign_oracle(ctx: Context<AssignOracle>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
     let market = &mut ctx.accounts.market;
-    let oracle = &ctx.accounts.oracle;
+    let oracle = &mut ctx.accounts.oracle;
+    let max_markets = ctx.accounts.protocol_state.max_markets_per_oracle;
 
     // Verify oracle can resolve this category
     require!(
         oracle.can_resolve_category(market.category),
         FortunaError::OracleNotAuthorizedForCategory
     );
+    require!(
+        max_markets == 0 || oracle.assigned_open_markets < max_markets,
+        FortunaError::OracleAssignmentLimitReached
+    );
 
+    oracle.assigned_open_markets = oracle.assigned_open_markets.checked_add(1)
+        .ok_or(FortunaError::Overflow)?;
     market.oracle = oracle.key();
+    market.authorized_at_assignment = true;
 
     msg!("Oracle {} assigned to market {}", oracle.name, market.title);
 
     Ok(())
 }
 
+/// Free an oracle's assignment slot for a market that closed without an oracle resolution
+/// (creator-resolved or cancelled). Anyone may call this; it clears the market's oracle.
+pub fn release_oracle_assignment(ctx: Context<ReleaseOracleAssignment>) -> Result<()> {
+    require_settled(&ctx.accounts.market)?;
+    let oracle = &mut ctx.accounts.oracle;
+    oracle.assigned_open_markets = oracle.assigned_open_markets.saturating_sub(1);
+    ctx.accounts.market.oracle = Pubkey::default();
+    ctx.accounts.market.authorized_at_assignment = false;
+
+    msg!("Oracle {} released from market {}", oracle.name, ctx.accounts.market.title);
+
+    Ok(())
+}
+
+/// Point an open market's creator fees at a corrected wallet (creator only)
+pub fn update_creator_fee_wallet(ctx: Context<UpdateCreatorFeeWallet>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market_key = ctx.accounts.market.key();
+    let market = &mut ctx.accounts.market;
+    if !market.is_house_market {
+        let vault = Pubkey::create_program_address(
+            &[MARKET_VAULT_SEED, market_key.as_ref(), &[market.vault_bump]],
+            &crate::ID,
+        ).map_err(|_| FortunaError::InvalidCreatorFeeWallet)?;
+        let pool_vault = Pubkey::create_program_address(
+            &[POOL_VAULT_SEED, market_key.as_ref(), &[market.pool_vault_bump]],
+            &crate::ID,
+        ).map_err(|_| FortunaError::InvalidCreatorFeeWallet)?;
+        require_clean_creator_fee_wallet(
+            &ctx.accounts.new_creator_fee_wallet.key(),
+            &ctx.accounts.protocol_state.treasury,
+            &market_key,
+            &[vault, pool_vault],
+        )?;
+    }
+    let old_wallet = market.creator_fee_wallet;
+    market.creator_fee_wallet = ctx.accounts.new_creator_fee_wallet.key();
+    msg!("Creator fee wallet updated from {} to {}", old_wallet, market.creator_fee_wallet);
+    Ok(())
+}
+
+/// Ensure creator fees can't be routed to the treasury, the market itself or its vaults
+fn require_clean_creator_fee_wallet(
+    wallet: &Pubkey,
+    treasury: &Pubkey,
+    market: &Pubkey,
+    vaults: &[Pubkey],
+) -> Result<()> {
+    require!(
+        wallet != treasury && wallet != market && !vaults.contains(wallet),
+        FortunaError::InvalidCreatorFeeWallet
+    );
+    Ok(())
+}
+
+/// Check a market is in the expected lifecycle state. Every status-sensitive instruction goes
+/// through here, so a market in the wrong state fails the same way whichever entry point is used.
+fn require_status(market: &Market, expected: MarketStatus) -> Result<()> {
+    if market.status == expected {
+        return Ok(());
+    }
+    match (expected, market.status) {
+        (MarketStatus::Open, MarketStatus::Resolved) => err!(FortunaError::MarketAlreadyResolved),
+        (MarketStatus::Open, _) => err!(FortunaError::MarketIsCancelled),
+        (MarketStatus::Resolved, _) => err!(FortunaError::MarketNotResolved),
+        (MarketStatus::Cancelled, _) => err!(FortunaError::MarketNotCancelled),
+    }
+}
+
+/// Check a market has settled, either resolved or cancelled
+fn require_settled(market: &Market) -> Result<()> {
+    require!(market.status != MarketStatus::Open, FortunaError::MarketNotResolved);
+    Ok(())
+}
+
+/// Reject bets from a wallet whose self-exclusion PDA (if it exists) is still in force
+fn require_not_self_excluded(self_exclusion: &AccountInfo, now: i64) -> Result<()> {
+    if self_exclusion.data_is_empty() {
+        return Ok(());
+    }
+    let exclusion: SelfExclusion = load_program_account(self_exclusion, FortunaError::SelfExcluded)?;
+    require!(!exclusion.is_excluded_at(now), FortunaError::SelfExcluded);
+    Ok(())
+}
+
+/// Bets refer to outcomes by index, so every outcome mutation must run before any bet is placed
+fn require_no_bets(market: &Market) -> Result<()> {
+    require!(market.total_bettors() == 0, FortunaError::OutcomesLocked);
+    Ok(())
+}
+
+/// Reorder a market's outcomes before any bets are placed (creator only).
+/// `new_order[i]` is the current index of the outcome that moves to position `i`.
+pub fn reorder_outcomes(ctx: Context<ReorderOutcomes>, new_order: Vec<u8>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
+    require_no_bets(market)?;
+    let outcome_count = market.outcomes.len();
+
+    // Validate the order is a bijection over the current indices
+    require!(new_order.len() == outcome_count, FortunaError::InvalidOutcomePermutation);
+    let mut seen = vec![false; outcome_count];
+    for &index in &new_order {
+        let index = index as usize;
+        require!(index < outcome_count && !seen[index], FortunaError::InvalidOutcomePermutation);
+        seen[index] = true;
+    }
+
+    market.outcomes = new_order
+        .iter()
+        .map(|&index| market.outcomes[index as usize].clone())
+        .collect();
+
+    msg!("Outcomes reordered for market {}", market.market_id);
+
+    Ok(())
+}
+
+/// Pause or unpause betting and resolution on a market (creator or admin)
+pub fn set_market_paused(ctx: Context<SetMarketPaused>, paused: bool) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
+    market.paused = paused;
+    msg!("Market {} paused: {}", market.market_id, paused);
+    Ok(())
+}
+
+/// Retag the front-end display channels of an open market (creator only)
+pub fn set_visibility_flags(ctx: Context<SetVisibilityFlags>, visibility_flags: u32) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
+    market.visibility_flags = visibility_flags;
+    msg!("Market {} visibility flags set to: {:#x}", market.market_id, visibility_flags);
+    Ok(())
+}
+
+/// Stop new bets on an outcome that can no longer happen (creator only). The outcome keeps its
+/// index and existing bets on it stay in place until the market settles.
+pub fn disable_outcome(ctx: Context<DisableOutcome>, outcome_index: u8) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
+    let outcome = market.outcomes
+        .get_mut(outcome_index as usize)
+        .ok_or(FortunaError::InvalidOutcome)?;
+    require!(outcome.is_open, FortunaError::OutcomeClosed);
+    outcome.is_open = false;
+
+    msg!("Outcome {} ({}) closed to new bets", outcome_index, outcome.label);
+
+    Ok(())
+}
+
+/// Release a market's unused reserved space and refund its rent to the creator
+pub fn compact_market(ctx: Context<CompactMarket>) -> Result<()> {
+    let market = &ctx.accounts.market;
+    msg!("Market {} compacted to {} bytes", market.market_id, Market::compact_space());
+    Ok(())
+}
+
 /// Place a bet on a specific outcome
 pub fn place_bet(
     ctx: Context<PlaceBet>,
     outcome_index: u8,
+    loss_protection_bps: u16,
+    memo: Option<String>,
 ) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let memo = memo.unwrap_or_default();
+    require!(memo.len() <= MAX_BET_MEMO_LEN, FortunaError::MemoTooLong);
+
     let market = &mut ctx.accounts.market;
     let protocol_state = &ctx.accounts.protocol_state;
 
@@ -246,6 +790,7 @@ pub fn place_bet(
         (outcome_index as usize) < market.outcomes.len(),
         FortunaError::InvalidOutcome
     );
+    require!(market.outcomes[outcome_index as usize].is_open, FortunaError::OutcomeClosed);
 
     // Check betting deadline
     let clock = Clock::get()?;
@@ -253,54 +798,123 @@ pub fn place_bet(
         !market.is_betting_closed(clock.unix_timestamp),
         FortunaError::BettingDeadlinePassed
     );
+    require_not_self_excluded(&ctx.accounts.self_exclusion, clock.unix_timestamp)?;
+    // A profile that already existed keeps its first-seen time; only a freshly allocated
+    // one (never stamped) is filled in, so the inline init can't reset a wallet's age
+    if let Some(profile) = ctx.accounts.bettor_profile.as_mut() {
+        if profile.created_at == 0 {
+            profile.wallet = ctx.accounts.bettor.key();
+            profile.created_at = clock.unix_timestamp;
+            profile.bump = ctx.bumps.bettor_profile;
+            msg!("Bettor profile created for {}", profile.wallet);
+        }
+    }
+    if market.require_established_wallet {
+        let profile = ctx.accounts.bettor_profile.as_ref().ok_or(FortunaError::WalletTooNew)?;
+        require!(profile.created_at < market.created_at, FortunaError::WalletTooNew);
+    }
 
     let bet_amount = market.bet_amount;
+    let bet_key = ctx.accounts.bet.key();
 
-    // Calculate fees
-    let (pool_fee, creator_fee, protocol_fee, net_amount) =
-        protocol_state.calculate_fees(bet_amount);
+    // Calculate fees; fee-token markets put the whole bet in the pool and pay a flat fee instead
+    let (pool_fee, creator_fee, protocol_fee, net_amount) = if market.has_fee_mint() {
+        (0, 0, 0, bet_amount)
+    } else {
+        protocol_state.calculate_market_fees(market, bet_amount, clock.unix_timestamp)
+    };
+    require!(market.outcome_has_room(outcome_index, net_amount), FortunaError::OutcomeCapReached);
+    require!(market.has_bet_capacity(), FortunaError::MarketBetLimitReached);
 
-    // Transfer bet amount to market vault
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.bettor_token_account.to_account_info(),
-        to: ctx.accounts.market_vault.to_account_info(),
-        authority: ctx.accounts.bettor.to_account_info(),
+    // Optional loss protection: cover part of the stake for a premium paid into the bonus pool
+    require!(
+        loss_protection_bps <= MAX_LOSS_PROTECTION_BPS,
+        FortunaError::InvalidLossProtection
+    );
+    let (protected_amount, premium) = if loss_protection_bps > 0 {
+        require!(
+            protocol_state.loss_protection_premium_bps > 0,
+            FortunaError::LossProtectionUnavailable
+        );
+        let protected_amount = (net_amount as u128)
+            .checked_mul(loss_protection_bps as u128)
+            .ok_or(FortunaError::Overflow)?
+            .checked_div(BPS_DENOMINATOR as u128)
+            .ok_or(FortunaError::Overflow)? as u64;
+        let premium = (protected_amount as u128)
+            .checked_mul(protocol_state.loss_protection_premium_bps as u128)
+            .ok_or(FortunaError::Overflow)?
+            .checked_div(BPS_DENOMINATOR as u128)
+            .ok_or(FortunaError::Overflow)? as u64;
+        (protected_amount, premium)
+    } else {
+        (0, 0)
     };
+
+    // Zero-amount legs (small bets, zero-bps fees, waived fees) are skipped rather than
+    // spending a CPI on an empty transfer
     let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program.clone(), cpi_accounts);
-    token::transfer(cpi_ctx, net_amount)?;
+
+    // Transfer bet amount and the escrowed creator fee to market vault
+    if net_amount + creator_fee > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.bettor_token_account.to_account_info(),
+            to: ctx.accounts.market_vault.to_account_info(),
+            authority: ctx.accounts.bettor.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program.clone(), cpi_accounts);
+        token::transfer(cpi_ctx, net_amount + creator_fee)?;
+    }
 
     // Transfer pool fee to pool vault
-    let cpi_accounts_pool = Transfer {
-        from: ctx.accounts.bettor_token_account.to_account_info(),
-        to: ctx.accounts.pool_vault.to_account_info(),
-        authority: ctx.accounts.bettor.to_account_info(),
-    };
-    let cpi_ctx_pool = CpiContext::new(cpi_program.clone(), cpi_accounts_pool);
-    token::transfer(cpi_ctx_pool, pool_fee)?;
+    if pool_fee + premium > 0 {
+        let cpi_accounts_pool = Transfer {
+            from: ctx.accounts.bettor_token_account.to_account_info(),
+            to: ctx.accounts.pool_vault.to_account_info(),
+            authority: ctx.accounts.bettor.to_account_info(),
+        };
+        let cpi_ctx_pool = CpiContext::new(cpi_program.clone(), cpi_accounts_pool);
+        token::transfer(cpi_ctx_pool, pool_fee + premium)?;
+    }
 
     // Transfer protocol fee to treasury
-    let cpi_accounts_treasury = Transfer {
-        from: ctx.accounts.bettor_token_account.to_account_info(),
-        to: ctx.accounts.treasury_token_account.to_account_info(),
-        authority: ctx.accounts.bettor.to_account_info(),
-    };
-    let cpi_ctx_treasury = CpiContext::new(cpi_program.clone(), cpi_accounts_treasury);
-    token::transfer(cpi_ctx_treasury, protocol_fee)?;
+    if protocol_fee > 0 {
+        let cpi_accounts_treasury = Transfer {
+            from: ctx.accounts.bettor_token_account.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            authority: ctx.accounts.bettor.to_account_info(),
+        };
+        let cpi_ctx_treasury = CpiContext::new(cpi_program.clone(), cpi_accounts_treasury);
+        token::transfer(cpi_ctx_treasury, protocol_fee)?;
+    }
 
-    // Transfer creator fee
-    let cpi_accounts_creator = Transfer {
-        from: ctx.accounts.bettor_token_account.to_account_info(),
-        to: ctx.accounts.creator_token_account.to_account_info(),
-        authority: ctx.accounts.bettor.to_account_info(),
-    };
-    let cpi_ctx_creator = CpiContext::new(cpi_program, cpi_accounts_creator);
-    token::transfer(cpi_ctx_creator, creator_fee)?;
+    // Transfer the flat protocol fee in the fee mint
+    if market.has_fee_mint() {
+        let (Some(bettor_fee_account), Some(treasury_fee_account)) = (
+            ctx.accounts.bettor_fee_token_account.as_ref(),
+            ctx.accounts.treasury_fee_token_account.as_ref(),
+        ) else {
+            return err!(FortunaError::FeeTokenAccountRequired);
+        };
+        let flat_fee = if market.fees_waived() { 0 } else { protocol_state.flat_protocol_fee };
+        if flat_fee > 0 {
+            let cpi_accounts_fee = Transfer {
+                from: bettor_fee_account.to_account_info(),
+                to: treasury_fee_account.to_account_info(),
+                authority: ctx.accounts.bettor.to_account_info(),
+            };
+            token::transfer(CpiContext::new(cpi_program.clone(), cpi_accounts_fee), flat_fee)?;
+        }
+    }
 
     // Update market state
     market.total_pool = market.total_pool.checked_add(net_amount)
         .ok_or(FortunaError::Overflow)?;
-    market.bonus_pool = market.bonus_pool.checked_add(pool_fee)
+    market.bonus_pool = market.bonus_pool.checked_add(pool_fee + premium)
+        .ok_or(FortunaError::Overflow)?;
+    market.escrowed_creator_fees = market.escrowed_creator_fees.checked_add(creator_fee)
+        .ok_or(FortunaError::Overflow)?;
+    market.bet_count = market.bet_count.checked_add(1)
         .ok_or(FortunaError::Overflow)?;
 
     // Update outcome
@@ -309,6 +923,12 @@ pub fn place_bet(
         .ok_or(FortunaError::Overflow)?;
     outcome.bettor_count = outcome.bettor_count.checked_add(1)
         .ok_or(FortunaError::Overflow)?;
+    outcome.protected_amount = outcome.protected_amount.checked_add(protected_amount)
+        .ok_or(FortunaError::Overflow)?;
+    // Ties within a slot resolve in processing order, which is deterministic
+    if outcome.first_bet == Pubkey::default() {
+        outcome.first_bet = bet_key;
+    }
 
     // Create bet record
     let bet = &mut ctx.accounts.bet;
@@ -317,14 +937,30 @@ pub fn place_bet(
     bet.outcome_index = outcome_index;
     bet.original_amount = bet_amount;
     bet.pool_amount = net_amount;
-    bet.claimed = false;
+    bet.creator_fee = creator_fee;
+    bet.bet_state = BetState::Active;
+    bet.loss_protection_bps = loss_protection_bps;
+    bet.protected_amount = protected_amount;
+    bet.voted = false;
+    bet.resolution_requested = false;
+    bet.collateral_mint = Pubkey::default();
+    bet.collateral_amount = 0;
+    bet.memo = memo;
     bet.placed_at = clock.unix_timestamp;
     bet.bump = ctx.bumps.bet;
-    bet.reserved = vec![];
+
+    ctx.accounts.leaderboard.record(bet.bettor, bet.pool_amount);
+    if market.track_odds {
+        let odds_history = ctx.accounts.odds_history.as_mut()
+            .ok_or(FortunaError::OddsHistoryRequired)?;
+        odds_history.record(market.odds_snapshot(clock.unix_timestamp));
+    }
 
     msg!("Bet placed: {} on outcome {} (index {})",
         bet_amount, market.outcomes[outcome_index as usize].label, outcome_index);
 
+    InstructionResult::for_position(market, bet, 0).set()?;
+
     Ok(())
 }
 
@@ -333,6 +969,7 @@ pub fn resolve_market(
     ctx: Context<ResolveMarket>,
     winning_outcome: u8,
 ) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
     let market = &mut ctx.accounts.market;
 
     // Validate winning outcome
@@ -341,18 +978,16 @@ pub fn resolve_market(
         FortunaError::InvalidOutcome
     );
 
-    // Check if betting deadline has passed
+    // Check if betting deadline has passed, unless the market allows early resolution
     let clock = Clock::get()?;
     require!(
-        market.is_betting_closed(clock.unix_timestamp),
+        market.can_resolve_at(clock.unix_timestamp),
         FortunaError::CannotResolveBeforeBettingDeadline
     );
 
     // Update market state
-    market.status = MarketStatus::Resolved;
-    market.winning_outcome = winning_outcome;
-    market.resolved_at = clock.unix_timestamp;
-    market.resolved_by_oracle = false;
+    market.close_betting(clock.unix_timestamp);
+    market.resolve(winning_outcome, clock.unix_timestamp, false)?;
 
     msg!("Market resolved by creator: winning outcome = {} ({})",
         winning_outcome, market.outcomes[winning_outcome as usize].label);
@@ -365,6 +1000,7 @@ pub fn oracle_resolve_market(
     ctx: Context<OracleResolveMarket>,
     winning_outcome: u8,
 ) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
     let market = &mut ctx.accounts.market;
     let oracle = &mut ctx.accounts.oracle;
 
@@ -374,28 +1010,28 @@ pub fn oracle_resolve_market(
         FortunaError::InvalidOutcome
     );
 
-    // Verify oracle can resolve this category
+    // Verify oracle can resolve this category; an oracle that could when it was assigned
+    // keeps that right, so later category changes don't strand the market
     require!(
-        oracle.can_resolve_category(market.category),
+        market.authorized_at_assignment || oracle.can_resolve_category(market.category),
         FortunaError::OracleNotAuthorizedForCategory
     );
 
-    // Check if betting deadline has passed
+    // Check if betting deadline has passed, unless the market allows early resolution
     let clock = Clock::get()?;
     require!(
-        market.is_betting_closed(clock.unix_timestamp),
+        market.can_resolve_at(clock.unix_timestamp),
         FortunaError::CannotResolveBeforeBettingDeadline
     );
 
     // Update market state
-    market.status = MarketStatus::Resolved;
-    market.winning_outcome = winning_outcome;
-    market.resolved_at = clock.unix_timestamp;
-    market.resolved_by_oracle = true;
+    market.close_betting(clock.unix_timestamp);
+    market.resolve(winning_outcome, clock.unix_timestamp, true)?;
 
     // Update oracle stats
     oracle.markets_resolved = oracle.markets_resolved.checked_add(1)
         .ok_or(FortunaError::Overflow)?;
+    oracle.assigned_open_markets = oracle.assigned_open_markets.saturating_sub(1);
     oracle.last_resolution_at = clock.unix_timestamp;
 
     msg!("Market resolved by oracle {}: winning outcome = {} ({})",
@@ -404,74 +1040,572 @@ pub fn oracle_resolve_market(
     Ok(())
 }
 
-/// Claim winnings after market resolution
-pub fn claim_winnings(ctx: Context<ClaimWinnings>) -> Result<()> {
-    let market = &ctx.accounts.market;
+/// Cast a stake-weighted vote on the outcome of a community-resolved market
+pub fn cast_resolution_vote(
+    ctx: Context<CastResolutionVote>,
+    outcome_index: u8,
+) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
     let bet = &mut ctx.accounts.bet;
 
-    // Check if bet won
     require!(
-        bet.outcome_index == market.winning_outcome,
-        FortunaError::LostBet
+        (outcome_index as usize) < market.outcomes.len(),
+        FortunaError::InvalidOutcome
     );
 
-    // Calculate payout
-    let payout = market.calculate_payout(bet);
-    require!(payout > 0, FortunaError::LostBet);
+    // Voting runs between the betting and resolution deadlines
+    let clock = Clock::get()?;
+    require!(
+        market.is_betting_closed(clock.unix_timestamp)
+            && !market.is_past_resolution_deadline(clock.unix_timestamp),
+        FortunaError::VotingNotOpen
+    );
 
-    // Transfer winnings from market vault to claimer
-    let market_id_bytes = market.market_id.to_le_bytes();
-    let seeds = &[
-        MARKET_SEED,
-        market_id_bytes.as_ref(),
-        &[market.bump],
-    ];
-    let signer = &[&seeds[..]];
+    let outcome = &mut market.outcomes[outcome_index as usize];
+    outcome.vote_weight = outcome.vote_weight.checked_add(bet.pool_amount)
+        .ok_or(FortunaError::Overflow)?;
 
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.market_vault.to_account_info(),
-        to: ctx.accounts.claimer_token_account.to_account_info(),
-        authority: ctx.accounts.market.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-    token::transfer(cpi_ctx, payout)?;
+    bet.voted = true;
+
+    msg!("Resolution vote cast: outcome {} with weight {}", outcome_index, bet.pool_amount);
+
+    Ok(())
+}
+
+/// Signal that a market is due for resolution; counts each bettor once and resolves nothing
+pub fn request_resolution(ctx: Context<RequestResolution>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
+    let bet = &mut ctx.accounts.bet;
+
+    let clock = Clock::get()?;
+    require!(
+        market.is_betting_closed(clock.unix_timestamp),
+        FortunaError::ResolutionRequestTooEarly
+    );
 
-    // Mark bet as claimed
-    bet.claimed = true;
+    market.resolution_requests = market.resolution_requests.checked_add(1)
+        .ok_or(FortunaError::Overflow)?;
+    bet.resolution_requested = true;
+
+    emit!(ResolutionRequested {
+        market: market.key(),
+        bettor: bet.bettor,
+        resolution_requests: market.resolution_requests,
+        resolution_deadline: market.resolution_deadline,
+        timestamp: clock.unix_timestamp,
+    });
 
-    msg!("Winnings claimed: {} tokens", payout);
+    msg!("Resolution requested ({} requests)", market.resolution_requests);
 
     Ok(())
 }
 
-/// Cancel a market (only before any bets or by admin)
-pub fn cancel_market(ctx: Context<CancelMarket>) -> Result<()> {
+/// Resolve a community-voted market to the outcome with the most vote weight
+pub fn finalize_community_resolution(ctx: Context<FinalizeCommunityResolution>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
     let market = &mut ctx.accounts.market;
 
-    // Update market status
-    market.status = MarketStatus::Cancelled;
+    let clock = Clock::get()?;
+    require!(
+        market.is_past_resolution_deadline(clock.unix_timestamp),
+        FortunaError::VotingNotEnded
+    );
+
+    let winning_outcome = market.community_winning_outcome()
+        .ok_or(FortunaError::NoResolutionVotes)?;
 
-    msg!("Market cancelled: {}", market.title);
+    market.resolve(winning_outcome, clock.unix_timestamp, false)?;
+
+    msg!("Market resolved by community vote: winning outcome = {} ({})",
+        winning_outcome, market.outcomes[winning_outcome as usize].label);
 
     Ok(())
 }
 
-/// Refund bet for cancelled market
-pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
-    let market = &ctx.accounts.market;
-    let bet = &mut ctx.accounts.bet;
+/// Bind a random-resolution market to a pending randomness account (creator only)
+pub fn request_randomness(ctx: Context<RequestRandomness>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let randomness = RandomnessAccount::load(
+        &ctx.accounts.randomness_account,
+        &ctx.accounts.protocol_state.randomness_program,
+    )?;
+    // A value that is already out would let the creator pick the winner
+    require!(!randomness.is_revealed(), FortunaError::RandomnessAlreadyRevealed);
+
+    let market = &mut ctx.accounts.market;
+    let clock = Clock::get()?;
+    require!(
+        market.is_betting_closed(clock.unix_timestamp),
+        FortunaError::CannotResolveBeforeBettingDeadline
+    );
+
+    market.randomness_requested_at = clock.unix_timestamp;
+    market.randomness_account = ctx.accounts.randomness_account.key();
+    market.randomness_seed_slot = randomness.seed_slot;
+
+    msg!("Randomness requested for market {} from {}",
+        market.market_id, market.randomness_account);
+
+    Ok(())
+}
+
+/// Resolve a random-resolution market from the value revealed in its bound randomness
+/// account (anyone may call)
+pub fn fulfill_randomness(ctx: Context<FulfillRandomness>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let randomness = RandomnessAccount::load(
+        &ctx.accounts.randomness_account,
+        &ctx.accounts.protocol_state.randomness_program,
+    )?;
+    let market = &mut ctx.accounts.market;
+    // A recommitted account would carry a value drawn after the request
+    require!(
+        randomness.seed_slot == market.randomness_seed_slot,
+        FortunaError::RandomnessAccountMismatch
+    );
+    require!(randomness.is_revealed(), FortunaError::RandomnessNotRevealed);
+    let clock = Clock::get()?;
+
+    let winning_outcome = market.random_winning_outcome(&randomness.value);
+    market.randomness = randomness.value;
+    market.resolve(winning_outcome, clock.unix_timestamp, false)?;
+
+    msg!("Market resolved by randomness: winning outcome = {} ({})",
+        winning_outcome, market.outcomes[winning_outcome as usize].label);
+
+    Ok(())
+}
+
+/// Resolve a commit-reveal market by revealing the committed outcome (creator only)
+pub fn reveal_resolution(
+    ctx: Context<RevealResolution>,
+    winning_outcome: u8,
+    salt: [u8; 32],
+) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
+
+    require!(
+        (winning_outcome as usize) < market.outcomes.len(),
+        FortunaError::InvalidOutcome
+    );
+
+    let clock = Clock::get()?;
+    require!(
+        market.is_betting_closed(clock.unix_timestamp),
+        FortunaError::CannotResolveBeforeBettingDeadline
+    );
+
+    require!(
+        Market::result_commitment_hash(winning_outcome, &salt) == market.result_commitment,
+        FortunaError::CommitmentMismatch
+    );
+
+    market.resolve(winning_outcome, clock.unix_timestamp, false)?;
+
+    msg!("Market resolved by reveal: winning outcome = {} ({})",
+        winning_outcome, market.outcomes[winning_outcome as usize].label);
+
+    Ok(())
+}
+
+/// Ensure the category's claim delay has passed since the market resolved
+fn require_claim_delay_elapsed(protocol_state: &ProtocolState, market: &Market) -> Result<()> {
+    let claimable_at = market.resolved_at
+        .checked_add(protocol_state.claim_delay(market.category))
+        .ok_or(FortunaError::Overflow)?;
+    require!(
+        Clock::get()?.unix_timestamp >= claimable_at,
+        FortunaError::ClaimDelayNotElapsed
+    );
+    Ok(())
+}
+
+/// Claim winnings after market resolution
+pub fn claim_winnings(ctx: Context<ClaimWinnings>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Resolved)?;
+    let market = &mut ctx.accounts.market;
+    let bet = &mut ctx.accounts.bet;
+
+    require_claim_delay_elapsed(&ctx.accounts.protocol_state, market)?;
+    require!(
+        !market.creator_fee_redirect_pending(Clock::get()?.unix_timestamp),
+        FortunaError::CreatorFeeRedirectPending
+    );
+
+    // Check if bet won
+    require!(
+        bet.outcome_index == market.winning_outcome,
+        FortunaError::LostBet
+    );
+
+    // Calculate payout
+    let (pool_share, mut bonus_share) = market.calculate_payout(&bet.key(), bet);
+    require!(pool_share + bonus_share > 0, FortunaError::LostBet);
+
+    // Hold the lock across the transfers; persist it so CPI'd programs see it
+    market.lock()?;
+
+    market.winning_bets_claimed = market.winning_bets_claimed.checked_add(1)
+        .ok_or(FortunaError::Overflow)?;
+
+    // Catch accounting bugs before this payout drains funds owed to other winners
+    require!(
+        market.is_collateralized(ctx.accounts.market_vault.amount, ctx.accounts.pool_vault.amount),
+        FortunaError::VaultUndercollateralized
+    );
+
+    // The final winning claim takes the rounding dust so the pool vault ends empty
+    let is_final_claim = market.winning_bets_claimed >= market.winning_bets_total;
+    if is_final_claim {
+        bonus_share = ctx.accounts.pool_vault.amount;
+    }
+    market.record_payout(pool_share, bonus_share)?;
+
+    bet.bet_state = BetState::ClaimedWin;
+    ctx.accounts.market.exit(&crate::ID)?;
+
+    // Contested categories pay into the bettor's time-locked escrow instead
+    let category = ctx.accounts.market.category;
+    let destination = if ctx.accounts.protocol_state.escrows_claims(category) {
+        let release_at = Clock::get()?.unix_timestamp
+            .checked_add(ctx.accounts.protocol_state.claim_delay(category))
+            .ok_or(FortunaError::Overflow)?;
+        let escrow = ctx.accounts.claim_escrow.as_mut()
+            .ok_or(FortunaError::ClaimEscrowRequired)?;
+        escrow.amount = escrow.amount.checked_add(pool_share + bonus_share)
+            .ok_or(FortunaError::Overflow)?;
+        escrow.release_at = escrow.release_at.max(release_at);
+        ctx.accounts.escrow_vault.as_ref()
+            .ok_or(FortunaError::ClaimEscrowRequired)?
+            .to_account_info()
+    } else {
+        ctx.accounts.claimer_token_account.to_account_info()
+    };
+
+    // Transfer the pool share from the market vault and the bonus share from the pool vault
+    let market = &ctx.accounts.market;
+    let market_creator = market.creator;
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let seeds = &[
+        MARKET_SEED,
+        market_creator.as_ref(),
+        market_id_bytes.as_ref(),
+        &[market.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    if pool_share > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.market_vault.to_account_info(),
+            to: destination.clone(),
+            authority: ctx.accounts.market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, pool_share)?;
+    }
+
+    if bonus_share > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.pool_vault.to_account_info(),
+            to: destination.clone(),
+            authority: ctx.accounts.market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, bonus_share)?;
+    }
+
+    ctx.accounts.market.unlock();
+
+    // Breadcrumb for explorers and accounting tools; opt-in since it costs compute
+    if ctx.accounts.market.payout_memo {
+        let memo_program = ctx.accounts.memo_program.as_ref()
+            .ok_or(FortunaError::MemoProgramRequired)?;
+        let memo = format!("{} {}", PAYOUT_MEMO_PREFIX, ctx.accounts.market.market_id);
+        memo::build_memo(
+            CpiContext::new(memo_program.to_account_info(), memo::BuildMemo {}),
+            memo.as_bytes(),
+        )?;
+    }
+
+    msg!("Winnings claimed: {} tokens ({} pool + {} bonus)",
+        pool_share + bonus_share, pool_share, bonus_share);
+
+    let market = &ctx.accounts.market;
+    emit!(WinningsClaimed {
+        market: market.key(),
+        bettor: ctx.accounts.bet.bettor,
+        amount: pool_share + bonus_share,
+        winning_outcome: market.winning_outcome,
+        winning_outcome_label: market.outcomes[market.winning_outcome as usize].label.clone(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    InstructionResult::for_position(market, &ctx.accounts.bet, pool_share + bonus_share).set()?;
+
+    Ok(())
+}
+
+/// Claim a winning bet and stake the proceeds on another open market in one transaction.
+/// Winnings pass through the bettor's token account; any surplus over the new bet stays there.
+pub fn claim_and_bet<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimAndBet<'info>>,
+    outcome_index: u8,
+    loss_protection_bps: u16,
+    memo: Option<String>,
+) -> Result<()> {
+    let balance_before = ctx.accounts.claim.claimer_token_account.amount;
+    claim_winnings(Context::new(
+        ctx.program_id,
+        &mut ctx.accounts.claim,
+        ctx.remaining_accounts,
+        ctx.bumps.claim,
+    ))?;
+
+    ctx.accounts.claim.claimer_token_account.reload()?;
+    let winnings = ctx.accounts.claim.claimer_token_account.amount
+        .checked_sub(balance_before)
+        .ok_or(FortunaError::Overflow)?;
+    require!(
+        winnings >= ctx.accounts.place.market.bet_amount,
+        FortunaError::InsufficientWinnings
+    );
+
+    place_bet(
+        Context::new(
+            ctx.program_id,
+            &mut ctx.accounts.place,
+            ctx.remaining_accounts,
+            ctx.bumps.place,
+        ),
+        outcome_index,
+        loss_protection_bps,
+        memo,
+    )
+}
+
+/// Exclude the signing wallet from betting until the given timestamp.
+/// An existing exclusion can be extended but never shortened.
+pub fn self_exclude(ctx: Context<SelfExclude>, until: i64) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(until > clock.unix_timestamp, FortunaError::InvalidDeadline);
+
+    let exclusion = &mut ctx.accounts.self_exclusion;
+    require!(until >= exclusion.until, FortunaError::SelfExclusionShortened);
+    exclusion.wallet = ctx.accounts.wallet.key();
+    exclusion.until = until;
+    exclusion.bump = ctx.bumps.self_exclusion;
+
+    msg!("Wallet {} self-excluded until {}", exclusion.wallet, until);
+
+    Ok(())
+}
+
+/// Open the signing bettor's claim escrow for a mint, ahead of claiming in a contested category
+pub fn open_claim_escrow(ctx: Context<OpenClaimEscrow>) -> Result<()> {
+    let escrow = &mut ctx.accounts.claim_escrow;
+    escrow.bettor = ctx.accounts.bettor.key();
+    escrow.token_mint = ctx.accounts.token_mint.key();
+    escrow.bump = ctx.bumps.claim_escrow;
+
+    msg!("Claim escrow open for {} ({} held)", escrow.bettor, escrow.amount);
+
+    Ok(())
+}
+
+/// Pay out a bettor's escrowed winnings once their release time has passed
+pub fn release_claim_escrow(ctx: Context<ReleaseClaimEscrow>) -> Result<()> {
+    let escrow = &mut ctx.accounts.claim_escrow;
+    let amount = escrow.amount;
+    require!(amount > 0, FortunaError::NoEscrowedWinnings);
+    require!(
+        Clock::get()?.unix_timestamp >= escrow.release_at,
+        FortunaError::ClaimEscrowLocked
+    );
+    escrow.amount = 0;
+
+    let seeds = &[PROTOCOL_SEED, &[ctx.accounts.protocol_state.bump]];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.escrow_vault.to_account_info(),
+        to: ctx.accounts.bettor_token_account.to_account_info(),
+        authority: ctx.accounts.protocol_state.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)?;
+
+    msg!("Escrowed winnings released: {} tokens", amount);
+
+    Ok(())
+}
+
+/// Record the signing wallet's first-seen time
+pub fn create_bettor_profile(ctx: Context<CreateBettorProfile>) -> Result<()> {
+    let profile = &mut ctx.accounts.bettor_profile;
+    profile.wallet = ctx.accounts.wallet.key();
+    profile.created_at = Clock::get()?.unix_timestamp;
+    profile.bump = ctx.bumps.bettor_profile;
+
+    msg!("Bettor profile created for {}", profile.wallet);
+
+    Ok(())
+}
+
+/// Escrow a market's bet amount for the bettor without adding it to the pool yet
+pub fn reserve_bet(ctx: Context<ReserveBet>, outcome_index: u8) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &ctx.accounts.market;
+    require!(
+        (outcome_index as usize) < market.outcomes.len(),
+        FortunaError::InvalidOutcome
+    );
+    require!(market.outcomes[outcome_index as usize].is_open, FortunaError::OutcomeClosed);
+    let clock = Clock::get()?;
+    require!(
+        !market.is_betting_closed(clock.unix_timestamp),
+        FortunaError::BettingDeadlinePassed
+    );
+
+    let amount = market.bet_amount;
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.bettor_token_account.to_account_info(),
+        to: ctx.accounts.reservation_vault.to_account_info(),
+        authority: ctx.accounts.bettor.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token::transfer(cpi_ctx, amount)?;
+
+    let reservation = &mut ctx.accounts.reservation;
+    reservation.market = market.key();
+    reservation.bettor = ctx.accounts.bettor.key();
+    reservation.token_mint = market.token_mint;
+    reservation.outcome_index = outcome_index;
+    reservation.amount = amount;
+    reservation.expires_at = clock.unix_timestamp
+        .checked_add(ctx.accounts.protocol_state.reservation_window_secs)
+        .ok_or(FortunaError::Overflow)?;
+    reservation.bump = ctx.bumps.reservation;
+
+    msg!("Bet reserved: {} on outcome {} until {}", amount, outcome_index, reservation.expires_at);
+
+    Ok(())
+}
+
+/// Confirm a pending reservation, moving the escrowed stake into a regular bet
+pub fn confirm_bet<'info>(
+    ctx: Context<'_, '_, '_, 'info, ConfirmBet<'info>>,
+    outcome_index: u8,
+    loss_protection_bps: u16,
+    memo: Option<String>,
+) -> Result<()> {
+    let reservation = &ctx.accounts.reservation;
+    require!(outcome_index == reservation.outcome_index, FortunaError::InvalidOutcome);
+    let clock = Clock::get()?;
+    require!(
+        !reservation.is_expired_at(clock.unix_timestamp),
+        FortunaError::ReservationExpired
+    );
+
+    // Release the escrow to the bettor; place_bet then moves it into the pool in the same transaction
+    let seeds = &[PROTOCOL_SEED, &[ctx.accounts.place.protocol_state.bump]];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.reservation_vault.to_account_info(),
+        to: ctx.accounts.place.bettor_token_account.to_account_info(),
+        authority: ctx.accounts.place.protocol_state.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.place.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, reservation.amount)?;
+    ctx.accounts.reservation.close(ctx.accounts.place.bettor.to_account_info())?;
+
+    place_bet(
+        Context::new(
+            ctx.program_id,
+            &mut ctx.accounts.place,
+            ctx.remaining_accounts,
+            ctx.bumps.place,
+        ),
+        outcome_index,
+        loss_protection_bps,
+        memo,
+    )
+}
+
+/// Refund a reservation's escrowed stake. The bettor may cancel at any time; once the
+/// reservation has expired anyone may trigger the refund.
+pub fn cancel_reservation(ctx: Context<CancelReservation>) -> Result<()> {
+    let reservation = &ctx.accounts.reservation;
+    let clock = Clock::get()?;
+    require!(
+        ctx.accounts.caller.key() == reservation.bettor
+            || reservation.is_expired_at(clock.unix_timestamp),
+        FortunaError::ReservationNotExpired
+    );
+
+    let seeds = &[PROTOCOL_SEED, &[ctx.accounts.protocol_state.bump]];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.reservation_vault.to_account_info(),
+        to: ctx.accounts.bettor_token_account.to_account_info(),
+        authority: ctx.accounts.protocol_state.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    token::transfer(cpi_ctx, reservation.amount)?;
+
+    msg!("Bet reservation refunded: {}", reservation.amount);
+
+    Ok(())
+}
+
+/// Reclaim the loss-protected portion of a losing bet
+pub fn claim_loss_protection(ctx: Context<ClaimLossProtection>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Resolved)?;
+    let market = &mut ctx.accounts.market;
+    let bet = &mut ctx.accounts.bet;
+
+    // Losers wait out the same claim delay as winners
+    require_claim_delay_elapsed(&ctx.accounts.protocol_state, market)?;
+
+    require!(bet.protected_amount > 0, FortunaError::NoLossProtection);
+    // Winning bets are paid through claim_winnings instead
+    require!(
+        bet.outcome_index != market.winning_outcome,
+        FortunaError::InvalidOutcome
+    );
+
+    market.lock()?;
+    market.loss_protection_claimed = market.loss_protection_claimed
+        .checked_add(bet.protected_amount)
+        .ok_or(FortunaError::Overflow)?;
+    bet.bet_state = BetState::ClaimedLoss;
+    ctx.accounts.market.exit(&crate::ID)?;
+
+    let market = &ctx.accounts.market;
+    let bet = &ctx.accounts.bet;
+    let market_creator = market.creator;
 
-    // Transfer refund from market vault
     let market_id_bytes = market.market_id.to_le_bytes();
     let seeds = &[
         MARKET_SEED,
+        market_creator.as_ref(),
         market_id_bytes.as_ref(),
         &[market.bump],
     ];
     let signer = &[&seeds[..]];
 
-    // Refund the pool amount (after fees were taken)
     let cpi_accounts = Transfer {
         from: ctx.accounts.market_vault.to_account_info(),
         to: ctx.accounts.claimer_token_account.to_account_info(),
@@ -479,283 +1613,2019 @@ pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-    token::transfer(cpi_ctx, bet.pool_amount)?;
+    token::transfer(cpi_ctx, bet.protected_amount)?;
+
+    ctx.accounts.market.unlock();
+
+    msg!("Loss protection claimed: {} tokens", bet.protected_amount);
+
+    Ok(())
+}
+
+/// Release a resolved market's escrowed creator fees to the creator fee wallet
+pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Resolved)?;
+    let market = &mut ctx.accounts.market;
+    let amount = market.escrowed_creator_fees;
+    require!(amount > 0, FortunaError::NoEscrowedCreatorFees);
+    require!(
+        !market.creator_fees_forfeited(Clock::get()?.unix_timestamp),
+        FortunaError::CreatorFeesForfeited
+    );
+    market.escrowed_creator_fees = 0;
+
+    let market_creator = market.creator;
+
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let seeds = &[
+        MARKET_SEED,
+        market_creator.as_ref(),
+        market_id_bytes.as_ref(),
+        &[market.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.market_vault.to_account_info(),
+        to: ctx.accounts.creator_token_account.to_account_info(),
+        authority: ctx.accounts.market.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)?;
+
+    msg!("Creator fees released: {} tokens", amount);
+
+    Ok(())
+}
+
+/// Move a low-participation market's escrowed creator fees into its bonus pool
+pub fn redirect_creator_fees(ctx: Context<RedirectCreatorFees>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Resolved)?;
+    let market = &mut ctx.accounts.market;
+    let amount = market.escrowed_creator_fees;
+    require!(amount > 0, FortunaError::NoEscrowedCreatorFees);
+    require!(
+        market.creator_fees_forfeited(Clock::get()?.unix_timestamp),
+        FortunaError::CreatorFeesNotForfeited
+    );
+    market.escrowed_creator_fees = 0;
+    market.bonus_pool = market.bonus_pool.checked_add(amount)
+        .ok_or(FortunaError::Overflow)?;
+
+    let market_creator = market.creator;
+
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let seeds = &[
+        MARKET_SEED,
+        market_creator.as_ref(),
+        market_id_bytes.as_ref(),
+        &[market.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.market_vault.to_account_info(),
+        to: ctx.accounts.pool_vault.to_account_info(),
+        authority: ctx.accounts.market.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)?;
+
+    msg!("Creator fees redirected to bonus pool: {} tokens", amount);
+
+    Ok(())
+}
+
+/// Sweep winnings left unclaimed past the market's claim expiry to the treasury (admin only).
+/// The winning bets are passed as remaining accounts and are marked claimed.
+pub fn sweep_expired_claims<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SweepExpiredClaims<'info>>,
+) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Resolved)?;
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let market_key = ctx.accounts.market.key();
+    let market = &mut ctx.accounts.market;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(market.claims_expired(now), FortunaError::ClaimsNotExpired);
+    require!(
+        !market.creator_fee_redirect_pending(now),
+        FortunaError::CreatorFeeRedirectPending
+    );
+    require!(!ctx.remaining_accounts.is_empty(), FortunaError::InvalidExpiredClaim);
+
+    let mut pool_total: u64 = 0;
+    let mut bonus_total: u64 = 0;
+    for info in ctx.remaining_accounts.iter() {
+        require!(info.is_writable, FortunaError::InvalidExpiredClaim);
+        let mut bet: Bet = load_program_account(info, FortunaError::InvalidExpiredClaim)?;
+        require!(
+            bet.market == market_key
+                && info.key() == Pubkey::find_program_address(
+                    &[BET_SEED, market_key.as_ref(), bet.bettor.as_ref()],
+                    &crate::ID,
+                ).0,
+            FortunaError::InvalidExpiredClaim
+        );
+        require!(bet.is_active(), FortunaError::AlreadyClaimed);
+
+        let (pool_share, bonus_share) = market.calculate_payout(&info.key(), &bet);
+        require!(pool_share + bonus_share > 0, FortunaError::LostBet);
+
+        pool_total = pool_total.checked_add(pool_share).ok_or(FortunaError::Overflow)?;
+        bonus_total = bonus_total.checked_add(bonus_share).ok_or(FortunaError::Overflow)?;
+        market.winning_bets_claimed = market.winning_bets_claimed.checked_add(1)
+            .ok_or(FortunaError::Overflow)?;
+
+        bet.bet_state = BetState::ClaimedWin;
+        bet.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+    }
+
+    // The final winning claim takes the rounding dust so the pool vault ends empty
+    if market.winning_bets_claimed >= market.winning_bets_total {
+        bonus_total = ctx.accounts.pool_vault.amount;
+    }
+    market.record_payout(pool_total, bonus_total)?;
+
+    let market_creator = market.creator;
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let seeds = &[
+        MARKET_SEED,
+        market_creator.as_ref(),
+        market_id_bytes.as_ref(),
+        &[market.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    if pool_total > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.market_vault.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, pool_total)?;
+    }
+
+    if bonus_total > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.pool_vault.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, bonus_total)?;
+    }
+
+    msg!("Expired claims swept: {} bets, {} tokens ({} pool + {} bonus)",
+        ctx.remaining_accounts.len(), pool_total + bonus_total, pool_total, bonus_total);
+
+    Ok(())
+}
+
+/// Deposit creator liquidity backing a fixed-multiplier market's payouts
+pub fn deposit_market_liquidity(ctx: Context<DepositMarketLiquidity>, amount: u64) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    require!(amount > 0, FortunaError::InvalidBetAmount);
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.creator_token_account.to_account_info(),
+        to: ctx.accounts.market_vault.to_account_info(),
+        authority: ctx.accounts.creator.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, amount)?;
+
+    let market = &mut ctx.accounts.market;
+    market.liquidity = market.liquidity.checked_add(amount)
+        .ok_or(FortunaError::Overflow)?;
+
+    msg!("Liquidity deposited: {} tokens (total {})", amount, market.liquidity);
+
+    Ok(())
+}
+
+/// Return a settled fixed-multiplier market's remaining liquidity and house winnings to the creator
+pub fn withdraw_market_liquidity(ctx: Context<WithdrawMarketLiquidity>) -> Result<()> {
+    require_settled(&ctx.accounts.market)?;
+    let market = &mut ctx.accounts.market;
+    let amount = market.withdrawable_liquidity();
+    market.liquidity_withdrawn = true;
+
+    let market_creator = market.creator;
+
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let seeds = &[
+        MARKET_SEED,
+        market_creator.as_ref(),
+        market_id_bytes.as_ref(),
+        &[market.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    if amount > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.market_vault.to_account_info(),
+            to: ctx.accounts.creator_token_account.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+    }
+
+    msg!("Liquidity withdrawn: {} tokens", amount);
+
+    Ok(())
+}
+
+/// Sweep a resolved market's pool to the treasury when its winning outcome had no bets
+pub fn sweep_stranded_pool(ctx: Context<SweepStrandedPool>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Resolved)?;
+    let market = &ctx.accounts.market;
+
+    require!(market.is_stranded(), FortunaError::PoolNotStranded);
+
+    // Loss-protected losing bets keep their covered amount in the vault
+    let pool_amount = market.total_pool.saturating_sub(market.losing_protected_amount());
+    let bonus_amount = market.bonus_pool;
+
+    let market_creator = market.creator;
+
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let seeds = &[
+        MARKET_SEED,
+        market_creator.as_ref(),
+        market_id_bytes.as_ref(),
+        &[market.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    if pool_amount > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.market_vault.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, pool_amount)?;
+    }
+
+    if bonus_amount > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.pool_vault.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, bonus_amount)?;
+    }
+
+    ctx.accounts.market.stranded_swept = true;
+
+    msg!("Stranded pool swept to treasury: {} pool + {} bonus", pool_amount, bonus_amount);
+
+    Ok(())
+}
+
+/// Close a settled market once every winner has claimed, returning the remaining vault
+/// balance (rounding dust and unwithdrawn liquidity) and all account rent to the creator
+pub fn close_market(ctx: Context<CloseMarket>) -> Result<()> {
+    require_settled(&ctx.accounts.market)?;
+    let market = &ctx.accounts.market;
+    require!(market.all_claims_settled(), FortunaError::MarketHasUnclaimedFunds);
+
+    let market_creator = market.creator;
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let seeds = &[
+        MARKET_SEED,
+        market_creator.as_ref(),
+        market_id_bytes.as_ref(),
+        &[market.bump],
+    ];
+    let signer = &[&seeds[..]];
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+
+    for vault in [&ctx.accounts.market_vault, &ctx.accounts.pool_vault] {
+        if vault.amount > 0 {
+            let cpi_accounts = Transfer {
+                from: vault.to_account_info(),
+                to: ctx.accounts.creator_token_account.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer);
+            token::transfer(cpi_ctx, vault.amount)?;
+        }
+
+        let cpi_accounts = CloseAccount {
+            account: vault.to_account_info(),
+            destination: ctx.accounts.creator.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        };
+        token::close_account(CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer))?;
+    }
+
+    if market.status == MarketStatus::Cancelled {
+        msg!("Market {} closed: {} bets refunded", market.market_id, market.refunds_claimed);
+    } else {
+        msg!("Market {} closed: {} of {} winning bets claimed",
+            market.market_id, market.winning_bets_claimed, market.winning_bets_total);
+    }
+
+    Ok(())
+}
+
+/// Cancel a market (only before any bets or by admin)
+pub fn cancel_market(ctx: Context<CancelMarket>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
+
+    // Update market status
+    market.status = MarketStatus::Cancelled;
+    // The protocol authority's check wins, so an admin cancelling their own market acts as admin
+    market.cancel_reason = if ctx.accounts.authority.key() == ctx.accounts.protocol_state.authority {
+        CancelReason::AdminCancelled
+    } else {
+        CancelReason::CreatorCancelled
+    };
+
+    msg!("Market cancelled: {} ({:?})", market.title, market.cancel_reason);
+
+    Ok(())
+}
+
+/// Cancel a market nobody resolved by its resolution deadline so bettors can take refunds
+/// (anyone may call). Community-vote markets with votes are finalized instead.
+pub fn expire_market(ctx: Context<ExpireMarket>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
+
+    let clock = Clock::get()?;
+    require!(
+        market.is_past_resolution_deadline(clock.unix_timestamp),
+        FortunaError::ResolutionDeadlineNotReached
+    );
+    require!(
+        market.resolution_mode != ResolutionMode::CommunityVote
+            || market.community_winning_outcome().is_none(),
+        FortunaError::ResolutionVotesCast
+    );
+
+    market.status = MarketStatus::Cancelled;
+    market.cancel_reason = if market.has_oracle() {
+        CancelReason::OracleFailure
+    } else {
+        CancelReason::Expired
+    };
+
+    msg!("Market expired: {} ({:?})", market.title, market.cancel_reason);
+
+    Ok(())
+}
+
+/// Cancel the signer's markets passed as remaining accounts that are still open with no bets;
+/// markets with bets or that have already settled are left untouched
+pub fn cancel_markets_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CancelMarketsBatch<'info>>,
+) -> Result<()> {
+    let creator = ctx.accounts.creator.key();
+    require!(!ctx.remaining_accounts.is_empty(), FortunaError::InvalidBatchMarket);
+
+    let mut cancelled: u32 = 0;
+    for info in ctx.remaining_accounts.iter() {
+        require!(info.is_writable, FortunaError::InvalidBatchMarket);
+        let mut market: Market = load_program_account(info, FortunaError::InvalidBatchMarket)?;
+        require!(
+            info.key() == Pubkey::find_program_address(
+                &[MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+                &crate::ID,
+            ).0,
+            FortunaError::InvalidBatchMarket
+        );
+        require!(market.creator == creator, FortunaError::Unauthorized);
+
+        if market.status != MarketStatus::Open || market.total_bettors() > 0 {
+            continue;
+        }
+
+        market.status = MarketStatus::Cancelled;
+        market.cancel_reason = CancelReason::CreatorCancelled;
+        market.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        cancelled += 1;
+    }
+
+    msg!("Batch cancelled {} of {} markets", cancelled, ctx.remaining_accounts.len());
+
+    Ok(())
+}
+
+/// Refund bet for cancelled market
+pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Cancelled)?;
+    let market = &mut ctx.accounts.market;
+    let bet = &mut ctx.accounts.bet;
+
+    // The escrowed creator fee goes back to the bettor along with the stake
+    let refund = bet.pool_amount + bet.creator_fee;
+    market.escrowed_creator_fees = market.escrowed_creator_fees.checked_sub(bet.creator_fee)
+        .ok_or(FortunaError::Overflow)?;
+    market.refunds_claimed = market.refunds_claimed.checked_add(1)
+        .ok_or(FortunaError::Overflow)?;
+
+    // Transfer refund from market vault
+    let market_creator = market.creator;
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let seeds = &[
+        MARKET_SEED,
+        market_creator.as_ref(),
+        market_id_bytes.as_ref(),
+        &[market.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Refund the pool amount and creator fee (pool and protocol fees are not refunded)
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.market_vault.to_account_info(),
+        to: ctx.accounts.claimer_token_account.to_account_info(),
+        authority: ctx.accounts.market.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, refund)?;
+
+    // Mark bet as refunded
+    bet.bet_state = BetState::Refunded;
+
+    msg!("Refund claimed: {} tokens", refund);
+
+    Ok(())
+}
+
+/// Withdraw a bet before market resolution (user gets back their stake minus fees)
+pub fn withdraw_bet(ctx: Context<WithdrawBet>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
+    let bet = &mut ctx.accounts.bet;
+
+    // Check betting is still open (can only withdraw before deadline)
+    let clock = Clock::get()?;
+    require!(
+        !market.is_betting_closed(clock.unix_timestamp),
+        FortunaError::WithdrawDeadlinePassed
+    );
+    require!(bet.is_withdrawable_at(market, clock.unix_timestamp), FortunaError::BetStillLocked);
+
+    // The designated winner-take-all bet must stay put so the earliest bet is well defined
+    if market.payout_mode == PayoutMode::WinnerTakeAll {
+        require!(
+            market.outcomes[bet.outcome_index as usize].first_bet != bet.key(),
+            FortunaError::LeadingBetLocked
+        );
+    }
+
+    let withdraw_amount = bet.pool_amount;
+    let refund = withdraw_amount + bet.creator_fee;
+
+    // Update market totals; the creator fee is only earned by bets that stay to resolution
+    market.total_pool = market.total_pool.checked_sub(withdraw_amount)
+        .ok_or(FortunaError::Overflow)?;
+    market.escrowed_creator_fees = market.escrowed_creator_fees.checked_sub(bet.creator_fee)
+        .ok_or(FortunaError::Overflow)?;
+    market.bet_count = market.bet_count.checked_sub(1)
+        .ok_or(FortunaError::Overflow)?;
+
+    // Update outcome totals
+    let outcome = &mut market.outcomes[bet.outcome_index as usize];
+    outcome.total_amount = outcome.total_amount.checked_sub(withdraw_amount)
+        .ok_or(FortunaError::Overflow)?;
+    outcome.bettor_count = outcome.bettor_count.checked_sub(1)
+        .ok_or(FortunaError::Overflow)?;
+    outcome.protected_amount = outcome.protected_amount.checked_sub(bet.protected_amount)
+        .ok_or(FortunaError::Overflow)?;
+
+    // Transfer tokens back to bettor from market vault
+    let market_creator = market.creator;
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let seeds = &[
+        MARKET_SEED,
+        market_creator.as_ref(),
+        market_id_bytes.as_ref(),
+        &[market.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.market_vault.to_account_info(),
+        to: ctx.accounts.bettor_token_account.to_account_info(),
+        authority: ctx.accounts.market.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, refund)?;
+
+    // Mark bet as withdrawn
+    bet.bet_state = BetState::Withdrawn;
+    ctx.accounts.leaderboard.remove(bet.bettor);
+
+    msg!("Bet withdrawn: {} tokens (pool and protocol fees non-refundable)", refund);
+
+    Ok(())
+}
+
+/// Stamp an admin action, pushing back when the recovery authority may claim a dormant
+/// authority. Every instruction gated on the protocol authority calls this first.
+fn record_authority_activity(protocol_state: &mut ProtocolState) -> Result<()> {
+    protocol_state.last_authority_activity = Clock::get()?.unix_timestamp;
+    Ok(())
+}
+
+/// Update protocol settings (admin only)
+///
+/// A new treasury applies from the next transfer on. Protocol fees are paid to the treasury
+/// when each bet is placed, so fees accrued before the change have already reached the old
+/// treasury and nothing is left to misdirect. House markets snapshot the treasury as their
+/// creator fee wallet at creation and keep paying it. Sweeps of stranded pools and expired
+/// claims go to the treasury at sweep time.
+pub fn update_protocol(
+    ctx: Context<UpdateProtocol>,
+    new_treasury: Option<Pubkey>,
+    new_protocol_fee_bps: Option<u16>,
+    new_creator_fee_bps: Option<u16>,
+    new_pool_fee_bps: Option<u16>,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+
+    // Update treasury if provided
+    if let Some(treasury) = new_treasury {
+        protocol_state.treasury = treasury;
+        msg!("Treasury updated to: {}", treasury);
+    }
+
+    // Calculate new total fee
+    let protocol_fee = new_protocol_fee_bps.unwrap_or(protocol_state.protocol_fee_bps);
+    let creator_fee = new_creator_fee_bps.unwrap_or(protocol_state.creator_fee_bps);
+    let pool_fee = new_pool_fee_bps.unwrap_or(protocol_state.pool_fee_bps);
+
+    // Category overrides replace the protocol fee, so the highest one must fit as well
+    let max_protocol_fee = protocol_state.protocol_fee_bps_per_category
+        .iter()
+        .copied()
+        .fold(protocol_fee, u16::max);
+    let total_fee = max_protocol_fee as u32 + creator_fee as u32 + pool_fee as u32;
+    require!(total_fee <= MAX_TOTAL_FEE_BPS as u32, FortunaError::InvalidFeeConfig);
+
+    // Update fees if provided
+    if let Some(fee) = new_protocol_fee_bps {
+        protocol_state.protocol_fee_bps = fee;
+        msg!("Protocol fee updated to: {}bps", fee);
+    }
+
+    if let Some(fee) = new_creator_fee_bps {
+        protocol_state.creator_fee_bps = fee;
+        msg!("Creator fee updated to: {}bps", fee);
+    }
+
+    if let Some(fee) = new_pool_fee_bps {
+        protocol_state.pool_fee_bps = fee;
+        msg!("Pool fee updated to: {}bps", fee);
+    }
+
+    Ok(())
+}
+
+/// Toggle whether license is required to create markets
+pub fn set_require_license(
+    ctx: Context<UpdateProtocol>,
+    require_license: bool,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.require_license = require_license;
+    msg!("License requirement set to: {}", require_license);
+    Ok(())
+}
+
+/// Set the loss protection premium (admin only, 0 disables loss protection)
+pub fn set_loss_protection_premium(
+    ctx: Context<UpdateProtocol>,
+    premium_bps: u16,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    require!(premium_bps <= BPS_DENOMINATOR, FortunaError::InvalidLossProtection);
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.loss_protection_premium_bps = premium_bps;
+    msg!("Loss protection premium set to: {}bps", premium_bps);
+    Ok(())
+}
+
+/// Restrict the betting mints allowed for a category (admin only, empty allows any)
+pub fn set_category_allowed_mints(
+    ctx: Context<SetCategoryAllowedMints>,
+    category: u8,
+    allowed_mints: Vec<Pubkey>,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let market_category = MarketCategory::from_u8(category)
+        .ok_or(FortunaError::InvalidCategory)?;
+    require!(allowed_mints.len() <= MAX_CATEGORY_MINTS, FortunaError::TooManyCategoryMints);
+
+    let category_config = &mut ctx.accounts.category_config;
+    category_config.category = market_category;
+    category_config.allowed_mints = allowed_mints;
+    category_config.bump = ctx.bumps.category_config;
+
+    msg!("Category {} allowed mints updated: {} mints", category, category_config.allowed_mints.len());
+    Ok(())
+}
+
+/// Set the bounds on markets' fixed bet amount (admin only, 0 = unbounded)
+pub fn set_bet_amount_bounds(
+    ctx: Context<UpdateProtocol>,
+    min_bet_amount: u64,
+    max_bet_amount: u64,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    require!(
+        max_bet_amount == 0 || min_bet_amount <= max_bet_amount,
+        FortunaError::InvalidBetAmount
+    );
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.min_bet_amount = min_bet_amount;
+    protocol_state.max_bet_amount = max_bet_amount;
+    msg!("Bet amount bounds set to: {} - {}", min_bet_amount, max_bet_amount);
+    Ok(())
+}
+
+/// Configure the fee mint and flat protocol fee for fee-token markets (admin only)
+pub fn set_fee_token(
+    ctx: Context<UpdateProtocol>,
+    fee_mint: Pubkey,
+    flat_protocol_fee: u64,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.fee_mint = fee_mint;
+    protocol_state.flat_protocol_fee = flat_protocol_fee;
+    msg!("Fee token set to: {} (flat fee {})", fee_mint, flat_protocol_fee);
+    Ok(())
+}
+
+/// Route creator fees on new bets to the treasury instead of the market's creator (admin only)
+pub fn set_redirect_creator_fees_to_treasury(
+    ctx: Context<UpdateProtocol>,
+    redirect: bool,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.redirect_creator_fees_to_treasury = redirect;
+    msg!("Creator fees redirected to treasury: {}", redirect);
+    Ok(())
+}
+
+/// Set how far in the future new markets' betting deadlines may be (admin only, 0 = unbounded)
+pub fn set_max_future_deadline(
+    ctx: Context<UpdateProtocol>,
+    max_future_deadline_secs: i64,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    require!(max_future_deadline_secs >= 0, FortunaError::InvalidDeadline);
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.max_future_deadline_secs = max_future_deadline_secs;
+    msg!("Max future deadline set to: {}s", max_future_deadline_secs);
+    Ok(())
+}
+
+/// Set the mint clients create markets in when they don't name one (admin only, None = no default)
+pub fn set_default_token_mint(ctx: Context<SetDefaultTokenMint>) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.default_token_mint = ctx.accounts.default_token_mint
+        .as_ref()
+        .map_or(Pubkey::default(), |mint| mint.key());
+    msg!("Default token mint set to: {}", protocol_state.default_token_mint);
+    Ok(())
+}
+
+/// Set the bond new oracles post at registration and its withdrawal cooldown (admin only)
+pub fn set_oracle_bond(
+    ctx: Context<SetOracleBond>,
+    amount: u64,
+    cooldown_secs: i64,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    require!(cooldown_secs >= 0, FortunaError::InvalidDeadline);
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.oracle_bond_mint = ctx.accounts.bond_mint.key();
+    protocol_state.oracle_bond_amount = amount;
+    protocol_state.oracle_bond_cooldown_secs = cooldown_secs;
+    msg!("Oracle bond set to: {} of {} with {}s cooldown", amount, protocol_state.oracle_bond_mint, cooldown_secs);
+    Ok(())
+}
+
+/// Cap how many open markets a single oracle can be assigned (admin only, 0 = unlimited)
+pub fn set_max_markets_per_oracle(
+    ctx: Context<UpdateProtocol>,
+    max_markets_per_oracle: u32,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.max_markets_per_oracle = max_markets_per_oracle;
+    msg!("Max markets per oracle set to: {}", max_markets_per_oracle);
+    Ok(())
+}
+
+/// Cap how many markets a license can create per window (admin only, 0 = unlimited)
+pub fn set_license_rate_limit(
+    ctx: Context<UpdateProtocol>,
+    license_rate_limit: u32,
+    license_rate_window_secs: i64,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    require!(
+        license_rate_window_secs >= 0
+            && (license_rate_limit == 0) == (license_rate_window_secs == 0),
+        FortunaError::InvalidLicenseRateLimit
+    );
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.license_rate_limit = license_rate_limit;
+    protocol_state.license_rate_window_secs = license_rate_window_secs;
+    msg!("License rate limit set to: {} per {}s", license_rate_limit, license_rate_window_secs);
+    Ok(())
+}
+
+/// Name the key that may take over the authority after `dormancy_secs` without an admin
+/// action (admin only, default key and 0 = no recovery)
+pub fn set_recovery_authority(
+    ctx: Context<UpdateProtocol>,
+    recovery_authority: Pubkey,
+    dormancy_secs: i64,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    require!(
+        dormancy_secs >= 0
+            && (recovery_authority == Pubkey::default()) == (dormancy_secs == 0)
+            && recovery_authority != protocol_state.authority,
+        FortunaError::InvalidRecoveryAuthority
+    );
+    protocol_state.recovery_authority = recovery_authority;
+    protocol_state.authority_dormancy_secs = dormancy_secs;
+    msg!("Recovery authority set to: {} after {}s dormancy", recovery_authority, dormancy_secs);
+    Ok(())
+}
+
+/// Hand the protocol authority to the recovery authority once the current one has gone
+/// dormant (recovery authority only)
+pub fn claim_dormant_authority(ctx: Context<ClaimDormantAuthority>) -> Result<()> {
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    let now = Clock::get()?.unix_timestamp;
+    require!(protocol_state.authority_is_dormant(now), FortunaError::AuthorityNotDormant);
+
+    let previous_authority = protocol_state.authority;
+    protocol_state.authority = protocol_state.recovery_authority;
+    // The new authority names its own recovery key
+    protocol_state.recovery_authority = Pubkey::default();
+    protocol_state.authority_dormancy_secs = 0;
+    protocol_state.last_authority_activity = now;
+
+    msg!("Dormant authority {} claimed by {}", previous_authority, protocol_state.authority);
+
+    emit!(DormantAuthorityClaimed {
+        previous_authority,
+        new_authority: protocol_state.authority,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Set how long bet reservations stay confirmable (admin only)
+pub fn set_reservation_window(
+    ctx: Context<UpdateProtocol>,
+    reservation_window_secs: i64,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    require!(reservation_window_secs > 0, FortunaError::InvalidDeadline);
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.reservation_window_secs = reservation_window_secs;
+    msg!("Bet reservation window set to: {}s", reservation_window_secs);
+    Ok(())
+}
+
+/// Count a batch of markets (passed as remaining accounts) into the stats recompute scratch
+/// counters (admin only). `restart` discards any previous progress and begins a new recompute.
+pub fn recompute_protocol_stats<'info>(
+    ctx: Context<'_, '_, 'info, 'info, UpdateProtocol<'info>>,
+    restart: bool,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let scratch = &mut ctx.accounts.protocol_state.stats_recompute;
+    if restart {
+        *scratch = StatsRecompute { in_progress: true, ..StatsRecompute::default() };
+    }
+    require!(scratch.in_progress, FortunaError::StatsRecomputeNotStarted);
+
+    for info in ctx.remaining_accounts.iter() {
+        let market: Market = load_program_account(info, FortunaError::InvalidStatsMarket)?;
+        require!(
+            info.key() == Pubkey::find_program_address(
+                &[MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+                &crate::ID,
+            ).0,
+            FortunaError::InvalidStatsMarket
+        );
+        require!(info.key() > scratch.last_market, FortunaError::StatsMarketsOutOfOrder);
+
+        scratch.total_markets = scratch.total_markets.checked_add(1).ok_or(FortunaError::Overflow)?;
+        scratch.total_volume = scratch.total_volume
+            .checked_add(market.total_pool as u128)
+            .ok_or(FortunaError::Overflow)?;
+        scratch.last_market = info.key();
+    }
+
+    msg!("Stats recompute progress: {} markets, volume {}", scratch.total_markets, scratch.total_volume);
+    Ok(())
+}
+
+/// Replace the protocol statistics with the recomputed counters (admin only)
+pub fn finalize_stats_recompute(ctx: Context<UpdateProtocol>) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    let scratch = protocol_state.stats_recompute;
+    require!(scratch.in_progress, FortunaError::StatsRecomputeNotStarted);
+
+    protocol_state.total_markets = scratch.total_markets;
+    protocol_state.total_volume = scratch.total_volume;
+    protocol_state.stats_recompute = StatsRecompute::default();
+
+    msg!("Protocol stats recomputed: {} markets, volume {}", scratch.total_markets, scratch.total_volume);
+    Ok(())
+}
+
+/// Route winnings in one market category through the time-locked claim escrow (admin only)
+pub fn set_category_escrow_claims(
+    ctx: Context<UpdateProtocol>,
+    category: u8,
+    escrow_claims: bool,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let category = MarketCategory::from_u8(category).ok_or(FortunaError::InvalidCategory)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.escrow_claims[category as usize] = escrow_claims;
+    msg!("Claim escrow for category {:?} set to: {}", category, escrow_claims);
+    Ok(())
+}
+
+/// Set the claim delay for one market category (admin only)
+pub fn set_claim_delay(
+    ctx: Context<UpdateProtocol>,
+    category: u8,
+    delay: i64,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let category = MarketCategory::from_u8(category).ok_or(FortunaError::InvalidCategory)?;
+    require!(delay >= 0, FortunaError::InvalidDeadline);
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.claim_delay_per_category[category as usize] = delay;
+    msg!("Claim delay for category {:?} set to: {}s", category, delay);
+    Ok(())
+}
+
+/// Override the protocol fee for one market category; 0 restores the global fee (admin only)
+pub fn set_category_protocol_fee(
+    ctx: Context<UpdateProtocol>,
+    category: u8,
+    fee_bps: u16,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let category = MarketCategory::from_u8(category).ok_or(FortunaError::InvalidCategory)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    let total_fee = fee_bps as u32
+        + protocol_state.creator_fee_bps as u32
+        + protocol_state.pool_fee_bps as u32;
+    require!(total_fee <= MAX_TOTAL_FEE_BPS as u32, FortunaError::InvalidFeeConfig);
+    protocol_state.protocol_fee_bps_per_category[category as usize] = fee_bps;
+    msg!("Protocol fee for category {:?} set to: {}bps", category, fee_bps);
+    Ok(())
+}
+
+/// Set the outcome count bounds for one market category; 0 keeps the global bound (admin only)
+pub fn set_category_outcome_bounds(
+    ctx: Context<UpdateProtocol>,
+    category: u8,
+    min_outcomes: u8,
+    max_outcomes: u8,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let category = MarketCategory::from_u8(category).ok_or(FortunaError::InvalidCategory)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.min_outcomes_per_category[category as usize] = min_outcomes;
+    protocol_state.max_outcomes_per_category[category as usize] = max_outcomes;
+
+    let (min, max) = protocol_state.outcome_bounds(category);
+    require!(
+        min >= MIN_OUTCOMES && max <= MAX_OUTCOMES && min <= max,
+        FortunaError::InvalidOutcomeBounds
+    );
+    msg!("Outcome bounds for category {:?} set to: {}..={}", category, min, max);
+    Ok(())
+}
+
+/// Allow or forbid creators resolving their own markets (admin only)
+pub fn set_creator_resolution_allowed(
+    ctx: Context<UpdateProtocol>,
+    allowed: bool,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.creator_resolution_allowed = allowed;
+    msg!("Creator resolution allowed: {}", allowed);
+    Ok(())
+}
+
+/// Set the program whose randomness accounts resolve random markets (admin only)
+pub fn set_randomness_program(
+    ctx: Context<UpdateProtocol>,
+    randomness_program: Pubkey,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.randomness_program = randomness_program;
+    msg!("Randomness program set to: {}", randomness_program);
+    Ok(())
+}
+
+/// Set the policy for markets whose winning outcome has no bets (admin only)
+pub fn set_stranded_pool_policy(
+    ctx: Context<UpdateProtocol>,
+    policy: StrandedPoolPolicy,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.stranded_pool_policy = policy;
+    msg!("Stranded pool policy set to: {:?}", policy);
+    Ok(())
+}
+
+/// Replace the protocol fee rebate tiers (admin only)
+pub fn set_rebate_tiers(
+    ctx: Context<UpdateProtocol>,
+    rebate_tiers: Vec<RebateTier>,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    require!(rebate_tiers.len() <= MAX_REBATE_TIERS, FortunaError::InvalidRebateTiers);
+
+    for tier in &rebate_tiers {
+        require!(tier.rebate_bps <= BPS_DENOMINATOR, FortunaError::InvalidRebateTiers);
+    }
+    for pair in rebate_tiers.windows(2) {
+        require!(pair[0].threshold < pair[1].threshold, FortunaError::InvalidRebateTiers);
+    }
+
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.rebate_tiers = rebate_tiers;
+    msg!("Rebate tiers updated: {} tiers", protocol_state.rebate_tiers.len());
+    Ok(())
+}
+
+/// Replace the pool-size fee taper schedule (admin only)
+pub fn set_fee_taper_schedule(
+    ctx: Context<UpdateProtocol>,
+    fee_taper_schedule: Vec<FeeTaperTier>,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    require!(fee_taper_schedule.len() <= MAX_FEE_TAPER_TIERS, FortunaError::InvalidFeeTaperSchedule);
+
+    for tier in &fee_taper_schedule {
+        require!(tier.fee_multiplier_bps <= BPS_DENOMINATOR, FortunaError::InvalidFeeTaperSchedule);
+    }
+    for pair in fee_taper_schedule.windows(2) {
+        require!(pair[0].pool_threshold < pair[1].pool_threshold, FortunaError::InvalidFeeTaperSchedule);
+    }
+
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    protocol_state.fee_taper_schedule = fee_taper_schedule;
+    msg!("Fee taper schedule updated: {} tiers", protocol_state.fee_taper_schedule.len());
+    Ok(())
+}
+
+/// Exempt a market from all fees (admin only)
+pub fn set_market_fee_exempt(
+    ctx: Context<SetMarketFeeExempt>,
+    fee_exempt: bool,
+) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let market = &mut ctx.accounts.market;
+    market.fee_exempt = fee_exempt;
+    msg!("Market {} fee exemption set to: {}", market.market_id, fee_exempt);
+    Ok(())
+}
+
+/// Send lamports above a program account's rent-exempt minimum to the treasury (admin only)
+pub fn recover_excess_lamports(ctx: Context<RecoverExcessLamports>) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let target = ctx.accounts.target.to_account_info();
+
+    // A native SOL market's balance above rent is bettors' stakes, not stray deposits
+    let market = Market::try_deserialize(&mut &target.try_borrow_data()?[..]);
+    if let Ok(market) = market {
+        require!(!market.native_sol, FortunaError::WrongMarketDenomination);
+    }
+
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(target.data_len());
+    let excess = target.lamports().saturating_sub(rent_exempt_minimum);
+    require!(excess > 0, FortunaError::NoExcessLamports);
+
+    **target.try_borrow_mut_lamports()? -= excess;
+    **ctx.accounts.treasury.try_borrow_mut_lamports()? = ctx.accounts.treasury.lamports()
+        .checked_add(excess)
+        .ok_or(FortunaError::Overflow)?;
+
+    msg!("Recovered {} excess lamports from {}", excess, target.key());
+    Ok(())
+}
+
+// ============================================================================
+// License Management
+// ============================================================================
+
+/// Issue a new license to a wallet
+pub fn issue_license(
+    ctx: Context<IssueLicense>,
+    license_key: [u8; 32],
+    license_type: u8,
+    allowed_domains: Vec<String>,
+    allowed_wallets: Vec<Pubkey>,
+    max_markets: u32,
+    is_transferable: bool,
+    expires_at: i64,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    // Validate license type
+    let lt = LicenseType::from_u8(license_type)
+        .ok_or(FortunaError::InvalidLicenseType)?;
+
+    // Validate domains
+    require!(allowed_domains.len() <= MAX_LICENSE_DOMAINS, FortunaError::TooManyDomains);
+    for domain in &allowed_domains {
+        require!(domain.len() <= MAX_DOMAIN_NAME_LEN, FortunaError::DomainTooLong);
+    }
+
+    // Validate wallets
+    require!(allowed_wallets.len() <= MAX_LICENSE_WALLETS, FortunaError::TooManyWallets);
+
+    let clock = Clock::get()?;
+    let license = &mut ctx.accounts.license;
+    let protocol_state = &mut ctx.accounts.protocol_state;
+
+    license.license_key = license_key;
+    license.holder = ctx.accounts.holder.key();
+    license.license_type = lt;
+    license.features = LicenseFeatures::for_license_type(lt);
+    license.allowed_domains = allowed_domains;
+    license.allowed_wallets = allowed_wallets;
+    license.max_markets = if max_markets == 0 { lt.max_markets() } else { max_markets };
+    license.markets_created = 0;
+    license.is_active = true;
+    license.is_transferable = is_transferable;
+    license.issued_at = clock.unix_timestamp;
+    license.expires_at = expires_at;
+    license.last_used_at = 0;
+    license.window_start = 0;
+    license.created_in_window = 0;
+    license.issued_by = ctx.accounts.authority.key();
+    license.bump = ctx.bumps.license;
+
+    protocol_state.total_licenses = protocol_state.total_licenses.checked_add(1)
+        .ok_or(FortunaError::Overflow)?;
+
+    msg!("License issued: {} license to {}", lt.name(), license.holder);
+
+    emit!(LicenseIssued {
+        license_key,
+        holder: license.holder,
+        license_type: lt,
+        issued_by: license.issued_by,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Revoke/deactivate a license
+pub fn revoke_license(ctx: Context<RevokeLicense>) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let license = &mut ctx.accounts.license;
+    license.is_active = false;
+    msg!("License revoked for holder: {}", license.holder);
+
+    emit!(LicenseRevoked {
+        license_key: license.license_key,
+        holder: license.holder,
+        license_type: license.license_type,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Activate a previously deactivated license
+pub fn activate_license(ctx: Context<RevokeLicense>) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let license = &mut ctx.accounts.license;
+    license.is_active = true;
+    msg!("License activated for holder: {}", license.holder);
+
+    emit_license_updated(license)?;
+
+    Ok(())
+}
+
+/// Ensure a holder-side license operation targets an active, unexpired license
+fn require_license_usable(license: &License) -> Result<()> {
+    require!(license.is_active, FortunaError::LicenseNotActive);
+    require!(
+        !license.is_expired(Clock::get()?.unix_timestamp),
+        FortunaError::LicenseExpired
+    );
+    Ok(())
+}
+
+/// Transfer a license to a new holder
+pub fn transfer_license(ctx: Context<TransferLicense>) -> Result<()> {
+    let license = &mut ctx.accounts.license;
+    require_license_usable(license)?;
+
+    let old_holder = license.holder;
+    license.holder = ctx.accounts.new_holder.key();
+    // Clear allowed wallets on transfer (new holder can add their own)
+    license.allowed_wallets = vec![];
+    msg!("License transferred from {} to {}", old_holder, license.holder);
+
+    emit!(LicenseTransferred {
+        license_key: license.license_key,
+        previous_holder: old_holder,
+        holder: license.holder,
+        license_type: license.license_type,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Update license settings (admin only)
+pub fn update_license(
+    ctx: Context<UpdateLicense>,
+    new_max_markets: Option<u32>,
+    new_expires_at: Option<i64>,
+    new_features: Option<LicenseFeatures>,
+) -> Result<()> {
+    record_authority_activity(&mut ctx.accounts.protocol_state)?;
+    let license = &mut ctx.accounts.license;
+
+    if let Some(max_markets) = new_max_markets {
+        license.max_markets = max_markets;
+        msg!("License max markets updated to: {}", max_markets);
+    }
+
+    if let Some(expires_at) = new_expires_at {
+        license.expires_at = expires_at;
+        msg!("License expiration updated to: {}", expires_at);
+    }
+
+    if let Some(features) = new_features {
+        license.features = features;
+        msg!("License features updated");
+    }
+
+    emit_license_updated(license)?;
+
+    Ok(())
+}
+
+/// Emit the current settings of a license after a change
+fn emit_license_updated(license: &License) -> Result<()> {
+    emit!(LicenseUpdated {
+        license_key: license.license_key,
+        holder: license.holder,
+        license_type: license.license_type,
+        is_active: license.is_active,
+        max_markets: license.max_markets,
+        expires_at: license.expires_at,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Add an authorized wallet to a license
+pub fn add_authorized_wallet(
+    ctx: Context<ModifyLicenseWallets>,
+    wallet: Pubkey,
+) -> Result<()> {
+    let license = &mut ctx.accounts.license;
+    require_license_usable(license)?;
+    require!(license.allowed_wallets.len() < MAX_LICENSE_WALLETS, FortunaError::TooManyWallets);
+
+    if !license.allowed_wallets.contains(&wallet) {
+        license.allowed_wallets.push(wallet);
+        msg!("Wallet {} added to license", wallet);
+    }
+
+    Ok(())
+}
+
+/// Remove an authorized wallet from a license
+pub fn remove_authorized_wallet(
+    ctx: Context<ModifyLicenseWallets>,
+    wallet: Pubkey,
+) -> Result<()> {
+    let license = &mut ctx.accounts.license;
+    require_license_usable(license)?;
+    license.allowed_wallets.retain(|w| *w != wallet);
+    msg!("Wallet {} removed from license", wallet);
+    Ok(())
+}
+
+/// Add an authorized domain to a license
+pub fn add_authorized_domain(
+    ctx: Context<ModifyLicenseDomains>,
+    domain: String,
+) -> Result<()> {
+    let license = &mut ctx.accounts.license;
+    require_license_usable(license)?;
+    require!(license.allowed_domains.len() < MAX_LICENSE_DOMAINS, FortunaError::TooManyDomains);
+    require!(domain.len() <= MAX_DOMAIN_NAME_LEN, FortunaError::DomainTooLong);
+
+    if !license.allowed_domains.contains(&domain) {
+        license.allowed_domains.push(domain.clone());
+        msg!("Domain {} added to license", domain);
+    }
+
+    Ok(())
+}
+
+/// Remove an authorized domain from a license
+pub fn remove_authorized_domain(
+    ctx: Context<ModifyLicenseDomains>,
+    domain: String,
+) -> Result<()> {
+    let license = &mut ctx.accounts.license;
+    require_license_usable(license)?;
+    license.allowed_domains.retain(|d| *d != domain);
+    msg!("Domain {} removed from license", domain);
+    Ok(())
+}
+
+/// Report a license's remaining quota, validity and features as return data
+pub fn license_status_view(ctx: Context<LicenseStatusView>) -> Result<LicenseStatus> {
+    let license = &ctx.accounts.license;
+    Ok(LicenseStatus {
+        remaining_markets: license.remaining_markets(),
+        is_valid: license.is_valid(Clock::get()?.unix_timestamp),
+        is_active: license.is_active,
+        expires_at: license.expires_at,
+        features: license.features,
+    })
+}
+
+// ============================================================================
+// Native SOL Markets
+// ============================================================================
+
+/// Move lamports out of a program-owned market account
+fn transfer_lamports_from_market(
+    market: &AccountInfo,
+    to: &AccountInfo,
+    amount: u64,
+) -> Result<()> {
+    **market.try_borrow_mut_lamports()? = market.lamports()
+        .checked_sub(amount)
+        .ok_or(FortunaError::Overflow)?;
+    **to.try_borrow_mut_lamports()? = to.lamports()
+        .checked_add(amount)
+        .ok_or(FortunaError::Overflow)?;
+    Ok(())
+}
+
+/// Place a bet on a native SOL market
+pub fn place_bet_sol(ctx: Context<PlaceBetSol>, outcome_index: u8) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
+    let protocol_state = &ctx.accounts.protocol_state;
+
+    // Validate outcome
+    require!(
+        (outcome_index as usize) < market.outcomes.len(),
+        FortunaError::InvalidOutcome
+    );
+    require!(market.outcomes[outcome_index as usize].is_open, FortunaError::OutcomeClosed);
+
+    // Check betting deadline
+    let clock = Clock::get()?;
+    require!(
+        !market.is_betting_closed(clock.unix_timestamp),
+        FortunaError::BettingDeadlinePassed
+    );
+    require_not_self_excluded(&ctx.accounts.self_exclusion, clock.unix_timestamp)?;
+
+    let bet_amount = market.bet_amount;
+    let bet_key = ctx.accounts.bet.key();
+
+    let (pool_fee, creator_fee, protocol_fee, net_amount) =
+        protocol_state.calculate_market_fees(market, bet_amount, clock.unix_timestamp);
+    require!(market.outcome_has_room(outcome_index, net_amount), FortunaError::OutcomeCapReached);
+    require!(market.has_bet_capacity(), FortunaError::MarketBetLimitReached);
+
+    // Stake, pool fee and escrowed creator fee are held by the market account itself
+    let cpi_program = ctx.accounts.system_program.to_account_info();
+    system_program::transfer(
+        CpiContext::new(cpi_program.clone(), system_program::Transfer {
+            from: ctx.accounts.bettor.to_account_info(),
+            to: market.to_account_info(),
+        }),
+        net_amount + pool_fee + creator_fee,
+    )?;
+
+    if protocol_fee > 0 {
+        system_program::transfer(
+            CpiContext::new(cpi_program, system_program::Transfer {
+                from: ctx.accounts.bettor.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+            }),
+            protocol_fee,
+        )?;
+    }
+
+    // Update market state
+    market.total_pool = market.total_pool.checked_add(net_amount)
+        .ok_or(FortunaError::Overflow)?;
+    market.bonus_pool = market.bonus_pool.checked_add(pool_fee)
+        .ok_or(FortunaError::Overflow)?;
+    market.escrowed_creator_fees = market.escrowed_creator_fees.checked_add(creator_fee)
+        .ok_or(FortunaError::Overflow)?;
+    market.bet_count = market.bet_count.checked_add(1)
+        .ok_or(FortunaError::Overflow)?;
+
+    // Update outcome
+    let outcome = &mut market.outcomes[outcome_index as usize];
+    outcome.total_amount = outcome.total_amount.checked_add(net_amount)
+        .ok_or(FortunaError::Overflow)?;
+    outcome.bettor_count = outcome.bettor_count.checked_add(1)
+        .ok_or(FortunaError::Overflow)?;
+    if outcome.first_bet == Pubkey::default() {
+        outcome.first_bet = bet_key;
+    }
+
+    // Create bet record
+    let bet = &mut ctx.accounts.bet;
+    bet.market = market.key();
+    bet.bettor = ctx.accounts.bettor.key();
+    bet.outcome_index = outcome_index;
+    bet.original_amount = bet_amount;
+    bet.pool_amount = net_amount;
+    bet.creator_fee = creator_fee;
+    bet.bet_state = BetState::Active;
+    bet.loss_protection_bps = 0;
+    bet.protected_amount = 0;
+    bet.voted = false;
+    bet.resolution_requested = false;
+    bet.collateral_mint = Pubkey::default();
+    bet.collateral_amount = 0;
+    bet.memo = String::new();
+    bet.placed_at = clock.unix_timestamp;
+    bet.bump = ctx.bumps.bet;
+
+    ctx.accounts.leaderboard.record(bet.bettor, bet.pool_amount);
+
+    msg!("SOL bet placed: {} lamports on outcome {} (index {})",
+        bet_amount, market.outcomes[outcome_index as usize].label, outcome_index);
+
+    InstructionResult::for_position(market, bet, 0).set()?;
+
+    Ok(())
+}
+
+/// Claim winnings from a resolved native SOL market
+pub fn claim_winnings_sol(ctx: Context<ClaimWinningsSol>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Resolved)?;
+    let market = &mut ctx.accounts.market;
+    let bet = &mut ctx.accounts.bet;
+
+    require_claim_delay_elapsed(&ctx.accounts.protocol_state, market)?;
+    require!(
+        !market.creator_fee_redirect_pending(Clock::get()?.unix_timestamp),
+        FortunaError::CreatorFeeRedirectPending
+    );
+
+    require!(
+        bet.outcome_index == market.winning_outcome,
+        FortunaError::LostBet
+    );
+
+    let (pool_share, bonus_share) = market.calculate_payout(&bet.key(), bet);
+    let payout = pool_share + bonus_share;
+    require!(payout > 0, FortunaError::LostBet);
+
+    market.winning_bets_claimed = market.winning_bets_claimed.checked_add(1)
+        .ok_or(FortunaError::Overflow)?;
+    market.record_payout(pool_share, bonus_share)?;
+    bet.bet_state = BetState::ClaimedWin;
+
+    transfer_lamports_from_market(
+        &ctx.accounts.market.to_account_info(),
+        &ctx.accounts.claimer.to_account_info(),
+        payout,
+    )?;
+
+    msg!("SOL winnings claimed: {} lamports", payout);
+
+    let market = &ctx.accounts.market;
+    emit!(WinningsClaimed {
+        market: market.key(),
+        bettor: ctx.accounts.bet.bettor,
+        amount: payout,
+        winning_outcome: market.winning_outcome,
+        winning_outcome_label: market.outcomes[market.winning_outcome as usize].label.clone(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    InstructionResult::for_position(market, &ctx.accounts.bet, payout).set()?;
+
+    Ok(())
+}
+
+/// Refund a bet on a cancelled native SOL market
+pub fn claim_refund_sol(ctx: Context<ClaimRefundSol>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Cancelled)?;
+    let market = &mut ctx.accounts.market;
+    let bet = &mut ctx.accounts.bet;
+    let refund = bet.pool_amount + bet.creator_fee;
+
+    market.escrowed_creator_fees = market.escrowed_creator_fees.checked_sub(bet.creator_fee)
+        .ok_or(FortunaError::Overflow)?;
+    market.refunds_claimed = market.refunds_claimed.checked_add(1)
+        .ok_or(FortunaError::Overflow)?;
+    bet.bet_state = BetState::Refunded;
+
+    transfer_lamports_from_market(
+        &ctx.accounts.market.to_account_info(),
+        &ctx.accounts.claimer.to_account_info(),
+        refund,
+    )?;
+
+    msg!("SOL refund claimed: {} lamports", refund);
+
+    Ok(())
+}
+
+/// Withdraw a bet from a native SOL market before the betting deadline
+pub fn withdraw_bet_sol(ctx: Context<WithdrawBetSol>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
+    let bet = &mut ctx.accounts.bet;
+
+    let clock = Clock::get()?;
+    require!(
+        !market.is_betting_closed(clock.unix_timestamp),
+        FortunaError::WithdrawDeadlinePassed
+    );
+    require!(bet.is_withdrawable_at(market, clock.unix_timestamp), FortunaError::BetStillLocked);
+
+    if market.payout_mode == PayoutMode::WinnerTakeAll {
+        require!(
+            market.outcomes[bet.outcome_index as usize].first_bet != bet.key(),
+            FortunaError::LeadingBetLocked
+        );
+    }
+
+    let withdraw_amount = bet.pool_amount;
+    let refund = withdraw_amount + bet.creator_fee;
+
+    market.total_pool = market.total_pool.checked_sub(withdraw_amount)
+        .ok_or(FortunaError::Overflow)?;
+    market.escrowed_creator_fees = market.escrowed_creator_fees.checked_sub(bet.creator_fee)
+        .ok_or(FortunaError::Overflow)?;
+    market.bet_count = market.bet_count.checked_sub(1)
+        .ok_or(FortunaError::Overflow)?;
+
+    let outcome = &mut market.outcomes[bet.outcome_index as usize];
+    outcome.total_amount = outcome.total_amount.checked_sub(withdraw_amount)
+        .ok_or(FortunaError::Overflow)?;
+    outcome.bettor_count = outcome.bettor_count.checked_sub(1)
+        .ok_or(FortunaError::Overflow)?;
+
+    bet.bet_state = BetState::Withdrawn;
+    ctx.accounts.leaderboard.remove(bet.bettor);
+
+    transfer_lamports_from_market(
+        &ctx.accounts.market.to_account_info(),
+        &ctx.accounts.bettor.to_account_info(),
+        refund,
+    )?;
+
+    msg!("SOL bet withdrawn: {} lamports (pool and protocol fees non-refundable)", refund);
+
+    Ok(())
+}
+
+/// Release a resolved native SOL market's escrowed creator fees to the creator fee wallet
+pub fn claim_creator_fees_sol(ctx: Context<ClaimCreatorFeesSol>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Resolved)?;
+    let market = &mut ctx.accounts.market;
+    let amount = market.escrowed_creator_fees;
+    require!(amount > 0, FortunaError::NoEscrowedCreatorFees);
+    require!(
+        !market.creator_fees_forfeited(Clock::get()?.unix_timestamp),
+        FortunaError::CreatorFeesForfeited
+    );
+    market.escrowed_creator_fees = 0;
+
+    transfer_lamports_from_market(
+        &ctx.accounts.market.to_account_info(),
+        &ctx.accounts.creator_fee_wallet.to_account_info(),
+        amount,
+    )?;
+
+    msg!("SOL creator fees released: {} lamports", amount);
+
+    Ok(())
+}
+
+/// Move a low-participation native SOL market's escrowed creator fees into its bonus pool
+pub fn redirect_creator_fees_sol(ctx: Context<RedirectCreatorFeesSol>) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Resolved)?;
+    let market = &mut ctx.accounts.market;
+    let amount = market.escrowed_creator_fees;
+    require!(amount > 0, FortunaError::NoEscrowedCreatorFees);
+    require!(
+        market.creator_fees_forfeited(Clock::get()?.unix_timestamp),
+        FortunaError::CreatorFeesNotForfeited
+    );
+
+    // Both balances are held by the market account, so only the accounting moves
+    market.escrowed_creator_fees = 0;
+    market.bonus_pool = market.bonus_pool.checked_add(amount)
+        .ok_or(FortunaError::Overflow)?;
+
+    msg!("SOL creator fees redirected to bonus pool: {} lamports", amount);
+
+    Ok(())
+}
+
+// ============================================================================
+// Multi-Collateral Markets
+// ============================================================================
+
+/// Place a bet in one of a multi-collateral market's accepted mints
+/// The market's fixed bet amount is in common units; the stake is converted at the mint's price
+pub fn place_collateral_bet(ctx: Context<PlaceCollateralBet>, outcome_index: u8) -> Result<()> {
+    require_status(&ctx.accounts.market, MarketStatus::Open)?;
+    let market = &mut ctx.accounts.market;
+    let protocol_state = &ctx.accounts.protocol_state;
+
+    require!(
+        (outcome_index as usize) < market.outcomes.len(),
+        FortunaError::InvalidOutcome
+    );
+    require!(market.outcomes[outcome_index as usize].is_open, FortunaError::OutcomeClosed);
+
+    let clock = Clock::get()?;
+    require!(
+        !market.is_betting_closed(clock.unix_timestamp),
+        FortunaError::BettingDeadlinePassed
+    );
+    require_not_self_excluded(&ctx.accounts.self_exclusion, clock.unix_timestamp)?;
+
+    let collateral = market
+        .collateral(&ctx.accounts.collateral_mint.key())
+        .ok_or(FortunaError::CollateralNotAccepted)?;
+    let stake = collateral
+        .from_common_units(market.bet_amount)
+        .ok_or(FortunaError::Overflow)?;
+    require!(stake > 0, FortunaError::InvalidBetAmount);
+
+    // Fees are taken in the collateral mint; the pool fee stays in the collateral vault
+    let (pool_fee, creator_fee, protocol_fee, net_amount) =
+        protocol_state.calculate_market_fees(market, stake, clock.unix_timestamp);
+    let pool_units = collateral.to_common_units(net_amount).ok_or(FortunaError::Overflow)?;
+    require!(market.outcome_has_room(outcome_index, pool_units), FortunaError::OutcomeCapReached);
+    require!(market.has_bet_capacity(), FortunaError::MarketBetLimitReached);
+
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    for (to, amount) in [
+        (ctx.accounts.collateral_vault.to_account_info(), net_amount + pool_fee),
+        (ctx.accounts.treasury_token_account.to_account_info(), protocol_fee),
+        (ctx.accounts.creator_token_account.to_account_info(), creator_fee),
+    ] {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.bettor_token_account.to_account_info(),
+            to,
+            authority: ctx.accounts.bettor.to_account_info(),
+        };
+        token::transfer(CpiContext::new(cpi_program.clone(), cpi_accounts), amount)?;
+    }
+
+    // Pool accounting is in common units
+    let bonus_units = collateral.to_common_units(pool_fee).ok_or(FortunaError::Overflow)?;
+
+    market.total_pool = market.total_pool.checked_add(pool_units)
+        .ok_or(FortunaError::Overflow)?;
+    market.bonus_pool = market.bonus_pool.checked_add(bonus_units)
+        .ok_or(FortunaError::Overflow)?;
+    market.bet_count = market.bet_count.checked_add(1)
+        .ok_or(FortunaError::Overflow)?;
+
+    let bet_key = ctx.accounts.bet.key();
+    let outcome = &mut market.outcomes[outcome_index as usize];
+    outcome.total_amount = outcome.total_amount.checked_add(pool_units)
+        .ok_or(FortunaError::Overflow)?;
+    outcome.bettor_count = outcome.bettor_count.checked_add(1)
+        .ok_or(FortunaError::Overflow)?;
+    if outcome.first_bet == Pubkey::default() {
+        outcome.first_bet = bet_key;
+    }
 
-    // Mark bet as claimed
-    bet.claimed = true;
+    let bet = &mut ctx.accounts.bet;
+    bet.market = ctx.accounts.market.key();
+    bet.bettor = ctx.accounts.bettor.key();
+    bet.outcome_index = outcome_index;
+    bet.original_amount = ctx.accounts.market.bet_amount;
+    bet.pool_amount = pool_units;
+    // Collateral creator fees are paid out at bet time rather than escrowed
+    bet.creator_fee = 0;
+    bet.bet_state = BetState::Active;
+    bet.loss_protection_bps = 0;
+    bet.protected_amount = 0;
+    bet.voted = false;
+    bet.resolution_requested = false;
+    bet.collateral_mint = collateral.mint;
+    bet.collateral_amount = net_amount;
+    bet.memo = String::new();
+    bet.placed_at = clock.unix_timestamp;
+    bet.bump = ctx.bumps.bet;
 
-    msg!("Refund claimed: {} tokens", bet.pool_amount);
+    // Ranked by pool units so collateral and base-mint stakes compare directly
+    ctx.accounts.leaderboard.record(bet.bettor, bet.pool_amount);
+
+    msg!("Collateral bet placed: {} of mint {} ({} units) on outcome {}",
+        stake, collateral.mint, pool_units, outcome_index);
 
     Ok(())
 }
 
-/// Withdraw a bet before market resolution (user gets back their stake minus fees)
-pub fn withdraw_bet(ctx: Context<WithdrawBet>) -> Result<()> {
+/// Claim winnings or a refund from a multi-collateral market. A refund comes back in the
+/// bet's mint. Losing stakes may sit in any mint, so winnings take the same share of every
+/// collateral vault, and the last winner takes what is left. Remaining accounts hold a
+/// (collateral vault, claimer token account) pair for each other accepted mint, in market order.
+pub fn claim_collateral<'info>(ctx: Context<'_, '_, '_, 'info, ClaimCollateral<'info>>) -> Result<()> {
+    require_settled(&ctx.accounts.market)?;
     let market = &mut ctx.accounts.market;
     let bet = &mut ctx.accounts.bet;
+    let market_key = market.key();
+    let claimer = ctx.accounts.claimer.key();
 
-    // Check betting is still open (can only withdraw before deadline)
-    let clock = Clock::get()?;
-    require!(
-        !market.is_betting_closed(clock.unix_timestamp),
-        FortunaError::WithdrawDeadlinePassed
-    );
+    // (mint, vault, claimer token account, amount) for each transfer
+    let mut payments: Vec<(Pubkey, AccountInfo<'info>, AccountInfo<'info>, u64)> = Vec::new();
 
-    let withdraw_amount = bet.pool_amount;
+    if market.status == MarketStatus::Cancelled {
+        require!(
+            ctx.accounts.collateral_vault.amount >= bet.collateral_amount,
+            FortunaError::InsufficientFunds
+        );
+        market.refunds_claimed = market.refunds_claimed.checked_add(1)
+            .ok_or(FortunaError::Overflow)?;
+        payments.push((
+            bet.collateral_mint,
+            ctx.accounts.collateral_vault.to_account_info(),
+            ctx.accounts.claimer_token_account.to_account_info(),
+            bet.collateral_amount,
+        ));
+        bet.bet_state = BetState::Refunded;
+    } else {
+        require_claim_delay_elapsed(&ctx.accounts.protocol_state, market)?;
+        require!(
+            bet.outcome_index == market.winning_outcome,
+            FortunaError::LostBet
+        );
+        require!(
+            ctx.remaining_accounts.len() == 2 * (market.collateral_mints.len() - 1),
+            FortunaError::CollateralAccountMismatch
+        );
 
-    // Update market totals
-    market.total_pool = market.total_pool.checked_sub(withdraw_amount)
-        .ok_or(FortunaError::Overflow)?;
+        let (pool_share, bonus_share) = market.calculate_payout(&bet.key(), bet);
+        let units = pool_share.checked_add(bonus_share).ok_or(FortunaError::Overflow)?;
+        require!(units > 0, FortunaError::LostBet);
 
-    // Update outcome totals
-    let outcome = &mut market.outcomes[bet.outcome_index as usize];
-    outcome.total_amount = outcome.total_amount.checked_sub(withdraw_amount)
-        .ok_or(FortunaError::Overflow)?;
-    outcome.bettor_count = outcome.bettor_count.checked_sub(1)
-        .ok_or(FortunaError::Overflow)?;
+        // Common units still owed to the winners who haven't claimed, this one included
+        let owed = (market.total_pool as u128 + market.bonus_pool as u128)
+            .saturating_sub(market.pool_paid as u128 + market.bonus_paid as u128)
+            .max(units as u128);
+        market.winning_bets_claimed = market.winning_bets_claimed.checked_add(1)
+            .ok_or(FortunaError::Overflow)?;
+        market.record_payout(pool_share, bonus_share)?;
+        let last_winner = market.winning_bets_claimed >= market.winning_bets_total;
+
+        let mut other_accounts = ctx.remaining_accounts.chunks(2);
+        for collateral in market.collateral_mints.iter() {
+            let (vault_info, claimer_info, vault_amount) = if collateral.mint == bet.collateral_mint {
+                (
+                    ctx.accounts.collateral_vault.to_account_info(),
+                    ctx.accounts.claimer_token_account.to_account_info(),
+                    ctx.accounts.collateral_vault.amount,
+                )
+            } else {
+                let pair = other_accounts.next().ok_or(FortunaError::CollateralAccountMismatch)?;
+                let (vault_key, _) = Pubkey::find_program_address(
+                    &[COLLATERAL_VAULT_SEED, market_key.as_ref(), collateral.mint.as_ref()],
+                    &crate::ID,
+                );
+                require!(
+                    pair[0].key() == vault_key && pair[0].is_writable && pair[1].is_writable,
+                    FortunaError::CollateralAccountMismatch
+                );
+                // A mint nobody bet in never had its vault created
+                if pair[0].data_is_empty() {
+                    continue;
+                }
+                let vault = load_token_account(&pair[0])?;
+                let claim_account = load_token_account(&pair[1])?;
+                require!(
+                    claim_account.owner == claimer
+                        && claim_account.mint == collateral.mint
+                        && market.accepts_claim_account(&pair[1].key(), &claimer, &collateral.mint),
+                    FortunaError::CollateralAccountMismatch
+                );
+                (pair[0].clone(), pair[1].clone(), vault.amount)
+            };
+
+            let amount = if last_winner {
+                vault_amount
+            } else {
+                (vault_amount as u128 * units as u128 / owed) as u64
+            };
+            if amount > 0 {
+                payments.push((collateral.mint, vault_info, claimer_info, amount));
+            }
+        }
+        bet.bet_state = BetState::ClaimedWin;
+    }
 
-    // Transfer tokens back to bettor from market vault
+    let market = &ctx.accounts.market;
+    let market_creator = market.creator;
     let market_id_bytes = market.market_id.to_le_bytes();
     let seeds = &[
         MARKET_SEED,
+        market_creator.as_ref(),
         market_id_bytes.as_ref(),
         &[market.bump],
     ];
     let signer = &[&seeds[..]];
-
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.market_vault.to_account_info(),
-        to: ctx.accounts.bettor_token_account.to_account_info(),
-        authority: ctx.accounts.market.to_account_info(),
-    };
     let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-    token::transfer(cpi_ctx, withdraw_amount)?;
 
-    // Mark bet as claimed/withdrawn
-    bet.claimed = true;
+    for (mint, from, to, amount) in payments {
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: ctx.accounts.market.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
 
-    msg!("Bet withdrawn: {} tokens (fees non-refundable)", withdraw_amount);
+        msg!("Collateral claimed: {} of mint {}", amount, mint);
+    }
 
     Ok(())
 }
 
-/// Update protocol settings (admin only)
-pub fn update_protocol(
-    ctx: Context<UpdateProtocol>,
-    new_treasury: Option<Pubkey>,
-    new_protocol_fee_bps: Option<u16>,
-    new_creator_fee_bps: Option<u16>,
-    new_pool_fee_bps: Option<u16>,
-) -> Result<()> {
-    let protocol_state = &mut ctx.accounts.protocol_state;
-
-    // Update treasury if provided
-    if let Some(treasury) = new_treasury {
-        protocol_state.treasury = treasury;
-        msg!("Treasury updated to: {}", treasury);
-    }
-
-    // Calculate new total fee
-    let protocol_fee = new_protocol_fee_bps.unwrap_or(protocol_state.protocol_fee_bps);
-    let creator_fee = new_creator_fee_bps.unwrap_or(protocol_state.creator_fee_bps);
-    let pool_fee = new_pool_fee_bps.unwrap_or(protocol_state.pool_fee_bps);
-
-    let total_fee = protocol_fee + creator_fee + pool_fee;
-    require!(total_fee <= MAX_TOTAL_FEE_BPS, FortunaError::InvalidFeeConfig);
-
-    // Update fees if provided
-    if let Some(fee) = new_protocol_fee_bps {
-        protocol_state.protocol_fee_bps = fee;
-        msg!("Protocol fee updated to: {}bps", fee);
-    }
+/// Deserialize an SPL token account passed through remaining accounts
+fn load_token_account(info: &AccountInfo) -> Result<token::TokenAccount> {
+    require!(info.owner == &token::ID, FortunaError::CollateralAccountMismatch);
+    token::TokenAccount::try_deserialize(&mut &info.data.borrow()[..])
+}
 
-    if let Some(fee) = new_creator_fee_bps {
-        protocol_state.creator_fee_bps = fee;
-        msg!("Creator fee updated to: {}bps", fee);
-    }
+// ============================================================================
+// Parlays
+// ============================================================================
 
-    if let Some(fee) = new_pool_fee_bps {
-        protocol_state.pool_fee_bps = fee;
-        msg!("Pool fee updated to: {}bps", fee);
+/// Deserialize a program-owned account passed through remaining accounts
+fn load_program_account<T: AccountDeserialize>(info: &AccountInfo, error: FortunaError) -> Result<T> {
+    if info.owner != &crate::ID {
+        return Err(error.into());
     }
-
-    Ok(())
+    T::try_deserialize(&mut &info.data.borrow()[..])
 }
 
-/// Toggle whether license is required to create markets
-pub fn set_require_license(
-    ctx: Context<UpdateProtocol>,
-    require_license: bool,
-) -> Result<()> {
-    let protocol_state = &mut ctx.accounts.protocol_state;
-    protocol_state.require_license = require_license;
-    msg!("License requirement set to: {}", require_license);
-    Ok(())
-}
+/// Stake on a parlay built from bets the bettor already holds on open markets
+pub fn create_parlay(ctx: Context<CreateParlay>, parlay_id: u64, stake: u64) -> Result<()> {
+    require!(stake > 0, FortunaError::InvalidBetAmount);
 
-// ============================================================================
-// License Management
-// ============================================================================
+    let leg_accounts = ctx.remaining_accounts;
+    let leg_count = leg_accounts.len() / 2;
+    require!(
+        leg_accounts.len() % 2 == 0
+            && (MIN_PARLAY_LEGS..=MAX_PARLAY_LEGS).contains(&leg_count),
+        FortunaError::InvalidParlayLegs
+    );
 
-/// Issue a new license to a wallet
-pub fn issue_license(
-    ctx: Context<IssueLicense>,
-    license_key: [u8; 32],
-    license_type: u8,
-    allowed_domains: Vec<String>,
-    allowed_wallets: Vec<Pubkey>,
-    max_markets: u32,
-    is_transferable: bool,
-    expires_at: i64,
-) -> Result<()> {
-    // Validate license type
-    let lt = LicenseType::from_u8(license_type)
-        .ok_or(FortunaError::InvalidLicenseType)?;
+    let clock = Clock::get()?;
+    require_not_self_excluded(&ctx.accounts.self_exclusion, clock.unix_timestamp)?;
+    let bettor = ctx.accounts.bettor.key();
+    let token_mint = ctx.accounts.token_mint.key();
+
+    let mut legs: Vec<ParlayLeg> = Vec::with_capacity(leg_count);
+    let mut markets: Vec<Market> = Vec::with_capacity(leg_count);
+    for pair in leg_accounts.chunks(2) {
+        require!(pair[0].is_writable, FortunaError::ParlayLegMismatch);
+        let market: Market = load_program_account(&pair[0], FortunaError::ParlayLegMismatch)?;
+        let bet: Bet = load_program_account(&pair[1], FortunaError::ParlayLegMismatch)?;
+
+        require_status(&market, MarketStatus::Open)?;
+        require!(!market.paused, FortunaError::MarketPaused);
+        require!(
+            !market.is_betting_closed(clock.unix_timestamp),
+            FortunaError::BettingDeadlinePassed
+        );
+        require!(
+            !market.native_sol && market.token_mint == token_mint,
+            FortunaError::WrongMarketDenomination
+        );
+        require!(
+            bet.market == pair[0].key() && bet.bettor == bettor && bet.is_active(),
+            FortunaError::ParlayLegMismatch
+        );
+        require!(
+            legs.iter().all(|leg| leg.market != pair[0].key()),
+            FortunaError::InvalidParlayLegs
+        );
 
-    // Validate domains
-    require!(allowed_domains.len() <= MAX_LICENSE_DOMAINS, FortunaError::TooManyDomains);
-    for domain in &allowed_domains {
-        require!(domain.len() <= MAX_DOMAIN_NAME_LEN, FortunaError::DomainTooLong);
+        legs.push(ParlayLeg {
+            market: pair[0].key(),
+            bet: pair[1].key(),
+            outcome_index: bet.outcome_index,
+        });
+        markets.push(market);
     }
 
-    // Validate wallets
-    require!(allowed_wallets.len() <= MAX_LICENSE_WALLETS, FortunaError::TooManyWallets);
+    let payout = Parlay::combined_payout(stake, &legs, &markets).ok_or(FortunaError::Overflow)?;
 
-    let clock = Clock::get()?;
-    let license = &mut ctx.accounts.license;
-    let protocol_state = &mut ctx.accounts.protocol_state;
+    // Leg markets stay open to closing only once this parlay has settled
+    for (market, pair) in markets.iter_mut().zip(leg_accounts.chunks(2)) {
+        market.open_parlays = market.open_parlays.checked_add(1).ok_or(FortunaError::Overflow)?;
+        market.try_serialize(&mut &mut pair[0].try_borrow_mut_data()?[..])?;
+    }
 
-    license.license_key = license_key;
-    license.holder = ctx.accounts.holder.key();
-    license.license_type = lt;
-    license.features = LicenseFeatures::for_license_type(lt);
-    license.allowed_domains = allowed_domains;
-    license.allowed_wallets = allowed_wallets;
-    license.max_markets = if max_markets == 0 { lt.max_markets() } else { max_markets };
-    license.markets_created = 0;
-    license.is_active = true;
-    license.is_transferable = is_transferable;
-    license.issued_at = clock.unix_timestamp;
-    license.expires_at = expires_at;
-    license.last_used_at = 0;
-    license.issued_by = ctx.accounts.authority.key();
-    license.bump = ctx.bumps.license;
-    license.reserved = vec![];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.bettor_token_account.to_account_info(),
+        to: ctx.accounts.parlay_vault.to_account_info(),
+        authority: ctx.accounts.bettor.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, stake)?;
 
-    protocol_state.total_licenses = protocol_state.total_licenses.checked_add(1)
-        .ok_or(FortunaError::Overflow)?;
+    // The vault must be able to pay every open parlay in full should all of them win
+    let book = &mut ctx.accounts.parlay_book;
+    book.liability = book.liability.checked_add(payout).ok_or(FortunaError::Overflow)?;
+    ctx.accounts.parlay_vault.reload()?;
+    require!(
+        ctx.accounts.parlay_vault.amount >= book.liability,
+        FortunaError::ParlayReserveInsufficient
+    );
 
-    msg!("License issued: {} license to {}", lt.name(), license.holder);
+    let parlay = &mut ctx.accounts.parlay;
+    parlay.parlay_id = parlay_id;
+    parlay.bettor = bettor;
+    parlay.token_mint = token_mint;
+    parlay.legs = legs;
+    parlay.stake = stake;
+    parlay.payout = payout;
+    parlay.claimed = false;
+    parlay.created_at = clock.unix_timestamp;
+    parlay.bump = ctx.bumps.parlay;
 
-    Ok(())
-}
+    msg!("Parlay {} created: {} legs, {} staked, {} if every leg wins",
+        parlay_id, leg_count, stake, payout);
 
-/// Revoke/deactivate a license
-pub fn revoke_license(ctx: Context<RevokeLicense>) -> Result<()> {
-    let license = &mut ctx.accounts.license;
-    license.is_active = false;
-    msg!("License revoked for holder: {}", license.holder);
     Ok(())
 }
 
-/// Activate a previously deactivated license
-pub fn activate_license(ctx: Context<RevokeLicense>) -> Result<()> {
-    let license = &mut ctx.accounts.license;
-    license.is_active = true;
-    msg!("License activated for holder: {}", license.holder);
-    Ok(())
+/// Load a parlay's leg markets from remaining accounts, in leg order
+fn load_parlay_markets(parlay: &Parlay, leg_accounts: &[AccountInfo]) -> Result<Vec<Market>> {
+    require!(leg_accounts.len() == parlay.legs.len(), FortunaError::ParlayLegMismatch);
+    let mut markets: Vec<Market> = Vec::with_capacity(parlay.legs.len());
+    for (leg, info) in parlay.legs.iter().zip(leg_accounts) {
+        require!(info.key() == leg.market && info.is_writable, FortunaError::ParlayLegMismatch);
+        markets.push(load_program_account(info, FortunaError::ParlayLegMismatch)?);
+    }
+    Ok(markets)
 }
 
-/// Transfer a license to a new holder
-pub fn transfer_license(ctx: Context<TransferLicense>) -> Result<()> {
-    let license = &mut ctx.accounts.license;
-    let old_holder = license.holder;
-    license.holder = ctx.accounts.new_holder.key();
-    // Clear allowed wallets on transfer (new holder can add their own)
-    license.allowed_wallets = vec![];
-    msg!("License transferred from {} to {}", old_holder, license.holder);
+/// Drop a settled parlay from its leg markets' open parlay counts so they can close
+fn release_parlay_legs(markets: &mut [Market], leg_accounts: &[AccountInfo]) -> Result<()> {
+    for (market, info) in markets.iter_mut().zip(leg_accounts) {
+        market.open_parlays = market.open_parlays.checked_sub(1).ok_or(FortunaError::Overflow)?;
+        market.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+    }
     Ok(())
 }
 
-/// Update license settings (admin only)
-pub fn update_license(
-    ctx: Context<UpdateLicense>,
-    new_max_markets: Option<u32>,
-    new_expires_at: Option<i64>,
-    new_features: Option<LicenseFeatures>,
-) -> Result<()> {
-    let license = &mut ctx.accounts.license;
-
-    if let Some(max_markets) = new_max_markets {
-        license.max_markets = max_markets;
-        msg!("License max markets updated to: {}", max_markets);
+/// What a parlay is owed once its legs have settled: the stake back if any leg was
+/// cancelled (or voided), otherwise the locked payout if every leg won and nothing if one lost
+fn parlay_settlement(parlay: &Parlay, markets: &[Market]) -> Result<u64> {
+    if markets.iter().any(|m| m.status == MarketStatus::Cancelled) {
+        return Ok(parlay.stake);
     }
-
-    if let Some(expires_at) = new_expires_at {
-        license.expires_at = expires_at;
-        msg!("License expiration updated to: {}", expires_at);
+    for market in markets {
+        require_status(market, MarketStatus::Resolved)?;
     }
+    let won = parlay.legs.iter().zip(markets).all(|(leg, m)| m.winning_outcome == leg.outcome_index);
+    Ok(if won { parlay.payout } else { 0 })
+}
 
-    if let Some(features) = new_features {
-        license.features = features;
-        msg!("License features updated");
+/// Pay a parlay whose legs all won, or refund it if any leg was cancelled
+pub fn claim_parlay(ctx: Context<ClaimParlay>) -> Result<()> {
+    let parlay = &ctx.accounts.parlay;
+    let mut markets = load_parlay_markets(parlay, ctx.remaining_accounts)?;
+
+    let amount = parlay_settlement(parlay, &markets)?;
+    require!(amount > 0, FortunaError::LostBet);
+    // A winning parlay waits out every leg's claim delay, as a single bet would
+    if markets.iter().all(|m| m.status == MarketStatus::Resolved) {
+        for market in &markets {
+            require_claim_delay_elapsed(&ctx.accounts.protocol_state, market)?;
+        }
     }
 
-    Ok(())
-}
+    let book = &mut ctx.accounts.parlay_book;
+    book.liability = book.liability.checked_sub(parlay.payout).ok_or(FortunaError::Overflow)?;
+    require!(ctx.accounts.parlay_vault.amount >= amount, FortunaError::InsufficientFunds);
 
-/// Add an authorized wallet to a license
-pub fn add_authorized_wallet(
-    ctx: Context<ModifyLicenseWallets>,
-    wallet: Pubkey,
-) -> Result<()> {
-    let license = &mut ctx.accounts.license;
-    require!(license.allowed_wallets.len() < MAX_LICENSE_WALLETS, FortunaError::TooManyWallets);
+    let seeds = &[PROTOCOL_SEED, &[ctx.accounts.protocol_state.bump]];
+    let signer = &[&seeds[..]];
 
-    if !license.allowed_wallets.contains(&wallet) {
-        license.allowed_wallets.push(wallet);
-        msg!("Wallet {} added to license", wallet);
-    }
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.parlay_vault.to_account_info(),
+        to: ctx.accounts.bettor_token_account.to_account_info(),
+        authority: ctx.accounts.protocol_state.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)?;
+
+    release_parlay_legs(&mut markets, ctx.remaining_accounts)?;
+    ctx.accounts.parlay.claimed = true;
+
+    msg!("Parlay claimed: {} tokens", amount);
 
     Ok(())
 }
 
-/// Remove an authorized wallet from a license
-pub fn remove_authorized_wallet(
-    ctx: Context<ModifyLicenseWallets>,
-    wallet: Pubkey,
-) -> Result<()> {
-    let license = &mut ctx.accounts.license;
-    license.allowed_wallets.retain(|w| *w != wallet);
-    msg!("Wallet {} removed from license", wallet);
+/// Close out a parlay with a losing leg so its payout stops counting against the reserve
+pub fn settle_lost_parlay(ctx: Context<SettleLostParlay>) -> Result<()> {
+    let parlay = &ctx.accounts.parlay;
+    let mut markets = load_parlay_markets(parlay, ctx.remaining_accounts)?;
+    require!(parlay_settlement(parlay, &markets)? == 0, FortunaError::ParlayNotLost);
+
+    let book = &mut ctx.accounts.parlay_book;
+    book.liability = book.liability.checked_sub(parlay.payout).ok_or(FortunaError::Overflow)?;
+    release_parlay_legs(&mut markets, ctx.remaining_accounts)?;
+    ctx.accounts.parlay.claimed = true;
+
+    msg!("Lost parlay {} settled, releasing {}",
+        ctx.accounts.parlay.parlay_id, ctx.accounts.parlay.payout);
+
     Ok(())
 }
 
-/// Add an authorized domain to a license
-pub fn add_authorized_domain(
-    ctx: Context<ModifyLicenseDomains>,
-    domain: String,
-) -> Result<()> {
-    let license = &mut ctx.accounts.license;
-    require!(license.allowed_domains.len() < MAX_LICENSE_DOMAINS, FortunaError::TooManyDomains);
-    require!(domain.len() <= MAX_DOMAIN_NAME_LEN, FortunaError::DomainTooLong);
+/// Add funds to a mint's parlay reserve
+pub fn fund_parlay_reserve(ctx: Context<FundParlayReserve>, amount: u64) -> Result<()> {
+    require!(amount > 0, FortunaError::InvalidBetAmount);
 
-    if !license.allowed_domains.contains(&domain) {
-        license.allowed_domains.push(domain.clone());
-        msg!("Domain {} added to license", domain);
+    let book = &mut ctx.accounts.parlay_book;
+    if book.token_mint == Pubkey::default() {
+        book.token_mint = ctx.accounts.token_mint.key();
+        book.bump = ctx.bumps.parlay_book;
     }
 
-    Ok(())
-}
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.funder_token_account.to_account_info(),
+        to: ctx.accounts.parlay_vault.to_account_info(),
+        authority: ctx.accounts.funder.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, amount)?;
+
+    msg!("Parlay reserve for {} funded with {}", book.token_mint, amount);
 
-/// Remove an authorized domain from a license
-pub fn remove_authorized_domain(
-    ctx: Context<ModifyLicenseDomains>,
-    domain: String,
-) -> Result<()> {
-    let license = &mut ctx.accounts.license;
-    license.allowed_domains.retain(|d| *d != domain);
-    msg!("Domain {} removed from license", domain);
     Ok(())
 }
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_spl::memo::Memo;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 
 declare_id!("FortunaProt1111111111111111111111111111111");
@@ -7,6 +8,7 @@ pub mod state;
 pub mod errors;
 pub mod instructions;
 pub mod constants;
+pub mod events;
 
 use state::*;
 use errors::*;
@@ -48,6 +50,31 @@ pub mod fortuna_protocol {
         instructions::update_oracle(ctx, name, categories, data_source, is_active)
     }
 
+    /// Close a deactivated oracle and reclaim its rent (admin only)
+    pub fn close_oracle(ctx: Context<CloseOracle>) -> Result<()> {
+        instructions::close_oracle(ctx)
+    }
+
+    /// Move part of an oracle's bond to the treasury for a bad resolution (admin only)
+    pub fn slash_oracle(ctx: Context<SlashOracle>, amount: u64) -> Result<()> {
+        instructions::slash_oracle(ctx, amount)
+    }
+
+    /// Withdraw a deactivated oracle's bond after the cooldown (oracle authority only)
+    pub fn withdraw_oracle_bond(ctx: Context<WithdrawOracleBond>) -> Result<()> {
+        instructions::withdraw_oracle_bond(ctx)
+    }
+
+    /// Set the default betting mint for markets created without one (admin only)
+    pub fn set_default_token_mint(ctx: Context<SetDefaultTokenMint>) -> Result<()> {
+        instructions::set_default_token_mint(ctx)
+    }
+
+    /// Set the bond mint, amount and withdrawal cooldown for new oracles (admin only)
+    pub fn set_oracle_bond(ctx: Context<SetOracleBond>, amount: u64, cooldown_secs: i64) -> Result<()> {
+        instructions::set_oracle_bond(ctx, amount, cooldown_secs)
+    }
+
     /// Create a new prediction market with category
     pub fn create_market(
         ctx: Context<CreateMarket>,
@@ -60,6 +87,7 @@ pub mod fortuna_protocol {
         betting_deadline: i64,
         outcomes: Vec<String>,
         oracle_event_id: String,
+        options: MarketOptions,
     ) -> Result<()> {
         instructions::create_market(
             ctx,
@@ -72,6 +100,7 @@ pub mod fortuna_protocol {
             betting_deadline,
             outcomes,
             oracle_event_id,
+            options,
         )
     }
 
@@ -82,12 +111,49 @@ pub mod fortuna_protocol {
         instructions::assign_oracle(ctx)
     }
 
-    /// Place a bet on a specific outcome
+    /// Free an oracle's assignment slot once its market closed without an oracle resolution
+    pub fn release_oracle_assignment(ctx: Context<ReleaseOracleAssignment>) -> Result<()> {
+        instructions::release_oracle_assignment(ctx)
+    }
+
+    /// Point an open market's creator fees at a corrected wallet (creator only)
+    pub fn update_creator_fee_wallet(ctx: Context<UpdateCreatorFeeWallet>) -> Result<()> {
+        instructions::update_creator_fee_wallet(ctx)
+    }
+
+    /// Reorder a market's outcomes before any bets are placed (creator only)
+    pub fn reorder_outcomes(ctx: Context<ReorderOutcomes>, new_order: Vec<u8>) -> Result<()> {
+        instructions::reorder_outcomes(ctx, new_order)
+    }
+
+    /// Close an outcome to new bets without changing outcome indices (creator only)
+    pub fn disable_outcome(ctx: Context<DisableOutcome>, outcome_index: u8) -> Result<()> {
+        instructions::disable_outcome(ctx, outcome_index)
+    }
+
+    /// Pause or unpause betting and resolution on a market (creator or admin)
+    pub fn set_market_paused(ctx: Context<SetMarketPaused>, paused: bool) -> Result<()> {
+        instructions::set_market_paused(ctx, paused)
+    }
+
+    /// Retag the front-end display channels of an open market (creator only)
+    pub fn set_visibility_flags(ctx: Context<SetVisibilityFlags>, visibility_flags: u32) -> Result<()> {
+        instructions::set_visibility_flags(ctx, visibility_flags)
+    }
+
+    /// Shrink a market account by its unused reserved space, refunding rent (creator only)
+    pub fn compact_market(ctx: Context<CompactMarket>) -> Result<()> {
+        instructions::compact_market(ctx)
+    }
+
+    /// Place a bet on a specific outcome, optionally buying loss protection
     pub fn place_bet(
         ctx: Context<PlaceBet>,
         outcome_index: u8,
+        loss_protection_bps: u16,
+        memo: Option<String>,
     ) -> Result<()> {
-        instructions::place_bet(ctx, outcome_index)
+        instructions::place_bet(ctx, outcome_index, loss_protection_bps, memo)
     }
 
     /// Resolve the market with the winning outcome (creator only)
@@ -106,27 +172,158 @@ pub mod fortuna_protocol {
         instructions::oracle_resolve_market(ctx, winning_outcome)
     }
 
+    /// Cast a stake-weighted resolution vote (community-resolved markets)
+    pub fn cast_resolution_vote(
+        ctx: Context<CastResolutionVote>,
+        outcome_index: u8,
+    ) -> Result<()> {
+        instructions::cast_resolution_vote(ctx, outcome_index)
+    }
+
+    /// Ask for an unresolved market to be resolved once betting has closed (bettors only)
+    pub fn request_resolution(ctx: Context<RequestResolution>) -> Result<()> {
+        instructions::request_resolution(ctx)
+    }
+
+    /// Resolve a community-resolved market once voting has ended (anyone can call)
+    pub fn finalize_community_resolution(ctx: Context<FinalizeCommunityResolution>) -> Result<()> {
+        instructions::finalize_community_resolution(ctx)
+    }
+
+    /// Bind a random-resolution market to a pending randomness account (creator only)
+    pub fn request_randomness(ctx: Context<RequestRandomness>) -> Result<()> {
+        instructions::request_randomness(ctx)
+    }
+
+    /// Resolve a random-resolution market from its revealed randomness account
+    pub fn fulfill_randomness(ctx: Context<FulfillRandomness>) -> Result<()> {
+        instructions::fulfill_randomness(ctx)
+    }
+
+    /// Resolve a commit-reveal market by revealing the committed outcome and salt (creator only)
+    pub fn reveal_resolution(
+        ctx: Context<RevealResolution>,
+        winning_outcome: u8,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        instructions::reveal_resolution(ctx, winning_outcome, salt)
+    }
+
+    /// Sweep a stranded pool to the treasury (anyone can call)
+    pub fn sweep_stranded_pool(ctx: Context<SweepStrandedPool>) -> Result<()> {
+        instructions::sweep_stranded_pool(ctx)
+    }
+
     /// Claim winnings after market resolution
     pub fn claim_winnings(ctx: Context<ClaimWinnings>) -> Result<()> {
         instructions::claim_winnings(ctx)
     }
 
+    /// Claim winnings and place a bet on another open market with the proceeds
+    pub fn claim_and_bet<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimAndBet<'info>>,
+        outcome_index: u8,
+        loss_protection_bps: u16,
+        memo: Option<String>,
+    ) -> Result<()> {
+        instructions::claim_and_bet(ctx, outcome_index, loss_protection_bps, memo)
+    }
+
+    /// Exclude the signing wallet from betting until `until` (can only be extended)
+    pub fn self_exclude(ctx: Context<SelfExclude>, until: i64) -> Result<()> {
+        instructions::self_exclude(ctx, until)
+    }
+
+    /// Open the bettor's claim escrow for a mint (needed before claiming in contested categories)
+    pub fn open_claim_escrow(ctx: Context<OpenClaimEscrow>) -> Result<()> {
+        instructions::open_claim_escrow(ctx)
+    }
+
+    /// Pay out escrowed winnings once the category's delay has passed
+    pub fn release_claim_escrow(ctx: Context<ReleaseClaimEscrow>) -> Result<()> {
+        instructions::release_claim_escrow(ctx)
+    }
+
+    /// Record the signing wallet's first-seen time, for markets that screen out new wallets
+    pub fn create_bettor_profile(ctx: Context<CreateBettorProfile>) -> Result<()> {
+        instructions::create_bettor_profile(ctx)
+    }
+
+    /// Escrow the bet amount for a later confirmation (two-step betting for large stakes)
+    pub fn reserve_bet(ctx: Context<ReserveBet>, outcome_index: u8) -> Result<()> {
+        instructions::reserve_bet(ctx, outcome_index)
+    }
+
+    /// Confirm a pending reservation, placing the escrowed stake as a bet
+    pub fn confirm_bet<'info>(
+        ctx: Context<'_, '_, '_, 'info, ConfirmBet<'info>>,
+        outcome_index: u8,
+        loss_protection_bps: u16,
+        memo: Option<String>,
+    ) -> Result<()> {
+        instructions::confirm_bet(ctx, outcome_index, loss_protection_bps, memo)
+    }
+
+    /// Refund a reservation (bettor at any time, anyone once it has expired)
+    pub fn cancel_reservation(ctx: Context<CancelReservation>) -> Result<()> {
+        instructions::cancel_reservation(ctx)
+    }
+
+    /// Reclaim the loss-protected portion of a losing bet
+    pub fn claim_loss_protection(ctx: Context<ClaimLossProtection>) -> Result<()> {
+        instructions::claim_loss_protection(ctx)
+    }
+
+    /// Release a resolved market's escrowed creator fees to the creator fee wallet
+    pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {
+        instructions::claim_creator_fees(ctx)
+    }
+
+    /// Sweep winnings left unclaimed past the market's claim expiry to the treasury (admin only)
+    pub fn sweep_expired_claims<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepExpiredClaims<'info>>,
+    ) -> Result<()> {
+        instructions::sweep_expired_claims(ctx)
+    }
+
+    /// Move a low-participation market's escrowed creator fees into its bonus pool (anyone can call)
+    pub fn redirect_creator_fees(ctx: Context<RedirectCreatorFees>) -> Result<()> {
+        instructions::redirect_creator_fees(ctx)
+    }
+
+    /// Close a settled market and its vaults once every winner or refund has claimed (creator only)
+    pub fn close_market(ctx: Context<CloseMarket>) -> Result<()> {
+        instructions::close_market(ctx)
+    }
+
     /// Cancel a market (only before any bets or by admin)
     pub fn cancel_market(ctx: Context<CancelMarket>) -> Result<()> {
         instructions::cancel_market(ctx)
     }
 
+    /// Cancel a market left unresolved past its resolution deadline (anyone may call)
+    pub fn expire_market(ctx: Context<ExpireMarket>) -> Result<()> {
+        instructions::expire_market(ctx)
+    }
+
+    /// Cancel several of the signer's open, bet-less markets at once, skipping the rest (creator only)
+    pub fn cancel_markets_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelMarketsBatch<'info>>,
+    ) -> Result<()> {
+        instructions::cancel_markets_batch(ctx)
+    }
+
     /// Refund bet for cancelled market
     pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
         instructions::claim_refund(ctx)
     }
 
-    /// Withdraw a bet before market resolution (user gets back their stake minus fees)
+    /// Withdraw a bet before market resolution (user gets back their stake and creator fee)
     pub fn withdraw_bet(ctx: Context<WithdrawBet>) -> Result<()> {
         instructions::withdraw_bet(ctx)
     }
 
-    /// Update protocol settings (admin only)
+    /// Update protocol settings (admin only); a new treasury receives fees from the next bet on
     pub fn update_protocol(
         ctx: Context<UpdateProtocol>,
         new_treasury: Option<Pubkey>,
@@ -145,6 +342,199 @@ pub mod fortuna_protocol {
         instructions::set_require_license(ctx, require_license)
     }
 
+    /// Set the loss protection premium (admin only, 0 disables loss protection)
+    pub fn set_loss_protection_premium(
+        ctx: Context<UpdateProtocol>,
+        premium_bps: u16,
+    ) -> Result<()> {
+        instructions::set_loss_protection_premium(ctx, premium_bps)
+    }
+
+    /// Restrict the betting mints allowed for a category (admin only, empty allows any)
+    pub fn set_category_allowed_mints(
+        ctx: Context<SetCategoryAllowedMints>,
+        category: u8,
+        allowed_mints: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::set_category_allowed_mints(ctx, category, allowed_mints)
+    }
+
+    /// Set the bounds on markets' fixed bet amount (admin only, 0 = unbounded)
+    pub fn set_bet_amount_bounds(
+        ctx: Context<UpdateProtocol>,
+        min_bet_amount: u64,
+        max_bet_amount: u64,
+    ) -> Result<()> {
+        instructions::set_bet_amount_bounds(ctx, min_bet_amount, max_bet_amount)
+    }
+
+    /// Configure the fee mint and flat protocol fee for fee-token markets (admin only)
+    pub fn set_fee_token(
+        ctx: Context<UpdateProtocol>,
+        fee_mint: Pubkey,
+        flat_protocol_fee: u64,
+    ) -> Result<()> {
+        instructions::set_fee_token(ctx, fee_mint, flat_protocol_fee)
+    }
+
+    /// Route creator fees on new bets to the treasury instead of the market's creator (admin only)
+    pub fn set_redirect_creator_fees_to_treasury(
+        ctx: Context<UpdateProtocol>,
+        redirect: bool,
+    ) -> Result<()> {
+        instructions::set_redirect_creator_fees_to_treasury(ctx, redirect)
+    }
+
+    /// Cap how many open markets a single oracle can be assigned (admin only, 0 = unlimited)
+    pub fn set_max_markets_per_oracle(
+        ctx: Context<UpdateProtocol>,
+        max_markets_per_oracle: u32,
+    ) -> Result<()> {
+        instructions::set_max_markets_per_oracle(ctx, max_markets_per_oracle)
+    }
+
+    /// Cap how many markets a license can create per window (admin only, 0 = unlimited)
+    pub fn set_license_rate_limit(
+        ctx: Context<UpdateProtocol>,
+        license_rate_limit: u32,
+        license_rate_window_secs: i64,
+    ) -> Result<()> {
+        instructions::set_license_rate_limit(ctx, license_rate_limit, license_rate_window_secs)
+    }
+
+    /// Name the key that may take over a dormant authority (admin only)
+    pub fn set_recovery_authority(
+        ctx: Context<UpdateProtocol>,
+        recovery_authority: Pubkey,
+        dormancy_secs: i64,
+    ) -> Result<()> {
+        instructions::set_recovery_authority(ctx, recovery_authority, dormancy_secs)
+    }
+
+    /// Take over the protocol authority after its dormancy period (recovery authority only)
+    pub fn claim_dormant_authority(ctx: Context<ClaimDormantAuthority>) -> Result<()> {
+        instructions::claim_dormant_authority(ctx)
+    }
+
+    /// Set how long bet reservations stay confirmable (admin only)
+    pub fn set_reservation_window(
+        ctx: Context<UpdateProtocol>,
+        reservation_window_secs: i64,
+    ) -> Result<()> {
+        instructions::set_reservation_window(ctx, reservation_window_secs)
+    }
+
+    /// Set how far in the future new markets' betting deadlines may be (admin only, 0 = unbounded)
+    pub fn set_max_future_deadline(
+        ctx: Context<UpdateProtocol>,
+        max_future_deadline_secs: i64,
+    ) -> Result<()> {
+        instructions::set_max_future_deadline(ctx, max_future_deadline_secs)
+    }
+
+    /// Count a batch of markets, passed as remaining accounts, into a protocol stats recompute (admin only)
+    pub fn recompute_protocol_stats<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateProtocol<'info>>,
+        restart: bool,
+    ) -> Result<()> {
+        instructions::recompute_protocol_stats(ctx, restart)
+    }
+
+    /// Overwrite the protocol stats with the recomputed values (admin only)
+    pub fn finalize_stats_recompute(ctx: Context<UpdateProtocol>) -> Result<()> {
+        instructions::finalize_stats_recompute(ctx)
+    }
+
+    /// Route winnings in one market category through the time-locked claim escrow (admin only)
+    pub fn set_category_escrow_claims(
+        ctx: Context<UpdateProtocol>,
+        category: u8,
+        escrow_claims: bool,
+    ) -> Result<()> {
+        instructions::set_category_escrow_claims(ctx, category, escrow_claims)
+    }
+
+    /// Set the claim delay for one market category (admin only)
+    pub fn set_claim_delay(
+        ctx: Context<UpdateProtocol>,
+        category: u8,
+        delay: i64,
+    ) -> Result<()> {
+        instructions::set_claim_delay(ctx, category, delay)
+    }
+
+    /// Override the protocol fee for one market category (admin only, 0 = global fee)
+    pub fn set_category_protocol_fee(
+        ctx: Context<UpdateProtocol>,
+        category: u8,
+        fee_bps: u16,
+    ) -> Result<()> {
+        instructions::set_category_protocol_fee(ctx, category, fee_bps)
+    }
+
+    /// Set the outcome count bounds for one market category (admin only)
+    pub fn set_category_outcome_bounds(
+        ctx: Context<UpdateProtocol>,
+        category: u8,
+        min_outcomes: u8,
+        max_outcomes: u8,
+    ) -> Result<()> {
+        instructions::set_category_outcome_bounds(ctx, category, min_outcomes, max_outcomes)
+    }
+
+    /// Allow or forbid creators resolving their own markets (admin only)
+    pub fn set_creator_resolution_allowed(
+        ctx: Context<UpdateProtocol>,
+        allowed: bool,
+    ) -> Result<()> {
+        instructions::set_creator_resolution_allowed(ctx, allowed)
+    }
+
+    /// Set the program whose randomness accounts resolve random markets (admin only)
+    pub fn set_randomness_program(
+        ctx: Context<UpdateProtocol>,
+        randomness_program: Pubkey,
+    ) -> Result<()> {
+        instructions::set_randomness_program(ctx, randomness_program)
+    }
+
+    /// Set the policy for markets whose winning outcome has no bets (admin only)
+    pub fn set_stranded_pool_policy(
+        ctx: Context<UpdateProtocol>,
+        policy: StrandedPoolPolicy,
+    ) -> Result<()> {
+        instructions::set_stranded_pool_policy(ctx, policy)
+    }
+
+    /// Replace the protocol fee rebate tiers (admin only)
+    pub fn set_rebate_tiers(
+        ctx: Context<UpdateProtocol>,
+        rebate_tiers: Vec<RebateTier>,
+    ) -> Result<()> {
+        instructions::set_rebate_tiers(ctx, rebate_tiers)
+    }
+
+    /// Replace the pool-size fee taper schedule (admin only)
+    pub fn set_fee_taper_schedule(
+        ctx: Context<UpdateProtocol>,
+        fee_taper_schedule: Vec<FeeTaperTier>,
+    ) -> Result<()> {
+        instructions::set_fee_taper_schedule(ctx, fee_taper_schedule)
+    }
+
+    /// Exempt a market from all fees (admin only)
+    pub fn set_market_fee_exempt(
+        ctx: Context<SetMarketFeeExempt>,
+        fee_exempt: bool,
+    ) -> Result<()> {
+        instructions::set_market_fee_exempt(ctx, fee_exempt)
+    }
+
+    /// Send lamports above a program account's rent-exempt minimum to the treasury (admin only)
+    pub fn recover_excess_lamports(ctx: Context<RecoverExcessLamports>) -> Result<()> {
+        instructions::recover_excess_lamports(ctx)
+    }
+
     // =========================================================================
     // License Management
     // =========================================================================
@@ -228,6 +618,96 @@ pub mod fortuna_protocol {
     ) -> Result<()> {
         instructions::remove_authorized_domain(ctx, domain)
     }
+
+    /// Report a license's remaining quota, validity and features as return data
+    pub fn license_status_view(ctx: Context<LicenseStatusView>) -> Result<LicenseStatus> {
+        instructions::license_status_view(ctx)
+    }
+
+    // =========================================================================
+    // Native SOL Markets
+    // =========================================================================
+
+    /// Place a bet on a native SOL market
+    pub fn place_bet_sol(ctx: Context<PlaceBetSol>, outcome_index: u8) -> Result<()> {
+        instructions::place_bet_sol(ctx, outcome_index)
+    }
+
+    /// Claim winnings from a resolved native SOL market
+    pub fn claim_winnings_sol(ctx: Context<ClaimWinningsSol>) -> Result<()> {
+        instructions::claim_winnings_sol(ctx)
+    }
+
+    /// Refund a bet on a cancelled native SOL market
+    pub fn claim_refund_sol(ctx: Context<ClaimRefundSol>) -> Result<()> {
+        instructions::claim_refund_sol(ctx)
+    }
+
+    /// Withdraw a bet from a native SOL market before the betting deadline
+    pub fn withdraw_bet_sol(ctx: Context<WithdrawBetSol>) -> Result<()> {
+        instructions::withdraw_bet_sol(ctx)
+    }
+
+    /// Release a resolved native SOL market's escrowed creator fees to the creator fee wallet
+    pub fn claim_creator_fees_sol(ctx: Context<ClaimCreatorFeesSol>) -> Result<()> {
+        instructions::claim_creator_fees_sol(ctx)
+    }
+
+    /// Move a low-participation native SOL market's escrowed creator fees into its bonus pool
+    pub fn redirect_creator_fees_sol(ctx: Context<RedirectCreatorFeesSol>) -> Result<()> {
+        instructions::redirect_creator_fees_sol(ctx)
+    }
+
+    /// Deposit creator liquidity backing a fixed-multiplier market's payouts
+    pub fn deposit_market_liquidity(ctx: Context<DepositMarketLiquidity>, amount: u64) -> Result<()> {
+        instructions::deposit_market_liquidity(ctx, amount)
+    }
+
+    /// Return a settled fixed-multiplier market's leftover liquidity and losing stakes to the creator
+    pub fn withdraw_market_liquidity(ctx: Context<WithdrawMarketLiquidity>) -> Result<()> {
+        instructions::withdraw_market_liquidity(ctx)
+    }
+
+    // =========================================================================
+    // Multi-Collateral Markets
+    // =========================================================================
+
+    /// Place a bet on a multi-collateral market in one of its accepted mints
+    pub fn place_collateral_bet(ctx: Context<PlaceCollateralBet>, outcome_index: u8) -> Result<()> {
+        instructions::place_collateral_bet(ctx, outcome_index)
+    }
+
+    /// Claim winnings or a refund from a multi-collateral market
+    pub fn claim_collateral<'info>(ctx: Context<'_, '_, '_, 'info, ClaimCollateral<'info>>) -> Result<()> {
+        instructions::claim_collateral(ctx)
+    }
+
+    // =========================================================================
+    // Parlays
+    // =========================================================================
+
+    /// Stake on a parlay over bets already placed on several markets
+    /// Leg accounts are passed as (market, bet) pairs in remaining accounts
+    pub fn create_parlay(ctx: Context<CreateParlay>, parlay_id: u64, stake: u64) -> Result<()> {
+        instructions::create_parlay(ctx, parlay_id, stake)
+    }
+
+    /// Claim a parlay's payout, or its stake back if a leg was cancelled
+    /// Leg markets are passed in order in remaining accounts
+    pub fn claim_parlay(ctx: Context<ClaimParlay>) -> Result<()> {
+        instructions::claim_parlay(ctx)
+    }
+
+    /// Close out a losing parlay, releasing its payout from the book (anyone may call)
+    /// Leg markets are passed in order in remaining accounts
+    pub fn settle_lost_parlay(ctx: Context<SettleLostParlay>) -> Result<()> {
+        instructions::settle_lost_parlay(ctx)
+    }
+
+    /// Add funds to a mint's parlay reserve, which backs winning parlays' odds
+    pub fn fund_parlay_reserve(ctx: Context<FundParlayReserve>, amount: u64) -> Result<()> {
+        instructions::fund_parlay_reserve(ctx, amount)
+    }
 }
 
 // ============================================================================
@@ -265,8 +745,9 @@ pub struct RegisterOracle<'info> {
     )]
     pub protocol_state: Account<'info, ProtocolState>,
 
+    /// `init_if_needed` lets the handler reject a taken ID with `OracleAlreadyExists`
     #[account(
-        init,
+        init_if_needed,
         payer = authority,
         space = 8 + Oracle::INIT_SPACE,
         seeds = [ORACLE_SEED, &oracle_id.to_le_bytes()],
@@ -274,18 +755,43 @@ pub struct RegisterOracle<'info> {
     )]
     pub oracle: Account<'info, Oracle>,
 
-    /// CHECK: Oracle authority that can submit results
+    /// CHECK: Oracle authority that can submit results; must also sign when a bond is required
     pub oracle_authority: UncheckedAccount<'info>,
 
+    /// Mint of the oracle bond (only when the protocol requires a bond)
+    #[account(address = protocol_state.oracle_bond_mint @ FortunaError::OracleBondRequired)]
+    pub bond_mint: Option<Account<'info, Mint>>,
+
+    /// Holds the oracle's bond until it is slashed or withdrawn
+    #[account(
+        init,
+        payer = authority,
+        seeds = [ORACLE_BOND_VAULT_SEED, oracle.key().as_ref()],
+        bump,
+        token::mint = bond_mint,
+        token::authority = protocol_state
+    )]
+    pub bond_vault: Option<Account<'info, TokenAccount>>,
+
+    /// Oracle authority's token account the bond is taken from
+    #[account(
+        mut,
+        constraint = oracle_bond_source.owner == oracle_authority.key(),
+        constraint = oracle_bond_source.mint == protocol_state.oracle_bond_mint
+    )]
+    pub oracle_bond_source: Option<Account<'info, TokenAccount>>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
+    pub token_program: Option<Program<'info, Token>>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct UpdateOracle<'info> {
     #[account(
+        mut,
         seeds = [PROTOCOL_SEED],
         bump = protocol_state.bump,
         constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
@@ -304,90 +810,1831 @@ pub struct UpdateOracle<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(market_id: u64)]
-pub struct CreateMarket<'info> {
+pub struct CloseOracle<'info> {
     #[account(
+        mut,
         seeds = [PROTOCOL_SEED],
-        bump = protocol_state.bump
+        bump = protocol_state.bump,
+        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
     )]
     pub protocol_state: Account<'info, ProtocolState>,
 
     #[account(
-        init,
-        payer = creator,
-        space = 8 + Market::INIT_SPACE,
-        seeds = [MARKET_SEED, &market_id.to_le_bytes()],
-        bump
+        mut,
+        close = authority,
+        seeds = [ORACLE_SEED, &oracle.oracle_id.to_le_bytes()],
+        bump = oracle.bump,
+        constraint = !oracle.is_active @ FortunaError::OracleStillActive,
+        constraint = oracle.bond_amount == 0 @ FortunaError::OracleBondOutstanding
     )]
-    pub market: Account<'info, Market>,
+    pub oracle: Account<'info, Oracle>,
 
-    /// The token mint for betting (e.g., USDC)
-    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
 
+#[derive(Accounts)]
+pub struct SlashOracle<'info> {
     #[account(
-        init,
-        payer = creator,
-        token::mint = token_mint,
-        token::authority = market,
-        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
-        bump
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
     )]
-    pub market_vault: Account<'info, TokenAccount>,
+    pub protocol_state: Account<'info, ProtocolState>,
 
     #[account(
-        init,
-        payer = creator,
-        token::mint = token_mint,
-        token::authority = market,
+        mut,
+        seeds = [ORACLE_SEED, &oracle.oracle_id.to_le_bytes()],
+        bump = oracle.bump
+    )]
+    pub oracle: Account<'info, Oracle>,
+
+    #[account(
+        mut,
+        seeds = [ORACLE_BOND_VAULT_SEED, oracle.key().as_ref()],
+        bump
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == protocol_state.treasury,
+        constraint = treasury_token_account.mint == bond_vault.mint
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawOracleBond<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [ORACLE_SEED, &oracle.oracle_id.to_le_bytes()],
+        bump = oracle.bump,
+        constraint = oracle.authority == oracle_authority.key() @ FortunaError::Unauthorized
+    )]
+    pub oracle: Account<'info, Oracle>,
+
+    #[account(
+        mut,
+        seeds = [ORACLE_BOND_VAULT_SEED, oracle.key().as_ref()],
+        bump
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = oracle_token_account.owner == oracle_authority.key(),
+        constraint = oracle_token_account.mint == bond_vault.mint
+    )]
+    pub oracle_token_account: Account<'info, TokenAccount>,
+
+    pub oracle_authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetOracleBond<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    pub bond_mint: Account<'info, Mint>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDefaultTokenMint<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// New default mint (omit to clear the default)
+    pub default_token_mint: Option<Account<'info, Mint>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    market_id: u64,
+    category: u8,
+    title: String,
+    description: String,
+    bet_amount: u64,
+    resolution_deadline: i64,
+    betting_deadline: i64,
+    outcomes: Vec<String>,
+    oracle_event_id: String
+)]
+pub struct CreateMarket<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Market::INIT_SPACE,
+        seeds = [MARKET_SEED, creator.key().as_ref(), &market_id.to_le_bytes()],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// The token mint for betting (e.g., USDC); clients that don't name one pass the
+    /// protocol's `default_token_mint`, since the vaults below need a concrete mint
+    pub token_mint: Account<'info, Mint>,
+
+    /// Stake vault. Seeded by this market's address and created here, so an existing
+    /// account (another market's vault, the treasury) fails the seeds or `init` check
+    #[account(
+        init,
+        payer = creator,
+        token::mint = token_mint,
+        token::authority = market,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    /// Bonus pool vault, derived and created the same way as the stake vault
+    #[account(
+        init,
+        payer = creator,
+        token::mint = token_mint,
+        token::authority = market,
         seeds = [POOL_VAULT_SEED, market.key().as_ref()],
         bump
     )]
     pub pool_vault: Account<'info, TokenAccount>,
 
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + MarketLeaderboard::INIT_SPACE,
+        seeds = [LEADERBOARD_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub leaderboard: Account<'info, MarketLeaderboard>,
+
+    /// Index of markets sharing the oracle event ID (required when the ID is non-empty)
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + EventIndex::INIT_SPACE,
+        seeds = [EVENT_INDEX_SEED, &EventIndex::event_id_hash(&oracle_event_id)],
+        bump
+    )]
+    pub event_index: Option<Account<'info, EventIndex>>,
+
+    /// Odds history ring buffer (required when the market tracks odds)
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + MarketOddsHistory::INIT_SPACE,
+        seeds = [ODDS_HISTORY_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub odds_history: Option<Account<'info, MarketOddsHistory>>,
+
     /// Optional license account - required if protocol.require_license is true
     #[account(
         mut,
         seeds = [LICENSE_SEED, &license.license_key],
         bump = license.bump
     )]
-    pub license: Option<Account<'info, License>>,
+    pub license: Option<Account<'info, License>>,
+
+    /// CHECK: Category config PDA; may be uninitialized, in which case any mint is allowed
+    #[account(
+        seeds = [CATEGORY_CONFIG_SEED, &[category]],
+        bump
+    )]
+    pub category_config: UncheckedAccount<'info>,
+
+    /// Creator's token account funding a seeded bonus (required when the market seeds one)
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == creator.key(),
+        constraint = creator_token_account.mint == token_mint.key()
+    )]
+    pub creator_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// CHECK: Creator's wallet to receive creator fees
+    pub creator_fee_wallet: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct AssignOracle<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ FortunaError::Unauthorized,
+        constraint = market.oracle == Pubkey::default() @ FortunaError::MarketAlreadyHasOracle
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [ORACLE_SEED, &oracle.oracle_id.to_le_bytes()],
+        bump = oracle.bump,
+        constraint = oracle.is_active @ FortunaError::OracleNotActive
+    )]
+    pub oracle: Account<'info, Oracle>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseOracleAssignment<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.resolved_by_oracle @ FortunaError::MarketAlreadyResolved,
+        constraint = market.oracle == oracle.key() @ FortunaError::OracleMismatch
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [ORACLE_SEED, &oracle.oracle_id.to_le_bytes()],
+        bump = oracle.bump
+    )]
+    pub oracle: Account<'info, Oracle>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCreatorFeeWallet<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: New wallet to receive creator fees
+    pub new_creator_fee_wallet: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReorderOutcomes<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DisableOutcome<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMarketPaused<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.creator == authority.key()
+            || protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetVisibilityFlags<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CompactMarket<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ FortunaError::Unauthorized,
+        constraint = market.reserved.is_empty() @ FortunaError::ReservedSpaceInUse,
+        realloc = Market::compact_space(),
+        realloc::payer = creator,
+        realloc::zero = false
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(outcome_index: u8, loss_protection_bps: u16, memo: Option<String>)]
+pub struct PlaceBet<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.paused @ FortunaError::MarketPaused,
+        constraint = !market.native_sol @ FortunaError::WrongMarketDenomination,
+        constraint = !market.multi_collateral @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = bettor,
+        space = Bet::space(memo.as_ref().map_or(0, |m| m.len())),
+        seeds = [BET_SEED, market.key().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump = market.vault_bump,
+        constraint = market_vault.mint == market.token_mint @ FortunaError::VaultMintMismatch
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED, market.key().as_ref()],
+        bump = market.pool_vault_bump,
+        constraint = pool_vault.mint == market.token_mint @ FortunaError::VaultMintMismatch
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, market.key().as_ref()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, MarketLeaderboard>,
+
+    #[account(
+        mut,
+        constraint = bettor_token_account.owner == bettor.key(),
+        constraint = bettor_token_account.mint == market.token_mint
+    )]
+    pub bettor_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Treasury wallet to receive protocol fees
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == protocol_state.treasury,
+        constraint = treasury_token_account.mint == market.token_mint
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    /// Bettor's fee-mint token account (fee-token markets only)
+    #[account(
+        mut,
+        constraint = bettor_fee_token_account.owner == bettor.key(),
+        constraint = bettor_fee_token_account.mint == market.fee_mint @ FortunaError::InvalidFeeMint
+    )]
+    pub bettor_fee_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Treasury's fee-mint token account (fee-token markets only)
+    #[account(
+        mut,
+        constraint = treasury_fee_token_account.owner == protocol_state.treasury,
+        constraint = treasury_fee_token_account.mint == market.fee_mint @ FortunaError::InvalidFeeMint
+    )]
+    pub treasury_fee_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Bettor's self-exclusion PDA; may be uninitialized, in which case the bettor is not excluded
+    #[account(
+        seeds = [SELF_EXCLUSION_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub self_exclusion: UncheckedAccount<'info>,
+
+    /// Bettor's profile (required by markets that only accept established wallets);
+    /// created inline on the wallet's first bet when passed but not yet initialized
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = 8 + BettorProfile::INIT_SPACE,
+        seeds = [BETTOR_PROFILE_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub bettor_profile: Option<Account<'info, BettorProfile>>,
+
+    /// Market's odds history (required when the market tracks odds)
+    #[account(
+        mut,
+        seeds = [ODDS_HISTORY_SEED, market.key().as_ref()],
+        bump = odds_history.bump
+    )]
+    pub odds_history: Option<Account<'info, MarketOddsHistory>>,
+
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveMarket<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.creator_resolution_allowed @ FortunaError::CreatorResolutionDisabled
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.paused @ FortunaError::MarketPaused,
+        constraint = market.resolution_mode == ResolutionMode::Standard @ FortunaError::WrongResolutionMode,
+        constraint = market.creator == resolver.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut)]
+    pub resolver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OracleResolveMarket<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.paused @ FortunaError::MarketPaused,
+        constraint = market.resolution_mode == ResolutionMode::Standard @ FortunaError::WrongResolutionMode,
+        constraint = market.oracle == oracle.key() @ FortunaError::OracleMismatch
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [ORACLE_SEED, &oracle.oracle_id.to_le_bytes()],
+        bump = oracle.bump,
+        constraint = oracle.is_active @ FortunaError::OracleNotActive,
+        constraint = oracle.authority == oracle_authority.key() @ FortunaError::Unauthorized
+    )]
+    pub oracle: Account<'info, Oracle>,
+
+    #[account(mut)]
+    pub oracle_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CastResolutionVote<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.paused @ FortunaError::MarketPaused,
+        constraint = market.resolution_mode == ResolutionMode::CommunityVote @ FortunaError::WrongResolutionMode
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [BET_SEED, market.key().as_ref(), voter.key().as_ref()],
+        bump = bet.bump,
+        constraint = bet.bettor == voter.key() @ FortunaError::Unauthorized,
+        constraint = bet.is_active() @ FortunaError::BetAlreadyWithdrawn,
+        constraint = !bet.voted @ FortunaError::AlreadyVoted
+    )]
+    pub bet: Account<'info, Bet>,
+
+    pub voter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RequestResolution<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [BET_SEED, market.key().as_ref(), bettor.key().as_ref()],
+        bump = bet.bump,
+        constraint = bet.bettor == bettor.key() @ FortunaError::Unauthorized,
+        constraint = bet.is_active() @ FortunaError::BetAlreadyWithdrawn,
+        constraint = !bet.resolution_requested @ FortunaError::ResolutionAlreadyRequested
+    )]
+    pub bet: Account<'info, Bet>,
+
+    pub bettor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeCommunityResolution<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.paused @ FortunaError::MarketPaused,
+        constraint = market.resolution_mode == ResolutionMode::CommunityVote @ FortunaError::WrongResolutionMode
+    )]
+    pub market: Account<'info, Market>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RequestRandomness<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.paused @ FortunaError::MarketPaused,
+        constraint = market.resolution_mode == ResolutionMode::Random @ FortunaError::WrongResolutionMode,
+        constraint = market.randomness_requested_at == 0 @ FortunaError::RandomnessAlreadyRequested,
+        constraint = market.creator == creator.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: Owner and layout are checked against the protocol's randomness program
+    pub randomness_account: UncheckedAccount<'info>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FulfillRandomness<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.paused @ FortunaError::MarketPaused,
+        constraint = market.resolution_mode == ResolutionMode::Random @ FortunaError::WrongResolutionMode,
+        constraint = market.randomness_requested_at > 0 @ FortunaError::RandomnessNotRequested
+    )]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: Must be the account bound at request; owner and layout are checked in the handler
+    #[account(
+        constraint = randomness_account.key() == market.randomness_account @ FortunaError::RandomnessAccountMismatch
+    )]
+    pub randomness_account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevealResolution<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.creator_resolution_allowed @ FortunaError::CreatorResolutionDisabled
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.paused @ FortunaError::MarketPaused,
+        constraint = market.resolution_mode == ResolutionMode::CommitReveal @ FortunaError::WrongResolutionMode,
+        constraint = market.creator == resolver.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    pub resolver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWinnings<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.multi_collateral @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    /// A claimer with no bet on this market fails here with Anchor's `AccountNotInitialized`
+    #[account(
+        mut,
+        seeds = [BET_SEED, market.key().as_ref(), claimer.key().as_ref()],
+        bump = bet.bump,
+        constraint = bet.bettor == claimer.key() @ FortunaError::Unauthorized,
+        constraint = bet.is_active() @ FortunaError::AlreadyClaimed
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump = market.vault_bump,
+        constraint = market_vault.mint == market.token_mint @ FortunaError::VaultMintMismatch
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED, market.key().as_ref()],
+        bump = market.pool_vault_bump,
+        constraint = pool_vault.mint == market.token_mint @ FortunaError::VaultMintMismatch
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = claimer_token_account.owner == claimer.key(),
+        constraint = claimer_token_account.mint == market.token_mint,
+        constraint = market.accepts_claim_account(&claimer_token_account.key(), &claimer.key(), &market.token_mint)
+            @ FortunaError::ClaimAccountNotAta
+    )]
+    pub claimer_token_account: Account<'info, TokenAccount>,
+
+    /// Claimer's escrow (required in categories whose winnings are escrowed)
+    #[account(
+        mut,
+        seeds = [CLAIM_ESCROW_SEED, claimer.key().as_ref(), market.token_mint.as_ref()],
+        bump = claim_escrow.bump
+    )]
+    pub claim_escrow: Option<Account<'info, ClaimEscrow>>,
+
+    /// Vault holding escrowed winnings for the market's mint
+    #[account(
+        mut,
+        seeds = [CLAIM_ESCROW_VAULT_SEED, market.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_vault: Option<Account<'info, TokenAccount>>,
+
+    /// SPL Memo program (required on markets with payout memos)
+    pub memo_program: Option<Program<'info, Memo>>,
+
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAndBet<'info> {
+    /// Winning bet on the resolved market
+    pub claim: ClaimWinnings<'info>,
+
+    /// New bet on the target market, funded from the same token account
+    pub place: PlaceBet<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SelfExclude<'info> {
+    #[account(
+        init_if_needed,
+        payer = wallet,
+        space = 8 + SelfExclusion::INIT_SPACE,
+        seeds = [SELF_EXCLUSION_SEED, wallet.key().as_ref()],
+        bump
+    )]
+    pub self_exclusion: Account<'info, SelfExclusion>,
+
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenClaimEscrow<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = 8 + ClaimEscrow::INIT_SPACE,
+        seeds = [CLAIM_ESCROW_SEED, bettor.key().as_ref(), token_mint.key().as_ref()],
+        bump
+    )]
+    pub claim_escrow: Account<'info, ClaimEscrow>,
+
+    /// Holds escrowed winnings for this mint until they are released
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        seeds = [CLAIM_ESCROW_VAULT_SEED, token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = protocol_state
+    )]
+    pub escrow_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseClaimEscrow<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [CLAIM_ESCROW_SEED, bettor.key().as_ref(), claim_escrow.token_mint.as_ref()],
+        bump = claim_escrow.bump
+    )]
+    pub claim_escrow: Account<'info, ClaimEscrow>,
+
+    #[account(
+        mut,
+        seeds = [CLAIM_ESCROW_VAULT_SEED, claim_escrow.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = bettor_token_account.owner == bettor.key(),
+        constraint = bettor_token_account.mint == claim_escrow.token_mint
+    )]
+    pub bettor_token_account: Account<'info, TokenAccount>,
+
+    pub bettor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateBettorProfile<'info> {
+    #[account(
+        init,
+        payer = wallet,
+        space = 8 + BettorProfile::INIT_SPACE,
+        seeds = [BETTOR_PROFILE_SEED, wallet.key().as_ref()],
+        bump
+    )]
+    pub bettor_profile: Account<'info, BettorProfile>,
+
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReserveBet<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.paused @ FortunaError::MarketPaused,
+        constraint = !market.native_sol @ FortunaError::WrongMarketDenomination,
+        constraint = !market.multi_collateral @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = bettor,
+        space = 8 + BetReservation::INIT_SPACE,
+        seeds = [RESERVATION_SEED, market.key().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub reservation: Account<'info, BetReservation>,
+
+    #[account(address = market.token_mint)]
+    pub token_mint: Account<'info, Mint>,
+
+    /// Holds reserved stakes for this mint until they are confirmed or refunded
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        seeds = [RESERVATION_VAULT_SEED, token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = protocol_state
+    )]
+    pub reservation_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = bettor_token_account.owner == bettor.key(),
+        constraint = bettor_token_account.mint == market.token_mint
+    )]
+    pub bettor_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmBet<'info> {
+    /// The bet being placed; the market, bettor and token account must match the reservation
+    pub place: PlaceBet<'info>,
+
+    #[account(
+        mut,
+        seeds = [RESERVATION_SEED, place.market.key().as_ref(), place.bettor.key().as_ref()],
+        bump = reservation.bump
+    )]
+    pub reservation: Account<'info, BetReservation>,
+
+    #[account(
+        mut,
+        seeds = [RESERVATION_VAULT_SEED, reservation.token_mint.as_ref()],
+        bump
+    )]
+    pub reservation_vault: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct CancelReservation<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        close = bettor,
+        seeds = [RESERVATION_SEED, reservation.market.as_ref(), bettor.key().as_ref()],
+        bump = reservation.bump
+    )]
+    pub reservation: Account<'info, BetReservation>,
+
+    #[account(
+        mut,
+        seeds = [RESERVATION_VAULT_SEED, reservation.token_mint.as_ref()],
+        bump
+    )]
+    pub reservation_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = bettor_token_account.owner == bettor.key(),
+        constraint = bettor_token_account.mint == reservation.token_mint
+    )]
+    pub bettor_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: The reservation's bettor, refunded the stake and the account rent
+    #[account(mut)]
+    pub bettor: UncheckedAccount<'info>,
+
+    /// Bettor, or anyone once the reservation has expired
+    pub caller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimLossProtection<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.paused @ FortunaError::MarketPaused
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [BET_SEED, market.key().as_ref(), claimer.key().as_ref()],
+        bump = bet.bump,
+        constraint = bet.bettor == claimer.key() @ FortunaError::Unauthorized,
+        constraint = bet.is_active() @ FortunaError::AlreadyClaimed
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = claimer_token_account.owner == claimer.key(),
+        constraint = claimer_token_account.mint == market.token_mint,
+        constraint = market.accepts_claim_account(&claimer_token_account.key(), &claimer.key(), &market.token_mint)
+            @ FortunaError::ClaimAccountNotAta
+    )]
+    pub claimer_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SweepStrandedPool<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.stranded_pool_policy == StrandedPoolPolicy::SweepToTreasury @ FortunaError::PoolNotStranded,
+        constraint = !market.stranded_swept @ FortunaError::StrandedPoolAlreadySwept,
+        constraint = !market.multi_collateral @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED, market.key().as_ref()],
+        bump = market.pool_vault_bump
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == protocol_state.treasury,
+        constraint = treasury_token_account.mint == market.token_mint
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub caller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCreatorFees<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.native_sol @ FortunaError::WrongMarketDenomination,
+        constraint = market.creator == creator.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == market.creator_fee_wallet,
+        constraint = creator_token_account.mint == market.token_mint
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    pub creator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SweepExpiredClaims<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.native_sol @ FortunaError::WrongMarketDenomination,
+        constraint = !market.multi_collateral @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED, market.key().as_ref()],
+        bump = market.pool_vault_bump
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == protocol_state.treasury,
+        constraint = treasury_token_account.mint == market.token_mint
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RedirectCreatorFees<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.native_sol @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED, market.key().as_ref()],
+        bump = market.pool_vault_bump
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    pub caller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseMarket<'info> {
+    #[account(
+        mut,
+        close = creator,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.native_sol @ FortunaError::WrongMarketDenomination,
+        constraint = !market.multi_collateral @ FortunaError::WrongMarketDenomination,
+        constraint = !market.processing @ FortunaError::MarketBusy,
+        constraint = market.creator == creator.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED, market.key().as_ref()],
+        bump = market.pool_vault_bump
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = creator,
+        seeds = [LEADERBOARD_SEED, market.key().as_ref()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, MarketLeaderboard>,
+
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == market.creator,
+        constraint = creator_token_account.mint == market.token_mint
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelMarket<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.creator == authority.key()
+            || protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireMarket<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct CancelMarketsBatch<'info> {
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.multi_collateral @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    /// A claimer with no bet on this market fails here with Anchor's `AccountNotInitialized`
+    #[account(
+        mut,
+        seeds = [BET_SEED, market.key().as_ref(), claimer.key().as_ref()],
+        bump = bet.bump,
+        constraint = bet.bettor == claimer.key() @ FortunaError::Unauthorized,
+        constraint = bet.is_active() @ FortunaError::AlreadyClaimed
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = claimer_token_account.owner == claimer.key(),
+        constraint = claimer_token_account.mint == market.token_mint,
+        constraint = market.accepts_claim_account(&claimer_token_account.key(), &claimer.key(), &market.token_mint)
+            @ FortunaError::ClaimAccountNotAta
+    )]
+    pub claimer_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawBet<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.multi_collateral @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    /// A bettor with no bet on this market fails here with Anchor's `AccountNotInitialized`
+    #[account(
+        mut,
+        seeds = [BET_SEED, market.key().as_ref(), bettor.key().as_ref()],
+        bump = bet.bump,
+        constraint = bet.bettor == bettor.key() @ FortunaError::Unauthorized,
+        constraint = bet.is_active() @ FortunaError::BetAlreadyWithdrawn
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump = market.vault_bump,
+        constraint = market_vault.mint == market.token_mint @ FortunaError::VaultMintMismatch
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = bettor_token_account.owner == bettor.key(),
+        constraint = bettor_token_account.mint == market.token_mint
+    )]
+    pub bettor_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, market.key().as_ref()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, MarketLeaderboard>,
+
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProtocol<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimDormantAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.recovery_authority != Pubkey::default()
+            && protocol_state.recovery_authority == recovery_authority.key() @ FortunaError::Unauthorized
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    pub recovery_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(category: u8)]
+pub struct SetCategoryAllowedMints<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + CategoryConfig::INIT_SPACE,
+        seeds = [CATEGORY_CONFIG_SEED, &[category]],
+        bump
+    )]
+    pub category_config: Account<'info, CategoryConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMarketFeeExempt<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecoverExcessLamports<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// CHECK: Any account owned by this program; native SOL markets are rejected in the handler
+    #[account(mut, owner = crate::ID)]
+    pub target: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury wallet receiving the recovered lamports
+    #[account(
+        mut,
+        address = protocol_state.treasury @ FortunaError::Unauthorized
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// ============================================================================
+// License Account Contexts
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(license_key: [u8; 32])]
+pub struct IssueLicense<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + License::INIT_SPACE,
+        seeds = [LICENSE_SEED, &license_key],
+        bump
+    )]
+    pub license: Account<'info, License>,
+
+    /// CHECK: The wallet that will hold this license
+    pub holder: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeLicense<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [LICENSE_SEED, &license.license_key],
+        bump = license.bump
+    )]
+    pub license: Account<'info, License>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferLicense<'info> {
+    #[account(
+        mut,
+        seeds = [LICENSE_SEED, &license.license_key],
+        bump = license.bump,
+        constraint = license.holder == current_holder.key() @ FortunaError::Unauthorized,
+        constraint = license.is_transferable @ FortunaError::LicenseNotTransferable
+    )]
+    pub license: Account<'info, License>,
+
+    /// CHECK: The new holder of the license
+    pub new_holder: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub current_holder: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLicense<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump,
+        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [LICENSE_SEED, &license.license_key],
+        bump = license.bump
+    )]
+    pub license: Account<'info, License>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ModifyLicenseWallets<'info> {
+    #[account(
+        mut,
+        seeds = [LICENSE_SEED, &license.license_key],
+        bump = license.bump,
+        constraint = license.holder == holder.key() @ FortunaError::Unauthorized
+    )]
+    pub license: Account<'info, License>,
+
+    #[account(mut)]
+    pub holder: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ModifyLicenseDomains<'info> {
+    #[account(
+        mut,
+        seeds = [LICENSE_SEED, &license.license_key],
+        bump = license.bump,
+        constraint = license.holder == holder.key() @ FortunaError::Unauthorized
+    )]
+    pub license: Account<'info, License>,
+
+    #[account(mut)]
+    pub holder: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LicenseStatusView<'info> {
+    #[account(
+        seeds = [LICENSE_SEED, &license.license_key],
+        bump = license.bump
+    )]
+    pub license: Account<'info, License>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceBetSol<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = !market.paused @ FortunaError::MarketPaused,
+        constraint = market.native_sol @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = bettor,
+        space = Bet::space(0),
+        seeds = [BET_SEED, market.key().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, market.key().as_ref()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, MarketLeaderboard>,
+
+    /// CHECK: Treasury wallet receiving protocol fees
+    #[account(
+        mut,
+        address = protocol_state.treasury @ FortunaError::Unauthorized
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Bettor's self-exclusion PDA; may be uninitialized, in which case the bettor is not excluded
+    #[account(
+        seeds = [SELF_EXCLUSION_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub self_exclusion: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWinningsSol<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.native_sol @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [BET_SEED, market.key().as_ref(), claimer.key().as_ref()],
+        bump = bet.bump,
+        constraint = bet.bettor == claimer.key() @ FortunaError::Unauthorized,
+        constraint = bet.is_active() @ FortunaError::AlreadyClaimed
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefundSol<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.native_sol @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [BET_SEED, market.key().as_ref(), claimer.key().as_ref()],
+        bump = bet.bump,
+        constraint = bet.bettor == claimer.key() @ FortunaError::Unauthorized,
+        constraint = bet.is_active() @ FortunaError::AlreadyClaimed
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawBetSol<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.native_sol @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [BET_SEED, market.key().as_ref(), bettor.key().as_ref()],
+        bump = bet.bump,
+        constraint = bet.bettor == bettor.key() @ FortunaError::Unauthorized,
+        constraint = bet.is_active() @ FortunaError::BetAlreadyWithdrawn
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, market.key().as_ref()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, MarketLeaderboard>,
+
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCreatorFeesSol<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.native_sol @ FortunaError::WrongMarketDenomination,
+        constraint = market.creator == creator.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: Creator's fee wallet receiving the released fees
+    #[account(
+        mut,
+        address = market.creator_fee_wallet @ FortunaError::Unauthorized
+    )]
+    pub creator_fee_wallet: UncheckedAccount<'info>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RedirectCreatorFeesSol<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.native_sol @ FortunaError::WrongMarketDenomination
+    )]
+    pub market: Account<'info, Market>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositMarketLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
+        bump = market.bump,
+        constraint = market.payout_mode == PayoutMode::FixedMultiplier @ FortunaError::WrongPayoutMode,
+        constraint = market.creator == creator.key() @ FortunaError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == creator.key(),
+        constraint = creator_token_account.mint == market.token_mint
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
 
-    #[account(mut)]
     pub creator: Signer<'info>,
 
-    /// CHECK: Creator's wallet to receive creator fees
-    pub creator_fee_wallet: UncheckedAccount<'info>,
-
     pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct AssignOracle<'info> {
+pub struct WithdrawMarketLiquidity<'info> {
     #[account(
         mut,
-        seeds = [MARKET_SEED, &market.market_id.to_le_bytes()],
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
         bump = market.bump,
-        constraint = market.status == MarketStatus::Open @ FortunaError::MarketNotOpen,
-        constraint = market.creator == creator.key() @ FortunaError::Unauthorized,
-        constraint = market.oracle == Pubkey::default() @ FortunaError::MarketAlreadyHasOracle
+        constraint = market.payout_mode == PayoutMode::FixedMultiplier @ FortunaError::WrongPayoutMode,
+        constraint = !market.liquidity_withdrawn @ FortunaError::LiquidityAlreadyWithdrawn,
+        constraint = market.creator == creator.key() @ FortunaError::Unauthorized
     )]
     pub market: Account<'info, Market>,
 
     #[account(
-        seeds = [ORACLE_SEED, &oracle.oracle_id.to_le_bytes()],
-        bump = oracle.bump,
-        constraint = oracle.is_active @ FortunaError::OracleNotActive
+        mut,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump = market.vault_bump
     )]
-    pub oracle: Account<'info, Oracle>,
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == creator.key(),
+        constraint = creator_token_account.mint == market.token_mint
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
 
-    #[account(mut)]
     pub creator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct PlaceBet<'info> {
+pub struct PlaceCollateralBet<'info> {
     #[account(
         seeds = [PROTOCOL_SEED],
         bump = protocol_state.bump
@@ -396,16 +2643,17 @@ pub struct PlaceBet<'info> {
 
     #[account(
         mut,
-        seeds = [MARKET_SEED, &market.market_id.to_le_bytes()],
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
         bump = market.bump,
-        constraint = market.status == MarketStatus::Open @ FortunaError::MarketNotOpen
+        constraint = !market.paused @ FortunaError::MarketPaused,
+        constraint = market.multi_collateral @ FortunaError::WrongMarketDenomination
     )]
     pub market: Account<'info, Market>,
 
     #[account(
         init,
         payer = bettor,
-        space = 8 + Bet::INIT_SPACE,
+        space = Bet::space(0),
         seeds = [BET_SEED, market.key().as_ref(), bettor.key().as_ref()],
         bump
     )]
@@ -413,89 +2661,61 @@ pub struct PlaceBet<'info> {
 
     #[account(
         mut,
-        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
-        bump = market.vault_bump
+        seeds = [LEADERBOARD_SEED, market.key().as_ref()],
+        bump = leaderboard.bump
     )]
-    pub market_vault: Account<'info, TokenAccount>,
+    pub leaderboard: Account<'info, MarketLeaderboard>,
+
+    pub collateral_mint: Account<'info, Mint>,
 
     #[account(
-        mut,
-        seeds = [POOL_VAULT_SEED, market.key().as_ref()],
-        bump = market.pool_vault_bump
+        init_if_needed,
+        payer = bettor,
+        seeds = [COLLATERAL_VAULT_SEED, market.key().as_ref(), collateral_mint.key().as_ref()],
+        bump,
+        token::mint = collateral_mint,
+        token::authority = market
     )]
-    pub pool_vault: Account<'info, TokenAccount>,
+    pub collateral_vault: Account<'info, TokenAccount>,
 
     #[account(
         mut,
         constraint = bettor_token_account.owner == bettor.key(),
-        constraint = bettor_token_account.mint == market.token_mint
+        constraint = bettor_token_account.mint == collateral_mint.key()
     )]
     pub bettor_token_account: Account<'info, TokenAccount>,
 
-    /// CHECK: Treasury wallet to receive protocol fees
     #[account(
         mut,
         constraint = treasury_token_account.owner == protocol_state.treasury,
-        constraint = treasury_token_account.mint == market.token_mint
+        constraint = treasury_token_account.mint == collateral_mint.key()
     )]
     pub treasury_token_account: Account<'info, TokenAccount>,
 
-    /// CHECK: Creator's token account for fees
     #[account(
         mut,
         constraint = creator_token_account.owner == market.creator_fee_wallet,
-        constraint = creator_token_account.mint == market.token_mint
+        constraint = creator_token_account.mint == collateral_mint.key()
     )]
     pub creator_token_account: Account<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub bettor: Signer<'info>,
-
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct ResolveMarket<'info> {
+    /// CHECK: Bettor's self-exclusion PDA; may be uninitialized, in which case the bettor is not excluded
     #[account(
-        mut,
-        seeds = [MARKET_SEED, &market.market_id.to_le_bytes()],
-        bump = market.bump,
-        constraint = market.status == MarketStatus::Open @ FortunaError::MarketNotOpen,
-        constraint = market.creator == resolver.key() @ FortunaError::Unauthorized
+        seeds = [SELF_EXCLUSION_SEED, bettor.key().as_ref()],
+        bump
     )]
-    pub market: Account<'info, Market>,
+    pub self_exclusion: UncheckedAccount<'info>,
 
     #[account(mut)]
-    pub resolver: Signer<'info>,
-}
-
-#[derive(Accounts)]
-pub struct OracleResolveMarket<'info> {
-    #[account(
-        mut,
-        seeds = [MARKET_SEED, &market.market_id.to_le_bytes()],
-        bump = market.bump,
-        constraint = market.status == MarketStatus::Open @ FortunaError::MarketNotOpen,
-        constraint = market.oracle == oracle.key() @ FortunaError::OracleMismatch
-    )]
-    pub market: Account<'info, Market>,
-
-    #[account(
-        mut,
-        seeds = [ORACLE_SEED, &oracle.oracle_id.to_le_bytes()],
-        bump = oracle.bump,
-        constraint = oracle.is_active @ FortunaError::OracleNotActive,
-        constraint = oracle.authority == oracle_authority.key() @ FortunaError::Unauthorized
-    )]
-    pub oracle: Account<'info, Oracle>,
+    pub bettor: Signer<'info>,
 
-    #[account(mut)]
-    pub oracle_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimWinnings<'info> {
+pub struct ClaimCollateral<'info> {
     #[account(
         seeds = [PROTOCOL_SEED],
         bump = protocol_state.bump
@@ -503,9 +2723,10 @@ pub struct ClaimWinnings<'info> {
     pub protocol_state: Account<'info, ProtocolState>,
 
     #[account(
-        seeds = [MARKET_SEED, &market.market_id.to_le_bytes()],
+        mut,
+        seeds = [MARKET_SEED, market.creator.as_ref(), &market.market_id.to_le_bytes()],
         bump = market.bump,
-        constraint = market.status == MarketStatus::Resolved @ FortunaError::MarketNotResolved
+        constraint = market.multi_collateral @ FortunaError::WrongMarketDenomination
     )]
     pub market: Account<'info, Market>,
 
@@ -514,21 +2735,23 @@ pub struct ClaimWinnings<'info> {
         seeds = [BET_SEED, market.key().as_ref(), claimer.key().as_ref()],
         bump = bet.bump,
         constraint = bet.bettor == claimer.key() @ FortunaError::Unauthorized,
-        constraint = !bet.claimed @ FortunaError::AlreadyClaimed
+        constraint = bet.is_active() @ FortunaError::AlreadyClaimed
     )]
     pub bet: Account<'info, Bet>,
 
     #[account(
         mut,
-        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
-        bump = market.vault_bump
+        seeds = [COLLATERAL_VAULT_SEED, market.key().as_ref(), bet.collateral_mint.as_ref()],
+        bump
     )]
-    pub market_vault: Account<'info, TokenAccount>,
+    pub collateral_vault: Account<'info, TokenAccount>,
 
     #[account(
         mut,
         constraint = claimer_token_account.owner == claimer.key(),
-        constraint = claimer_token_account.mint == market.token_mint
+        constraint = claimer_token_account.mint == bet.collateral_mint,
+        constraint = market.accepts_claim_account(&claimer_token_account.key(), &claimer.key(), &bet.collateral_mint)
+            @ FortunaError::ClaimAccountNotAta
     )]
     pub claimer_token_account: Account<'info, TokenAccount>,
 
@@ -539,88 +2762,96 @@ pub struct ClaimWinnings<'info> {
 }
 
 #[derive(Accounts)]
-pub struct CancelMarket<'info> {
+#[instruction(parlay_id: u64)]
+pub struct CreateParlay<'info> {
     #[account(
-        mut,
-        seeds = [MARKET_SEED, &market.market_id.to_le_bytes()],
-        bump = market.bump,
-        constraint = market.status == MarketStatus::Open @ FortunaError::MarketNotOpen,
-        constraint = market.creator == authority.key() @ FortunaError::Unauthorized
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
     )]
-    pub market: Account<'info, Market>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-}
+    pub protocol_state: Account<'info, ProtocolState>,
 
-#[derive(Accounts)]
-pub struct ClaimRefund<'info> {
     #[account(
-        seeds = [MARKET_SEED, &market.market_id.to_le_bytes()],
-        bump = market.bump,
-        constraint = market.status == MarketStatus::Cancelled @ FortunaError::MarketNotCancelled
+        init,
+        payer = bettor,
+        space = 8 + Parlay::INIT_SPACE,
+        seeds = [PARLAY_SEED, bettor.key().as_ref(), &parlay_id.to_le_bytes()],
+        bump
     )]
-    pub market: Account<'info, Market>,
+    pub parlay: Account<'info, Parlay>,
 
+    pub token_mint: Account<'info, Mint>,
+
+    /// Holds parlay stakes and the reserve for this mint; pays winning parlays
     #[account(
         mut,
-        seeds = [BET_SEED, market.key().as_ref(), claimer.key().as_ref()],
-        bump = bet.bump,
-        constraint = bet.bettor == claimer.key() @ FortunaError::Unauthorized,
-        constraint = !bet.claimed @ FortunaError::AlreadyClaimed
+        seeds = [PARLAY_VAULT_SEED, token_mint.key().as_ref()],
+        bump
     )]
-    pub bet: Account<'info, Bet>,
+    pub parlay_vault: Account<'info, TokenAccount>,
 
     #[account(
         mut,
-        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
-        bump = market.vault_bump
+        seeds = [PARLAY_BOOK_SEED, token_mint.key().as_ref()],
+        bump = parlay_book.bump
     )]
-    pub market_vault: Account<'info, TokenAccount>,
+    pub parlay_book: Account<'info, ParlayBook>,
 
     #[account(
         mut,
-        constraint = claimer_token_account.owner == claimer.key(),
-        constraint = claimer_token_account.mint == market.token_mint
+        constraint = bettor_token_account.owner == bettor.key(),
+        constraint = bettor_token_account.mint == token_mint.key()
     )]
-    pub claimer_token_account: Account<'info, TokenAccount>,
+    pub bettor_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Bettor's self-exclusion PDA; may be uninitialized, in which case the bettor is not excluded
+    #[account(
+        seeds = [SELF_EXCLUSION_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub self_exclusion: UncheckedAccount<'info>,
 
     #[account(mut)]
-    pub claimer: Signer<'info>,
+    pub bettor: Signer<'info>,
 
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct WithdrawBet<'info> {
+pub struct ClaimParlay<'info> {
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_state.bump
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
     #[account(
         mut,
-        seeds = [MARKET_SEED, &market.market_id.to_le_bytes()],
-        bump = market.bump,
-        constraint = market.status == MarketStatus::Open @ FortunaError::MarketNotOpen
+        seeds = [PARLAY_SEED, bettor.key().as_ref(), &parlay.parlay_id.to_le_bytes()],
+        bump = parlay.bump,
+        constraint = parlay.bettor == bettor.key() @ FortunaError::Unauthorized,
+        constraint = !parlay.claimed @ FortunaError::AlreadyClaimed
     )]
-    pub market: Account<'info, Market>,
+    pub parlay: Account<'info, Parlay>,
 
     #[account(
         mut,
-        seeds = [BET_SEED, market.key().as_ref(), bettor.key().as_ref()],
-        bump = bet.bump,
-        constraint = bet.bettor == bettor.key() @ FortunaError::Unauthorized,
-        constraint = !bet.claimed @ FortunaError::BetAlreadyWithdrawn
+        seeds = [PARLAY_VAULT_SEED, parlay.token_mint.as_ref()],
+        bump
     )]
-    pub bet: Account<'info, Bet>,
+    pub parlay_vault: Account<'info, TokenAccount>,
 
     #[account(
         mut,
-        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
-        bump = market.vault_bump
+        seeds = [PARLAY_BOOK_SEED, parlay.token_mint.as_ref()],
+        bump = parlay_book.bump
     )]
-    pub market_vault: Account<'info, TokenAccount>,
+    pub parlay_book: Account<'info, ParlayBook>,
 
     #[account(
         mut,
         constraint = bettor_token_account.owner == bettor.key(),
-        constraint = bettor_token_account.mint == market.token_mint
+        constraint = bettor_token_account.mint == parlay.token_mint
     )]
     pub bettor_token_account: Account<'info, TokenAccount>,
 
@@ -631,134 +2862,64 @@ pub struct WithdrawBet<'info> {
 }
 
 #[derive(Accounts)]
-pub struct UpdateProtocol<'info> {
+pub struct SettleLostParlay<'info> {
     #[account(
         mut,
-        seeds = [PROTOCOL_SEED],
-        bump = protocol_state.bump,
-        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+        seeds = [PARLAY_SEED, parlay.bettor.as_ref(), &parlay.parlay_id.to_le_bytes()],
+        bump = parlay.bump,
+        constraint = !parlay.claimed @ FortunaError::AlreadyClaimed
     )]
-    pub protocol_state: Account<'info, ProtocolState>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-}
-
-// ============================================================================
-// License Account Contexts
-// ============================================================================
+    pub parlay: Account<'info, Parlay>,
 
-#[derive(Accounts)]
-#[instruction(license_key: [u8; 32])]
-pub struct IssueLicense<'info> {
     #[account(
         mut,
-        seeds = [PROTOCOL_SEED],
-        bump = protocol_state.bump,
-        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
-    )]
-    pub protocol_state: Account<'info, ProtocolState>,
-
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + License::INIT_SPACE,
-        seeds = [LICENSE_SEED, &license_key],
-        bump
+        seeds = [PARLAY_BOOK_SEED, parlay.token_mint.as_ref()],
+        bump = parlay_book.bump
     )]
-    pub license: Account<'info, License>,
-
-    /// CHECK: The wallet that will hold this license
-    pub holder: UncheckedAccount<'info>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
+    pub parlay_book: Account<'info, ParlayBook>,
 }
 
 #[derive(Accounts)]
-pub struct RevokeLicense<'info> {
+pub struct FundParlayReserve<'info> {
     #[account(
         seeds = [PROTOCOL_SEED],
-        bump = protocol_state.bump,
-        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+        bump = protocol_state.bump
     )]
     pub protocol_state: Account<'info, ProtocolState>,
 
-    #[account(
-        mut,
-        seeds = [LICENSE_SEED, &license.license_key],
-        bump = license.bump
-    )]
-    pub license: Account<'info, License>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-}
-
-#[derive(Accounts)]
-pub struct TransferLicense<'info> {
-    #[account(
-        mut,
-        seeds = [LICENSE_SEED, &license.license_key],
-        bump = license.bump,
-        constraint = license.holder == current_holder.key() @ FortunaError::Unauthorized,
-        constraint = license.is_transferable @ FortunaError::LicenseNotTransferable
-    )]
-    pub license: Account<'info, License>,
-
-    /// CHECK: The new holder of the license
-    pub new_holder: UncheckedAccount<'info>,
-
-    #[account(mut)]
-    pub current_holder: Signer<'info>,
-}
+    pub token_mint: Account<'info, Mint>,
 
-#[derive(Accounts)]
-pub struct UpdateLicense<'info> {
+    /// Holds parlay stakes and the reserve for this mint; pays winning parlays
     #[account(
-        seeds = [PROTOCOL_SEED],
-        bump = protocol_state.bump,
-        constraint = protocol_state.authority == authority.key() @ FortunaError::Unauthorized
+        init_if_needed,
+        payer = funder,
+        seeds = [PARLAY_VAULT_SEED, token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = protocol_state
     )]
-    pub protocol_state: Account<'info, ProtocolState>,
+    pub parlay_vault: Account<'info, TokenAccount>,
 
     #[account(
-        mut,
-        seeds = [LICENSE_SEED, &license.license_key],
-        bump = license.bump
+        init_if_needed,
+        payer = funder,
+        space = 8 + ParlayBook::INIT_SPACE,
+        seeds = [PARLAY_BOOK_SEED, token_mint.key().as_ref()],
+        bump
     )]
-    pub license: Account<'info, License>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-}
+    pub parlay_book: Account<'info, ParlayBook>,
 
-#[derive(Accounts)]
-pub struct ModifyLicenseWallets<'info> {
     #[account(
         mut,
-        seeds = [LICENSE_SEED, &license.license_key],
-        bump = license.bump,
-        constraint = license.holder == holder.key() @ FortunaError::Unauthorized
+        constraint = funder_token_account.owner == funder.key(),
+        constraint = funder_token_account.mint == token_mint.key()
     )]
-    pub license: Account<'info, License>,
+    pub funder_token_account: Account<'info, TokenAccount>,
 
     #[account(mut)]
-    pub holder: Signer<'info>,
-}
-
-#[derive(Accounts)]
-pub struct ModifyLicenseDomains<'info> {
-    #[account(
-        mut,
-        seeds = [LICENSE_SEED, &license.license_key],
-        bump = license.bump,
-        constraint = license.holder == holder.key() @ FortunaError::Unauthorized
-    )]
-    pub license: Account<'info, License>,
+    pub funder: Signer<'info>,
 
-    #[account(mut)]
-    pub holder: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
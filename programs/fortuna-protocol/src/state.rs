@@ -1,6 +1,11 @@
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address;
+
+use crate::constants::*;
+use crate::errors::FortunaError;
 
 /// Maximum number of outcomes for a market (e.g., Yes/No = 2, or multiple choice)
+pub const MIN_OUTCOMES: usize = 2;
 pub const MAX_OUTCOMES: usize = 10;
 /// Maximum title length
 pub const MAX_TITLE_LEN: usize = 128;
@@ -12,10 +17,6 @@ pub const MAX_OUTCOME_LEN: usize = 64;
 pub const MAX_ORACLE_NAME_LEN: usize = 64;
 /// Maximum data source URL length
 pub const MAX_DATA_SOURCE_LEN: usize = 256;
-/// Maximum allowed domains for a license
-pub const MAX_ALLOWED_DOMAINS: usize = 5;
-/// Maximum domain length
-pub const MAX_DOMAIN_LEN: usize = 64;
 
 /// License types for different feature tiers
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
@@ -121,6 +122,21 @@ impl LicenseFeatures {
     }
 }
 
+/// Snapshot of a license's usability, returned by `license_status_view`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LicenseStatus {
+    /// Markets the license may still create
+    pub remaining_markets: u32,
+    /// Whether the license is active and unexpired right now
+    pub is_valid: bool,
+    /// Whether the license is active
+    pub is_active: bool,
+    /// Expiry timestamp (0 = never expires)
+    pub expires_at: i64,
+    /// Enabled features
+    pub features: LicenseFeatures,
+}
+
 /// License account - grants access to protocol features
 #[account]
 #[derive(InitSpace)]
@@ -138,11 +154,11 @@ pub struct License {
     pub features: LicenseFeatures,
 
     /// Allowed domains (for domain locking) - empty means any domain
-    #[max_len(5, 64)]
+    #[max_len(MAX_LICENSE_DOMAINS, MAX_DOMAIN_NAME_LEN)]
     pub allowed_domains: Vec<String>,
 
     /// Allowed wallets (for wallet locking) - empty means only holder
-    #[max_len(10)]
+    #[max_len(MAX_LICENSE_WALLETS)]
     pub allowed_wallets: Vec<Pubkey>,
 
     /// Maximum markets this license can create
@@ -166,13 +182,19 @@ pub struct License {
     /// Last activity timestamp
     pub last_used_at: i64,
 
+    /// Start of the current market creation rate limit window
+    pub window_start: i64,
+
+    /// Markets created since `window_start`
+    pub created_in_window: u32,
+
     /// Who issued this license
     pub issued_by: Pubkey,
 
     /// Bump seed for PDA
     pub bump: u8,
 
-    /// Reserved for future use
+    /// Reserved for future use (always empty; INIT_SPACE sizes it at 4 + max_len bytes)
     #[max_len(32)]
     pub reserved: Vec<u8>,
 }
@@ -183,17 +205,42 @@ impl License {
         if !self.is_active {
             return false;
         }
-        if self.expires_at > 0 && current_time > self.expires_at {
+        if self.is_expired(current_time) {
             return false;
         }
         true
     }
 
+    /// Check if license has passed its expiry (0 = never expires)
+    pub fn is_expired(&self, current_time: i64) -> bool {
+        self.expires_at > 0 && current_time > self.expires_at
+    }
+
+    /// Markets the license may still create
+    pub fn remaining_markets(&self) -> u32 {
+        self.max_markets.saturating_sub(self.markets_created)
+    }
+
     /// Check if license can create more markets
     pub fn can_create_market(&self) -> bool {
         self.features.can_create_markets && self.markets_created < self.max_markets
     }
 
+    /// Count a market creation against the rate limit, starting a fresh window once the
+    /// current one has run out (a limit of 0 = unlimited)
+    pub fn record_rate_limited_creation(&mut self, now: i64, limit: u32, window_secs: i64) -> Result<()> {
+        if limit == 0 {
+            return Ok(());
+        }
+        if now >= self.window_start.saturating_add(window_secs) {
+            self.window_start = now;
+            self.created_in_window = 0;
+        }
+        require!(self.created_in_window < limit, FortunaError::LicenseRateLimited);
+        self.created_in_window += 1;
+        Ok(())
+    }
+
     /// Check if a wallet is authorized under this license
     pub fn is_wallet_authorized(&self, wallet: &Pubkey) -> bool {
         // Holder is always authorized
@@ -211,6 +258,19 @@ impl License {
         }
         self.allowed_domains.iter().any(|d| d == domain)
     }
+
+    /// Check a license key against this license without an early exit on the first mismatch
+    pub fn key_matches(&self, license_key: &[u8; 32]) -> bool {
+        constant_time_eq(&self.license_key, license_key)
+    }
+}
+
+/// Compare two license keys in time independent of where they differ.
+/// Use this instead of `==` wherever license key bytes are compared directly.
+pub fn constant_time_eq(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    let diff = a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    // Keep the optimizer from turning the fold back into a short-circuiting compare
+    std::hint::black_box(diff) == 0
 }
 
 /// Market categories for prediction markets
@@ -288,6 +348,56 @@ impl MarketCategory {
     }
 }
 
+/// Volume-based protocol fee rebate tier
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct RebateTier {
+    /// Minimum bet amount (in smallest token unit) to qualify for this tier
+    pub threshold: u64,
+
+    /// Portion of the protocol fee rebated to the bettor, in basis points
+    pub rebate_bps: u16,
+}
+
+/// Pool-size fee discount tier
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct FeeTaperTier {
+    /// Minimum market total pool (in smallest token unit) for this tier to apply
+    pub pool_threshold: u64,
+
+    /// Portion of the normal fees still charged, in basis points
+    pub fee_multiplier_bps: u16,
+}
+
+/// A window of a market's betting period with its own pool and creator fees
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct BettingPhase {
+    /// Unix timestamp the phase ends at (exclusive)
+    pub phase_end: i64,
+
+    /// Pool fee charged during the phase, in basis points
+    pub pool_fee_bps: u16,
+
+    /// Creator fee charged during the phase, in basis points
+    pub creator_fee_bps: u16,
+}
+
+/// Scratch counters for an in-progress recompute of the protocol statistics
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug, Default)]
+pub struct StatsRecompute {
+    /// Whether a recompute has been started and not yet finalized
+    pub in_progress: bool,
+
+    /// Markets counted so far
+    pub total_markets: u64,
+
+    /// Volume counted so far (sum of market pools)
+    pub total_volume: u128,
+
+    /// Last market counted; markets must be supplied in ascending address order
+    /// (IDs are only unique per creator)
+    pub last_market: Pubkey,
+}
+
 /// Protocol-wide configuration state
 #[account]
 #[derive(InitSpace)]
@@ -307,6 +417,9 @@ pub struct ProtocolState {
     /// Pool fee in basis points (5% = 500 bps)
     pub pool_fee_bps: u16,
 
+    /// Protocol fee override in basis points, indexed by market category (0 = global fee)
+    pub protocol_fee_bps_per_category: [u16; 12],
+
     /// Total markets created
     pub total_markets: u64,
 
@@ -322,10 +435,96 @@ pub struct ProtocolState {
     /// Whether a valid license is required to create markets
     pub require_license: bool,
 
+    /// Protocol fee rebate tiers, sorted by ascending threshold
+    #[max_len(MAX_REBATE_TIERS)]
+    pub rebate_tiers: Vec<RebateTier>,
+
+    /// Loss protection premium in basis points of the covered amount (0 = unavailable)
+    pub loss_protection_premium_bps: u16,
+
+    /// Policy applied to new markets whose winning outcome ends up with no bets
+    pub stranded_pool_policy: StrandedPoolPolicy,
+
+    /// Program owning the randomness accounts random-resolution markets resolve from
+    pub randomness_program: Pubkey,
+
+    /// Whether market creators may resolve their own markets (false = oracle-only)
+    pub creator_resolution_allowed: bool,
+
+    /// Minimum fixed bet amount for new markets (0 = no minimum)
+    pub min_bet_amount: u64,
+
+    /// Maximum fixed bet amount for new markets (0 = no maximum)
+    pub max_bet_amount: u64,
+
+    /// Seconds after resolution before winnings can be claimed, indexed by market category
+    pub claim_delay_per_category: [i64; 12],
+
+    /// Furthest a new market's betting deadline may be from creation, in seconds (0 = unbounded)
+    pub max_future_deadline_secs: i64,
+
+    /// Mint that fee-token markets charge the flat protocol fee in (default = none)
+    pub fee_mint: Pubkey,
+
+    /// Flat protocol fee per bet on fee-token markets, in `fee_mint` units
+    pub flat_protocol_fee: u64,
+
+    /// Betting mint clients fall back to when creating a market without one (default = none)
+    pub default_token_mint: Pubkey,
+
+    /// Minimum outcome count for new markets, indexed by market category (0 = global minimum)
+    pub min_outcomes_per_category: [u8; 12],
+
+    /// Maximum outcome count for new markets, indexed by market category (0 = global maximum)
+    pub max_outcomes_per_category: [u8; 12],
+
+    /// Collect creator fees into the treasury as protocol revenue instead of paying creators
+    pub redirect_creator_fees_to_treasury: bool,
+
+    /// Seconds a bet reservation stays confirmable before it can only be refunded
+    pub reservation_window_secs: i64,
+
+    /// Mint oracle bonds are posted in
+    pub oracle_bond_mint: Pubkey,
+
+    /// Bond a new oracle must post at registration (0 = no bond)
+    pub oracle_bond_amount: u64,
+
+    /// Seconds after its last resolution before a deactivated oracle can withdraw its bond
+    pub oracle_bond_cooldown_secs: i64,
+
+    /// Most open markets a single oracle may be assigned to (0 = unlimited)
+    pub max_markets_per_oracle: u32,
+
+    /// Most markets a license may create per rate limit window (0 = unlimited)
+    pub license_rate_limit: u32,
+
+    /// Length of the license rate limit window in seconds
+    pub license_rate_window_secs: i64,
+
+    /// Fee discounts for deep markets, sorted by ascending pool threshold
+    #[max_len(MAX_FEE_TAPER_TIERS)]
+    pub fee_taper_schedule: Vec<FeeTaperTier>,
+
+    /// Categories whose winnings settle into a time-locked escrow (indexed by MarketCategory)
+    pub escrow_claims: [bool; 12],
+
+    /// Scratch state for `recompute_protocol_stats` / `finalize_stats_recompute`
+    pub stats_recompute: StatsRecompute,
+
+    /// Key that may take over a dormant authority (default = no recovery)
+    pub recovery_authority: Pubkey,
+
+    /// Seconds without an admin action before the authority counts as dormant
+    pub authority_dormancy_secs: i64,
+
+    /// Timestamp of the authority's last admin action
+    pub last_authority_activity: i64,
+
     /// Bump seed for PDA
     pub bump: u8,
 
-    /// Reserved for future use
+    /// Reserved for future use (always empty; INIT_SPACE sizes it at 4 + max_len bytes)
     #[max_len(64)]
     pub reserved: Vec<u8>,
 }
@@ -347,6 +546,221 @@ impl Default for MarketStatus {
     }
 }
 
+/// How a resolved market's pool is distributed among winning bets
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum PayoutMode {
+    /// Winning bets split the pool proportionally to their stake
+    Parimutuel,
+    /// The earliest bet on the winning outcome takes the entire pool
+    WinnerTakeAll,
+    /// Winning bets receive their stake times the outcome's preset multiplier,
+    /// backed by liquidity the creator deposits
+    FixedMultiplier,
+}
+
+impl Default for PayoutMode {
+    fn default() -> Self {
+        PayoutMode::Parimutuel
+    }
+}
+
+/// Who decides the winning outcome of a market
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum ResolutionMode {
+    /// Resolved by the creator or an assigned oracle
+    Standard,
+    /// Resolved by a stake-weighted vote of the market's bettors
+    CommunityVote,
+    /// Resolved by a value revealed in an account of the protocol's randomness program
+    Random,
+    /// Resolved by the creator revealing the outcome committed to at creation
+    CommitReveal,
+}
+
+impl Default for ResolutionMode {
+    fn default() -> Self {
+        ResolutionMode::Standard
+    }
+}
+
+/// What happens to a market whose declared winner received no bets
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum StrandedPoolPolicy {
+    /// Cancel the market so every bettor can claim a refund
+    Void,
+    /// Resolve normally and let the losing pool be swept to the treasury
+    SweepToTreasury,
+}
+
+impl Default for StrandedPoolPolicy {
+    fn default() -> Self {
+        StrandedPoolPolicy::Void
+    }
+}
+
+/// Why a market ended up cancelled
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum CancelReason {
+    /// Market has not been cancelled
+    None,
+    /// Cancelled by the market creator
+    CreatorCancelled,
+    /// Cancelled by the protocol authority
+    AdminCancelled,
+    /// Voided because the winning outcome received no bets
+    InsufficientParticipation,
+    /// Left unresolved past its resolution deadline
+    Expired,
+    /// Its assigned oracle never resolved it by the resolution deadline
+    OracleFailure,
+}
+
+impl Default for CancelReason {
+    fn default() -> Self {
+        CancelReason::None
+    }
+}
+
+/// Where a bet is in its lifecycle
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum BetState {
+    /// Placed and not yet settled
+    Active,
+    /// Withdrawn by the bettor before the betting deadline
+    Withdrawn,
+    /// Refunded after the market was cancelled
+    Refunded,
+    /// Winnings paid out after resolution
+    ClaimedWin,
+    /// Loss-protected amount paid out on a losing bet
+    ClaimedLoss,
+}
+
+impl Default for BetState {
+    fn default() -> Self {
+        BetState::Active
+    }
+}
+
+/// Optional market settings chosen at creation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct MarketOptions {
+    /// How the pool is distributed among winning bets
+    pub payout_mode: PayoutMode,
+
+    /// Who decides the winning outcome
+    pub resolution_mode: ResolutionMode,
+
+    /// Maximum total stake on any single outcome (0 = unlimited)
+    pub max_per_outcome: u64,
+
+    /// Maximum number of live bets on the market (0 = unlimited)
+    pub max_bets: u32,
+
+    /// Seconds a bet must be held before it can be withdrawn (0 = no hold)
+    pub min_hold_secs: i64,
+
+    /// Protocol-owned market created by the protocol authority (skips licensing, fees go to treasury)
+    pub is_house_market: bool,
+
+    /// Denominate the market in native SOL (lamports) instead of an SPL token
+    pub native_sol: bool,
+
+    /// Accept bets in several collateral mints, normalized to a common unit
+    pub multi_collateral: bool,
+
+    /// Accepted mints and their reference prices (multi-collateral markets only)
+    pub collateral_mints: Vec<CollateralMint>,
+
+    /// Hash of the winning outcome and a salt (commit-reveal markets only)
+    pub result_commitment: [u8; 32],
+
+    /// Only pay claims into the claimer's associated token account
+    pub require_ata: bool,
+
+    /// Let the creator or oracle resolve before the betting deadline
+    pub allow_early_resolution: bool,
+
+    /// Charge the protocol's flat fee in this mint instead of percentage fees (default = none)
+    pub fee_mint: Pubkey,
+
+    /// Payout multiplier per outcome in basis points (fixed-multiplier markets only)
+    pub payout_multipliers: Vec<u32>,
+
+    /// Number of initial bettors who pay no fees (0 = none)
+    pub fee_waiver_count: u32,
+
+    /// Waive all fees until at least two outcomes have bets, so a one-sided market pays
+    /// everything into the pool
+    pub two_sided_fees: bool,
+
+    /// Bettors needed by the deadline for the creator to keep their fees; below it the
+    /// escrowed creator fees go to the bonus pool (0 = always paid to the creator)
+    pub min_bettors_for_creator_fee: u32,
+
+    /// Seconds after resolution that winners have to claim before unclaimed winnings can be
+    /// swept to the treasury (0 = never expire)
+    pub claim_expiry_secs: i64,
+
+    /// Only accept bets from wallets whose bettor profile existed before the market
+    /// (single-mint SPL markets only)
+    pub require_established_wallet: bool,
+
+    /// Snapshot implied odds into an odds history account on every bet (single-mint SPL
+    /// markets only)
+    pub track_odds: bool,
+
+    /// Write an SPL memo naming the market alongside every winnings claim (single-mint SPL
+    /// markets only)
+    pub payout_memo: bool,
+
+    /// Share of the creator fee redirected into the bonus pool for bets placed in the early
+    /// window, in basis points (0 = creator keeps the whole fee)
+    pub creator_lp_share_bps: u16,
+
+    /// Length of the early window in seconds from market creation
+    pub creator_lp_share_window_secs: i64,
+
+    /// Prize the creator pays into the bonus pool at creation, shared by the winners
+    /// (single-mint SPL markets only, 0 = none)
+    pub seed_bonus: u64,
+
+    /// Display channels the market is tagged for, as a bitfield interpreted by front-ends
+    pub visibility_flags: u32,
+
+    /// Consecutive betting windows with their own pool and creator fees, ordered by end time;
+    /// bets after the last phase pay the protocol's fees (empty = protocol fees throughout)
+    pub betting_phases: Vec<BettingPhase>,
+}
+
+/// A collateral mint accepted by a multi-collateral market
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct CollateralMint {
+    /// Accepted token mint
+    pub mint: Pubkey,
+
+    /// Common units per token unit, scaled by PRICE_SCALE_ONE
+    pub price_scale: u64,
+}
+
+impl CollateralMint {
+    /// Convert an amount of this mint into common units
+    pub fn to_common_units(&self, amount: u64) -> Option<u64> {
+        let units = (amount as u128)
+            .checked_mul(self.price_scale as u128)?
+            .checked_div(PRICE_SCALE_ONE as u128)?;
+        u64::try_from(units).ok()
+    }
+
+    /// Convert common units back into an amount of this mint
+    pub fn from_common_units(&self, units: u64) -> Option<u64> {
+        let amount = (units as u128)
+            .checked_mul(PRICE_SCALE_ONE as u128)?
+            .checked_div(self.price_scale as u128)?;
+        u64::try_from(amount).ok()
+    }
+}
+
 /// Individual outcome tracking
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct Outcome {
@@ -359,6 +773,18 @@ pub struct Outcome {
 
     /// Number of bettors on this outcome
     pub bettor_count: u32,
+
+    /// Total loss-protected amount of bets on this outcome
+    pub protected_amount: u64,
+
+    /// Earliest bet placed on this outcome (used by winner-take-all markets)
+    pub first_bet: Pubkey,
+
+    /// Stake-weighted resolution votes cast for this outcome
+    pub vote_weight: u64,
+
+    /// Whether the outcome still accepts new bets
+    pub is_open: bool,
 }
 
 /// Oracle account for automated market resolution
@@ -394,15 +820,28 @@ pub struct Oracle {
     /// Last resolution timestamp
     pub last_resolution_at: i64,
 
+    /// Bond held in the oracle's bond vault, slashable by the protocol authority
+    pub bond_amount: u64,
+
+    /// Open markets currently assigned to this oracle
+    pub assigned_open_markets: u32,
+
     /// Bump seed for PDA
     pub bump: u8,
 
-    /// Reserved for future use
+    /// Reserved for future use (always empty; INIT_SPACE sizes it at 4 + max_len bytes)
     #[max_len(32)]
     pub reserved: Vec<u8>,
 }
 
 impl Oracle {
+    /// Whether the bond may be withdrawn: the oracle must be deactivated and its
+    /// last resolution (or registration) must be older than the slashing cooldown
+    pub fn bond_unlocked_at(&self, now: i64, cooldown_secs: i64) -> bool {
+        let last_activity = self.last_resolution_at.max(self.registered_at);
+        !self.is_active && now >= last_activity.saturating_add(cooldown_secs)
+    }
+
     /// Check if oracle can resolve a specific category
     pub fn can_resolve_category(&self, category: MarketCategory) -> bool {
         let index = category as usize;
@@ -430,6 +869,28 @@ impl Oracle {
     }
 }
 
+/// Per-category settings managed by the protocol authority
+#[account]
+#[derive(InitSpace)]
+pub struct CategoryConfig {
+    /// Category this config applies to
+    pub category: MarketCategory,
+
+    /// Mints markets in this category may bet in (empty = any mint)
+    #[max_len(MAX_CATEGORY_MINTS)]
+    pub allowed_mints: Vec<Pubkey>,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl CategoryConfig {
+    /// Check if markets in this category may use the given mint
+    pub fn allows_mint(&self, mint: &Pubkey) -> bool {
+        self.allowed_mints.is_empty() || self.allowed_mints.contains(mint)
+    }
+}
+
 /// Prediction market account
 #[account]
 #[derive(InitSpace)]
@@ -452,6 +913,10 @@ pub struct Market {
     /// Assigned oracle for automated resolution (optional)
     pub oracle: Pubkey,
 
+    /// Whether the assigned oracle covered the market's category when it was assigned; it
+    /// keeps the right to resolve even if its categories change later
+    pub authorized_at_assignment: bool,
+
     /// External event ID for oracle resolution (e.g., match ID, stock symbol)
     #[max_len(64)]
     pub oracle_event_id: String,
@@ -485,6 +950,9 @@ pub struct Market {
     /// Total amount in the bonus pool (from pool fees)
     pub bonus_pool: u64,
 
+    /// Creator fees held in the market until it resolves; refunded to bettors if it's cancelled
+    pub escrowed_creator_fees: u64,
+
     /// All possible outcomes
     #[max_len(10)]
     pub outcomes: Vec<Outcome>,
@@ -498,6 +966,151 @@ pub struct Market {
     /// Whether market was resolved by oracle
     pub resolved_by_oracle: bool,
 
+    /// Whether the market is exempt from all fees (promotional markets)
+    pub fee_exempt: bool,
+
+    /// Number of initial bettors who pay no fees (0 = none)
+    pub fee_waiver_count: u32,
+
+    /// Whether fees are waived until at least two outcomes have bets
+    pub two_sided_fees: bool,
+
+    /// Bettors needed by the deadline for the creator to keep their fees (0 = always kept)
+    pub min_bettors_for_creator_fee: u32,
+
+    /// Seconds after resolution before unclaimed winnings can be swept (0 = never expire)
+    pub claim_expiry_secs: i64,
+
+    /// Only accept bets from wallets whose bettor profile predates the market
+    pub require_established_wallet: bool,
+
+    /// Whether each bet snapshots the implied odds into the market's odds history
+    pub track_odds: bool,
+
+    /// Whether winnings claims write a memo naming the market
+    pub payout_memo: bool,
+
+    /// Share of the creator fee paid into the bonus pool during the early betting window, in
+    /// basis points
+    pub creator_lp_share_bps: u16,
+
+    /// Seconds after creation during which the creator fee share goes to the bonus pool
+    pub creator_lp_share_window_secs: i64,
+
+    /// Betting windows with their own pool and creator fees, ordered by end time
+    #[max_len(MAX_BETTING_PHASES)]
+    pub betting_phases: Vec<BettingPhase>,
+
+    /// Whether betting and resolution are paused (exits stay open)
+    pub paused: bool,
+
+    /// Display channels the market is tagged for, as a bitfield interpreted by front-ends
+    pub visibility_flags: u32,
+
+    /// How the pool is distributed among winning bets
+    pub payout_mode: PayoutMode,
+
+    /// Bet entitled to the whole pool in winner-take-all markets (set at resolution)
+    pub winning_bet: Pubkey,
+
+    /// Who decides the winning outcome
+    pub resolution_mode: ResolutionMode,
+
+    /// Policy applied if the winning outcome has no bets (snapshotted from the protocol)
+    pub stranded_pool_policy: StrandedPoolPolicy,
+
+    /// Whether a stranded pool has been swept to the treasury
+    pub stranded_swept: bool,
+
+    /// Maximum total stake on any single outcome, in pool units (0 = unlimited)
+    pub max_per_outcome: u64,
+
+    /// Maximum number of live bets on the market (0 = unlimited)
+    pub max_bets: u32,
+
+    /// Number of live bets (placed and not withdrawn)
+    pub bet_count: u32,
+
+    /// Number of bettors who have asked for the market to be resolved
+    pub resolution_requests: u32,
+
+    /// Seconds a bet must be held before it can be withdrawn (0 = no hold)
+    pub min_hold_secs: i64,
+
+    /// When randomness was requested for a random-resolution market (0 = not requested)
+    pub randomness_requested_at: i64,
+
+    /// Randomness account the request is bound to (random-resolution markets only)
+    pub randomness_account: Pubkey,
+
+    /// Commit slot of the randomness account when it was requested
+    pub randomness_seed_slot: u64,
+
+    /// Random value used to resolve the market (kept for auditability)
+    pub randomness: [u8; 32],
+
+    /// sha256(outcome || salt) committed by the creator (commit-reveal markets only)
+    pub result_commitment: [u8; 32],
+
+    /// Whether claims must be paid into the claimer's associated token account
+    pub require_ata: bool,
+
+    /// Whether the creator or oracle may resolve before the betting deadline
+    pub allow_early_resolution: bool,
+
+    /// Mint the flat protocol fee is paid in; the full bet goes to the pool (default = percentage fees)
+    pub fee_mint: Pubkey,
+
+    /// Whether this is a protocol-owned house market
+    pub is_house_market: bool,
+
+    /// Number of winning bets entitled to a payout (set at resolution)
+    pub winning_bets_total: u32,
+
+    /// Number of winning bets that have claimed their payout
+    pub winning_bets_claimed: u32,
+
+    /// Winnings already paid out of the betting pool
+    pub pool_paid: u64,
+
+    /// Winnings already paid out of the bonus pool
+    pub bonus_paid: u64,
+
+    /// Loss protection already paid out to losing bets
+    pub loss_protection_claimed: u64,
+
+    /// Number of bets refunded after the market was cancelled
+    pub refunds_claimed: u32,
+
+    /// Unsettled parlays with a leg on this market; they read it when they settle
+    pub open_parlays: u32,
+
+    /// Whether bets and payouts move native SOL held by the market account
+    pub native_sol: bool,
+
+    /// Why the market was cancelled (None while not cancelled)
+    pub cancel_reason: CancelReason,
+
+    /// Set while an instruction is moving funds across several accounts
+    pub processing: bool,
+
+    /// Whether bets are taken in several collateral mints (pool amounts are in common units)
+    pub multi_collateral: bool,
+
+    /// Accepted collateral mints and their reference prices
+    #[max_len(MAX_COLLATERAL_MINTS)]
+    pub collateral_mints: Vec<CollateralMint>,
+
+    /// Payout multiplier per outcome in basis points (fixed-multiplier markets only)
+    #[max_len(10)]
+    pub payout_multipliers: Vec<u32>,
+
+    /// Creator liquidity held in the market vault to back multiplier payouts
+    pub liquidity: u64,
+
+    /// Whether the creator has taken back the liquidity left after settlement
+    pub liquidity_withdrawn: bool,
+
     /// Market vault bump seed
     pub vault_bump: u8,
 
@@ -507,8 +1120,8 @@ pub struct Market {
     /// Market account bump seed
     pub bump: u8,
 
-    /// Reserved for future use
-    #[max_len(32)]
+    /// Reserved for future use (always empty; INIT_SPACE sizes it at 4 + max_len bytes)
+    #[max_len(MARKET_RESERVED_LEN)]
     pub reserved: Vec<u8>,
 }
 
@@ -531,8 +1144,33 @@ pub struct Bet {
     /// Amount added to pool (after fees)
     pub pool_amount: u64,
 
-    /// Whether winnings have been claimed
-    pub claimed: bool,
+    /// Creator fee paid with this bet, held in escrow until the market resolves
+    pub creator_fee: u64,
+
+    /// Lifecycle state; anything but `Active` is terminal
+    pub bet_state: BetState,
+
+    /// Share of the pool amount covered by loss protection, in basis points
+    pub loss_protection_bps: u16,
+
+    /// Amount refundable if this bet loses (0 = unprotected)
+    pub protected_amount: u64,
+
+    /// Whether this bet has cast its community resolution vote
+    pub voted: bool,
+
+    /// Whether the bettor has asked for the market to be resolved
+    pub resolution_requested: bool,
+
+    /// Mint the stake was paid in (multi-collateral markets only)
+    pub collateral_mint: Pubkey,
+
+    /// Stake held in the collateral vault, in collateral mint units (multi-collateral markets only)
+    pub collateral_amount: u64,
+
+    /// Optional public note from the bettor (empty = none)
+    #[max_len(MAX_BET_MEMO_LEN)]
+    pub memo: String,
 
     /// Timestamp when bet was placed
     pub placed_at: i64,
@@ -540,51 +1178,744 @@ pub struct Bet {
     /// Bump seed for PDA
     pub bump: u8,
 
-    /// Reserved for future use
+    /// Reserved for future use (always empty; INIT_SPACE sizes it at 4 + max_len bytes)
     #[max_len(16)]
     pub reserved: Vec<u8>,
 }
 
-impl Market {
-    /// Calculate the payout for a winning bet
-    pub fn calculate_payout(&self, bet: &Bet) -> u64 {
-        if self.status != MarketStatus::Resolved {
-            return 0;
-        }
+impl Bet {
+    /// Whether the bet is still live (not withdrawn, refunded or paid out)
+    pub fn is_active(&self) -> bool {
+        self.bet_state == BetState::Active
+    }
 
-        if bet.outcome_index != self.winning_outcome {
-            return 0;
-        }
+    /// Whether the market's minimum hold period has passed for this bet
+    pub fn is_withdrawable_at(&self, market: &Market, now: i64) -> bool {
+        now >= self.placed_at.saturating_add(market.min_hold_secs)
+    }
 
-        let winning_outcome = &self.outcomes[self.winning_outcome as usize];
+    /// Account space for a bet whose memo is `memo_len` bytes, so bets
+    /// without a memo don't pay rent for the full memo capacity
+    pub fn space(memo_len: usize) -> usize {
+        8 + Bet::INIT_SPACE - MAX_BET_MEMO_LEN + memo_len
+    }
+}
 
-        if winning_outcome.total_amount == 0 {
-            return 0;
-        }
+/// One leg of a parlay: a bet the bettor holds on a market
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ParlayLeg {
+    /// Market the leg is on
+    pub market: Pubkey,
 
-        // Calculate share of the total pool + bonus pool
-        let total_distributable = self.total_pool + self.bonus_pool;
+    /// The bettor's bet on that market
+    pub bet: Pubkey,
 
-        // Proportional share based on bet amount
-        let share = (bet.pool_amount as u128)
-            .checked_mul(total_distributable as u128)
-            .unwrap()
-            .checked_div(winning_outcome.total_amount as u128)
-            .unwrap();
+    /// Outcome the leg needs (copied from the bet)
+    pub outcome_index: u8,
+}
 
-        share as u64
-    }
+/// Combined stake that pays only if every leg's outcome wins
+#[account]
+#[derive(InitSpace)]
+pub struct Parlay {
+    /// Parlay identifier (unique per bettor)
+    pub parlay_id: u64,
 
-    /// Get the total number of bettors across all outcomes
-    pub fn total_bettors(&self) -> u32 {
-        self.outcomes.iter().map(|o| o.bettor_count).sum()
-    }
+    /// The bettor's wallet
+    pub bettor: Pubkey,
 
-    /// Check if betting deadline has passed
-    pub fn is_betting_closed(&self, current_time: i64) -> bool {
+    /// Token mint shared by every leg market
+    pub token_mint: Pubkey,
+
+    /// Legs, each on a distinct market
+    #[max_len(MAX_PARLAY_LEGS)]
+    pub legs: Vec<ParlayLeg>,
+
+    /// Amount staked on the parlay, held in the parlay vault
+    pub stake: u64,
+
+    /// Payout owed if every leg wins, locked from the legs' pool odds at creation
+    pub payout: u64,
+
+    /// Whether the payout or refund has been claimed
+    pub claimed: bool,
+
+    /// Timestamp when parlay was created
+    pub created_at: i64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl Parlay {
+    /// Payout for a parlay whose legs all win: the stake multiplied by each leg market's
+    /// pool odds (total pool / pool on the leg's outcome). Computed when the parlay is
+    /// created, so the reserve knows what the parlay can cost.
+    pub fn combined_payout(stake: u64, legs: &[ParlayLeg], markets: &[Market]) -> Option<u64> {
+        let mut payout = stake as u128;
+        for (leg, market) in legs.iter().zip(markets) {
+            let outcome_amount = market.outcomes.get(leg.outcome_index as usize)?.total_amount;
+            payout = payout
+                .checked_mul(market.total_pool as u128)?
+                .checked_div(outcome_amount as u128)?;
+        }
+        u64::try_from(payout).ok()
+    }
+}
+
+/// What the parlay vault of one mint owes its open parlays. Winning parlays are paid
+/// from stakes and the reserve funded into the vault, so the vault must always hold at
+/// least the liability.
+#[account]
+#[derive(InitSpace)]
+pub struct ParlayBook {
+    /// Mint of the parlay vault
+    pub token_mint: Pubkey,
+
+    /// Sum of the locked payouts of unsettled parlays (a refund never exceeds the payout)
+    pub liability: u64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+/// Markets sharing an oracle event ID, so keepers can look them up by the external ID
+#[account]
+#[derive(InitSpace)]
+pub struct EventIndex {
+    /// Hash of the oracle event ID the PDA is derived from
+    pub event_id_hash: [u8; 32],
+
+    /// Markets created with this event ID, in creation order
+    #[max_len(MAX_EVENT_INDEX_MARKETS)]
+    pub markets: Vec<Pubkey>,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl EventIndex {
+    /// PDA seed for an oracle event ID (IDs can exceed the 32-byte seed limit)
+    pub fn event_id_hash(event_id: &str) -> [u8; 32] {
+        anchor_lang::solana_program::hash::hash(event_id.as_bytes()).to_bytes()
+    }
+}
+
+/// A wallet's voluntary opt-out from betting
+#[account]
+#[derive(InitSpace)]
+pub struct SelfExclusion {
+    /// The excluded wallet
+    pub wallet: Pubkey,
+
+    /// Timestamp until which the wallet cannot place bets
+    pub until: i64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl SelfExclusion {
+    /// Whether the exclusion is still in force
+    pub fn is_excluded_at(&self, now: i64) -> bool {
+        now < self.until
+    }
+}
+
+/// First-seen record for a wallet, used to screen out freshly created sybil wallets
+#[account]
+#[derive(InitSpace)]
+pub struct BettorProfile {
+    /// The profiled wallet
+    pub wallet: Pubkey,
+
+    /// When the profile was created
+    pub created_at: i64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+/// A bettor's winnings from contested categories, held until the category's delay passes
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimEscrow {
+    /// The bettor's wallet
+    pub bettor: Pubkey,
+
+    /// Token mint of the escrowed winnings
+    pub token_mint: Pubkey,
+
+    /// Amount held in the claim escrow vault for this bettor
+    pub amount: u64,
+
+    /// Timestamp from which the escrowed amount can be released
+    pub release_at: i64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+/// Stake escrowed by `reserve_bet`, entering the market pool only once confirmed
+#[account]
+#[derive(InitSpace)]
+pub struct BetReservation {
+    /// Market the bet is reserved on
+    pub market: Pubkey,
+
+    /// The bettor's wallet
+    pub bettor: Pubkey,
+
+    /// Token mint of the escrowed stake
+    pub token_mint: Pubkey,
+
+    /// Outcome the bettor intends to back
+    pub outcome_index: u8,
+
+    /// Amount held in the reservation vault
+    pub amount: u64,
+
+    /// Timestamp after which the reservation can no longer be confirmed
+    pub expires_at: i64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl BetReservation {
+    /// Whether the confirmation window has closed
+    pub fn is_expired_at(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// Effects of a bet or claim, set as the instruction's return data so clients can
+/// confirm them without fetching the market
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct InstructionResult {
+    /// Market the instruction acted on
+    pub market: Pubkey,
+
+    /// Market's total pool afterwards
+    pub total_pool: u64,
+
+    /// Market's bonus pool afterwards
+    pub bonus_pool: u64,
+
+    /// Outcome the bettor's position is on
+    pub outcome_index: u8,
+
+    /// That outcome's total stake afterwards
+    pub outcome_total: u64,
+
+    /// Bettor's stake in the pool
+    pub position: u64,
+
+    /// Amount paid to the caller (claims only)
+    pub payout: u64,
+}
+
+impl InstructionResult {
+    /// Summarize a bettor's position on a market
+    pub fn for_position(market: &Market, bet: &Bet, payout: u64) -> Self {
+        Self {
+            market: bet.market,
+            total_pool: market.total_pool,
+            bonus_pool: market.bonus_pool,
+            outcome_index: bet.outcome_index,
+            outcome_total: market.outcomes[bet.outcome_index as usize].total_amount,
+            position: bet.pool_amount,
+            payout,
+        }
+    }
+
+    /// Publish as the current instruction's return data
+    pub fn set(&self) -> Result<()> {
+        anchor_lang::solana_program::program::set_return_data(&self.try_to_vec()?);
+        Ok(())
+    }
+}
+
+/// Randomness account written by the protocol's randomness (VRF) program: an 8-byte
+/// discriminator followed by these fields, little-endian. The program reveals the value only
+/// after the commit slot, so nobody knows it when a market binds to a pending account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RandomnessAccount {
+    /// Slot the randomness was committed at
+    pub seed_slot: u64,
+
+    /// Slot the value was revealed at (0 while pending)
+    pub reveal_slot: u64,
+
+    /// Revealed random value (zeroed while pending)
+    pub value: [u8; 32],
+}
+
+impl RandomnessAccount {
+    /// Read a randomness account, checking its owner and layout
+    pub fn load(info: &AccountInfo, randomness_program: &Pubkey) -> Result<Self> {
+        require!(
+            *randomness_program != Pubkey::default() && info.owner == randomness_program,
+            FortunaError::InvalidRandomnessAccount
+        );
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == RANDOMNESS_ACCOUNT_DISCRIMINATOR,
+            FortunaError::InvalidRandomnessAccount
+        );
+        Self::deserialize(&mut &data[8..])
+            .map_err(|_| error!(FortunaError::InvalidRandomnessAccount))
+    }
+
+    /// Whether the value has been revealed
+    pub fn is_revealed(&self) -> bool {
+        self.reveal_slot > 0
+    }
+}
+
+/// A bettor's stake as ranked on a market leaderboard
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct LeaderboardEntry {
+    /// Bettor wallet
+    pub bettor: Pubkey,
+
+    /// Bettor's stake in the pool
+    pub pool_amount: u64,
+}
+
+/// Largest bettors on a market, sorted by stake (largest first)
+#[account]
+#[derive(InitSpace)]
+pub struct MarketLeaderboard {
+    /// Market this leaderboard ranks
+    pub market: Pubkey,
+
+    /// Top bettors; ties keep the earlier bettor ahead
+    #[max_len(LEADERBOARD_SIZE)]
+    pub entries: Vec<LeaderboardEntry>,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl MarketLeaderboard {
+    /// Record a bettor's current stake, replacing any earlier entry for the same bettor
+    pub fn record(&mut self, bettor: Pubkey, pool_amount: u64) {
+        self.entries.retain(|entry| entry.bettor != bettor);
+        let position = self.entries
+            .iter()
+            .position(|entry| pool_amount > entry.pool_amount)
+            .unwrap_or(self.entries.len());
+        if position < LEADERBOARD_SIZE {
+            self.entries.insert(position, LeaderboardEntry { bettor, pool_amount });
+            self.entries.truncate(LEADERBOARD_SIZE);
+        }
+    }
+
+    /// Drop a bettor whose stake has left the pool
+    pub fn remove(&mut self, bettor: Pubkey) {
+        self.entries.retain(|entry| entry.bettor != bettor);
+    }
+}
+
+/// Implied odds of every outcome after one bet
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug, Default)]
+pub struct OddsSnapshot {
+    /// When the bet was placed
+    pub timestamp: i64,
+
+    /// Market total pool after the bet
+    pub total_pool: u64,
+
+    /// Each outcome's share of the pool in basis points (unused outcomes stay 0)
+    pub implied_odds_bps: [u16; MAX_OUTCOMES],
+}
+
+/// Recent implied odds for a market, kept as a ring buffer so clients can chart them
+#[account]
+#[derive(InitSpace)]
+pub struct MarketOddsHistory {
+    /// Market these odds belong to
+    pub market: Pubkey,
+
+    /// Slot the next snapshot is written to once the buffer is full
+    pub next_index: u16,
+
+    /// Snapshots in ring order; the oldest is at `next_index` once the buffer is full
+    #[max_len(ODDS_HISTORY_LEN)]
+    pub snapshots: Vec<OddsSnapshot>,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl MarketOddsHistory {
+    /// Append a snapshot, overwriting the oldest once the buffer is full
+    pub fn record(&mut self, snapshot: OddsSnapshot) {
+        if self.snapshots.len() < ODDS_HISTORY_LEN {
+            self.snapshots.push(snapshot);
+        } else {
+            self.snapshots[self.next_index as usize] = snapshot;
+        }
+        self.next_index = ((self.next_index as usize + 1) % ODDS_HISTORY_LEN) as u16;
+    }
+}
+
+impl Market {
+    /// Share of the creator fee redirected to the bonus pool for a bet at `now`, in basis points
+    pub fn creator_lp_share_bps_at(&self, now: i64) -> u16 {
+        if now < self.created_at.saturating_add(self.creator_lp_share_window_secs) {
+            self.creator_lp_share_bps
+        } else {
+            0
+        }
+    }
+
+    /// Betting phase in effect at `now` (None = no phases, or all have ended)
+    pub fn active_phase(&self, now: i64) -> Option<&BettingPhase> {
+        self.betting_phases.iter().find(|phase| now < phase.phase_end)
+    }
+
+    /// Snapshot each outcome's current share of the pool
+    pub fn odds_snapshot(&self, timestamp: i64) -> OddsSnapshot {
+        let mut implied_odds_bps = [0u16; MAX_OUTCOMES];
+        if self.total_pool > 0 {
+            for (odds, outcome) in implied_odds_bps.iter_mut().zip(&self.outcomes) {
+                *odds = ((outcome.total_amount as u128) * BPS_DENOMINATOR as u128
+                    / self.total_pool as u128) as u16;
+            }
+        }
+        OddsSnapshot { timestamp, total_pool: self.total_pool, implied_odds_bps }
+    }
+
+    /// Calculate the payout for a winning bet
+    /// Returns (pool_share, bonus_share), paid from the market vault and pool vault respectively
+    pub fn calculate_payout(&self, bet_key: &Pubkey, bet: &Bet) -> (u64, u64) {
+        if self.status != MarketStatus::Resolved {
+            return (0, 0);
+        }
+
+        if bet.outcome_index != self.winning_outcome {
+            return (0, 0);
+        }
+
+        let winning_outcome = &self.outcomes[self.winning_outcome as usize];
+
+        if winning_outcome.total_amount == 0 {
+            return (0, 0);
+        }
+
+        // Fixed multipliers pay the stake times the outcome's rate; the bonus pool is still shared
+        if self.payout_mode == PayoutMode::FixedMultiplier {
+            let bonus_share = (bet.pool_amount as u128)
+                .checked_mul(self.bonus_pool as u128)
+                .unwrap()
+                .checked_div(winning_outcome.total_amount as u128)
+                .unwrap();
+            return (self.multiplier_payout(self.winning_outcome, bet.pool_amount), bonus_share as u64);
+        }
+
+        // Share of the total pool minus what is owed back to loss-protected
+        // losing bets, plus share of the bonus pool
+        let distributable_pool = self.total_pool
+            .saturating_sub(self.losing_protected_amount());
+
+        // Only the designated bet is paid in winner-take-all markets
+        if self.payout_mode == PayoutMode::WinnerTakeAll {
+            return if *bet_key == self.winning_bet {
+                (distributable_pool, self.bonus_pool)
+            } else {
+                (0, 0)
+            };
+        }
+
+        // Proportional share based on bet amount
+        let pool_share = (bet.pool_amount as u128)
+            .checked_mul(distributable_pool as u128)
+            .unwrap()
+            .checked_div(winning_outcome.total_amount as u128)
+            .unwrap();
+
+        let bonus_share = (bet.pool_amount as u128)
+            .checked_mul(self.bonus_pool as u128)
+            .unwrap()
+            .checked_div(winning_outcome.total_amount as u128)
+            .unwrap();
+
+        (pool_share as u64, bonus_share as u64)
+    }
+
+    /// Whether the pool holds bets but the winning outcome received none
+    pub fn is_stranded(&self) -> bool {
+        self.total_pool > 0 && self.outcomes[self.winning_outcome as usize].total_amount == 0
+    }
+
+    /// Outcome with the most vote weight (ties go to the lowest index), if any votes were cast
+    pub fn community_winning_outcome(&self) -> Option<u8> {
+        let mut winner: Option<(u8, u64)> = None;
+        for (index, outcome) in self.outcomes.iter().enumerate() {
+            if outcome.vote_weight > winner.map_or(0, |(_, weight)| weight) {
+                winner = Some((index as u8, outcome.vote_weight));
+            }
+        }
+        winner.map(|(index, _)| index)
+    }
+
+    /// Pick an outcome from a random value, weighted by the stake on each outcome
+    pub fn random_winning_outcome(&self, randomness: &[u8; 32]) -> u8 {
+        let mut value_bytes = [0u8; 8];
+        value_bytes.copy_from_slice(&randomness[..8]);
+        let value = u64::from_le_bytes(value_bytes);
+
+        // Without stake every outcome is equally likely
+        if self.total_pool == 0 {
+            return (value % self.outcomes.len() as u64) as u8;
+        }
+
+        let target = value % self.total_pool;
+        let mut cumulative = 0u64;
+        for (index, outcome) in self.outcomes.iter().enumerate() {
+            cumulative += outcome.total_amount;
+            if target < cumulative {
+                return index as u8;
+            }
+        }
+        (self.outcomes.len() - 1) as u8
+    }
+
+    /// Record the resolution of the market
+    pub fn resolve(&mut self, winning_outcome: u8, resolved_at: i64, by_oracle: bool) -> Result<()> {
+        // The vault must hold enough to pay every winning bet at its committed multiplier
+        if self.payout_mode == PayoutMode::FixedMultiplier {
+            require!(self.payout_covered(winning_outcome), FortunaError::InsufficientLiquidity);
+        }
+
+        self.status = MarketStatus::Resolved;
+        self.winning_outcome = winning_outcome;
+        self.resolved_at = resolved_at;
+        self.resolved_by_oracle = by_oracle;
+
+        // Nobody can claim a pool whose winner had no bets; void it if configured
+        if self.is_stranded() && self.stranded_pool_policy == StrandedPoolPolicy::Void {
+            self.status = MarketStatus::Cancelled;
+            self.cancel_reason = CancelReason::InsufficientParticipation;
+            msg!("Winning outcome has no bets; market voided for refunds");
+            return Ok(());
+        }
+
+        // Only the earliest bet is paid in winner-take-all markets
+        let winning_bettors = self.outcomes[winning_outcome as usize].bettor_count;
+        self.winning_bets_total = if self.payout_mode == PayoutMode::WinnerTakeAll {
+            winning_bettors.min(1)
+        } else {
+            winning_bettors
+        };
+
+        if self.payout_mode == PayoutMode::WinnerTakeAll {
+            self.winning_bet = self.outcomes[winning_outcome as usize].first_bet;
+        }
+
+        Ok(())
+    }
+
+    /// Amount a stake on the given outcome pays out at that outcome's multiplier
+    pub fn multiplier_payout(&self, outcome_index: u8, amount: u64) -> u64 {
+        let multiplier = self.payout_multipliers.get(outcome_index as usize).copied().unwrap_or(0);
+        ((amount as u128) * (multiplier as u128) / (BPS_DENOMINATOR as u128)) as u64
+    }
+
+    /// Whether pool and liquidity cover the multiplier payouts and loss protection
+    /// owed if the given outcome wins
+    pub fn payout_covered(&self, winning_outcome: u8) -> bool {
+        let committed = self.multiplier_payout(
+            winning_outcome,
+            self.outcomes[winning_outcome as usize].total_amount,
+        ) as u128;
+        let protected: u128 = self.outcomes
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != winning_outcome as usize)
+            .map(|(_, o)| o.protected_amount as u128)
+            .sum();
+        committed + protected <= self.total_pool as u128 + self.liquidity as u128
+    }
+
+    /// Liquidity and house winnings the creator may withdraw once the market settles
+    pub fn withdrawable_liquidity(&self) -> u64 {
+        if self.status == MarketStatus::Cancelled || self.is_stranded() {
+            return self.liquidity;
+        }
+        let committed = self.multiplier_payout(
+            self.winning_outcome,
+            self.outcomes[self.winning_outcome as usize].total_amount,
+        );
+        (self.total_pool as u128 + self.liquidity as u128)
+            .saturating_sub(committed as u128 + self.losing_protected_amount() as u128) as u64
+    }
+
+    /// Whether bet fees are charged as a flat amount in a separate fee mint
+    pub fn has_fee_mint(&self) -> bool {
+        self.fee_mint != Pubkey::default()
+    }
+
+    /// Whether claims may be paid into this token account
+    pub fn accepts_claim_account(&self, token_account: &Pubkey, claimer: &Pubkey, mint: &Pubkey) -> bool {
+        !self.require_ata || *token_account == get_associated_token_address(claimer, mint)
+    }
+
+    /// Account space for a market without its reserved capacity. `reserved` is never
+    /// written, so a new account's zeroed length prefix decodes as an empty vec and only
+    /// the capacity bytes after it are dropped.
+    pub fn compact_space() -> usize {
+        8 + Market::INIT_SPACE - MARKET_RESERVED_LEN
+    }
+
+    /// Commitment to a winning outcome: sha256(outcome || salt)
+    pub fn result_commitment_hash(outcome: u8, salt: &[u8; 32]) -> [u8; 32] {
+        anchor_lang::solana_program::hash::hashv(&[&[outcome], salt]).to_bytes()
+    }
+
+    /// Collateral entry for a mint accepted by this market
+    pub fn collateral(&self, mint: &Pubkey) -> Option<CollateralMint> {
+        self.collateral_mints.iter().find(|c| c.mint == *mint).copied()
+    }
+
+    /// Take the processing lock, rejecting entry while another mutation holds it
+    pub fn lock(&mut self) -> Result<()> {
+        require!(!self.processing, FortunaError::MarketBusy);
+        self.processing = true;
+        Ok(())
+    }
+
+    /// Release the processing lock
+    pub fn unlock(&mut self) {
+        self.processing = false;
+    }
+
+    /// Whether the vaults still hold every winner's unpaid share. The market vault must cover
+    /// the distributable pool (the committed multiplier payouts in fixed-multiplier markets)
+    /// less what has been paid from it, and the pool vault the bonus pool less what has been
+    /// paid from that; anything else held (creator fees, liquidity, loss protection owed)
+    /// only adds headroom.
+    pub fn is_collateralized(&self, market_vault_amount: u64, pool_vault_amount: u64) -> bool {
+        let distributable_pool = if self.payout_mode == PayoutMode::FixedMultiplier {
+            self.multiplier_payout(
+                self.winning_outcome,
+                self.outcomes[self.winning_outcome as usize].total_amount,
+            )
+        } else {
+            self.total_pool.saturating_sub(self.losing_protected_amount())
+        };
+        market_vault_amount >= distributable_pool.saturating_sub(self.pool_paid)
+            && pool_vault_amount >= self.bonus_pool.saturating_sub(self.bonus_paid)
+    }
+
+    /// Record a payout against the pools it came from
+    pub fn record_payout(&mut self, pool_share: u64, bonus_share: u64) -> Result<()> {
+        self.pool_paid = self.pool_paid.checked_add(pool_share)
+            .ok_or(FortunaError::Overflow)?;
+        self.bonus_paid = self.bonus_paid.checked_add(bonus_share)
+            .ok_or(FortunaError::Overflow)?;
+        Ok(())
+    }
+
+    /// Total loss-protected amount on outcomes other than the winning one
+    pub fn losing_protected_amount(&self) -> u64 {
+        self.outcomes
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != self.winning_outcome as usize)
+            .map(|(_, o)| o.protected_amount)
+            .sum()
+    }
+
+    /// Whether every winner, loss-protected loser, the creator fee wallet and any
+    /// stranded-pool sweep have been paid, so nothing but rounding dust and creator
+    /// liquidity remains in the vaults. A cancelled market is settled once every live
+    /// bet has taken its refund. Either way no parlay may still depend on the market.
+    pub fn all_claims_settled(&self) -> bool {
+        if self.open_parlays > 0 {
+            return false;
+        }
+        if self.status == MarketStatus::Cancelled {
+            return self.refunds_claimed >= self.bet_count;
+        }
+        self.winning_bets_claimed >= self.winning_bets_total
+            && self.escrowed_creator_fees == 0
+            && self.loss_protection_claimed >= self.losing_protected_amount()
+            && (!self.is_stranded() || self.stranded_swept)
+    }
+
+    /// Whether the next bet pays no fees: fee-exempt markets, while the market still
+    /// has fewer bettors than its fee waiver count, or while a two-sided-fee market still
+    /// has bets on fewer than two outcomes
+    pub fn fees_waived(&self) -> bool {
+        self.fee_exempt
+            || self.total_bettors() < self.fee_waiver_count
+            || (self.two_sided_fees && self.outcomes_with_bets() < 2)
+    }
+
+    /// Whether betting closed with fewer bettors than the creator fee threshold, forfeiting
+    /// the escrowed creator fees to the bonus pool
+    pub fn creator_fees_forfeited(&self, current_time: i64) -> bool {
+        self.min_bettors_for_creator_fee > 0
+            && self.is_betting_closed(current_time)
+            && self.total_bettors() < self.min_bettors_for_creator_fee
+    }
+
+    /// Whether forfeited creator fees are still escrowed and must reach the bonus pool
+    /// before winners are paid
+    pub fn creator_fee_redirect_pending(&self, current_time: i64) -> bool {
+        self.escrowed_creator_fees > 0 && self.creator_fees_forfeited(current_time)
+    }
+
+    /// Whether the claim window has closed and unclaimed winnings may be swept
+    pub fn claims_expired(&self, current_time: i64) -> bool {
+        self.claim_expiry_secs > 0
+            && self.status == MarketStatus::Resolved
+            && current_time >= self.resolved_at.saturating_add(self.claim_expiry_secs)
+    }
+
+    /// Get the total number of bettors across all outcomes
+    pub fn total_bettors(&self) -> u32 {
+        self.outcomes.iter().map(|o| o.bettor_count).sum()
+    }
+
+    /// Number of outcomes with at least one bettor
+    pub fn outcomes_with_bets(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.bettor_count > 0).count()
+    }
+
+    /// Check if betting deadline has passed
+    pub fn is_betting_closed(&self, current_time: i64) -> bool {
         current_time > self.betting_deadline
     }
 
+    /// Whether the market can take another bet under its bet limit
+    pub fn has_bet_capacity(&self) -> bool {
+        self.max_bets == 0 || self.bet_count < self.max_bets
+    }
+
+    /// Whether an outcome can take `amount` more stake without exceeding the per-outcome cap
+    pub fn outcome_has_room(&self, outcome_index: u8, amount: u64) -> bool {
+        self.max_per_outcome == 0
+            || self.outcomes[outcome_index as usize].total_amount
+                .checked_add(amount)
+                .map_or(false, |total| total <= self.max_per_outcome)
+    }
+
+    /// Whether the creator or oracle may resolve the market at the given time
+    pub fn can_resolve_at(&self, current_time: i64) -> bool {
+        self.allow_early_resolution || self.is_betting_closed(current_time)
+    }
+
+    /// Pull the betting deadline forward to `current_time` if betting is still open
+    pub fn close_betting(&mut self, current_time: i64) {
+        if !self.is_betting_closed(current_time) {
+            self.betting_deadline = current_time;
+        }
+    }
+
     /// Check if resolution deadline has passed
     pub fn is_past_resolution_deadline(&self, current_time: i64) -> bool {
         current_time > self.resolution_deadline
@@ -597,35 +1928,193 @@ impl Market {
 }
 
 impl ProtocolState {
+    /// Whether the authority has gone a full dormancy period without an admin action
+    pub fn authority_is_dormant(&self, now: i64) -> bool {
+        self.authority_dormancy_secs > 0
+            && now >= self.last_authority_activity.saturating_add(self.authority_dormancy_secs)
+    }
+
     /// Calculate all fees for a given bet amount
     /// Returns (pool_fee, creator_fee, protocol_fee, net_amount)
-    pub fn calculate_fees(&self, amount: u64) -> (u64, u64, u64, u64) {
-        let pool_fee = (amount as u128)
-            .checked_mul(self.pool_fee_bps as u128)
-            .unwrap()
-            .checked_div(10000)
-            .unwrap() as u64;
+    ///
+    /// Each configured fee is at least 1 unit on a nonzero bet so tiny bets
+    /// can't slip through fee-free; the floors never take more than the bet.
+    pub fn calculate_fees(&self, amount: u64, category: MarketCategory) -> (u64, u64, u64, u64) {
+        self.split_fees(amount, category, self.creator_fee_bps, self.pool_fee_bps, BPS_DENOMINATOR)
+    }
 
-        let creator_fee = (amount as u128)
-            .checked_mul(self.creator_fee_bps as u128)
+    /// Split fees off an amount with the given creator and pool fee rates, each scaled by
+    /// `multiplier_bps` before the 1-unit floor so a tapered fee still can't round to zero
+    /// Returns (pool_fee, creator_fee, protocol_fee, net_amount)
+    fn split_fees(
+        &self,
+        amount: u64,
+        category: MarketCategory,
+        creator_fee_bps: u16,
+        pool_fee_bps: u16,
+        multiplier_bps: u16,
+    ) -> (u64, u64, u64, u64) {
+        let mut remaining = amount;
+
+        let protocol_fee_bps = Self::tapered_bps(self.protocol_fee_bps_for(category), multiplier_bps);
+        let protocol_fee = Self::floored_fee(amount, protocol_fee_bps).min(remaining);
+        remaining -= protocol_fee;
+
+        let creator_fee_bps = Self::tapered_bps(creator_fee_bps, multiplier_bps);
+        let creator_fee = Self::floored_fee(amount, creator_fee_bps).min(remaining);
+        remaining -= creator_fee;
+
+        let pool_fee_bps = Self::tapered_bps(pool_fee_bps, multiplier_bps);
+        let pool_fee = Self::floored_fee(amount, pool_fee_bps).min(remaining);
+        remaining -= pool_fee;
+
+        (pool_fee, creator_fee, protocol_fee, remaining)
+    }
+
+    /// Fee in basis points of the amount, rounded up to 1 unit when both are nonzero
+    fn floored_fee(amount: u64, fee_bps: u16) -> u64 {
+        let fee = (amount as u128)
+            .checked_mul(fee_bps as u128)
             .unwrap()
             .checked_div(10000)
             .unwrap() as u64;
 
-        let protocol_fee = (amount as u128)
-            .checked_mul(self.protocol_fee_bps as u128)
+        if fee == 0 && amount > 0 && fee_bps > 0 {
+            1
+        } else {
+            fee
+        }
+    }
+
+    /// Protocol fee that applies to markets in the given category
+    pub fn protocol_fee_bps_for(&self, category: MarketCategory) -> u16 {
+        let index = category as usize;
+        match self.protocol_fee_bps_per_category.get(index) {
+            Some(&fee_bps) if fee_bps > 0 => fee_bps,
+            _ => self.protocol_fee_bps,
+        }
+    }
+
+    /// Claim delay that applies to markets in the given category
+    pub fn claim_delay(&self, category: MarketCategory) -> i64 {
+        let index = category as usize;
+        if index < 12 {
+            self.claim_delay_per_category[index]
+        } else {
+            0
+        }
+    }
+
+    /// Whether winnings in the given category settle into a time-locked escrow
+    pub fn escrows_claims(&self, category: MarketCategory) -> bool {
+        self.escrow_claims.get(category as usize).copied().unwrap_or(false)
+    }
+
+    /// Allowed outcome counts for new markets in the given category
+    pub fn outcome_bounds(&self, category: MarketCategory) -> (usize, usize) {
+        let index = category as usize;
+        if index >= 12 {
+            return (MIN_OUTCOMES, MAX_OUTCOMES);
+        }
+        let min = match self.min_outcomes_per_category[index] {
+            0 => MIN_OUTCOMES,
+            min => min as usize,
+        };
+        let max = match self.max_outcomes_per_category[index] {
+            0 => MAX_OUTCOMES,
+            max => max as usize,
+        };
+        (min, max)
+    }
+
+    /// Check a new market's betting deadline against the configured horizon
+    pub fn is_deadline_within_horizon(&self, betting_deadline: i64, current_time: i64) -> bool {
+        self.max_future_deadline_secs == 0
+            || betting_deadline <= current_time.saturating_add(self.max_future_deadline_secs)
+    }
+
+    /// Check a market's fixed bet amount against the protocol bounds
+    pub fn is_bet_amount_allowed(&self, bet_amount: u64) -> bool {
+        (self.min_bet_amount == 0 || bet_amount >= self.min_bet_amount)
+            && (self.max_bet_amount == 0 || bet_amount <= self.max_bet_amount)
+    }
+
+    /// Portion of the protocol fee rebated for a bet of the given amount
+    /// (highest qualifying tier wins; the rebate stays with the bettor)
+    pub fn protocol_fee_rebate(&self, amount: u64, protocol_fee: u64) -> u64 {
+        let rebate_bps = self.rebate_tiers
+            .iter()
+            .filter(|tier| amount >= tier.threshold)
+            .map(|tier| tier.rebate_bps)
+            .max()
+            .unwrap_or(0);
+
+        (protocol_fee as u128)
+            .checked_mul(rebate_bps as u128)
             .unwrap()
-            .checked_div(10000)
-            .unwrap() as u64;
+            .checked_div(BPS_DENOMINATOR as u128)
+            .unwrap() as u64
+    }
+
+    /// Share of the normal fees charged on a market with the given total pool, in basis points,
+    /// taken from the highest tier the pool has reached (tiers are sorted by threshold)
+    pub fn fee_multiplier_bps(&self, total_pool: u64) -> u16 {
+        self.fee_taper_schedule
+            .iter()
+            .rev()
+            .find(|tier| total_pool >= tier.pool_threshold)
+            .map_or(BPS_DENOMINATOR, |tier| tier.fee_multiplier_bps)
+    }
+
+    fn tapered_bps(fee_bps: u16, multiplier_bps: u16) -> u16 {
+        (fee_bps as u32 * multiplier_bps as u32 / BPS_DENOMINATOR as u32) as u16
+    }
 
-        let total_fees = pool_fee + creator_fee + protocol_fee;
-        let net_amount = amount.checked_sub(total_fees).unwrap();
+    fn tapered_fee(fee: u64, multiplier_bps: u16) -> u64 {
+        (fee as u128)
+            .checked_mul(multiplier_bps as u128)
+            .unwrap()
+            .checked_div(BPS_DENOMINATOR as u128)
+            .unwrap() as u64
+    }
 
-        (pool_fee, creator_fee, protocol_fee, net_amount)
+    /// Calculate fees for a bet on a specific market, honoring market-level exemptions and
+    /// the market's betting phase at `now`
+    /// Returns (pool_fee, creator_fee, protocol_fee, net_amount)
+    pub fn calculate_market_fees(&self, market: &Market, amount: u64, now: i64) -> (u64, u64, u64, u64) {
+        if market.fees_waived() {
+            return (0, 0, 0, amount);
+        }
+        let (creator_fee_bps, pool_fee_bps) = match market.active_phase(now) {
+            Some(phase) => (phase.creator_fee_bps, phase.pool_fee_bps),
+            None => (self.creator_fee_bps, self.pool_fee_bps),
+        };
+        // Deep markets pay a tapered share of every fee rate
+        let multiplier_bps = self.fee_multiplier_bps(market.total_pool);
+        let (pool_fee, creator_fee, protocol_fee, net_amount) = self.split_fees(
+            amount,
+            market.category,
+            creator_fee_bps,
+            pool_fee_bps,
+            multiplier_bps,
+        );
+
+        // Early bettors earn part of the creator fee through the bonus pool
+        let lp_share = Self::tapered_fee(creator_fee, market.creator_lp_share_bps_at(now));
+        let pool_fee = pool_fee + lp_share;
+        let creator_fee = creator_fee - lp_share;
+
+        let rebate = self.protocol_fee_rebate(amount, protocol_fee);
+        // Deployments that take creator fees as protocol revenue send them to the treasury
+        if self.redirect_creator_fees_to_treasury {
+            return (pool_fee, 0, protocol_fee - rebate + creator_fee, net_amount);
+        }
+        (pool_fee, creator_fee, protocol_fee - rebate, net_amount)
     }
 
     /// Total fee percentage in basis points
-    pub fn total_fee_bps(&self) -> u16 {
-        self.pool_fee_bps + self.creator_fee_bps + self.protocol_fee_bps
+    /// Widened to u32 so the sum can't overflow whatever fees are stored
+    pub fn total_fee_bps(&self) -> u32 {
+        self.pool_fee_bps as u32 + self.creator_fee_bps as u32 + self.protocol_fee_bps as u32
     }
 }
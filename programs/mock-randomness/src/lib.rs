@@ -0,0 +1,60 @@
+//! Test-only stand-in for a VRF program. It writes randomness accounts in the layout
+//! fortuna-protocol reads, and lets tests choose the revealed value so random-resolution
+//! markets get a deterministic winner.
+
+use anchor_lang::prelude::*;
+
+declare_id!("3A4MSiNScmPVds7swYRvockdCX1btbhmZ1TJJfpmehp3");
+
+#[program]
+pub mod mock_randomness {
+    use super::*;
+
+    /// Open a pending randomness account committed at the current slot
+    pub fn commit(ctx: Context<Commit>) -> Result<()> {
+        let randomness = &mut ctx.accounts.randomness;
+        randomness.seed_slot = Clock::get()?.slot;
+        randomness.reveal_slot = 0;
+        randomness.value = [0u8; 32];
+        Ok(())
+    }
+
+    /// Reveal the given value
+    pub fn reveal(ctx: Context<Reveal>, value: [u8; 32]) -> Result<()> {
+        let randomness = &mut ctx.accounts.randomness;
+        randomness.reveal_slot = Clock::get()?.slot;
+        randomness.value = value;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Commit<'info> {
+    #[account(init, payer = payer, space = 8 + RandomnessAccount::INIT_SPACE)]
+    pub randomness: Account<'info, RandomnessAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Reveal<'info> {
+    #[account(mut)]
+    pub randomness: Account<'info, RandomnessAccount>,
+}
+
+/// Matches fortuna-protocol's `RandomnessAccount` layout after the discriminator
+#[account]
+#[derive(InitSpace)]
+pub struct RandomnessAccount {
+    /// Slot the randomness was committed at
+    pub seed_slot: u64,
+
+    /// Slot the value was revealed at (0 while pending)
+    pub reveal_slot: u64,
+
+    /// Revealed random value
+    pub value: [u8; 32],
+}
@@ -6,6 +6,9 @@ export const FORTUNA_PROGRAM_ID = new PublicKey('FortunaProt11111111111111111111
 // Treasury wallet address
 export const TREASURY_WALLET = new PublicKey('6Lbx8fvKRf1aE8Zi977sGHYqNeKvzxyjnGt5pee9FwoZ');
 
+// SPL Memo program (payout memos)
+export const MEMO_PROGRAM_ID = new PublicKey('MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr');
+
 // PDA Seeds
 export const PROTOCOL_SEED = Buffer.from('protocol');
 export const MARKET_SEED = Buffer.from('market');
@@ -14,6 +17,11 @@ export const POOL_VAULT_SEED = Buffer.from('pool_vault');
 export const BET_SEED = Buffer.from('bet');
 export const ORACLE_SEED = Buffer.from('oracle');
 export const LICENSE_SEED = Buffer.from('license');
+export const LEADERBOARD_SEED = Buffer.from('leaderboard');
+export const ODDS_HISTORY_SEED = Buffer.from('odds_history');
+export const SELF_EXCLUSION_SEED = Buffer.from('self_exclusion');
+export const BETTOR_PROFILE_SEED = Buffer.from('bettor_profile');
+export const EVENT_INDEX_SEED = Buffer.from('event_index');
 
 // Default fee configuration (in basis points)
 export const DEFAULT_PROTOCOL_FEE_BPS = 50;  // 0.5%
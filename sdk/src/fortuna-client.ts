@@ -31,15 +31,22 @@ import {
 import {
   FORTUNA_PROGRAM_ID,
   TREASURY_WALLET,
+  MEMO_PROGRAM_ID,
   DEFAULT_PROTOCOL_FEE_BPS,
   DEFAULT_CREATOR_FEE_BPS,
   DEFAULT_POOL_FEE_BPS,
+  MarketCategory,
 } from './constants';
 import {
   getProtocolStatePDA,
   getMarketPDA,
   getMarketVaultPDA,
   getPoolVaultPDA,
+  getLeaderboardPDA,
+  getOddsHistoryPDA,
+  getSelfExclusionPDA,
+  getBettorProfilePDA,
+  getEventIndexPDA,
   getBetPDA,
   getLicensePDA,
   calculateFees,
@@ -77,7 +84,7 @@ import type { FortunaProtocol } from './idl/fortuna_protocol';
  * });
  *
  * // Place a bet
- * await client.placeBet(marketId, 0); // Bet on "Yes"
+ * await client.placeBet(wallet.publicKey, 1, 0); // Bet on "Yes"
  * ```
  */
 export class FortunaClient {
@@ -206,9 +213,19 @@ export class FortunaClient {
       ? new BN(config.marketId)
       : config.marketId;
 
-    const [market] = getMarketPDA(marketId, this.programId);
+    const [market] = getMarketPDA(this.wallet.publicKey, marketId, this.programId);
     const [marketVault] = getMarketVaultPDA(market, this.programId);
     const [poolVault] = getPoolVaultPDA(market, this.programId);
+    const [leaderboard] = getLeaderboardPDA(market, this.programId);
+
+    let tokenMint = config.tokenMint;
+    if (!tokenMint) {
+      const protocol = await this.getProtocolState();
+      if (!protocol || protocol.defaultTokenMint.equals(PublicKey.default)) {
+        throw new Error('No token mint given and the protocol has no default token mint');
+      }
+      tokenMint = protocol.defaultTokenMint;
+    }
 
     const tx = await program.methods
       .createMarket(
@@ -223,9 +240,10 @@ export class FortunaClient {
       .accounts({
         protocolState,
         market,
-        tokenMint: config.tokenMint,
+        tokenMint,
         marketVault,
         poolVault,
+        leaderboard,
         creator: this.wallet.publicKey,
         creatorFeeWallet: config.creatorFeeWallet,
         tokenProgram: TOKEN_PROGRAM_ID,
@@ -240,9 +258,9 @@ export class FortunaClient {
   /**
    * Get market data by ID
    */
-  async getMarket(marketId: BN | number): Promise<Market | null> {
+  async getMarket(creator: PublicKey, marketId: BN | number): Promise<Market | null> {
     const program = this.getProgram();
-    const [marketPDA] = getMarketPDA(marketId, this.programId);
+    const [marketPDA] = getMarketPDA(creator, marketId, this.programId);
 
     try {
       const market = await program.account.market.fetch(marketPDA);
@@ -255,8 +273,8 @@ export class FortunaClient {
   /**
    * Get market statistics
    */
-  async getMarketStats(marketId: BN | number): Promise<MarketStats | null> {
-    const market = await this.getMarket(marketId);
+  async getMarketStats(creator: PublicKey, marketId: BN | number): Promise<MarketStats | null> {
+    const market = await this.getMarket(creator, marketId);
     if (!market) return null;
 
     const totalPool = market.totalPool;
@@ -290,6 +308,18 @@ export class FortunaClient {
     return markets.map((m) => m.account as unknown as Market);
   }
 
+  /**
+   * Get the markets created for an external oracle event ID (match ID, ticker, ...)
+   */
+  async getMarketsByEventId(oracleEventId: string): Promise<Market[]> {
+    const program = this.getProgram();
+    const [eventIndexPDA] = getEventIndexPDA(oracleEventId, this.programId);
+    const eventIndex = await program.account.eventIndex.fetchNullable(eventIndexPDA);
+    if (!eventIndex) return [];
+    const markets = await program.account.market.fetchMultiple(eventIndex.markets);
+    return markets.filter((m) => m !== null) as unknown as Market[];
+  }
+
   /**
    * Get markets by status
    */
@@ -306,19 +336,24 @@ export class FortunaClient {
    * Place a bet on a market outcome
    */
   async placeBet(
+    creator: PublicKey,
     marketId: BN | number,
     outcomeIndex: number,
     bettorTokenAccount?: PublicKey
   ): Promise<string> {
     const program = this.getProgram();
     const [protocolState] = getProtocolStatePDA(this.programId);
-    const [marketPDA] = getMarketPDA(marketId, this.programId);
+    const [marketPDA] = getMarketPDA(creator, marketId, this.programId);
     const [marketVault] = getMarketVaultPDA(marketPDA, this.programId);
     const [poolVault] = getPoolVaultPDA(marketPDA, this.programId);
+    const [leaderboard] = getLeaderboardPDA(marketPDA, this.programId);
     const [betPDA] = getBetPDA(marketPDA, this.wallet.publicKey, this.programId);
+    const [selfExclusion] = getSelfExclusionPDA(this.wallet.publicKey, this.programId);
+    // The program creates the profile inline on the wallet's first bet
+    const [bettorProfile] = getBettorProfilePDA(this.wallet.publicKey, this.programId);
 
     // Get market to find token mint
-    const market = await this.getMarket(marketId);
+    const market = await this.getMarket(creator, marketId);
     if (!market) throw new Error('Market not found');
 
     const protocol = await this.getProtocolState();
@@ -335,11 +370,6 @@ export class FortunaClient {
       protocol.treasury
     );
 
-    const creatorTokenAccount = await getAssociatedTokenAddress(
-      market.tokenMint,
-      market.creatorFeeWallet
-    );
-
     const tx = await program.methods
       .placeBet(outcomeIndex)
       .accounts({
@@ -348,9 +378,12 @@ export class FortunaClient {
         bet: betPDA,
         marketVault,
         poolVault,
+        leaderboard,
         bettorTokenAccount: userTokenAccount,
         treasuryTokenAccount,
-        creatorTokenAccount,
+        selfExclusion,
+        bettorProfile,
+        oddsHistory: market.trackOdds ? getOddsHistoryPDA(marketPDA, this.programId)[0] : null,
         bettor: this.wallet.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
@@ -363,9 +396,9 @@ export class FortunaClient {
   /**
    * Get bet data for a specific market and bettor
    */
-  async getBet(marketId: BN | number, bettor?: PublicKey): Promise<Bet | null> {
+  async getBet(creator: PublicKey, marketId: BN | number, bettor?: PublicKey): Promise<Bet | null> {
     const program = this.getProgram();
-    const [marketPDA] = getMarketPDA(marketId, this.programId);
+    const [marketPDA] = getMarketPDA(creator, marketId, this.programId);
     const [betPDA] = getBetPDA(marketPDA, bettor || this.wallet.publicKey, this.programId);
 
     try {
@@ -379,9 +412,9 @@ export class FortunaClient {
   /**
    * Get all bets for a market
    */
-  async getMarketBets(marketId: BN | number): Promise<Bet[]> {
+  async getMarketBets(creator: PublicKey, marketId: BN | number): Promise<Bet[]> {
     const program = this.getProgram();
-    const [marketPDA] = getMarketPDA(marketId, this.programId);
+    const [marketPDA] = getMarketPDA(creator, marketId, this.programId);
 
     const bets = await program.account.bet.all([
       {
@@ -402,13 +435,15 @@ export class FortunaClient {
   /**
    * Resolve a market (creator only)
    */
-  async resolveMarket(marketId: BN | number, winningOutcome: number): Promise<string> {
+  async resolveMarket(creator: PublicKey, marketId: BN | number, winningOutcome: number): Promise<string> {
     const program = this.getProgram();
-    const [marketPDA] = getMarketPDA(marketId, this.programId);
+    const [protocolState] = getProtocolStatePDA(this.programId);
+    const [marketPDA] = getMarketPDA(creator, marketId, this.programId);
 
     const tx = await program.methods
       .resolveMarket(winningOutcome)
       .accounts({
+        protocolState,
         market: marketPDA,
         resolver: this.wallet.publicKey,
       })
@@ -417,20 +452,42 @@ export class FortunaClient {
     return tx;
   }
 
+  /**
+   * Ask for a market to be resolved once betting has closed (bettors only, once per bet)
+   */
+  async requestResolution(creator: PublicKey, marketId: BN | number): Promise<string> {
+    const program = this.getProgram();
+    const [marketPDA] = getMarketPDA(creator, marketId, this.programId);
+    const [betPDA] = getBetPDA(marketPDA, this.wallet.publicKey, this.programId);
+
+    const tx = await program.methods
+      .requestResolution()
+      .accounts({
+        market: marketPDA,
+        bet: betPDA,
+        bettor: this.wallet.publicKey,
+      })
+      .rpc();
+
+    return tx;
+  }
+
   /**
    * Claim winnings after market resolution
    */
   async claimWinnings(
+    creator: PublicKey,
     marketId: BN | number,
     claimerTokenAccount?: PublicKey
   ): Promise<string> {
     const program = this.getProgram();
     const [protocolState] = getProtocolStatePDA(this.programId);
-    const [marketPDA] = getMarketPDA(marketId, this.programId);
+    const [marketPDA] = getMarketPDA(creator, marketId, this.programId);
     const [marketVault] = getMarketVaultPDA(marketPDA, this.programId);
+    const [poolVault] = getPoolVaultPDA(marketPDA, this.programId);
     const [betPDA] = getBetPDA(marketPDA, this.wallet.publicKey, this.programId);
 
-    const market = await this.getMarket(marketId);
+    const market = await this.getMarket(creator, marketId);
     if (!market) throw new Error('Market not found');
 
     const userTokenAccount = claimerTokenAccount || await getAssociatedTokenAddress(
@@ -445,7 +502,9 @@ export class FortunaClient {
         market: marketPDA,
         bet: betPDA,
         marketVault,
+        poolVault,
         claimerTokenAccount: userTokenAccount,
+        memoProgram: market.payoutMemo ? MEMO_PROGRAM_ID : null,
         claimer: this.wallet.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
@@ -455,15 +514,17 @@ export class FortunaClient {
   }
 
   /**
-   * Cancel a market (creator only)
+   * Cancel a market (creator or protocol authority)
    */
-  async cancelMarket(marketId: BN | number): Promise<string> {
+  async cancelMarket(creator: PublicKey, marketId: BN | number): Promise<string> {
     const program = this.getProgram();
-    const [marketPDA] = getMarketPDA(marketId, this.programId);
+    const [protocolState] = getProtocolStatePDA(this.programId);
+    const [marketPDA] = getMarketPDA(creator, marketId, this.programId);
 
     const tx = await program.methods
       .cancelMarket()
       .accounts({
+        protocolState,
         market: marketPDA,
         authority: this.wallet.publicKey,
       })
@@ -472,19 +533,41 @@ export class FortunaClient {
     return tx;
   }
 
+  /**
+   * Cancel several of the wallet's markets at once; markets with bets or already settled are skipped
+   */
+  async cancelMarketsBatch(marketIds: (BN | number)[]): Promise<string> {
+    const program = this.getProgram();
+
+    const tx = await program.methods
+      .cancelMarketsBatch()
+      .accounts({
+        creator: this.wallet.publicKey,
+      })
+      .remainingAccounts(marketIds.map((marketId) => ({
+        pubkey: getMarketPDA(this.wallet.publicKey, marketId, this.programId)[0],
+        isWritable: true,
+        isSigner: false,
+      })))
+      .rpc();
+
+    return tx;
+  }
+
   /**
    * Claim refund for cancelled market
    */
   async claimRefund(
+    creator: PublicKey,
     marketId: BN | number,
     claimerTokenAccount?: PublicKey
   ): Promise<string> {
     const program = this.getProgram();
-    const [marketPDA] = getMarketPDA(marketId, this.programId);
+    const [marketPDA] = getMarketPDA(creator, marketId, this.programId);
     const [marketVault] = getMarketVaultPDA(marketPDA, this.programId);
     const [betPDA] = getBetPDA(marketPDA, this.wallet.publicKey, this.programId);
 
-    const market = await this.getMarket(marketId);
+    const market = await this.getMarket(creator, marketId);
     if (!market) throw new Error('Market not found');
 
     const userTokenAccount = claimerTokenAccount || await getAssociatedTokenAddress(
@@ -512,15 +595,17 @@ export class FortunaClient {
    * Returns the stake minus fees (fees are non-refundable)
    */
   async withdrawBet(
+    creator: PublicKey,
     marketId: BN | number,
     bettorTokenAccount?: PublicKey
   ): Promise<string> {
     const program = this.getProgram();
-    const [marketPDA] = getMarketPDA(marketId, this.programId);
+    const [marketPDA] = getMarketPDA(creator, marketId, this.programId);
     const [marketVault] = getMarketVaultPDA(marketPDA, this.programId);
+    const [leaderboard] = getLeaderboardPDA(marketPDA, this.programId);
     const [betPDA] = getBetPDA(marketPDA, this.wallet.publicKey, this.programId);
 
-    const market = await this.getMarket(marketId);
+    const market = await this.getMarket(creator, marketId);
     if (!market) throw new Error('Market not found');
 
     const userTokenAccount = bettorTokenAccount || await getAssociatedTokenAddress(
@@ -534,6 +619,7 @@ export class FortunaClient {
         market: marketPDA,
         bet: betPDA,
         marketVault,
+        leaderboard,
         bettorTokenAccount: userTokenAccount,
         bettor: this.wallet.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
@@ -548,15 +634,17 @@ export class FortunaClient {
   // ============================================================================
 
   /**
-   * Calculate fees for a bet amount
+   * Calculate fees for a bet amount, applying the category's protocol fee override if given
    */
-  async calculateBetFees(betAmount: BN | number): Promise<FeeBreakdown> {
+  async calculateBetFees(betAmount: BN | number, category?: MarketCategory): Promise<FeeBreakdown> {
     const protocol = await this.getProtocolState();
     if (!protocol) throw new Error('Protocol not initialized');
 
+    const categoryFeeBps =
+      category !== undefined ? protocol.protocolFeeBpsPerCategory[category] : 0;
     return calculateFees(
       betAmount,
-      protocol.protocolFeeBps,
+      categoryFeeBps || protocol.protocolFeeBps,
       protocol.creatorFeeBps,
       protocol.poolFeeBps
     );
@@ -565,15 +653,15 @@ export class FortunaClient {
   /**
    * Get market PDA
    */
-  getMarketPDA(marketId: BN | number): PublicKey {
-    return getMarketPDA(marketId, this.programId)[0];
+  getMarketPDA(creator: PublicKey, marketId: BN | number): PublicKey {
+    return getMarketPDA(creator, marketId, this.programId)[0];
   }
 
   /**
    * Get bet PDA
    */
-  getBetPDA(marketId: BN | number, bettor?: PublicKey): PublicKey {
-    const [marketPDA] = getMarketPDA(marketId, this.programId);
+  getBetPDA(creator: PublicKey, marketId: BN | number, bettor?: PublicKey): PublicKey {
+    const [marketPDA] = getMarketPDA(creator, marketId, this.programId);
     return getBetPDA(marketPDA, bettor || this.wallet.publicKey, this.programId)[0];
   }
 
@@ -802,6 +890,25 @@ export class FortunaClient {
     return tx;
   }
 
+  /**
+   * Set the mint markets are created in when no token mint is given (admin only, null clears it)
+   */
+  async setDefaultTokenMint(defaultTokenMint: PublicKey | null): Promise<string> {
+    const program = this.getProgram();
+    const [protocolState] = getProtocolStatePDA(this.programId);
+
+    const tx = await program.methods
+      .setDefaultTokenMint()
+      .accounts({
+        protocolState,
+        defaultTokenMint,
+        authority: this.wallet.publicKey,
+      })
+      .rpc();
+
+    return tx;
+  }
+
   /**
    * Set whether license is required to create markets (admin only)
    */
@@ -11,6 +11,17 @@ export enum MarketStatus {
   Cancelled = 2,
 }
 
+/**
+ * Bet lifecycle state enum
+ */
+export enum BetState {
+  Active = 0,
+  Withdrawn = 1,
+  Refunded = 2,
+  ClaimedWin = 3,
+  ClaimedLoss = 4,
+}
+
 /**
  * License type enum
  */
@@ -39,6 +50,8 @@ export interface Outcome {
   label: string;
   totalAmount: BN;
   bettorCount: number;
+  /** Whether the outcome still accepts new bets */
+  isOpen: boolean;
 }
 
 /**
@@ -50,11 +63,21 @@ export interface ProtocolState {
   protocolFeeBps: number;
   creatorFeeBps: number;
   poolFeeBps: number;
+  /** Protocol fee override per market category (0 = use protocolFeeBps) */
+  protocolFeeBpsPerCategory: number[];
   totalMarkets: BN;
   totalVolume: BN;
   totalOracles: number;
   totalLicenses: number;
   requireLicense: boolean;
+  /** Mint markets are created in when no token mint is given (default = none) */
+  defaultTokenMint: PublicKey;
+  /** Key that may take over a dormant authority (default = no recovery) */
+  recoveryAuthority: PublicKey;
+  /** Seconds without an admin action before the authority counts as dormant */
+  authorityDormancySecs: BN;
+  /** Timestamp of the authority's last admin action */
+  lastAuthorityActivity: BN;
   bump: number;
 }
 
@@ -88,6 +111,10 @@ export interface License {
   expiresAt: BN;
   /** Last activity timestamp */
   lastUsedAt: BN;
+  /** Start of the current market creation rate limit window */
+  windowStart: BN;
+  /** Markets created since windowStart */
+  createdInWindow: number;
   /** Who issued this license */
   issuedBy: PublicKey;
   /** Bump seed */
@@ -148,6 +175,26 @@ export interface Market {
   resolvedAt: BN;
   /** Whether market was resolved by oracle */
   resolvedByOracle: boolean;
+  /** Whether each bet snapshots implied odds into the market's odds history */
+  trackOdds: boolean;
+  /** Whether winnings claims write a memo naming the market */
+  payoutMemo: boolean;
+  /** Whether fees are waived until at least two outcomes have bets */
+  twoSidedFees: boolean;
+  /** Share of the creator fee paid into the bonus pool during the early window, in basis points */
+  creatorLpShareBps: number;
+  /** Seconds after creation during which the creator fee share applies */
+  creatorLpShareWindowSecs: BN;
+  /** Number of bettors who have asked for the market to be resolved */
+  resolutionRequests: number;
+  /** Winnings paid out of the market vault so far */
+  poolPaid: BN;
+  /** Winnings paid out of the bonus pool so far */
+  bonusPaid: BN;
+  /** Betting windows with their own pool and creator fees, ordered by end time */
+  bettingPhases: BettingPhase[];
+  /** Display channels the market is tagged for; the bit meanings are up to each front-end */
+  visibilityFlags: number;
   vaultBump: number;
   poolVaultBump: number;
   bump: number;
@@ -162,11 +209,59 @@ export interface Bet {
   outcomeIndex: number;
   originalAmount: BN;
   poolAmount: BN;
-  claimed: boolean;
+  /** Lifecycle state; anything but Active is terminal */
+  betState: BetState;
   placedAt: BN;
   bump: number;
 }
 
+/**
+ * A bettor's stake as ranked on a market leaderboard
+ */
+export interface LeaderboardEntry {
+  bettor: PublicKey;
+  poolAmount: BN;
+}
+
+/**
+ * A window of a market's betting period with its own fees
+ */
+export interface BettingPhase {
+  /** Unix timestamp the phase ends at (exclusive) */
+  phaseEnd: BN;
+  poolFeeBps: number;
+  creatorFeeBps: number;
+}
+
+/**
+ * Implied odds of every outcome after one bet
+ */
+export interface OddsSnapshot {
+  timestamp: BN;
+  totalPool: BN;
+  /** Each outcome's share of the pool in basis points */
+  impliedOddsBps: number[];
+}
+
+/**
+ * Recent implied odds for a market (ring buffer; oldest at nextIndex once full)
+ */
+export interface MarketOddsHistory {
+  market: PublicKey;
+  nextIndex: number;
+  snapshots: OddsSnapshot[];
+  bump: number;
+}
+
+/**
+ * Largest bettors on a market, sorted by stake (largest first)
+ */
+export interface MarketLeaderboard {
+  market: PublicKey;
+  entries: LeaderboardEntry[];
+  bump: number;
+}
+
 /**
  * Configuration for creating a new market
  */
@@ -187,8 +282,8 @@ export interface CreateMarketConfig {
   resolutionDeadline: number;
   /** Array of outcome labels (2-10 outcomes) */
   outcomes: string[];
-  /** Token mint for betting (e.g., USDC) */
-  tokenMint: PublicKey;
+  /** Token mint for betting (e.g., USDC); defaults to the protocol's default token mint */
+  tokenMint?: PublicKey;
   /** Wallet to receive creator fees */
   creatorFeeWallet: PublicKey;
   /** External event ID for oracle resolution (optional) */
@@ -279,6 +374,21 @@ export interface FeeBreakdown {
   totalFees: BN;
 }
 
+/**
+ * Effects of a bet or claim, returned as instruction return data
+ */
+export interface InstructionResult {
+  market: PublicKey;
+  totalPool: BN;
+  bonusPool: BN;
+  outcomeIndex: number;
+  outcomeTotal: BN;
+  /** Bettor's stake in the pool */
+  position: BN;
+  /** Amount paid to the caller (claims only) */
+  payout: BN;
+}
+
 /**
  * Market statistics
  */
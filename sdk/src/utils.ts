@@ -1,4 +1,5 @@
 import { PublicKey } from '@solana/web3.js';
+import { utils } from '@coral-xyz/anchor';
 import BN from 'bn.js';
 import {
   FORTUNA_PROGRAM_ID,
@@ -9,9 +10,14 @@ import {
   BET_SEED,
   ORACLE_SEED,
   LICENSE_SEED,
+  LEADERBOARD_SEED,
+  ODDS_HISTORY_SEED,
+  SELF_EXCLUSION_SEED,
+  BETTOR_PROFILE_SEED,
+  EVENT_INDEX_SEED,
   BPS_DENOMINATOR,
 } from './constants';
-import { FeeBreakdown } from './types';
+import { FeeBreakdown, InstructionResult } from './types';
 
 /**
  * Derive the protocol state PDA
@@ -26,15 +32,16 @@ export function getProtocolStatePDA(
 }
 
 /**
- * Derive the market PDA for a given market ID
+ * Derive the market PDA for a creator's market ID (IDs are unique per creator)
  */
 export function getMarketPDA(
+  creator: PublicKey,
   marketId: BN | number,
   programId: PublicKey = FORTUNA_PROGRAM_ID
 ): [PublicKey, number] {
   const id = typeof marketId === 'number' ? new BN(marketId) : marketId;
   return PublicKey.findProgramAddressSync(
-    [MARKET_SEED, id.toArrayLike(Buffer, 'le', 8)],
+    [MARKET_SEED, creator.toBuffer(), id.toArrayLike(Buffer, 'le', 8)],
     programId
   );
 }
@@ -65,6 +72,71 @@ export function getPoolVaultPDA(
   );
 }
 
+/**
+ * Derive the leaderboard PDA for a market
+ */
+export function getLeaderboardPDA(
+  marketPubkey: PublicKey,
+  programId: PublicKey = FORTUNA_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [LEADERBOARD_SEED, marketPubkey.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Derive the index PDA listing markets created for an oracle event ID
+ */
+export function getEventIndexPDA(
+  oracleEventId: string,
+  programId: PublicKey = FORTUNA_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [EVENT_INDEX_SEED, Buffer.from(utils.sha256.hash(oracleEventId), 'hex')],
+    programId
+  );
+}
+
+/**
+ * Derive a market's odds history PDA
+ */
+export function getOddsHistoryPDA(
+  market: PublicKey,
+  programId: PublicKey = FORTUNA_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [ODDS_HISTORY_SEED, market.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Derive a wallet's self-exclusion PDA
+ */
+export function getSelfExclusionPDA(
+  wallet: PublicKey,
+  programId: PublicKey = FORTUNA_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [SELF_EXCLUSION_SEED, wallet.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Derive a wallet's bettor profile PDA
+ */
+export function getBettorProfilePDA(
+  wallet: PublicKey,
+  programId: PublicKey = FORTUNA_PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [BETTOR_PROFILE_SEED, wallet.toBuffer()],
+    programId
+  );
+}
+
 /**
  * Derive the bet PDA for a bettor on a specific market
  */
@@ -183,6 +255,21 @@ export function calculatePotentialWinnings(
   return fees.netAmount.mul(totalDistributable).div(newOutcomeTotal);
 }
 
+/**
+ * Decode the return data set by place_bet and claim_winnings
+ */
+export function decodeInstructionResult(data: Buffer): InstructionResult {
+  return {
+    market: new PublicKey(data.subarray(0, 32)),
+    totalPool: new BN(data.subarray(32, 40), 'le'),
+    bonusPool: new BN(data.subarray(40, 48), 'le'),
+    outcomeIndex: data[48],
+    outcomeTotal: new BN(data.subarray(49, 57), 'le'),
+    position: new BN(data.subarray(57, 65), 'le'),
+    payout: new BN(data.subarray(65, 73), 'le'),
+  };
+}
+
 /**
  * Format BN amount to human-readable string with decimals
  */
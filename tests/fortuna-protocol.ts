@@ -15,9 +15,12 @@ import {
   getAccount,
   getAssociatedTokenAddress,
   createAssociatedTokenAccountInstruction,
+  NATIVE_MINT,
 } from '@solana/spl-token';
 import { expect } from 'chai';
+import { createHash } from 'crypto';
 import { FortunaProtocol } from '../target/types/fortuna_protocol';
+import { MockRandomness } from '../target/types/mock_randomness';
 
 describe('fortuna-protocol', () => {
   // Configure the client
@@ -25,6 +28,7 @@ describe('fortuna-protocol', () => {
   anchor.setProvider(provider);
 
   const program = anchor.workspace.FortunaProtocol as Program<FortunaProtocol>;
+  const randomnessProgram = anchor.workspace.MockRandomness as Program<MockRandomness>;
 
   // Test accounts
   let authority: Keypair;
@@ -50,6 +54,14 @@ describe('fortuna-protocol', () => {
   const MARKET_VAULT_SEED = Buffer.from('market_vault');
   const POOL_VAULT_SEED = Buffer.from('pool_vault');
   const BET_SEED = Buffer.from('bet');
+  const ORACLE_SEED = Buffer.from('oracle');
+  const CATEGORY_CONFIG_SEED = Buffer.from('category_config');
+  const LICENSE_SEED = Buffer.from('license');
+  const LEADERBOARD_SEED = Buffer.from('leaderboard');
+  const SELF_EXCLUSION_SEED = Buffer.from('self_exclusion');
+  const BETTOR_PROFILE_SEED = Buffer.from('bettor_profile');
+  const ODDS_HISTORY_SEED = Buffer.from('odds_history');
+  const EVENT_INDEX_SEED = Buffer.from('event_index');
 
   // Fee configuration (in basis points)
   const PROTOCOL_FEE_BPS = 50;  // 0.5%
@@ -144,6 +156,256 @@ describe('fortuna-protocol', () => {
     );
   });
 
+  // ==========================================================================
+  // Helpers
+  // ==========================================================================
+
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  function getMarketPDAs(marketId: BN, marketCreator: PublicKey = creator.publicKey) {
+    const [market] = PublicKey.findProgramAddressSync(
+      [MARKET_SEED, marketCreator.toBuffer(), marketId.toArrayLike(Buffer, 'le', 8)],
+      program.programId
+    );
+    const [marketVault] = PublicKey.findProgramAddressSync(
+      [MARKET_VAULT_SEED, market.toBuffer()],
+      program.programId
+    );
+    const [poolVault] = PublicKey.findProgramAddressSync(
+      [POOL_VAULT_SEED, market.toBuffer()],
+      program.programId
+    );
+    return { market, marketVault, poolVault, leaderboard: getLeaderboardPDA(market) };
+  }
+
+  function getLeaderboardPDA(market: PublicKey): PublicKey {
+    const [leaderboard] = PublicKey.findProgramAddressSync(
+      [LEADERBOARD_SEED, market.toBuffer()],
+      program.programId
+    );
+    return leaderboard;
+  }
+
+  function getEventIndexPDA(oracleEventId: string): PublicKey {
+    const [eventIndex] = PublicKey.findProgramAddressSync(
+      [EVENT_INDEX_SEED, createHash('sha256').update(oracleEventId).digest()],
+      program.programId
+    );
+    return eventIndex;
+  }
+
+  function getSelfExclusionPDA(wallet: PublicKey): PublicKey {
+    const [selfExclusion] = PublicKey.findProgramAddressSync(
+      [SELF_EXCLUSION_SEED, wallet.toBuffer()],
+      program.programId
+    );
+    return selfExclusion;
+  }
+
+  function getOddsHistoryPDA(market: PublicKey): PublicKey {
+    const [oddsHistory] = PublicKey.findProgramAddressSync(
+      [ODDS_HISTORY_SEED, market.toBuffer()],
+      program.programId
+    );
+    return oddsHistory;
+  }
+
+  function getBettorProfilePDA(wallet: PublicKey): PublicKey {
+    const [bettorProfile] = PublicKey.findProgramAddressSync(
+      [BETTOR_PROFILE_SEED, wallet.toBuffer()],
+      program.programId
+    );
+    return bettorProfile;
+  }
+
+  function getOraclePDA(oracleId: number): PublicKey {
+    const idBuffer = Buffer.alloc(4);
+    idBuffer.writeUInt32LE(oracleId);
+    const [oracle] = PublicKey.findProgramAddressSync(
+      [ORACLE_SEED, idBuffer],
+      program.programId
+    );
+    return oracle;
+  }
+
+  function getCategoryConfigPDA(category: number): PublicKey {
+    const [categoryConfig] = PublicKey.findProgramAddressSync(
+      [CATEGORY_CONFIG_SEED, Buffer.from([category])],
+      program.programId
+    );
+    return categoryConfig;
+  }
+
+  function getLicensePDA(licenseKey: number[]): PublicKey {
+    const [license] = PublicKey.findProgramAddressSync(
+      [LICENSE_SEED, Buffer.from(licenseKey)],
+      program.programId
+    );
+    return license;
+  }
+
+  function getBetPDA(market: PublicKey, bettor: PublicKey): PublicKey {
+    const [bet] = PublicKey.findProgramAddressSync(
+      [BET_SEED, market.toBuffer(), bettor.toBuffer()],
+      program.programId
+    );
+    return bet;
+  }
+
+  async function createFundedBettor(
+    amount: number = 100_000_000
+  ): Promise<{ keypair: Keypair; tokenAccount: PublicKey }> {
+    const keypair = Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(
+      keypair.publicKey,
+      10 * LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(sig);
+
+    const tokenAccount = await createAccount(
+      provider.connection,
+      authority,
+      tokenMint,
+      keypair.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      authority,
+      tokenMint,
+      tokenAccount,
+      authority,
+      amount
+    );
+    return { keypair, tokenAccount };
+  }
+
+  async function createTestMarket(
+    marketId: BN,
+    opts: {
+      category?: number;
+      bettingDeadline?: BN;
+      resolutionDeadline?: BN;
+      outcomes?: string[];
+      betAmount?: BN;
+      marketCreator?: Keypair;
+      creatorFeeWallet?: PublicKey;
+      options?: any;
+      tokenMint?: PublicKey;
+      license?: PublicKey;
+      creatorTokenAccount?: PublicKey;
+      oracleEventId?: string;
+      marketVault?: PublicKey;
+      poolVault?: PublicKey;
+    } = {}
+  ) {
+    const marketCreator = opts.marketCreator ?? creator;
+    const pdas = getMarketPDAs(marketId, marketCreator.publicKey);
+    const now = Math.floor(Date.now() / 1000);
+    const bettingDeadline = opts.bettingDeadline ?? new BN(now + 86400);
+    const resolutionDeadline =
+      opts.resolutionDeadline ?? bettingDeadline.add(new BN(86400));
+
+    await program.methods
+      .createMarket(
+        marketId,
+        opts.category ?? 3, // Crypto
+        'Test market',
+        'Test market description',
+        opts.betAmount ?? BET_AMOUNT,
+        resolutionDeadline,
+        bettingDeadline,
+        opts.outcomes ?? ['Yes', 'No'],
+        opts.oracleEventId ?? '',
+        {
+          payoutMode: { parimutuel: {} },
+          resolutionMode: { standard: {} },
+          maxPerOutcome: new BN(0),
+          maxBets: 0,
+          minHoldSecs: new BN(0),
+          isHouseMarket: false,
+          nativeSol: false,
+          multiCollateral: false,
+          collateralMints: [],
+          resultCommitment: new Array(32).fill(0),
+          requireAta: false,
+          allowEarlyResolution: false,
+          feeMint: PublicKey.default,
+          payoutMultipliers: [],
+          feeWaiverCount: 0,
+          twoSidedFees: false,
+          minBettorsForCreatorFee: 0,
+          claimExpirySecs: new BN(0),
+          requireEstablishedWallet: false,
+          trackOdds: false,
+          payoutMemo: false,
+          creatorLpShareBps: 0,
+          creatorLpShareWindowSecs: new BN(0),
+          seedBonus: new BN(0),
+          visibilityFlags: 0,
+          bettingPhases: [],
+          ...opts.options,
+        }
+      )
+      .accounts({
+        protocolState: protocolStatePDA,
+        market: pdas.market,
+        tokenMint: opts.tokenMint ?? tokenMint,
+        marketVault: opts.marketVault ?? pdas.marketVault,
+        poolVault: opts.poolVault ?? pdas.poolVault,
+        leaderboard: pdas.leaderboard,
+        eventIndex: opts.oracleEventId ? getEventIndexPDA(opts.oracleEventId) : null,
+        oddsHistory: opts.options?.trackOdds ? getOddsHistoryPDA(pdas.market) : null,
+        license: opts.license ?? null,
+        categoryConfig: getCategoryConfigPDA(opts.category ?? 3),
+        creatorTokenAccount: opts.creatorTokenAccount ?? null,
+        creator: marketCreator.publicKey,
+        creatorFeeWallet: opts.creatorFeeWallet ?? marketCreator.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([marketCreator])
+      .rpc();
+
+    return pdas;
+  }
+
+  async function placeTestBet(
+    marketId: BN,
+    bettor: { keypair: Keypair; tokenAccount: PublicKey },
+    outcomeIndex: number,
+    lossProtectionBps: number = 0,
+    memo: string | null = null
+  ) {
+    const pdas = getMarketPDAs(marketId);
+    const bet = getBetPDA(pdas.market, bettor.keypair.publicKey);
+
+    await program.methods
+      .placeBet(outcomeIndex, lossProtectionBps, memo)
+      .accounts({
+        protocolState: protocolStatePDA,
+        market: pdas.market,
+        bet,
+        marketVault: pdas.marketVault,
+        poolVault: pdas.poolVault,
+        leaderboard: pdas.leaderboard,
+        bettorTokenAccount: bettor.tokenAccount,
+        treasuryTokenAccount: treasuryTokenAccount,
+        bettorFeeTokenAccount: null,
+        treasuryFeeTokenAccount: null,
+        selfExclusion: getSelfExclusionPDA(bettor.keypair.publicKey),
+        bettorProfile: null,
+        oddsHistory: null,
+        bettor: bettor.keypair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([bettor.keypair])
+      .rpc();
+
+    return bet;
+  }
+
   describe('initialize_protocol', () => {
     it('initializes the protocol with correct fees', async () => {
       await program.methods
@@ -198,7 +460,7 @@ describe('fortuna-protocol', () => {
 
     before(async () => {
       [marketPDA] = PublicKey.findProgramAddressSync(
-        [MARKET_SEED, MARKET_ID.toArrayLike(Buffer, 'le', 8)],
+        [MARKET_SEED, creator.publicKey.toBuffer(), MARKET_ID.toArrayLike(Buffer, 'le', 8)],
         program.programId
       );
       [marketVaultPDA] = PublicKey.findProgramAddressSync(
@@ -232,6 +494,7 @@ describe('fortuna-protocol', () => {
           tokenMint: tokenMint,
           marketVault: marketVaultPDA,
           poolVault: poolVaultPDA,
+          leaderboard: getLeaderboardPDA(marketPDA),
           creator: creator.publicKey,
           creatorFeeWallet: creator.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
@@ -255,7 +518,7 @@ describe('fortuna-protocol', () => {
     it('fails to create market with invalid deadline', async () => {
       const invalidMarketId = new BN(999);
       const [invalidMarketPDA] = PublicKey.findProgramAddressSync(
-        [MARKET_SEED, invalidMarketId.toArrayLike(Buffer, 'le', 8)],
+        [MARKET_SEED, creator.publicKey.toBuffer(), invalidMarketId.toArrayLike(Buffer, 'le', 8)],
         program.programId
       );
       const [invalidMarketVaultPDA] = PublicKey.findProgramAddressSync(
@@ -287,6 +550,7 @@ describe('fortuna-protocol', () => {
             tokenMint: tokenMint,
             marketVault: invalidMarketVaultPDA,
             poolVault: invalidPoolVaultPDA,
+            leaderboard: getLeaderboardPDA(invalidMarketPDA),
             creator: creator.publicKey,
             creatorFeeWallet: creator.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
@@ -311,7 +575,7 @@ describe('fortuna-protocol', () => {
 
     before(async () => {
       [marketPDA] = PublicKey.findProgramAddressSync(
-        [MARKET_SEED, MARKET_ID.toArrayLike(Buffer, 'le', 8)],
+        [MARKET_SEED, creator.publicKey.toBuffer(), MARKET_ID.toArrayLike(Buffer, 'le', 8)],
         program.programId
       );
       [marketVaultPDA] = PublicKey.findProgramAddressSync(
@@ -339,16 +603,21 @@ describe('fortuna-protocol', () => {
       );
 
       await program.methods
-        .placeBet(0) // Yes
+        .placeBet(0, 0, null) // Yes
         .accounts({
           protocolState: protocolStatePDA,
           market: marketPDA,
           bet: bettor1BetPDA,
           marketVault: marketVaultPDA,
           poolVault: poolVaultPDA,
+          leaderboard: getLeaderboardPDA(marketPDA),
           bettorTokenAccount: bettor1TokenAccount,
           treasuryTokenAccount: treasuryTokenAccount,
-          creatorTokenAccount: creatorTokenAccount,
+          bettorFeeTokenAccount: null,
+          treasuryFeeTokenAccount: null,
+          selfExclusion: getSelfExclusionPDA(bettor1.publicKey),
+          bettorProfile: null,
+          oddsHistory: null,
           bettor: bettor1.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
@@ -360,7 +629,7 @@ describe('fortuna-protocol', () => {
       const bet = await program.account.bet.fetch(bettor1BetPDA);
       expect(bet.outcomeIndex).to.equal(0);
       expect(bet.originalAmount.toString()).to.equal(BET_AMOUNT.toString());
-      expect(bet.claimed).to.be.false;
+      expect(bet.betState).to.deep.equal({ active: {} });
 
       // Verify token transfer
       const finalBalance = await getAccount(
@@ -378,16 +647,21 @@ describe('fortuna-protocol', () => {
 
     it('bettor2 places a bet on No (outcome 1)', async () => {
       await program.methods
-        .placeBet(1) // No
+        .placeBet(1, 0, null) // No
         .accounts({
           protocolState: protocolStatePDA,
           market: marketPDA,
           bet: bettor2BetPDA,
           marketVault: marketVaultPDA,
           poolVault: poolVaultPDA,
+          leaderboard: getLeaderboardPDA(marketPDA),
           bettorTokenAccount: bettor2TokenAccount,
           treasuryTokenAccount: treasuryTokenAccount,
-          creatorTokenAccount: creatorTokenAccount,
+          bettorFeeTokenAccount: null,
+          treasuryFeeTokenAccount: null,
+          selfExclusion: getSelfExclusionPDA(bettor2.publicKey),
+          bettorProfile: null,
+          oddsHistory: null,
           bettor: bettor2.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
@@ -411,13 +685,15 @@ describe('fortuna-protocol', () => {
       const expectedProtocolFee = (BET_AMOUNT.toNumber() * PROTOCOL_FEE_BPS) / 10000;
       expect(Number(treasuryBalance.amount)).to.equal(expectedProtocolFee * 2);
 
-      // Check creator received creator fees (0.5% * 2 bets)
+      // Check creator fees (0.5% * 2 bets) are escrowed until resolution
       const creatorBalance = await getAccount(
         provider.connection,
         creatorTokenAccount
       );
       const expectedCreatorFee = (BET_AMOUNT.toNumber() * CREATOR_FEE_BPS) / 10000;
-      expect(Number(creatorBalance.amount)).to.equal(expectedCreatorFee * 2);
+      expect(Number(creatorBalance.amount)).to.equal(0);
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.escrowedCreatorFees.toNumber()).to.equal(expectedCreatorFee * 2);
 
       // Check pool vault received pool fees (5% * 2 bets)
       const poolBalance = await getAccount(provider.connection, poolVaultPDA);
@@ -431,7 +707,7 @@ describe('fortuna-protocol', () => {
 
     before(async () => {
       [marketPDA] = PublicKey.findProgramAddressSync(
-        [MARKET_SEED, MARKET_ID.toArrayLike(Buffer, 'le', 8)],
+        [MARKET_SEED, creator.publicKey.toBuffer(), MARKET_ID.toArrayLike(Buffer, 'le', 8)],
         program.programId
       );
     });
@@ -441,6 +717,7 @@ describe('fortuna-protocol', () => {
         await program.methods
           .resolveMarket(0)
           .accounts({
+            protocolState: protocolStatePDA,
             market: marketPDA,
             resolver: bettor1.publicKey,
           })
@@ -501,7 +778,7 @@ describe('fortuna-protocol', () => {
     before(async () => {
       cancelMarketId = new BN(100);
       [cancelMarketPDA] = PublicKey.findProgramAddressSync(
-        [MARKET_SEED, cancelMarketId.toArrayLike(Buffer, 'le', 8)],
+        [MARKET_SEED, creator.publicKey.toBuffer(), cancelMarketId.toArrayLike(Buffer, 'le', 8)],
         program.programId
       );
       [cancelMarketVaultPDA] = PublicKey.findProgramAddressSync(
@@ -531,6 +808,7 @@ describe('fortuna-protocol', () => {
           tokenMint: tokenMint,
           marketVault: cancelMarketVaultPDA,
           poolVault: cancelPoolVaultPDA,
+          leaderboard: getLeaderboardPDA(cancelMarketPDA),
           creator: creator.publicKey,
           creatorFeeWallet: creator.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
@@ -545,6 +823,7 @@ describe('fortuna-protocol', () => {
       await program.methods
         .cancelMarket()
         .accounts({
+          protocolState: protocolStatePDA,
           market: cancelMarketPDA,
           authority: creator.publicKey,
         })
@@ -560,6 +839,7 @@ describe('fortuna-protocol', () => {
         await program.methods
           .cancelMarket()
           .accounts({
+            protocolState: protocolStatePDA,
             market: cancelMarketPDA,
             authority: creator.publicKey,
           })
@@ -567,8 +847,7616 @@ describe('fortuna-protocol', () => {
           .rpc();
         expect.fail('Should have thrown an error');
       } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal('MarketNotOpen');
+        expect(error.error.errorCode.code).to.equal('MarketIsCancelled');
+      }
+    });
+  });
+
+  describe('set_market_fee_exempt', () => {
+    const feeExemptMarketId = new BN(109_001);
+    let marketPDA: PublicKey;
+
+    before(async () => {
+      ({ market: marketPDA } = await createTestMarket(feeExemptMarketId));
+    });
+
+    it('fails when non-authority tries to exempt a market', async () => {
+      try {
+        await program.methods
+          .setMarketFeeExempt(true)
+          .accounts({
+            protocolState: protocolStatePDA,
+            market: marketPDA,
+            authority: creator.publicKey,
+          })
+          .signers([creator])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+
+    it('authority exempts the market from fees', async () => {
+      await program.methods
+        .setMarketFeeExempt(true)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: marketPDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.feeExempt).to.be.true;
+    });
+
+    it('takes no fees on a fee-exempt market', async () => {
+      const bettor = await createFundedBettor();
+      const { poolVault } = getMarketPDAs(feeExemptMarketId);
+
+      const treasuryBefore = await getAccount(provider.connection, treasuryTokenAccount);
+      const creatorBefore = await getAccount(provider.connection, creatorTokenAccount);
+
+      const betPDA = await placeTestBet(feeExemptMarketId, bettor, 0);
+
+      const bet = await program.account.bet.fetch(betPDA);
+      expect(bet.poolAmount.toString()).to.equal(BET_AMOUNT.toString());
+
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.totalPool.toString()).to.equal(BET_AMOUNT.toString());
+      expect(market.bonusPool.toNumber()).to.equal(0);
+
+      const treasuryAfter = await getAccount(provider.connection, treasuryTokenAccount);
+      const creatorAfter = await getAccount(provider.connection, creatorTokenAccount);
+      const poolBalance = await getAccount(provider.connection, poolVault);
+      expect(treasuryAfter.amount).to.equal(treasuryBefore.amount);
+      expect(creatorAfter.amount).to.equal(creatorBefore.amount);
+      expect(Number(poolBalance.amount)).to.equal(0);
+    });
+  });
+
+  describe('update_creator_fee_wallet', () => {
+    const brokenMarketId = new BN(110_001);
+    let marketPDA: PublicKey;
+
+    before(async () => {
+      // Creator fee wallet has no token account, so escrowed fees could never be released
+      ({ market: marketPDA } = await createTestMarket(brokenMarketId, {
+        creatorFeeWallet: Keypair.generate().publicKey,
+      }));
+    });
+
+    it('escrows creator fees even while the creator fee wallet is misconfigured', async () => {
+      const bettor = await createFundedBettor();
+      const betPDA = await placeTestBet(brokenMarketId, bettor, 0);
+      const bet = await program.account.bet.fetch(betPDA);
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.escrowedCreatorFees.toString()).to.equal(bet.creatorFee.toString());
+    });
+
+    it('fails when a non-creator tries to update the fee wallet', async () => {
+      try {
+        await program.methods
+          .updateCreatorFeeWallet()
+          .accounts({
+            protocolState: protocolStatePDA,
+            market: marketPDA,
+            newCreatorFeeWallet: bettor1.publicKey,
+            creator: bettor1.publicKey,
+          })
+          .signers([bettor1])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+
+    it('creator fixes the fee wallet and bets succeed', async () => {
+      await program.methods
+        .updateCreatorFeeWallet()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: marketPDA,
+          newCreatorFeeWallet: creator.publicKey,
+          creator: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.creatorFeeWallet.toString()).to.equal(creator.publicKey.toString());
+
+      const bettor = await createFundedBettor();
+      const betPDA = await placeTestBet(brokenMarketId, bettor, 1);
+      const bet = await program.account.bet.fetch(betPDA);
+      expect(bet.outcomeIndex).to.equal(1);
+    });
+  });
+
+  describe('rebate_tiers', () => {
+    const smallMarketId = new BN(111_001);
+    const largeMarketId = new BN(111_002);
+    const LARGE_BET_AMOUNT = new BN(50_000_000);
+
+    before(async () => {
+      await createTestMarket(smallMarketId);
+      await createTestMarket(largeMarketId, { betAmount: LARGE_BET_AMOUNT });
+
+      await program.methods
+        .setRebateTiers([{ threshold: LARGE_BET_AMOUNT, rebateBps: 5000 }])
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    });
+
+    after(async () => {
+      await program.methods
+        .setRebateTiers([])
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    });
+
+    it('rejects tiers that are not sorted by threshold', async () => {
+      try {
+        await program.methods
+          .setRebateTiers([
+            { threshold: new BN(2_000), rebateBps: 1000 },
+            { threshold: new BN(1_000), rebateBps: 2000 },
+          ])
+          .accounts({
+            protocolState: protocolStatePDA,
+            authority: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('InvalidRebateTiers');
+      }
+    });
+
+    it('charges the full protocol fee below the rebate threshold', async () => {
+      const protocolState = await program.account.protocolState.fetch(protocolStatePDA);
+      const bettor = await createFundedBettor();
+      const treasuryBefore = await getAccount(provider.connection, treasuryTokenAccount);
+
+      await placeTestBet(smallMarketId, bettor, 0);
+
+      const treasuryAfter = await getAccount(provider.connection, treasuryTokenAccount);
+      const expectedProtocolFee = Math.floor(
+        (BET_AMOUNT.toNumber() * protocolState.protocolFeeBps) / 10000
+      );
+      expect(Number(treasuryAfter.amount - treasuryBefore.amount)).to.equal(
+        expectedProtocolFee
+      );
+    });
+
+    it('rebates part of the protocol fee for a bet crossing the tier', async () => {
+      const protocolState = await program.account.protocolState.fetch(protocolStatePDA);
+      const bettor = await createFundedBettor();
+      const bettorBefore = await getAccount(provider.connection, bettor.tokenAccount);
+      const treasuryBefore = await getAccount(provider.connection, treasuryTokenAccount);
+
+      await placeTestBet(largeMarketId, bettor, 0);
+
+      const fullProtocolFee = Math.floor(
+        (LARGE_BET_AMOUNT.toNumber() * protocolState.protocolFeeBps) / 10000
+      );
+      const rebate = Math.floor(fullProtocolFee / 2);
+
+      const treasuryAfter = await getAccount(provider.connection, treasuryTokenAccount);
+      expect(Number(treasuryAfter.amount - treasuryBefore.amount)).to.equal(
+        fullProtocolFee - rebate
+      );
+
+      // The rebate never leaves the bettor's token account
+      const bettorAfter = await getAccount(provider.connection, bettor.tokenAccount);
+      expect(Number(bettorBefore.amount - bettorAfter.amount)).to.equal(
+        LARGE_BET_AMOUNT.toNumber() - rebate
+      );
+    });
+  });
+
+  describe('place_bet on terminal markets', () => {
+    const resolvedMarketId = new BN(112_001);
+    const cancelledMarketId = new BN(112_002);
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const { market: resolvedPDA } = await createTestMarket(resolvedMarketId, {
+        bettingDeadline: new BN(now + 3),
+      });
+      const { market: cancelledPDA } = await createTestMarket(cancelledMarketId);
+
+      await sleep(5000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: resolvedPDA, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      await program.methods
+        .cancelMarket()
+        .accounts({ protocolState: protocolStatePDA, market: cancelledPDA, authority: creator.publicKey })
+        .signers([creator])
+        .rpc();
+    });
+
+    it('rejects a bet on a resolved market with MarketAlreadyResolved', async () => {
+      const bettor = await createFundedBettor();
+      try {
+        await placeTestBet(resolvedMarketId, bettor, 0);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('MarketAlreadyResolved');
+      }
+    });
+
+    it('rejects a bet on a cancelled market with MarketIsCancelled', async () => {
+      const bettor = await createFundedBettor();
+      try {
+        await placeTestBet(cancelledMarketId, bettor, 0);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('MarketIsCancelled');
+      }
+    });
+  });
+
+  describe('loss_protection', () => {
+    const protectedMarketId = new BN(113_001);
+    const PREMIUM_BPS = 1000; // 10% of the covered amount
+    let marketPDA: PublicKey;
+    let marketVaultPDA: PublicKey;
+    let protectedBettor: { keypair: Keypair; tokenAccount: PublicKey };
+    let unprotectedBettor: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function setCryptoClaimDelay(delay: number) {
+      return program.methods
+        .setClaimDelay(3, new BN(delay))
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    function claimLossProtection(bettor: { keypair: Keypair; tokenAccount: PublicKey }) {
+      return program.methods
+        .claimLossProtection()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: marketPDA,
+          bet: getBetPDA(marketPDA, bettor.keypair.publicKey),
+          marketVault: marketVaultPDA,
+          claimerTokenAccount: bettor.tokenAccount,
+          claimer: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      ({ market: marketPDA, marketVault: marketVaultPDA } = await createTestMarket(
+        protectedMarketId,
+        { bettingDeadline: new BN(now + 6) }
+      ));
+      protectedBettor = await createFundedBettor();
+      unprotectedBettor = await createFundedBettor();
+    });
+
+    after(async () => {
+      await program.methods
+        .setLossProtectionPremium(0)
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    });
+
+    it('rejects loss protection while the protocol has no premium configured', async () => {
+      try {
+        await placeTestBet(protectedMarketId, protectedBettor, 0, 5000);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('LossProtectionUnavailable');
+      }
+    });
+
+    it('charges the premium into the bonus pool for a protected bet', async () => {
+      await program.methods
+        .setLossProtectionPremium(PREMIUM_BPS)
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      const betPDA = await placeTestBet(protectedMarketId, protectedBettor, 0, 5000);
+      await placeTestBet(protectedMarketId, unprotectedBettor, 1);
+
+      const bet = await program.account.bet.fetch(betPDA);
+      const expectedProtected = Math.floor((bet.poolAmount.toNumber() * 5000) / 10000);
+      expect(bet.lossProtectionBps).to.equal(5000);
+      expect(bet.protectedAmount.toNumber()).to.equal(expectedProtected);
+
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.outcomes[0].protectedAmount.toNumber()).to.equal(expectedProtected);
+
+      const protocolState = await program.account.protocolState.fetch(protocolStatePDA);
+      const poolFee = Math.floor((BET_AMOUNT.toNumber() * protocolState.poolFeeBps) / 10000);
+      const premium = Math.floor((expectedProtected * PREMIUM_BPS) / 10000);
+      expect(market.bonusPool.toNumber()).to.equal(poolFee * 2 + premium);
+    });
+
+    it('lets the protected losing bet reclaim its covered portion', async () => {
+      await sleep(8000);
+      await program.methods
+        .resolveMarket(1)
+        .accounts({ protocolState: protocolStatePDA, market: marketPDA, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const betPDA = getBetPDA(marketPDA, protectedBettor.keypair.publicKey);
+      const bet = await program.account.bet.fetch(betPDA);
+
+      // Protected losers wait out the claim delay like winners do
+      await setCryptoClaimDelay(3600);
+      try {
+        await claimLossProtection(protectedBettor);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('ClaimDelayNotElapsed');
+      }
+      await setCryptoClaimDelay(0);
+
+      const balanceBefore = await getAccount(provider.connection, protectedBettor.tokenAccount);
+
+      await claimLossProtection(protectedBettor);
+
+      const balanceAfter = await getAccount(provider.connection, protectedBettor.tokenAccount);
+      expect(Number(balanceAfter.amount - balanceBefore.amount)).to.equal(
+        bet.protectedAmount.toNumber()
+      );
+
+      const claimedBet = await program.account.bet.fetch(betPDA);
+      expect(claimedBet.betState).to.deep.equal({ claimedLoss: {} });
+    });
+
+    it('rejects a loss protection claim on an unprotected bet', async () => {
+      try {
+        await claimLossProtection(unprotectedBettor);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('NoLossProtection');
+      }
+    });
+  });
+
+  describe('set_market_paused', () => {
+    const pausedMarketId = new BN(114_001);
+    let marketPDA: PublicKey;
+    let marketVaultPDA: PublicKey;
+    let earlyBettor: { keypair: Keypair; tokenAccount: PublicKey };
+
+    before(async () => {
+      ({ market: marketPDA, marketVault: marketVaultPDA } =
+        await createTestMarket(pausedMarketId));
+      earlyBettor = await createFundedBettor();
+      await placeTestBet(pausedMarketId, earlyBettor, 0);
+    });
+
+    it('fails when a random wallet tries to pause the market', async () => {
+      try {
+        await program.methods
+          .setMarketPaused(true)
+          .accounts({
+            protocolState: protocolStatePDA,
+            market: marketPDA,
+            authority: bettor1.publicKey,
+          })
+          .signers([bettor1])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+
+    it('admin pauses the market and new bets are rejected', async () => {
+      await program.methods
+        .setMarketPaused(true)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: marketPDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.paused).to.be.true;
+
+      const bettor = await createFundedBettor();
+      try {
+        await placeTestBet(pausedMarketId, bettor, 1);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('MarketPaused');
+      }
+    });
+
+    it('still allows withdrawals while paused', async () => {
+      const betPDA = getBetPDA(marketPDA, earlyBettor.keypair.publicKey);
+      await program.methods
+        .withdrawBet()
+        .accounts({
+          market: marketPDA,
+          bet: betPDA,
+          marketVault: marketVaultPDA,
+          leaderboard: getLeaderboardPDA(marketPDA),
+          bettorTokenAccount: earlyBettor.tokenAccount,
+          bettor: earlyBettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([earlyBettor.keypair])
+        .rpc();
+
+      const bet = await program.account.bet.fetch(betPDA);
+      expect(bet.betState).to.deep.equal({ withdrawn: {} });
+    });
+
+    it('creator unpauses the market and betting resumes', async () => {
+      await program.methods
+        .setMarketPaused(false)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: marketPDA,
+          authority: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+
+      const bettor = await createFundedBettor();
+      await placeTestBet(pausedMarketId, bettor, 1);
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.outcomes[1].bettorCount).to.equal(1);
+    });
+  });
+
+  describe('oracle categories validation', () => {
+    const oracleId = 115_001;
+    let oraclePDA: PublicKey;
+    const oracleAuthority = Keypair.generate();
+
+    before(() => {
+      oraclePDA = getOraclePDA(oracleId);
+    });
+
+    it('rejects registering an oracle with no categories enabled', async () => {
+      try {
+        await program.methods
+          .registerOracle(oracleId, 'Empty Oracle', new Array(12).fill(false), 'https://example.com')
+          .accounts({
+            protocolState: protocolStatePDA,
+            oracle: oraclePDA,
+            oracleAuthority: oracleAuthority.publicKey,
+            bondMint: null,
+            bondVault: null,
+            oracleBondSource: null,
+            authority: authority.publicKey,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('OracleHasNoCategories');
+      }
+    });
+
+    it('rejects updating an oracle to an all-false categories array', async () => {
+      const categories = new Array(12).fill(false);
+      categories[1] = true; // Sports
+
+      await program.methods
+        .registerOracle(oracleId, 'Sports Oracle', categories, 'https://example.com')
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: oraclePDA,
+          oracleAuthority: oracleAuthority.publicKey,
+          bondMint: null,
+          bondVault: null,
+          oracleBondSource: null,
+          authority: authority.publicKey,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      try {
+        await program.methods
+          .updateOracle(null, new Array(12).fill(false), null, null)
+          .accounts({
+            protocolState: protocolStatePDA,
+            oracle: oraclePDA,
+            authority: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('OracleHasNoCategories');
+      }
+
+      const oracle = await program.account.oracle.fetch(oraclePDA);
+      expect(oracle.categories[1]).to.be.true;
+    });
+  });
+
+  describe('winner_take_all payout mode', () => {
+    const wtaMarketId = new BN(116_001);
+    let marketPDA: PublicKey;
+    let marketVaultPDA: PublicKey;
+    let firstBettor: { keypair: Keypair; tokenAccount: PublicKey };
+    let laterBettor: { keypair: Keypair; tokenAccount: PublicKey };
+    let losingBettor: { keypair: Keypair; tokenAccount: PublicKey };
+
+    async function claim(bettor: { keypair: Keypair; tokenAccount: PublicKey }) {
+      await program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: marketPDA,
+          bet: getBetPDA(marketPDA, bettor.keypair.publicKey),
+          marketVault: marketVaultPDA,
+          poolVault: getMarketPDAs(wtaMarketId).poolVault,
+          claimerTokenAccount: bettor.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      ({ market: marketPDA, marketVault: marketVaultPDA } = await createTestMarket(
+        wtaMarketId,
+        {
+          bettingDeadline: new BN(now + 8),
+          options: { payoutMode: { winnerTakeAll: {} } },
+        }
+      ));
+
+      // Fee-exempt so the whole pool sits in the market vault
+      await program.methods
+        .setMarketFeeExempt(true)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: marketPDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      firstBettor = await createFundedBettor();
+      laterBettor = await createFundedBettor();
+      losingBettor = await createFundedBettor();
+    });
+
+    it('records the earliest bet on each outcome', async () => {
+      const firstBet = await placeTestBet(wtaMarketId, firstBettor, 0);
+      await placeTestBet(wtaMarketId, laterBettor, 0);
+      const losingBet = await placeTestBet(wtaMarketId, losingBettor, 1);
+
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.payoutMode).to.deep.equal({ winnerTakeAll: {} });
+      expect(market.outcomes[0].firstBet.toString()).to.equal(firstBet.toString());
+      expect(market.outcomes[1].firstBet.toString()).to.equal(losingBet.toString());
+    });
+
+    it('does not let the leading bet withdraw', async () => {
+      try {
+        await program.methods
+          .withdrawBet()
+          .accounts({
+            market: marketPDA,
+            bet: getBetPDA(marketPDA, firstBettor.keypair.publicKey),
+            marketVault: marketVaultPDA,
+            leaderboard: getLeaderboardPDA(marketPDA),
+            bettorTokenAccount: firstBettor.tokenAccount,
+            bettor: firstBettor.keypair.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([firstBettor.keypair])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('LeadingBetLocked');
+      }
+    });
+
+    it('pays the entire pool to the earliest correct bet only', async () => {
+      await sleep(10000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: marketPDA, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const market = await program.account.market.fetch(marketPDA);
+      const firstBet = getBetPDA(marketPDA, firstBettor.keypair.publicKey);
+      expect(market.winningBet.toString()).to.equal(firstBet.toString());
+
+      const firstBefore = await getAccount(provider.connection, firstBettor.tokenAccount);
+      await claim(firstBettor);
+      const firstAfter = await getAccount(provider.connection, firstBettor.tokenAccount);
+      expect(Number(firstAfter.amount - firstBefore.amount)).to.equal(
+        market.totalPool.toNumber()
+      );
+
+      try {
+        await claim(laterBettor);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('LostBet');
+      }
+    });
+  });
+
+  describe('create_market under a zero-fee protocol', () => {
+    let originalFees: { protocol: number; creator: number; pool: number };
+
+    before(async () => {
+      const protocolState = await program.account.protocolState.fetch(protocolStatePDA);
+      originalFees = {
+        protocol: protocolState.protocolFeeBps,
+        creator: protocolState.creatorFeeBps,
+        pool: protocolState.poolFeeBps,
+      };
+
+      await program.methods
+        .updateProtocol(null, 0, 0, 0)
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    });
+
+    after(async () => {
+      await program.methods
+        .updateProtocol(null, originalFees.protocol, originalFees.creator, originalFees.pool)
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    });
+
+    it('rejects market creation while all fees are zero', async () => {
+      try {
+        await createTestMarket(new BN(117_001));
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('ProtocolFeesNotConfigured');
+      }
+    });
+  });
+
+  describe('close_oracle', () => {
+    const oracleId = 118_001;
+    let oraclePDA: PublicKey;
+
+    before(async () => {
+      oraclePDA = getOraclePDA(oracleId);
+      const categories = new Array(12).fill(false);
+      categories[3] = true; // Crypto
+
+      await program.methods
+        .registerOracle(oracleId, 'Retiring Oracle', categories, 'https://example.com')
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: oraclePDA,
+          oracleAuthority: Keypair.generate().publicKey,
+          bondMint: null,
+          bondVault: null,
+          oracleBondSource: null,
+          authority: authority.publicKey,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    });
+
+    it('refuses to close an active oracle', async () => {
+      try {
+        await program.methods
+          .closeOracle()
+          .accounts({
+            protocolState: protocolStatePDA,
+            oracle: oraclePDA,
+            authority: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('OracleStillActive');
+      }
+    });
+
+    it('closes a deactivated oracle and decrements the counter', async () => {
+      await program.methods
+        .updateOracle(null, null, null, false)
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: oraclePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      const before = await program.account.protocolState.fetch(protocolStatePDA);
+
+      await program.methods
+        .closeOracle()
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: oraclePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      const after = await program.account.protocolState.fetch(protocolStatePDA);
+      expect(after.totalOracles).to.equal(before.totalOracles - 1);
+
+      const oracleInfo = await provider.connection.getAccountInfo(oraclePDA);
+      expect(oracleInfo).to.be.null;
+    });
+  });
+
+  describe('community resolution', () => {
+    const communityMarketId = new BN(119_001);
+    let marketPDA: PublicKey;
+    let yesBettor1: { keypair: Keypair; tokenAccount: PublicKey };
+    let yesBettor2: { keypair: Keypair; tokenAccount: PublicKey };
+    let noBettor: { keypair: Keypair; tokenAccount: PublicKey };
+
+    async function vote(bettor: { keypair: Keypair; tokenAccount: PublicKey }, outcomeIndex: number) {
+      await program.methods
+        .castResolutionVote(outcomeIndex)
+        .accounts({
+          market: marketPDA,
+          bet: getBetPDA(marketPDA, bettor.keypair.publicKey),
+          voter: bettor.keypair.publicKey,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      ({ market: marketPDA } = await createTestMarket(communityMarketId, {
+        bettingDeadline: new BN(now + 6),
+        resolutionDeadline: new BN(now + 14),
+        options: { resolutionMode: { communityVote: {} } },
+      }));
+
+      yesBettor1 = await createFundedBettor();
+      yesBettor2 = await createFundedBettor();
+      noBettor = await createFundedBettor();
+      await placeTestBet(communityMarketId, yesBettor1, 0);
+      await placeTestBet(communityMarketId, yesBettor2, 0);
+      await placeTestBet(communityMarketId, noBettor, 1);
+    });
+
+    it('rejects votes while betting is still open', async () => {
+      try {
+        await vote(yesBettor1, 0);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('VotingNotOpen');
+      }
+    });
+
+    it('does not allow the creator to resolve a community market', async () => {
+      await sleep(8000);
+      try {
+        await program.methods
+          .resolveMarket(0)
+          .accounts({ protocolState: protocolStatePDA, market: marketPDA, resolver: creator.publicKey })
+          .signers([creator])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('WrongResolutionMode');
+      }
+    });
+
+    it('tallies votes by stake and prevents double voting', async () => {
+      await vote(yesBettor1, 1);
+      await vote(yesBettor2, 1);
+      await vote(noBettor, 0);
+
+      try {
+        await vote(yesBettor1, 0);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('AlreadyVoted');
+      }
+
+      const market = await program.account.market.fetch(marketPDA);
+      const bet = await program.account.bet.fetch(getBetPDA(marketPDA, noBettor.keypair.publicKey));
+      expect(market.outcomes[0].voteWeight.toNumber()).to.equal(bet.poolAmount.toNumber());
+      expect(market.outcomes[1].voteWeight.toNumber()).to.equal(bet.poolAmount.toNumber() * 2);
+    });
+
+    it('finalizes to the outcome with the most stake-weight', async () => {
+      await sleep(8000);
+      await program.methods
+        .finalizeCommunityResolution()
+        .accounts({ market: marketPDA, caller: bettor1.publicKey })
+        .signers([bettor1])
+        .rpc();
+
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.status).to.deep.equal({ resolved: {} });
+      expect(market.winningOutcome).to.equal(1);
+    });
+  });
+
+  describe('stranded single-sided pools', () => {
+    async function createOneSidedMarket(marketId: BN) {
+      const now = Math.floor(Date.now() / 1000);
+      const pdas = await createTestMarket(marketId, { bettingDeadline: new BN(now + 6) });
+      const bettorA = await createFundedBettor();
+      const bettorB = await createFundedBettor();
+      await placeTestBet(marketId, bettorA, 0);
+      await placeTestBet(marketId, bettorB, 0);
+      return { ...pdas, bettorA };
+    }
+
+    async function setPolicy(policy: any) {
+      await program.methods
+        .setStrandedPoolPolicy(policy)
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    after(async () => {
+      await setPolicy({ void: {} });
+    });
+
+    it('voids the market when the winner has no bets under the default policy', async () => {
+      const marketId = new BN(120_001);
+      const { market, marketVault, bettorA } = await createOneSidedMarket(marketId);
+
+      await sleep(8000);
+      await program.methods
+        .resolveMarket(1)
+        .accounts({ protocolState: protocolStatePDA, market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.status).to.deep.equal({ cancelled: {} });
+
+      const betPDA = getBetPDA(market, bettorA.keypair.publicKey);
+      const bet = await program.account.bet.fetch(betPDA);
+      const balanceBefore = await getAccount(provider.connection, bettorA.tokenAccount);
+
+      await program.methods
+        .claimRefund()
+        .accounts({
+          market,
+          bet: betPDA,
+          marketVault,
+          claimerTokenAccount: bettorA.tokenAccount,
+          claimer: bettorA.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bettorA.keypair])
+        .rpc();
+
+      const balanceAfter = await getAccount(provider.connection, bettorA.tokenAccount);
+      expect(Number(balanceAfter.amount - balanceBefore.amount)).to.equal(
+        bet.poolAmount.toNumber() + bet.creatorFee.toNumber()
+      );
+    });
+
+    it('sweeps the stranded pool to the treasury when configured', async () => {
+      await setPolicy({ sweepToTreasury: {} });
+
+      const marketId = new BN(120_002);
+      const { market, marketVault, poolVault } = await createOneSidedMarket(marketId);
+
+      await sleep(8000);
+      await program.methods
+        .resolveMarket(1)
+        .accounts({ protocolState: protocolStatePDA, market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const resolved = await program.account.market.fetch(market);
+      expect(resolved.status).to.deep.equal({ resolved: {} });
+
+      const sweepAccounts = {
+        protocolState: protocolStatePDA,
+        market,
+        marketVault,
+        poolVault,
+        treasuryTokenAccount: treasuryTokenAccount,
+        caller: bettor1.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+
+      const treasuryBefore = await getAccount(provider.connection, treasuryTokenAccount);
+      await program.methods
+        .sweepStrandedPool()
+        .accounts(sweepAccounts)
+        .signers([bettor1])
+        .rpc();
+      const treasuryAfter = await getAccount(provider.connection, treasuryTokenAccount);
+
+      expect(Number(treasuryAfter.amount - treasuryBefore.amount)).to.equal(
+        resolved.totalPool.toNumber() + resolved.bonusPool.toNumber()
+      );
+
+      try {
+        await program.methods
+          .sweepStrandedPool()
+          .accounts(sweepAccounts)
+          .signers([bettor1])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('StrandedPoolAlreadySwept');
+      }
+    });
+  });
+
+  describe('nonexistent bet errors', () => {
+    const openMarketId = new BN(121_001);
+    const resolvedMarketId = new BN(121_002);
+    const cancelledMarketId = new BN(121_003);
+    let stranger: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function betAccounts(marketId: BN) {
+      const pdas = getMarketPDAs(marketId);
+      return {
+        market: pdas.market,
+        bet: getBetPDA(pdas.market, stranger.keypair.publicKey),
+        marketVault: pdas.marketVault,
+        poolVault: pdas.poolVault,
+        leaderboard: pdas.leaderboard,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      stranger = await createFundedBettor();
+
+      await createTestMarket(openMarketId);
+      await createTestMarket(cancelledMarketId);
+      const { market: resolvedPDA } = await createTestMarket(resolvedMarketId, {
+        bettingDeadline: new BN(now + 4),
+      });
+
+      await program.methods
+        .cancelMarket()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: getMarketPDAs(cancelledMarketId).market,
+          authority: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+
+      await sleep(6000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: resolvedPDA, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+    });
+
+    it('claim_winnings fails with AccountNotInitialized', async () => {
+      try {
+        await program.methods
+          .claimWinnings()
+          .accounts({
+            ...betAccounts(resolvedMarketId),
+            protocolState: protocolStatePDA,
+            claimerTokenAccount: stranger.tokenAccount,
+            claimEscrow: null,
+            escrowVault: null,
+            memoProgram: null,
+            claimer: stranger.keypair.publicKey,
+          })
+          .signers([stranger.keypair])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('AccountNotInitialized');
+      }
+    });
+
+    it('withdraw_bet fails with AccountNotInitialized', async () => {
+      try {
+        await program.methods
+          .withdrawBet()
+          .accounts({
+            ...betAccounts(openMarketId),
+            bettorTokenAccount: stranger.tokenAccount,
+            bettor: stranger.keypair.publicKey,
+          })
+          .signers([stranger.keypair])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('AccountNotInitialized');
+      }
+    });
+
+    it('claim_refund fails with AccountNotInitialized', async () => {
+      try {
+        await program.methods
+          .claimRefund()
+          .accounts({
+            ...betAccounts(cancelledMarketId),
+            claimerTokenAccount: stranger.tokenAccount,
+            claimer: stranger.keypair.publicKey,
+          })
+          .signers([stranger.keypair])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('AccountNotInitialized');
+      }
+    });
+  });
+
+  describe('category allowed mints', () => {
+    const ALLOWED_CATEGORY = 5;
+    const RESTRICTED_CATEGORY = 6;
+    let otherMint: PublicKey;
+
+    async function setAllowedMints(category: number, mints: PublicKey[]) {
+      await program.methods
+        .setCategoryAllowedMints(category, mints)
+        .accounts({
+          protocolState: protocolStatePDA,
+          categoryConfig: getCategoryConfigPDA(category),
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    before(async () => {
+      otherMint = await createMint(provider.connection, authority, authority.publicKey, null, 6);
+      await setAllowedMints(ALLOWED_CATEGORY, [tokenMint]);
+      await setAllowedMints(RESTRICTED_CATEGORY, [otherMint]);
+    });
+
+    after(async () => {
+      await setAllowedMints(ALLOWED_CATEGORY, []);
+      await setAllowedMints(RESTRICTED_CATEGORY, []);
+    });
+
+    it('creates a market with a mint on the category allow-list', async () => {
+      const { market } = await createTestMarket(new BN(122_001), { category: ALLOWED_CATEGORY });
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.tokenMint.toString()).to.equal(tokenMint.toString());
+    });
+
+    it('rejects a mint missing from the category allow-list', async () => {
+      try {
+        await createTestMarket(new BN(122_002), { category: RESTRICTED_CATEGORY });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('TokenNotAllowedForCategory');
+      }
+    });
+
+    it('accepts the allowed mint in the restricted category', async () => {
+      const { market } = await createTestMarket(new BN(122_003), {
+        category: RESTRICTED_CATEGORY,
+        tokenMint: otherMint,
+      });
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.tokenMint.toString()).to.equal(otherMint.toString());
+    });
+
+    it('allows any mint once the list is cleared', async () => {
+      await setAllowedMints(RESTRICTED_CATEGORY, []);
+      await createTestMarket(new BN(122_004), { category: RESTRICTED_CATEGORY });
+    });
+  });
+
+  describe('random resolution', () => {
+    const randomMarketId = new BN(125_001);
+    let marketPDA: PublicKey;
+    let randomness: Keypair;
+
+    async function setRandomnessProgram(key: PublicKey) {
+      await program.methods
+        .setRandomnessProgram(key)
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    async function commitRandomness(): Promise<Keypair> {
+      const account = Keypair.generate();
+      await randomnessProgram.methods
+        .commit()
+        .accounts({
+          randomness: account.publicKey,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([account])
+        .rpc();
+      return account;
+    }
+
+    function revealRandomness(account: PublicKey, value: number[]) {
+      return randomnessProgram.methods.reveal(value).accounts({ randomness: account }).rpc();
+    }
+
+    function requestRandomness(account: PublicKey) {
+      return program.methods
+        .requestRandomness()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: marketPDA,
+          randomnessAccount: account,
+          creator: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+    }
+
+    function fulfill(account: PublicKey) {
+      return program.methods
+        .fulfillRandomness()
+        .accounts({ protocolState: protocolStatePDA, market: marketPDA, randomnessAccount: account })
+        .rpc();
+    }
+
+    before(async () => {
+      await setRandomnessProgram(randomnessProgram.programId);
+
+      const now = Math.floor(Date.now() / 1000);
+      ({ market: marketPDA } = await createTestMarket(randomMarketId, {
+        bettingDeadline: new BN(now + 6),
+        options: { resolutionMode: { random: {} } },
+      }));
+      await placeTestBet(randomMarketId, await createFundedBettor(), 0);
+      await placeTestBet(randomMarketId, await createFundedBettor(), 1);
+      await sleep(8000);
+    });
+
+    after(async () => {
+      await setRandomnessProgram(PublicKey.default);
+    });
+
+    it('rejects resolving before randomness is requested', async () => {
+      try {
+        await fulfill(Keypair.generate().publicKey);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('RandomnessNotRequested');
+      }
+    });
+
+    it('rejects a randomness account from another program', async () => {
+      try {
+        await requestRandomness(creatorTokenAccount);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('InvalidRandomnessAccount');
+      }
+    });
+
+    it('rejects binding to a value that is already revealed', async () => {
+      const revealed = await commitRandomness();
+      await revealRandomness(revealed.publicKey, new Array(32).fill(7));
+      try {
+        await requestRandomness(revealed.publicKey);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('RandomnessAlreadyRevealed');
+      }
+    });
+
+    it('binds the request to a pending randomness account', async () => {
+      randomness = await commitRandomness();
+      await requestRandomness(randomness.publicKey);
+
+      const market = await program.account.market.fetch(marketPDA);
+      const account = await randomnessProgram.account.randomnessAccount.fetch(randomness.publicKey);
+      expect(market.randomnessAccount.toString()).to.equal(randomness.publicKey.toString());
+      expect(market.randomnessSeedSlot.toString()).to.equal(account.seedSlot.toString());
+      expect(market.randomnessRequestedAt.toNumber()).to.be.greaterThan(0);
+    });
+
+    it('rejects resolving before the value is revealed', async () => {
+      try {
+        await fulfill(randomness.publicKey);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('RandomnessNotRevealed');
+      }
+    });
+
+    it('rejects any randomness account but the bound one', async () => {
+      const other = await commitRandomness();
+      await revealRandomness(other.publicKey, new Array(32).fill(0));
+      try {
+        await fulfill(other.publicKey);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('RandomnessAccountMismatch');
+      }
+    });
+
+    it('resolves to the stake-weighted outcome selected by the revealed value', async () => {
+      const market = await program.account.market.fetch(marketPDA);
+      // Target the last unit of stake, which falls on the final outcome
+      const value = Array.from(market.totalPool.subn(1).toArrayLike(Buffer, 'le', 32));
+      await revealRandomness(randomness.publicKey, value);
+
+      await fulfill(randomness.publicKey);
+
+      const resolved = await program.account.market.fetch(marketPDA);
+      expect(resolved.status).to.deep.equal({ resolved: {} });
+      expect(resolved.winningOutcome).to.equal(1);
+      expect(resolved.randomness).to.deep.equal(value);
+    });
+  });
+
+  describe('fee floor on tiny bets', () => {
+    const tinyMarketId = new BN(126_001);
+    const TINY_BET = new BN(10);
+
+    before(async () => {
+      await createTestMarket(tinyMarketId, { betAmount: TINY_BET });
+    });
+
+    it('charges at least one unit for each configured fee', async () => {
+      const protocolState = await program.account.protocolState.fetch(protocolStatePDA);
+      const floored = (bps: number) =>
+        bps === 0 ? 0 : Math.max(1, Math.floor((TINY_BET.toNumber() * bps) / 10000));
+      const protocolFee = floored(protocolState.protocolFeeBps);
+      const creatorFee = floored(protocolState.creatorFeeBps);
+      const poolFee = floored(protocolState.poolFeeBps);
+
+      const treasuryBefore = await getAccount(provider.connection, treasuryTokenAccount);
+
+      const betPDA = await placeTestBet(tinyMarketId, await createFundedBettor(), 0);
+
+      const treasuryAfter = await getAccount(provider.connection, treasuryTokenAccount);
+      expect(Number(treasuryAfter.amount - treasuryBefore.amount)).to.equal(protocolFee);
+
+      const market = await program.account.market.fetch(getMarketPDAs(tinyMarketId).market);
+      expect(market.bonusPool.toNumber()).to.equal(poolFee);
+      expect(market.escrowedCreatorFees.toNumber()).to.equal(creatorFee);
+
+      const bet = await program.account.bet.fetch(betPDA);
+      expect(bet.poolAmount.toNumber()).to.equal(
+        TINY_BET.toNumber() - protocolFee - creatorFee - poolFee
+      );
+    });
+  });
+
+  describe('reorder_outcomes', () => {
+    const reorderMarketId = new BN(127_001);
+    let marketPDA: PublicKey;
+
+    function reorder(newOrder: number[]) {
+      return program.methods
+        .reorderOutcomes(Buffer.from(newOrder))
+        .accounts({ market: marketPDA, creator: creator.publicKey })
+        .signers([creator])
+        .rpc();
+    }
+
+    before(async () => {
+      ({ market: marketPDA } = await createTestMarket(reorderMarketId, {
+        outcomes: ['Home', 'Draw', 'Away'],
+      }));
+    });
+
+    it('rejects an order that is not a permutation', async () => {
+      try {
+        await reorder([0, 0, 2]);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('InvalidOutcomePermutation');
+      }
+    });
+
+    it('reorders outcomes before any bets', async () => {
+      await reorder([2, 0, 1]);
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.outcomes.map((o: any) => o.label)).to.deep.equal(['Away', 'Home', 'Draw']);
+    });
+
+    it('rejects reordering once a bet is placed', async () => {
+      await placeTestBet(reorderMarketId, await createFundedBettor(), 0);
+      try {
+        await reorder([1, 0, 2]);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('OutcomesLocked');
+      }
+    });
+
+    it('locks outcomes whichever outcome the first bet backs', async () => {
+      const { market } = await createTestMarket(new BN(127_002), { outcomes: ['Home', 'Draw', 'Away'] });
+      await placeTestBet(new BN(127_002), await createFundedBettor(), 2);
+      try {
+        await program.methods
+          .reorderOutcomes(Buffer.from([2, 1, 0]))
+          .accounts({ market, creator: creator.publicKey })
+          .signers([creator])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('OutcomesLocked');
+      }
+    });
+
+    it('locks outcomes once a native SOL bet is placed', async () => {
+      const { market } = await createTestMarket(new BN(127_003), {
+        betAmount: new BN(LAMPORTS_PER_SOL / 100),
+        tokenMint: NATIVE_MINT,
+        options: { nativeSol: true },
+      });
+      const bettor = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(bettor.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+      await program.methods
+        .placeBetSol(1)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market,
+          bet: getBetPDA(market, bettor.publicKey),
+          leaderboard: getLeaderboardPDA(market),
+          treasury: treasury.publicKey,
+          selfExclusion: getSelfExclusionPDA(bettor.publicKey),
+          bettor: bettor.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bettor])
+        .rpc();
+
+      try {
+        await program.methods
+          .reorderOutcomes(Buffer.from([1, 0]))
+          .accounts({ market, creator: creator.publicKey })
+          .signers([creator])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('OutcomesLocked');
+      }
+    });
+  });
+
+  describe('house markets', () => {
+    async function setRequireLicense(required: boolean) {
+      await program.methods
+        .setRequireLicense(required)
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    before(async () => {
+      await setRequireLicense(true);
+    });
+
+    after(async () => {
+      await setRequireLicense(false);
+    });
+
+    it('lets the protocol authority create a house market without a license', async () => {
+      const { market } = await createTestMarket(new BN(128_001), {
+        marketCreator: authority,
+        options: { isHouseMarket: true },
+      });
+
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.isHouseMarket).to.be.true;
+      expect(marketAccount.creatorFeeWallet.toString()).to.equal(treasury.publicKey.toString());
+    });
+
+    it('rejects house markets from anyone but the protocol authority', async () => {
+      try {
+        await createTestMarket(new BN(128_002), { options: { isHouseMarket: true } });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+
+    it('still requires a license for regular markets', async () => {
+      try {
+        await createTestMarket(new BN(128_003));
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('LicenseRequired');
+      }
+    });
+  });
+
+  describe('pool vault settlement', () => {
+    const settlementMarketId = new BN(129_001);
+    let pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    const winners: { keypair: Keypair; tokenAccount: PublicKey }[] = [];
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      pdas = await createTestMarket(settlementMarketId, { bettingDeadline: new BN(now + 6) });
+
+      for (let i = 0; i < 3; i++) {
+        const winner = await createFundedBettor();
+        await placeTestBet(settlementMarketId, winner, 0);
+        winners.push(winner);
+      }
+      await placeTestBet(settlementMarketId, await createFundedBettor(), 1);
+    });
+
+    it('pays the bonus share from the pool vault and drains it on the final claim', async () => {
+      await sleep(8000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const market = await program.account.market.fetch(pdas.market);
+      expect(market.bonusPool.toNumber()).to.be.greaterThan(0);
+
+      for (const winner of winners) {
+        await program.methods
+          .claimWinnings()
+          .accounts({
+            protocolState: protocolStatePDA,
+            market: pdas.market,
+            bet: getBetPDA(pdas.market, winner.keypair.publicKey),
+            marketVault: pdas.marketVault,
+            poolVault: pdas.poolVault,
+            claimerTokenAccount: winner.tokenAccount,
+            claimEscrow: null,
+            escrowVault: null,
+            memoProgram: null,
+            claimer: winner.keypair.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([winner.keypair])
+          .rpc();
+      }
+
+      const poolVault = await getAccount(provider.connection, pdas.poolVault);
+      expect(Number(poolVault.amount)).to.equal(0);
+
+      const settled = await program.account.market.fetch(pdas.market);
+      expect(settled.winningBetsClaimed).to.equal(winners.length);
+    });
+  });
+
+  describe('creator resolution toggle', () => {
+    const oracleOnlyMarketId = new BN(130_001);
+    let marketPDA: PublicKey;
+
+    async function setCreatorResolutionAllowed(allowed: boolean) {
+      await program.methods
+        .setCreatorResolutionAllowed(allowed)
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    function resolveAsCreator() {
+      return program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: marketPDA, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      ({ market: marketPDA } = await createTestMarket(oracleOnlyMarketId, {
+        bettingDeadline: new BN(now + 4),
+      }));
+      await sleep(6000);
+    });
+
+    after(async () => {
+      await setCreatorResolutionAllowed(true);
+    });
+
+    it('blocks creator resolution while disabled', async () => {
+      await setCreatorResolutionAllowed(false);
+      const protocolState = await program.account.protocolState.fetch(protocolStatePDA);
+      expect(protocolState.creatorResolutionAllowed).to.be.false;
+
+      try {
+        await resolveAsCreator();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('CreatorResolutionDisabled');
+      }
+    });
+
+    it('allows creator resolution again once re-enabled', async () => {
+      await setCreatorResolutionAllowed(true);
+      await resolveAsCreator();
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.status).to.deep.equal({ resolved: {} });
+    });
+  });
+
+  describe('native SOL markets', () => {
+    const solMarketId = new BN(131_001);
+    const SOL_BET = new BN(LAMPORTS_PER_SOL / 10);
+    let marketPDA: PublicKey;
+    let winner: Keypair;
+    let loser: Keypair;
+    let quitter: Keypair;
+
+    async function fundedWallet(): Promise<Keypair> {
+      const keypair = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(keypair.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+      return keypair;
+    }
+
+    function betSol(bettor: Keypair, outcomeIndex: number) {
+      return program.methods
+        .placeBetSol(outcomeIndex)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: marketPDA,
+          bet: getBetPDA(marketPDA, bettor.publicKey),
+          leaderboard: getLeaderboardPDA(marketPDA),
+          treasury: treasury.publicKey,
+          selfExclusion: getSelfExclusionPDA(bettor.publicKey),
+          bettor: bettor.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bettor])
+        .rpc();
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      ({ market: marketPDA } = await createTestMarket(solMarketId, {
+        betAmount: SOL_BET,
+        bettingDeadline: new BN(now + 8),
+        tokenMint: NATIVE_MINT,
+        options: { nativeSol: true },
+      }));
+      winner = await fundedWallet();
+      loser = await fundedWallet();
+      quitter = await fundedWallet();
+    });
+
+    it('rejects SPL bets on a native SOL market', async () => {
+      const bettor = await createFundedBettor();
+      try {
+        await placeTestBet(solMarketId, bettor, 0);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('WrongMarketDenomination');
+      }
+    });
+
+    it('holds SOL stakes, pool fees and escrowed creator fees in the market account', async () => {
+      const marketBefore = await provider.connection.getBalance(marketPDA);
+
+      await betSol(winner, 0);
+      await betSol(loser, 1);
+      await betSol(quitter, 1);
+
+      const market = await program.account.market.fetch(marketPDA);
+      const marketAfter = await provider.connection.getBalance(marketPDA);
+      expect(marketAfter - marketBefore).to.equal(
+        market.totalPool.toNumber() + market.bonusPool.toNumber() + market.escrowedCreatorFees.toNumber()
+      );
+    });
+
+    it('refunds the net stake and creator fee when a SOL bet is withdrawn', async () => {
+      const betPDA = getBetPDA(marketPDA, quitter.publicKey);
+      const bet = await program.account.bet.fetch(betPDA);
+      const balanceBefore = await provider.connection.getBalance(quitter.publicKey);
+
+      await program.methods
+        .withdrawBetSol()
+        .accounts({
+          market: marketPDA,
+          bet: betPDA,
+          leaderboard: getLeaderboardPDA(marketPDA),
+          bettor: quitter.publicKey,
+        })
+        .signers([quitter])
+        .rpc();
+
+      const balanceAfter = await provider.connection.getBalance(quitter.publicKey);
+      expect(balanceAfter - balanceBefore).to.equal(
+        bet.poolAmount.toNumber() + bet.creatorFee.toNumber()
+      );
+
+      const leaderboard = await program.account.marketLeaderboard.fetch(getLeaderboardPDA(marketPDA));
+      const ranked = leaderboard.entries.map((e: any) => e.bettor.toBase58());
+      expect(ranked).to.include(winner.publicKey.toBase58());
+      expect(ranked).to.not.include(quitter.publicKey.toBase58());
+    });
+
+    it('pays SOL winnings after resolution', async () => {
+      await sleep(10000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: marketPDA, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const market = await program.account.market.fetch(marketPDA);
+      const balanceBefore = await provider.connection.getBalance(winner.publicKey);
+
+      await program.methods
+        .claimWinningsSol()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: marketPDA,
+          bet: getBetPDA(marketPDA, winner.publicKey),
+          claimer: winner.publicKey,
+        })
+        .signers([winner])
+        .rpc();
+
+      const balanceAfter = await provider.connection.getBalance(winner.publicKey);
+      // Sole winner takes the whole pool and bonus pool
+      expect(balanceAfter - balanceBefore).to.equal(
+        market.totalPool.toNumber() + market.bonusPool.toNumber()
+      );
+    });
+  });
+
+  describe('bet amount bounds', () => {
+    const MIN_BET = new BN(1_000_000);
+    const MAX_BET = new BN(50_000_000);
+
+    async function setBounds(min: BN, max: BN) {
+      await program.methods
+        .setBetAmountBounds(min, max)
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    before(async () => {
+      await setBounds(MIN_BET, MAX_BET);
+    });
+
+    after(async () => {
+      await setBounds(new BN(0), new BN(0));
+    });
+
+    it('rejects a bet amount below the minimum', async () => {
+      try {
+        await createTestMarket(new BN(132_001), { betAmount: MIN_BET.subn(1) });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('InvalidBetAmount');
+      }
+    });
+
+    it('rejects a bet amount above the maximum', async () => {
+      try {
+        await createTestMarket(new BN(132_002), { betAmount: MAX_BET.addn(1) });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('InvalidBetAmount');
+      }
+    });
+
+    it('accepts bet amounts within range', async () => {
+      const { market } = await createTestMarket(new BN(132_003), { betAmount: MAX_BET });
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.betAmount.toString()).to.equal(MAX_BET.toString());
+    });
+  });
+
+  describe('license events', () => {
+    const licenseKey = Array.from(Keypair.generate().publicKey.toBytes());
+    let license: PublicKey;
+    let newHolder: Keypair;
+
+    async function sendAndParseEvents(builder: any): Promise<any[]> {
+      const sig = await builder.rpc({ commitment: 'confirmed' });
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl)
+      );
+      return Array.from(parser.parseLogs(tx!.meta!.logMessages!));
+    }
+
+    before(async () => {
+      license = getLicensePDA(licenseKey);
+      newHolder = Keypair.generate();
+    });
+
+    it('emits LicenseIssued', async () => {
+      const events = await sendAndParseEvents(
+        program.methods
+          .issueLicense(licenseKey, 1, [], [], 10, true, new BN(0))
+          .accounts({
+            protocolState: protocolStatePDA,
+            license,
+            holder: creator.publicKey,
+            authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+      );
+
+      const event = events.find((e) => e.name === 'LicenseIssued');
+      expect(event).to.not.be.undefined;
+      expect(event.data.licenseKey).to.deep.equal(licenseKey);
+      expect(event.data.holder.toString()).to.equal(creator.publicKey.toString());
+      expect(event.data.licenseType).to.deep.equal({ pro: {} });
+      expect(event.data.issuedBy.toString()).to.equal(authority.publicKey.toString());
+      expect(event.data.timestamp.toNumber()).to.be.greaterThan(0);
+    });
+
+    it('emits LicenseUpdated', async () => {
+      const events = await sendAndParseEvents(
+        program.methods
+          .updateLicense(25, null, null)
+          .accounts({
+            protocolState: protocolStatePDA,
+            license,
+            authority: authority.publicKey,
+          })
+          .signers([authority])
+      );
+
+      const event = events.find((e) => e.name === 'LicenseUpdated');
+      expect(event).to.not.be.undefined;
+      expect(event.data.holder.toString()).to.equal(creator.publicKey.toString());
+      expect(event.data.maxMarkets).to.equal(25);
+      expect(event.data.isActive).to.be.true;
+    });
+
+    it('emits LicenseRevoked, then LicenseUpdated on reactivation', async () => {
+      const accounts = {
+        protocolState: protocolStatePDA,
+        license,
+        authority: authority.publicKey,
+      };
+
+      const revoked = await sendAndParseEvents(
+        program.methods.revokeLicense().accounts(accounts).signers([authority])
+      );
+      const revokedEvent = revoked.find((e) => e.name === 'LicenseRevoked');
+      expect(revokedEvent).to.not.be.undefined;
+      expect(revokedEvent.data.licenseKey).to.deep.equal(licenseKey);
+      expect(revokedEvent.data.holder.toString()).to.equal(creator.publicKey.toString());
+
+      const activated = await sendAndParseEvents(
+        program.methods.activateLicense().accounts(accounts).signers([authority])
+      );
+      const activatedEvent = activated.find((e) => e.name === 'LicenseUpdated');
+      expect(activatedEvent).to.not.be.undefined;
+      expect(activatedEvent.data.isActive).to.be.true;
+    });
+
+    it('emits LicenseTransferred', async () => {
+      const events = await sendAndParseEvents(
+        program.methods
+          .transferLicense()
+          .accounts({
+            license,
+            newHolder: newHolder.publicKey,
+            currentHolder: creator.publicKey,
+          })
+          .signers([creator])
+      );
+
+      const event = events.find((e) => e.name === 'LicenseTransferred');
+      expect(event).to.not.be.undefined;
+      expect(event.data.previousHolder.toString()).to.equal(creator.publicKey.toString());
+      expect(event.data.holder.toString()).to.equal(newHolder.publicKey.toString());
+      expect(event.data.licenseType).to.deep.equal({ pro: {} });
+    });
+  });
+
+  describe('license sizing at capacity', () => {
+    const MAX_LICENSE_DOMAINS = 5;
+    const MAX_LICENSE_WALLETS = 10;
+    const MAX_DOMAIN_NAME_LEN = 64;
+
+    it('stores a license with domains and wallets filled to the cap', async () => {
+      const licenseKey = Array.from(Keypair.generate().publicKey.toBytes());
+      const license = getLicensePDA(licenseKey);
+      const domains = Array.from({ length: MAX_LICENSE_DOMAINS }, (_, i) =>
+        String(i).padEnd(MAX_DOMAIN_NAME_LEN, 'd')
+      );
+      const wallets = Array.from(
+        { length: MAX_LICENSE_WALLETS },
+        () => Keypair.generate().publicKey
+      );
+
+      await program.methods
+        .issueLicense(licenseKey, 2, domains, wallets, 0, false, new BN(0))
+        .accounts({
+          protocolState: protocolStatePDA,
+          license,
+          holder: creator.publicKey,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      const licenseAccount = await program.account.license.fetch(license);
+      expect(licenseAccount.allowedDomains).to.deep.equal(domains);
+      expect(licenseAccount.allowedWallets.map((w) => w.toString())).to.deep.equal(
+        wallets.map((w) => w.toString())
+      );
+
+      try {
+        await program.methods
+          .addAuthorizedWallet(Keypair.generate().publicKey)
+          .accounts({ license, holder: creator.publicKey })
+          .signers([creator])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('TooManyWallets');
+      }
+    });
+  });
+
+  describe('parlays', () => {
+    const PARLAY_SEED = Buffer.from('parlay');
+    const PARLAY_VAULT_SEED = Buffer.from('parlay_vault');
+    const PARLAY_BOOK_SEED = Buffer.from('parlay_book');
+    const marketAId = new BN(135_001);
+    const marketBId = new BN(135_002);
+    const PARLAY_STAKE = new BN(5_000_000);
+    let marketA: PublicKey;
+    let marketB: PublicKey;
+    let parlayVault: PublicKey;
+    let parlayBook: PublicKey;
+    let winner: { keypair: Keypair; tokenAccount: PublicKey };
+    let loser: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function getParlayPDA(bettor: PublicKey, parlayId: BN): PublicKey {
+      const [parlay] = PublicKey.findProgramAddressSync(
+        [PARLAY_SEED, bettor.toBuffer(), parlayId.toArrayLike(Buffer, 'le', 8)],
+        program.programId
+      );
+      return parlay;
+    }
+
+    async function createParlay(bettor: { keypair: Keypair; tokenAccount: PublicKey }) {
+      const bettorKey = bettor.keypair.publicKey;
+      await program.methods
+        .createParlay(new BN(1), PARLAY_STAKE)
+        .accounts({
+          protocolState: protocolStatePDA,
+          parlay: getParlayPDA(bettorKey, new BN(1)),
+          tokenMint,
+          parlayVault,
+          parlayBook,
+          bettorTokenAccount: bettor.tokenAccount,
+          selfExclusion: getSelfExclusionPDA(bettorKey),
+          bettor: bettorKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          // Leg markets are writable so they can count the parlay as open
+          [marketA, getBetPDA(marketA, bettorKey), marketB, getBetPDA(marketB, bettorKey)].map(
+            (pubkey, i) => ({ pubkey, isWritable: i % 2 === 0, isSigner: false })
+          )
+        )
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    async function claimParlay(bettor: { keypair: Keypair; tokenAccount: PublicKey }) {
+      const bettorKey = bettor.keypair.publicKey;
+      await program.methods
+        .claimParlay()
+        .accounts({
+          protocolState: protocolStatePDA,
+          parlay: getParlayPDA(bettorKey, new BN(1)),
+          parlayVault,
+          parlayBook,
+          bettorTokenAccount: bettor.tokenAccount,
+          bettor: bettorKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(
+          [marketA, marketB].map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      [parlayVault] = PublicKey.findProgramAddressSync(
+        [PARLAY_VAULT_SEED, tokenMint.toBuffer()],
+        program.programId
+      );
+      [parlayBook] = PublicKey.findProgramAddressSync(
+        [PARLAY_BOOK_SEED, tokenMint.toBuffer()],
+        program.programId
+      );
+
+      // Fund the reserve so it can cover the parlays' odds
+      const funder = await createFundedBettor();
+      await program.methods
+        .fundParlayReserve(new BN(100_000_000))
+        .accounts({
+          protocolState: protocolStatePDA,
+          tokenMint,
+          parlayVault,
+          parlayBook,
+          funderTokenAccount: funder.tokenAccount,
+          funder: funder.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([funder.keypair])
+        .rpc();
+
+      winner = await createFundedBettor();
+      loser = await createFundedBettor();
+      const other = await createFundedBettor();
+
+      const now = Math.floor(Date.now() / 1000);
+      ({ market: marketA } = await createTestMarket(marketAId, { bettingDeadline: new BN(now + 10) }));
+      ({ market: marketB } = await createTestMarket(marketBId, { bettingDeadline: new BN(now + 10) }));
+
+      // Winner picks outcome 0 on both markets; loser misses on market B
+      await placeTestBet(marketAId, winner, 0);
+      await placeTestBet(marketBId, winner, 0);
+      await placeTestBet(marketAId, loser, 0);
+      await placeTestBet(marketBId, loser, 1);
+      await placeTestBet(marketAId, other, 1);
+
+      await createParlay(winner);
+      await createParlay(loser);
+
+      await sleep(12000);
+      for (const market of [marketA, marketB]) {
+        await program.methods
+          .resolveMarket(0)
+          .accounts({ protocolState: protocolStatePDA, market, resolver: creator.publicKey })
+          .signers([creator])
+          .rpc();
+      }
+    });
+
+    it('pays a winning 2-leg parlay at the combined pool odds', async () => {
+      const parlay = await program.account.parlay.fetch(
+        getParlayPDA(winner.keypair.publicKey, new BN(1))
+      );
+      expect(parlay.legs.length).to.equal(2);
+
+      // Every leg bet was placed before the parlay, so the locked odds are the final ones
+      let expected = PARLAY_STAKE;
+      for (const market of [marketA, marketB]) {
+        const account = await program.account.market.fetch(market);
+        expected = expected.mul(account.totalPool).div(account.outcomes[0].totalAmount);
+      }
+      expect(parlay.payout.toString()).to.equal(expected.toString());
+
+      const before = await getAccount(provider.connection, winner.tokenAccount);
+      await claimParlay(winner);
+      const after = await getAccount(provider.connection, winner.tokenAccount);
+
+      expect((after.amount - before.amount).toString()).to.equal(expected.toString());
+      expect(expected.gt(PARLAY_STAKE)).to.be.true;
+    });
+
+    it('pays nothing when one leg loses', async () => {
+      try {
+        await claimParlay(loser);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('LostBet');
+      }
+    });
+
+    it('releases a lost parlay from the book once settled', async () => {
+      const parlayPDA = getParlayPDA(loser.keypair.publicKey, new BN(1));
+      const parlay = await program.account.parlay.fetch(parlayPDA);
+      const before = await program.account.parlayBook.fetch(parlayBook);
+
+      await program.methods
+        .settleLostParlay()
+        .accounts({ parlay: parlayPDA, parlayBook })
+        .remainingAccounts(
+          [marketA, marketB].map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .rpc();
+
+      const after = await program.account.parlayBook.fetch(parlayBook);
+      expect(before.liability.sub(after.liability).toString()).to.equal(parlay.payout.toString());
+      expect((await program.account.parlay.fetch(parlayPDA)).claimed).to.be.true;
+    });
+  });
+
+  describe('parlay reserve', () => {
+    const PARLAY_SEED = Buffer.from('parlay');
+    const PARLAY_VAULT_SEED = Buffer.from('parlay_vault');
+    const PARLAY_BOOK_SEED = Buffer.from('parlay_book');
+    const marketCId = new BN(135_003);
+    const marketDId = new BN(135_004);
+    const marketEId = new BN(135_005);
+    const PARLAY_STAKE = new BN(5_000_000);
+    let marketC: PublicKey;
+    let marketD: PublicKey;
+    let marketE: PublicKey;
+    let parlayVault: PublicKey;
+    let parlayBook: PublicKey;
+    let winner: { keypair: Keypair; tokenAccount: PublicKey };
+    let refundee: { keypair: Keypair; tokenAccount: PublicKey };
+    let liabilityBefore: BN;
+    let vaultBefore: bigint;
+
+    function getParlayPDA(bettor: PublicKey): PublicKey {
+      const [parlay] = PublicKey.findProgramAddressSync(
+        [PARLAY_SEED, bettor.toBuffer(), new BN(1).toArrayLike(Buffer, 'le', 8)],
+        program.programId
+      );
+      return parlay;
+    }
+
+    function createParlay(
+      bettor: { keypair: Keypair; tokenAccount: PublicKey },
+      markets: PublicKey[],
+      stake: BN
+    ) {
+      const bettorKey = bettor.keypair.publicKey;
+      return program.methods
+        .createParlay(new BN(1), stake)
+        .accounts({
+          protocolState: protocolStatePDA,
+          parlay: getParlayPDA(bettorKey),
+          tokenMint,
+          parlayVault,
+          parlayBook,
+          bettorTokenAccount: bettor.tokenAccount,
+          selfExclusion: getSelfExclusionPDA(bettorKey),
+          bettor: bettorKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          markets
+            .flatMap((market) => [market, getBetPDA(market, bettorKey)])
+            .map((pubkey, i) => ({ pubkey, isWritable: i % 2 === 0, isSigner: false }))
+        )
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    function claimParlay(bettor: { keypair: Keypair; tokenAccount: PublicKey }, markets: PublicKey[]) {
+      const bettorKey = bettor.keypair.publicKey;
+      return program.methods
+        .claimParlay()
+        .accounts({
+          protocolState: protocolStatePDA,
+          parlay: getParlayPDA(bettorKey),
+          parlayVault,
+          parlayBook,
+          bettorTokenAccount: bettor.tokenAccount,
+          bettor: bettorKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(
+          markets.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    async function vaultAmount(): Promise<bigint> {
+      return (await getAccount(provider.connection, parlayVault)).amount;
+    }
+
+    before(async () => {
+      [parlayVault] = PublicKey.findProgramAddressSync(
+        [PARLAY_VAULT_SEED, tokenMint.toBuffer()],
+        program.programId
+      );
+      [parlayBook] = PublicKey.findProgramAddressSync(
+        [PARLAY_BOOK_SEED, tokenMint.toBuffer()],
+        program.programId
+      );
+
+      winner = await createFundedBettor();
+      refundee = await createFundedBettor();
+      const other = await createFundedBettor();
+
+      const now = Math.floor(Date.now() / 1000);
+      ({ market: marketC } = await createTestMarket(marketCId, { bettingDeadline: new BN(now + 20) }));
+      ({ market: marketD } = await createTestMarket(marketDId, { bettingDeadline: new BN(now + 20) }));
+      ({ market: marketE } = await createTestMarket(marketEId, { bettingDeadline: new BN(now + 20) }));
+
+      await placeTestBet(marketCId, winner, 0);
+      await placeTestBet(marketDId, winner, 0);
+      await placeTestBet(marketCId, refundee, 0);
+      await placeTestBet(marketEId, refundee, 0);
+      await placeTestBet(marketCId, other, 1);
+      await placeTestBet(marketDId, other, 1);
+      await placeTestBet(marketEId, other, 1);
+
+      liabilityBefore = (await program.account.parlayBook.fetch(parlayBook)).liability;
+      vaultBefore = await vaultAmount();
+
+      // Both parlays draw on the same mint's vault
+      await createParlay(winner, [marketC, marketD], PARLAY_STAKE);
+      await createParlay(refundee, [marketC, marketE], PARLAY_STAKE);
+    });
+
+    it("books each parlay's payout at the odds when it was created", async () => {
+      let liabilityAdded = new BN(0);
+      for (const [bettor, markets] of [
+        [winner, [marketC, marketD]],
+        [refundee, [marketC, marketE]],
+      ] as const) {
+        const parlay = await program.account.parlay.fetch(getParlayPDA(bettor.keypair.publicKey));
+        let expected = PARLAY_STAKE;
+        for (const market of markets) {
+          const account = await program.account.market.fetch(market);
+          expected = expected.mul(account.totalPool).div(account.outcomes[0].totalAmount);
+        }
+        expect(parlay.payout.toString()).to.equal(expected.toString());
+        liabilityAdded = liabilityAdded.add(parlay.payout);
+      }
+
+      const book = await program.account.parlayBook.fetch(parlayBook);
+      expect(book.liability.sub(liabilityBefore).toString()).to.equal(liabilityAdded.toString());
+    });
+
+    it("rejects a parlay whose payout the reserve can't cover", async () => {
+      const book = await program.account.parlayBook.fetch(parlayBook);
+      const headroom = new BN((await vaultAmount()).toString()).sub(book.liability);
+      // Both legs pay 1.5x, so the payout outgrows what the stake brings in by more than the headroom
+      const stake = headroom.addn(1);
+      const bettor = await createFundedBettor(stake.add(BET_AMOUNT.muln(2)).toNumber());
+      await placeTestBet(marketDId, bettor, 0);
+      await placeTestBet(marketEId, bettor, 0);
+
+      try {
+        await createParlay(bettor, [marketD, marketE], stake);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('ParlayReserveInsufficient');
+      }
+    });
+
+    it('rejects a parlay from a self-excluded wallet', async () => {
+      const bettor = await createFundedBettor();
+      await placeTestBet(marketDId, bettor, 0);
+      await placeTestBet(marketEId, bettor, 0);
+      await program.methods
+        .selfExclude(new BN(Math.floor(Date.now() / 1000) + 3600))
+        .accounts({
+          selfExclusion: getSelfExclusionPDA(bettor.keypair.publicKey),
+          wallet: bettor.keypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+
+      try {
+        await createParlay(bettor, [marketD, marketE], PARLAY_STAKE);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('SelfExcluded');
+      }
+    });
+
+    function closeLegMarket(marketId: BN) {
+      const pdas = getMarketPDAs(marketId);
+      return program.methods
+        .closeMarket()
+        .accounts({
+          market: pdas.market,
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          leaderboard: pdas.leaderboard,
+          creatorTokenAccount: creatorTokenAccount,
+          creator: creator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([creator])
+        .rpc();
+    }
+
+    it('keeps a leg market from closing while a parlay still reads it', async () => {
+      await sleep(20000);
+      for (const market of [marketC, marketD]) {
+        await program.methods
+          .resolveMarket(0)
+          .accounts({ protocolState: protocolStatePDA, market, resolver: creator.publicKey })
+          .signers([creator])
+          .rpc();
+      }
+      await program.methods
+        .cancelMarket()
+        .accounts({ protocolState: protocolStatePDA, market: marketE, authority: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      // Settle market D's own claims so only the winning parlay holds it open
+      const pdas = getMarketPDAs(marketDId);
+      await program.methods
+        .claimCreatorFees()
+        .accounts({
+          market: marketD,
+          marketVault: pdas.marketVault,
+          creatorTokenAccount: creatorTokenAccount,
+          creator: creator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([creator])
+        .rpc();
+      await program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: marketD,
+          bet: getBetPDA(marketD, winner.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: winner.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([winner.keypair])
+        .rpc();
+
+      const market = await program.account.market.fetch(marketD);
+      expect(market.openParlays).to.equal(1);
+      try {
+        await closeLegMarket(marketDId);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('MarketHasUnclaimedFunds');
+      }
+    });
+
+    it('pays the winner its booked payout and refunds the cancelled parlay', async () => {
+      const winning = await program.account.parlay.fetch(getParlayPDA(winner.keypair.publicKey));
+
+      const winnerBefore = await getAccount(provider.connection, winner.tokenAccount);
+      await claimParlay(winner, [marketC, marketD]);
+      const winnerAfter = await getAccount(provider.connection, winner.tokenAccount);
+      expect(Number(winnerAfter.amount - winnerBefore.amount)).to.equal(winning.payout.toNumber());
+
+      const refundeeBefore = await getAccount(provider.connection, refundee.tokenAccount);
+      await claimParlay(refundee, [marketC, marketE]);
+      const refundeeAfter = await getAccount(provider.connection, refundee.tokenAccount);
+      expect(Number(refundeeAfter.amount - refundeeBefore.amount)).to.equal(PARLAY_STAKE.toNumber());
+
+      // The reserve paid the winner's odds; the book is back where it started
+      const book = await program.account.parlayBook.fetch(parlayBook);
+      expect(book.liability.toString()).to.equal(liabilityBefore.toString());
+      expect(Number(vaultBefore - (await vaultAmount()))).to.equal(
+        winning.payout.sub(PARLAY_STAKE).toNumber()
+      );
+    });
+
+    it('closes the leg market once its parlays have settled', async () => {
+      for (const market of [marketC, marketD, marketE]) {
+        expect((await program.account.market.fetch(market)).openParlays).to.equal(0);
+      }
+      await closeLegMarket(marketDId);
+      expect(await provider.connection.getAccountInfo(marketD)).to.equal(null);
+    });
+  });
+
+  describe('duplicate oracle registration', () => {
+    const oracleId = 136_001;
+    const categories = new Array(12).fill(false);
+    categories[3] = true; // Crypto
+
+    async function registerOracle(name: string, oracleAuthority: PublicKey) {
+      await program.methods
+        .registerOracle(oracleId, name, categories, 'https://example.com')
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: getOraclePDA(oracleId),
+          oracleAuthority,
+          bondMint: null,
+          bondVault: null,
+          oracleBondSource: null,
+          authority: authority.publicKey,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    it('rejects registering the same oracle_id twice', async () => {
+      const firstAuthority = Keypair.generate().publicKey;
+      await registerOracle('First Oracle', firstAuthority);
+
+      try {
+        await registerOracle('Second Oracle', Keypair.generate().publicKey);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('OracleAlreadyExists');
+      }
+
+      const oracle = await program.account.oracle.fetch(getOraclePDA(oracleId));
+      expect(oracle.name).to.equal('First Oracle');
+      expect(oracle.authority.toString()).to.equal(firstAuthority.toString());
+    });
+  });
+
+  describe('per-category claim delay', () => {
+    const SPORTS = 1;
+    const CRYPTO = 3;
+    const winner = { sports: null as any, crypto: null as any };
+    let sportsPdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let cryptoPdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+
+    async function setClaimDelay(category: number, delay: number) {
+      await program.methods
+        .setClaimDelay(category, new BN(delay))
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    async function claim(
+      pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey },
+      bettor: { keypair: Keypair; tokenAccount: PublicKey }
+    ) {
+      await program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: bettor.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      await setClaimDelay(SPORTS, 0);
+      await setClaimDelay(CRYPTO, 3600);
+
+      winner.sports = await createFundedBettor();
+      winner.crypto = await createFundedBettor();
+
+      const now = Math.floor(Date.now() / 1000);
+      sportsPdas = await createTestMarket(new BN(137_001), {
+        category: SPORTS,
+        bettingDeadline: new BN(now + 6),
+      });
+      cryptoPdas = await createTestMarket(new BN(137_002), {
+        category: CRYPTO,
+        bettingDeadline: new BN(now + 6),
+      });
+      await placeTestBet(new BN(137_001), winner.sports, 0);
+      await placeTestBet(new BN(137_002), winner.crypto, 0);
+
+      await sleep(8000);
+      for (const market of [sportsPdas.market, cryptoPdas.market]) {
+        await program.methods
+          .resolveMarket(0)
+          .accounts({ protocolState: protocolStatePDA, market, resolver: creator.publicKey })
+          .signers([creator])
+          .rpc();
+      }
+    });
+
+    after(async () => {
+      await setClaimDelay(CRYPTO, 0);
+    });
+
+    it('stores the delay per category', async () => {
+      const state = await program.account.protocolState.fetch(protocolStatePDA);
+      expect(state.claimDelayPerCategory[SPORTS].toNumber()).to.equal(0);
+      expect(state.claimDelayPerCategory[CRYPTO].toNumber()).to.equal(3600);
+    });
+
+    it('lets a category without a delay claim immediately', async () => {
+      await claim(sportsPdas, winner.sports);
+      const bet = await program.account.bet.fetch(
+        getBetPDA(sportsPdas.market, winner.sports.keypair.publicKey)
+      );
+      expect(bet.betState).to.deep.equal({ claimedWin: {} });
+    });
+
+    it("blocks claims until the category's delay has elapsed", async () => {
+      try {
+        await claim(cryptoPdas, winner.crypto);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('ClaimDelayNotElapsed');
+      }
+    });
+
+    it('rejects a negative delay', async () => {
+      try {
+        await setClaimDelay(SPORTS, -1);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('InvalidDeadline');
+      }
+    });
+  });
+
+  describe('bet memos', () => {
+    const memoMarketId = new BN(139_001);
+
+    before(async () => {
+      await createTestMarket(memoMarketId);
+    });
+
+    it('stores a memo on the bet', async () => {
+      const bettor = await createFundedBettor();
+      const memo = 'Polls have been trending this way all month';
+      const bet = await placeTestBet(memoMarketId, bettor, 0, 0, memo);
+
+      const betAccount = await program.account.bet.fetch(bet);
+      expect(betAccount.memo).to.equal(memo);
+    });
+
+    it('places a bet without a memo in a smaller account', async () => {
+      const withMemo = await createFundedBettor();
+      const withoutMemo = await createFundedBettor();
+      const memo = 'x'.repeat(140);
+      const memoBet = await placeTestBet(memoMarketId, withMemo, 0, 0, memo);
+      const plainBet = await placeTestBet(memoMarketId, withoutMemo, 1);
+
+      const plainAccount = await program.account.bet.fetch(plainBet);
+      expect(plainAccount.memo).to.equal('');
+
+      const memoInfo = await provider.connection.getAccountInfo(memoBet);
+      const plainInfo = await provider.connection.getAccountInfo(plainBet);
+      expect(memoInfo!.data.length - plainInfo!.data.length).to.equal(memo.length);
+    });
+
+    it('rejects a memo over 140 bytes', async () => {
+      const bettor = await createFundedBettor();
+      try {
+        await placeTestBet(memoMarketId, bettor, 0, 0, 'x'.repeat(141));
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('MemoTooLong');
+      }
+    });
+  });
+
+  describe('cancel reasons', () => {
+    async function cancel(market: PublicKey, signer: Keypair) {
+      await program.methods
+        .cancelMarket()
+        .accounts({ protocolState: protocolStatePDA, market, authority: signer.publicKey })
+        .signers([signer])
+        .rpc();
+    }
+
+    it('records CreatorCancelled when the creator cancels', async () => {
+      const { market } = await createTestMarket(new BN(140_001));
+      await cancel(market, creator);
+
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.cancelReason).to.deep.equal({ creatorCancelled: {} });
+    });
+
+    it('records AdminCancelled when the protocol authority cancels', async () => {
+      const { market } = await createTestMarket(new BN(140_002));
+      await cancel(market, authority);
+
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.status).to.deep.equal({ cancelled: {} });
+      expect(marketAccount.cancelReason).to.deep.equal({ adminCancelled: {} });
+    });
+
+    it('rejects cancellation by anyone else', async () => {
+      const { market } = await createTestMarket(new BN(140_003));
+      try {
+        await cancel(market, bettor1);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('Unauthorized');
+      }
+
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.cancelReason).to.deep.equal({ none: {} });
+    });
+
+    it('records InsufficientParticipation when resolution voids the market', async () => {
+      const bettor = await createFundedBettor();
+      const now = Math.floor(Date.now() / 1000);
+      const { market } = await createTestMarket(new BN(140_004), {
+        bettingDeadline: new BN(now + 5),
+      });
+      await placeTestBet(new BN(140_004), bettor, 1);
+
+      await sleep(7000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.status).to.deep.equal({ cancelled: {} });
+      expect(marketAccount.cancelReason).to.deep.equal({ insufficientParticipation: {} });
+    });
+
+    it('records AdminCancelled when the protocol authority cancels its own market', async () => {
+      const { market } = await createTestMarket(new BN(140_005), { marketCreator: authority });
+      await cancel(market, authority);
+
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.cancelReason).to.deep.equal({ adminCancelled: {} });
+    });
+  });
+
+  describe('market expiry', () => {
+    const oracleId = 140_001;
+    const oracleAuthority = Keypair.generate();
+    let expiring: PublicKey;
+    let oracleMarket: PublicKey;
+
+    function expire(market: PublicKey) {
+      return program.methods.expireMarket().accounts({ market }).rpc();
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const deadlines = {
+        bettingDeadline: new BN(now + 4),
+        resolutionDeadline: new BN(now + 6),
+      };
+      ({ market: expiring } = await createTestMarket(new BN(140_006), deadlines));
+      ({ market: oracleMarket } = await createTestMarket(new BN(140_007), deadlines));
+
+      await program.methods
+        .registerOracle(oracleId, 'Silent Oracle', new Array(12).fill(true), 'https://example.com')
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: getOraclePDA(oracleId),
+          oracleAuthority: oracleAuthority.publicKey,
+          bondMint: null,
+          bondVault: null,
+          oracleBondSource: null,
+          authority: authority.publicKey,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      await program.methods
+        .assignOracle()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: oracleMarket,
+          oracle: getOraclePDA(oracleId),
+          creator: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+    });
+
+    it('rejects expiring a market before its resolution deadline', async () => {
+      try {
+        await expire(expiring);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('ResolutionDeadlineNotReached');
+      }
+    });
+
+    it('records Expired for an unresolved market past its resolution deadline', async () => {
+      await sleep(8000);
+      await expire(expiring);
+
+      const marketAccount = await program.account.market.fetch(expiring);
+      expect(marketAccount.status).to.deep.equal({ cancelled: {} });
+      expect(marketAccount.cancelReason).to.deep.equal({ expired: {} });
+    });
+
+    it('records OracleFailure when the assigned oracle never resolved', async () => {
+      await expire(oracleMarket);
+
+      const marketAccount = await program.account.market.fetch(oracleMarket);
+      expect(marketAccount.status).to.deep.equal({ cancelled: {} });
+      expect(marketAccount.cancelReason).to.deep.equal({ oracleFailure: {} });
+    });
+  });
+
+  describe('market processing lock', () => {
+    const lockMarketId = new BN(141_001);
+
+    it('is clear on creation and released after a claim', async () => {
+      const winner = await createFundedBettor();
+      const loser = await createFundedBettor();
+      const now = Math.floor(Date.now() / 1000);
+      const pdas = await createTestMarket(lockMarketId, { bettingDeadline: new BN(now + 5) });
+
+      let market = await program.account.market.fetch(pdas.market);
+      expect(market.processing).to.be.false;
+
+      await placeTestBet(lockMarketId, winner, 0);
+      await placeTestBet(lockMarketId, loser, 1);
+
+      await sleep(7000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      await program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, winner.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: winner.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([winner.keypair])
+        .rpc();
+
+      market = await program.account.market.fetch(pdas.market);
+      expect(market.processing).to.be.false;
+      expect(market.winningBetsClaimed).to.equal(1);
+    });
+  });
+
+  describe('multi-collateral markets', () => {
+    const COLLATERAL_VAULT_SEED = Buffer.from('collateral_vault');
+    const PRICE_SCALE_ONE = 1_000_000;
+    const multiMarketId = new BN(142_001);
+    const unevenMarketId = new BN(142_002);
+    let mintB: PublicKey;
+    let market: PublicKey;
+    let unevenMarket: PublicKey;
+    // Winners are paid from every mint's vault, so each bettor also holds an account in the other mint
+    type CollateralBettor = { keypair: Keypair; tokenAccount: PublicKey; otherAccount: PublicKey };
+    const bettors: Record<string, CollateralBettor> = {};
+
+    function getCollateralVaultPDA(mint: PublicKey, target: PublicKey = market): PublicKey {
+      const [vault] = PublicKey.findProgramAddressSync(
+        [COLLATERAL_VAULT_SEED, target.toBuffer(), mint.toBuffer()],
+        program.programId
+      );
+      return vault;
+    }
+
+    async function createMintABettor(): Promise<CollateralBettor> {
+      const bettor = await createFundedBettor();
+      const otherAccount = await createAccount(provider.connection, authority, mintB, bettor.keypair.publicKey);
+      return { ...bettor, otherAccount };
+    }
+
+    async function createMintBBettor(): Promise<CollateralBettor> {
+      const bettor = await createFundedBettor(0);
+      const tokenAccount = await createAccount(
+        provider.connection,
+        authority,
+        mintB,
+        bettor.keypair.publicKey
+      );
+      await mintTo(provider.connection, authority, mintB, tokenAccount, authority, 100_000_000);
+      return { keypair: bettor.keypair, tokenAccount, otherAccount: bettor.tokenAccount };
+    }
+
+    async function placeCollateralBet(
+      bettor: CollateralBettor,
+      mint: PublicKey,
+      outcomeIndex: number,
+      feeAccounts: { treasury: PublicKey; creator: PublicKey },
+      target: PublicKey = market
+    ) {
+      await program.methods
+        .placeCollateralBet(outcomeIndex)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: target,
+          bet: getBetPDA(target, bettor.keypair.publicKey),
+          collateralMint: mint,
+          collateralVault: getCollateralVaultPDA(mint, target),
+          leaderboard: getLeaderboardPDA(target),
+          bettorTokenAccount: bettor.tokenAccount,
+          treasuryTokenAccount: feeAccounts.treasury,
+          creatorTokenAccount: feeAccounts.creator,
+          selfExclusion: getSelfExclusionPDA(bettor.keypair.publicKey),
+          bettor: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    async function claimCollateral(bettor: CollateralBettor, mint: PublicKey, target: PublicKey = market) {
+      const otherMint = mint.equals(tokenMint) ? mintB : tokenMint;
+      await program.methods
+        .claimCollateral()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: target,
+          bet: getBetPDA(target, bettor.keypair.publicKey),
+          collateralVault: getCollateralVaultPDA(mint, target),
+          claimerTokenAccount: bettor.tokenAccount,
+          claimer: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          { pubkey: getCollateralVaultPDA(otherMint, target), isWritable: true, isSigner: false },
+          { pubkey: bettor.otherAccount, isWritable: true, isSigner: false },
+        ])
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    async function resolveToFirstOutcome(target: PublicKey) {
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: target, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+    }
+
+    async function balance(account: PublicKey): Promise<BN> {
+      return new BN((await getAccount(provider.connection, account)).amount.toString());
+    }
+
+    // A winner who isn't last takes their share of what is still owed out of each vault
+    async function expectedShares(target: PublicKey, bettor: CollateralBettor) {
+      const marketAccount = await program.account.market.fetch(target);
+      const winningTotal = marketAccount.outcomes[0].totalAmount;
+      const bet = await program.account.bet.fetch(getBetPDA(target, bettor.keypair.publicKey));
+      const units = bet.poolAmount
+        .mul(marketAccount.totalPool)
+        .div(winningTotal)
+        .add(bet.poolAmount.mul(marketAccount.bonusPool).div(winningTotal));
+      const owed = marketAccount.totalPool
+        .add(marketAccount.bonusPool)
+        .sub(marketAccount.poolPaid)
+        .sub(marketAccount.bonusPaid);
+      const vaultA = await balance(getCollateralVaultPDA(tokenMint, target));
+      const vaultB = await balance(getCollateralVaultPDA(mintB, target));
+      return { a: vaultA.mul(units).div(owed), b: vaultB.mul(units).div(owed) };
+    }
+
+    before(async () => {
+      mintB = await createMint(provider.connection, authority, authority.publicKey, null, 6);
+      const mintBFeeAccounts = {
+        treasury: await createAccount(provider.connection, authority, mintB, treasury.publicKey),
+        creator: await createAccount(provider.connection, authority, mintB, creator.publicKey),
+      };
+      const mintAFeeAccounts = { treasury: treasuryTokenAccount, creator: creatorTokenAccount };
+
+      bettors.winnerA = await createMintABettor();
+      bettors.loserA = await createMintABettor();
+      bettors.winnerB = await createMintBBettor();
+      bettors.loserB = await createMintBBettor();
+      bettors.firstWinner = await createMintABettor();
+      bettors.secondWinner = await createMintABettor();
+      bettors.onlyLoser = await createMintBBettor();
+
+      // One mint B token is worth two common units
+      const now = Math.floor(Date.now() / 1000);
+      const options = {
+        multiCollateral: true,
+        collateralMints: [
+          { mint: tokenMint, priceScale: new BN(PRICE_SCALE_ONE) },
+          { mint: mintB, priceScale: new BN(2 * PRICE_SCALE_ONE) },
+        ],
+      };
+      ({ market } = await createTestMarket(multiMarketId, { bettingDeadline: new BN(now + 8), options }));
+      ({ market: unevenMarket } = await createTestMarket(unevenMarketId, {
+        bettingDeadline: new BN(now + 8),
+        options,
+      }));
+
+      await placeCollateralBet(bettors.winnerA, tokenMint, 0, mintAFeeAccounts);
+      await placeCollateralBet(bettors.loserA, tokenMint, 1, mintAFeeAccounts);
+      await placeCollateralBet(bettors.winnerB, mintB, 0, mintBFeeAccounts);
+      await placeCollateralBet(bettors.loserB, mintB, 1, mintBFeeAccounts);
+
+      // Every winner bets in mint A and every loser in mint B
+      await placeCollateralBet(bettors.firstWinner, tokenMint, 0, mintAFeeAccounts, unevenMarket);
+      await placeCollateralBet(bettors.secondWinner, tokenMint, 0, mintAFeeAccounts, unevenMarket);
+      await placeCollateralBet(bettors.onlyLoser, mintB, 1, mintBFeeAccounts, unevenMarket);
+    });
+
+    it('normalizes stakes from both mints into common units', async () => {
+      const betA = await program.account.bet.fetch(getBetPDA(market, bettors.winnerA.keypair.publicKey));
+      const betB = await program.account.bet.fetch(getBetPDA(market, bettors.winnerB.keypair.publicKey));
+
+      expect(betA.collateralMint.toString()).to.equal(tokenMint.toString());
+      expect(betB.collateralMint.toString()).to.equal(mintB.toString());
+      // Mint B bets pay half as many tokens for the same common-unit stake
+      expect(betB.collateralAmount.toNumber()).to.equal(betA.collateralAmount.toNumber() / 2);
+      expect(betB.poolAmount.toString()).to.equal(betA.poolAmount.toString());
+
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.totalPool.toString()).to.equal(betA.poolAmount.muln(4).toString());
+    });
+
+    it('rejects the single-mint bet path', async () => {
+      try {
+        await placeTestBet(multiMarketId, await createFundedBettor(), 0);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('WrongMarketDenomination');
+      }
+    });
+
+    it("pays each winner the same share of every mint's vault", async () => {
+      await sleep(10000);
+      await resolveToFirstOutcome(market);
+
+      for (const [name, mint] of [
+        ['winnerA', tokenMint],
+        ['winnerB', mintB],
+      ] as [string, PublicKey][]) {
+        const bettor = bettors[name];
+        const mintAAccount = mint.equals(tokenMint) ? bettor.tokenAccount : bettor.otherAccount;
+        const mintBAccount = mint.equals(tokenMint) ? bettor.otherAccount : bettor.tokenAccount;
+        const vaultsBefore = [
+          await balance(getCollateralVaultPDA(tokenMint)),
+          await balance(getCollateralVaultPDA(mintB)),
+        ];
+        const shares = await expectedShares(market, bettor);
+        const before = [await balance(mintAAccount), await balance(mintBAccount)];
+
+        await claimCollateral(bettor, mint);
+
+        const received = [
+          (await balance(mintAAccount)).sub(before[0]),
+          (await balance(mintBAccount)).sub(before[1]),
+        ];
+        if (name === 'winnerA') {
+          expect(received[0].toString()).to.equal(shares.a.toString());
+          expect(received[1].toString()).to.equal(shares.b.toString());
+        } else {
+          // The last winner sweeps both vaults
+          expect(received[0].toString()).to.equal(vaultsBefore[0].toString());
+          expect(received[1].toString()).to.equal(vaultsBefore[1].toString());
+        }
+      }
+
+      for (const mint of [tokenMint, mintB]) {
+        expect((await balance(getCollateralVaultPDA(mint))).toNumber()).to.equal(0);
+      }
+    });
+
+    it("pays mint A winners out of the mint B losers' vault", async () => {
+      await resolveToFirstOutcome(unevenMarket);
+
+      const first = bettors.firstWinner;
+      const shares = await expectedShares(unevenMarket, first);
+      const before = [await balance(first.tokenAccount), await balance(first.otherAccount)];
+      await claimCollateral(first, tokenMint, unevenMarket);
+      expect((await balance(first.tokenAccount)).sub(before[0]).toString()).to.equal(shares.a.toString());
+      expect((await balance(first.otherAccount)).sub(before[1]).toString()).to.equal(shares.b.toString());
+      expect(shares.b.toNumber()).to.be.greaterThan(0);
+
+      const second = bettors.secondWinner;
+      const secondB = await balance(second.otherAccount);
+      await claimCollateral(second, tokenMint, unevenMarket);
+      expect((await balance(second.otherAccount)).gt(secondB)).to.equal(true);
+
+      for (const mint of [tokenMint, mintB]) {
+        expect((await balance(getCollateralVaultPDA(mint, unevenMarket))).toNumber()).to.equal(0);
+      }
+    });
+  });
+
+  describe('commit-reveal resolution', () => {
+    const revealMarketId = new BN(143_001);
+    const committedOutcome = 1;
+    const salt = Array.from(Keypair.generate().publicKey.toBytes());
+    let market: PublicKey;
+
+    function commitment(outcome: number, saltBytes: number[]): number[] {
+      return Array.from(
+        createHash('sha256').update(Buffer.from([outcome, ...saltBytes])).digest()
+      );
+    }
+
+    async function reveal(outcome: number, saltBytes: number[]) {
+      await program.methods
+        .revealResolution(outcome, saltBytes)
+        .accounts({ protocolState: protocolStatePDA, market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      ({ market } = await createTestMarket(revealMarketId, {
+        bettingDeadline: new BN(now + 4),
+        options: {
+          resolutionMode: { commitReveal: {} },
+          resultCommitment: commitment(committedOutcome, salt),
+        },
+      }));
+    });
+
+    it('rejects a commit-reveal market without a commitment', async () => {
+      try {
+        await createTestMarket(new BN(143_002), { options: { resolutionMode: { commitReveal: {} } } });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('InvalidResultCommitment');
+      }
+    });
+
+    it('blocks standard creator resolution', async () => {
+      await sleep(6000);
+      try {
+        await program.methods
+          .resolveMarket(0)
+          .accounts({ protocolState: protocolStatePDA, market, resolver: creator.publicKey })
+          .signers([creator])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('WrongResolutionMode');
+      }
+    });
+
+    it('rejects a reveal with the wrong salt', async () => {
+      const wrongSalt = [...salt];
+      wrongSalt[0] ^= 0xff;
+      try {
+        await reveal(committedOutcome, wrongSalt);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('CommitmentMismatch');
+      }
+    });
+
+    it('rejects revealing a different outcome', async () => {
+      try {
+        await reveal(0, salt);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('CommitmentMismatch');
+      }
+    });
+
+    it('resolves on a valid reveal', async () => {
+      await reveal(committedOutcome, salt);
+
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.status).to.deep.equal({ resolved: {} });
+      expect(marketAccount.winningOutcome).to.equal(committedOutcome);
+    });
+  });
+
+  describe('max future deadline', () => {
+    const ONE_YEAR = 365 * 86400;
+
+    async function setMaxFutureDeadline(secs: number) {
+      await program.methods
+        .setMaxFutureDeadline(new BN(secs))
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    before(async () => {
+      await setMaxFutureDeadline(ONE_YEAR);
+    });
+
+    after(async () => {
+      await setMaxFutureDeadline(0);
+    });
+
+    it('rejects a betting deadline beyond the configured cap', async () => {
+      const now = Math.floor(Date.now() / 1000);
+      try {
+        // A fat-fingered deadline roughly a century out
+        await createTestMarket(new BN(144_001), { bettingDeadline: new BN(now + 100 * ONE_YEAR) });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('InvalidDeadline');
+      }
+    });
+
+    it('accepts a betting deadline within the cap', async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const { market } = await createTestMarket(new BN(144_002), {
+        bettingDeadline: new BN(now + ONE_YEAR - 3600),
+      });
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.status).to.deep.equal({ open: {} });
+    });
+
+    it('leaves deadlines unbounded when the cap is zero', async () => {
+      await setMaxFutureDeadline(0);
+      const now = Math.floor(Date.now() / 1000);
+      const { market } = await createTestMarket(new BN(144_003), {
+        bettingDeadline: new BN(now + 100 * ONE_YEAR),
+      });
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.status).to.deep.equal({ open: {} });
+    });
+  });
+
+  describe('compact_market', () => {
+    const MARKET_RESERVED_LEN = 32;
+
+    it('shrinks the market account and refunds the freed rent to the creator', async () => {
+      const { market } = await createTestMarket(new BN(145_001));
+      const before = await provider.connection.getAccountInfo(market);
+      const creatorBefore = await provider.connection.getBalance(creator.publicKey);
+
+      await program.methods
+        .compactMarket()
+        .accounts({
+          market,
+          creator: creator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+
+      const after = await provider.connection.getAccountInfo(market);
+      const creatorAfter = await provider.connection.getBalance(creator.publicKey);
+      expect(after!.data.length).to.equal(before!.data.length - MARKET_RESERVED_LEN);
+
+      const expectedRefund =
+        before!.lamports -
+        (await provider.connection.getMinimumBalanceForRentExemption(after!.data.length));
+      expect(expectedRefund).to.be.greaterThan(0);
+      expect(before!.lamports - after!.lamports).to.equal(expectedRefund);
+      expect(creatorAfter - creatorBefore).to.equal(expectedRefund);
+
+      // The compacted market still deserializes and behaves normally
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.status).to.deep.equal({ open: {} });
+    });
+
+    it('only lets the creator compact a market', async () => {
+      const { market } = await createTestMarket(new BN(145_002));
+      try {
+        await program.methods
+          .compactMarket()
+          .accounts({
+            market,
+            creator: bettor1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([bettor1])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+  });
+
+  describe('require_ata claims', () => {
+    const ataMarketId = new BN(146_001);
+    let pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let winner: { keypair: Keypair; tokenAccount: PublicKey };
+
+    async function claimTo(tokenAccount: PublicKey) {
+      await program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, winner.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([winner.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      // createFundedBettor funds the bettor's associated token account
+      winner = await createFundedBettor();
+      const loser = await createFundedBettor();
+
+      const now = Math.floor(Date.now() / 1000);
+      pdas = await createTestMarket(ataMarketId, {
+        bettingDeadline: new BN(now + 5),
+        options: { requireAta: true },
+      });
+      await placeTestBet(ataMarketId, winner, 0);
+      await placeTestBet(ataMarketId, loser, 1);
+
+      await sleep(7000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+    });
+
+    it('rejects a claim into a token account that is not the ATA', async () => {
+      const otherAccount = await createAccount(
+        provider.connection,
+        authority,
+        tokenMint,
+        winner.keypair.publicKey,
+        Keypair.generate()
+      );
+      try {
+        await claimTo(otherAccount);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal('ClaimAccountNotAta');
+      }
+    });
+
+    it('pays a claim into the ATA', async () => {
+      const ata = await getAssociatedTokenAddress(tokenMint, winner.keypair.publicKey);
+      expect(ata.toString()).to.equal(winner.tokenAccount.toString());
+
+      await claimTo(ata);
+      const bet = await program.account.bet.fetch(getBetPDA(pdas.market, winner.keypair.publicKey));
+      expect(bet.betState).to.deep.equal({ claimedWin: {} });
+    });
+  });
+
+  describe('license expiry enforcement', () => {
+    const holder = Keypair.generate();
+    let expiredLicense: PublicKey;
+    let revokedLicense: PublicKey;
+
+    async function issueLicense(expiresAt: BN): Promise<PublicKey> {
+      const licenseKey = Array.from(Keypair.generate().publicKey.toBytes());
+      const license = getLicensePDA(licenseKey);
+      await program.methods
+        .issueLicense(licenseKey, 1, [], [], 10, true, expiresAt)
+        .accounts({
+          protocolState: protocolStatePDA,
+          license,
+          holder: holder.publicKey,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      return license;
+    }
+
+    function holderMutations(license: PublicKey): [string, () => Promise<string>][] {
+      const holderAccounts = { license, holder: holder.publicKey };
+      return [
+        ['transfer', () => program.methods.transferLicense()
+          .accounts({ license, newHolder: Keypair.generate().publicKey, currentHolder: holder.publicKey })
+          .signers([holder]).rpc()],
+        ['add wallet', () => program.methods.addAuthorizedWallet(Keypair.generate().publicKey)
+          .accounts(holderAccounts).signers([holder]).rpc()],
+        ['remove wallet', () => program.methods.removeAuthorizedWallet(Keypair.generate().publicKey)
+          .accounts(holderAccounts).signers([holder]).rpc()],
+        ['add domain', () => program.methods.addAuthorizedDomain('example.com')
+          .accounts(holderAccounts).signers([holder]).rpc()],
+        ['remove domain', () => program.methods.removeAuthorizedDomain('example.com')
+          .accounts(holderAccounts).signers([holder]).rpc()],
+      ];
+    }
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(holder.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      const now = Math.floor(Date.now() / 1000);
+      expiredLicense = await issueLicense(new BN(now + 3));
+      revokedLicense = await issueLicense(new BN(0));
+      await program.methods
+        .revokeLicense()
+        .accounts({ protocolState: protocolStatePDA, license: revokedLicense, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+
+      await sleep(5000);
+    });
+
+    it('rejects holder mutations of an expired license', async () => {
+      for (const [name, mutate] of holderMutations(expiredLicense)) {
+        try {
+          await mutate();
+          expect.fail(`${name} should have thrown an error`);
+        } catch (error: any) {
+          expect(error.error?.errorCode.code, name).to.equal('LicenseExpired');
+        }
+      }
+    });
+
+    it('rejects holder mutations of a revoked license', async () => {
+      for (const [name, mutate] of holderMutations(revokedLicense)) {
+        try {
+          await mutate();
+          expect.fail(`${name} should have thrown an error`);
+        } catch (error: any) {
+          expect(error.error?.errorCode.code, name).to.equal('LicenseNotActive');
+        }
+      }
+    });
+
+    it('still lets the admin extend an expired license, after which it can be mutated', async () => {
+      const now = Math.floor(Date.now() / 1000);
+      await program.methods
+        .updateLicense(null, new BN(now + 86400), null)
+        .accounts({ protocolState: protocolStatePDA, license: expiredLicense, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+
+      const wallet = Keypair.generate().publicKey;
+      await program.methods
+        .addAuthorizedWallet(wallet)
+        .accounts({ license: expiredLicense, holder: holder.publicKey })
+        .signers([holder])
+        .rpc();
+
+      const license = await program.account.license.fetch(expiredLicense);
+      expect(license.allowedWallets.map((w) => w.toString())).to.include(wallet.toString());
+    });
+  });
+
+  describe('protocol stats recompute', () => {
+    const marketIds = [new BN(148_001), new BN(148_002), new BN(148_003)];
+    // Markets are counted in ascending address order
+    let sortedMarkets: PublicKey[];
+
+    before(async () => {
+      for (const marketId of marketIds) {
+        await createTestMarket(marketId);
+      }
+      const bettor = await createFundedBettor();
+      await placeTestBet(marketIds[0], bettor, 0);
+      await placeTestBet(marketIds[2], bettor, 1);
+      sortedMarkets = marketIds
+        .map((id) => getMarketPDAs(id).market)
+        .sort((a, b) => Buffer.compare(a.toBuffer(), b.toBuffer()));
+    });
+
+    function recompute(restart: boolean, markets: PublicKey[]) {
+      return program.methods
+        .recomputeProtocolStats(restart)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .remainingAccounts(
+          markets.map((pubkey) => ({ pubkey, isWritable: false, isSigner: false }))
+        )
+        .signers([authority])
+        .rpc();
+    }
+
+    it('rejects finalizing without a recompute in progress', async () => {
+      try {
+        await program.methods
+          .finalizeStatsRecompute()
+          .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+          .signers([authority])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('StatsRecomputeNotStarted');
+      }
+    });
+
+    it('rejects markets supplied out of order', async () => {
+      try {
+        await recompute(true, [sortedMarkets[1], sortedMarkets[0]]);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('StatsMarketsOutOfOrder');
+      }
+    });
+
+    it('recomputes stats across batches and finalizes them', async () => {
+      await recompute(true, [sortedMarkets[0]]);
+      await recompute(false, [sortedMarkets[1], sortedMarkets[2]]);
+
+      // A batch repeating an already-counted market is rejected
+      try {
+        await recompute(false, [sortedMarkets[2]]);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('StatsMarketsOutOfOrder');
+      }
+
+      let expectedVolume = new BN(0);
+      for (const id of marketIds) {
+        const market = await program.account.market.fetch(getMarketPDAs(id).market);
+        expectedVolume = expectedVolume.add(market.totalPool);
+      }
+
+      await program.methods
+        .finalizeStatsRecompute()
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+
+      const state = await program.account.protocolState.fetch(protocolStatePDA);
+      expect(state.totalMarkets.toNumber()).to.equal(marketIds.length);
+      expect(state.totalVolume.toString()).to.equal(expectedVolume.toString());
+      expect(state.statsRecompute.inProgress).to.be.false;
+    });
+
+    it('rejects recompute from a non-admin', async () => {
+      try {
+        await program.methods
+          .recomputeProtocolStats(true)
+          .accounts({ protocolState: protocolStatePDA, authority: creator.publicKey })
+          .signers([creator])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+  });
+
+  describe('early resolution', () => {
+    const earlyMarketId = new BN(149_001);
+    const defaultMarketId = new BN(149_002);
+    let bettor: { keypair: Keypair; tokenAccount: PublicKey };
+
+    before(async () => {
+      await createTestMarket(earlyMarketId, { options: { allowEarlyResolution: true } });
+      await createTestMarket(defaultMarketId);
+      bettor = await createFundedBettor();
+      await placeTestBet(earlyMarketId, bettor, 0);
+      await placeTestBet(defaultMarketId, bettor, 0);
+    });
+
+    function resolve(marketId: BN) {
+      return program.methods
+        .resolveMarket(0)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: getMarketPDAs(marketId).market,
+          resolver: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+    }
+
+    it('keeps the betting deadline guard by default', async () => {
+      try {
+        await resolve(defaultMarketId);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('CannotResolveBeforeBettingDeadline');
+      }
+    });
+
+    it('lets the creator resolve early when allowed, closing betting', async () => {
+      const before = await program.account.market.fetch(getMarketPDAs(earlyMarketId).market);
+      await resolve(earlyMarketId);
+
+      const market = await program.account.market.fetch(getMarketPDAs(earlyMarketId).market);
+      expect(market.status).to.deep.equal({ resolved: {} });
+      expect(market.bettingDeadline.toNumber()).to.be.lessThan(before.bettingDeadline.toNumber());
+      expect(market.bettingDeadline.toNumber()).to.equal(market.resolvedAt.toNumber());
+
+      try {
+        await placeTestBet(earlyMarketId, await createFundedBettor(), 1);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('MarketAlreadyResolved');
+      }
+    });
+  });
+
+  describe('fee-token markets', () => {
+    const FLAT_FEE = new BN(250_000);
+    const marketId = new BN(150_001);
+    let feeMint: PublicKey;
+    let treasuryFeeAccount: PublicKey;
+    let bettor: { keypair: Keypair; tokenAccount: PublicKey };
+    let bettorFeeAccount: PublicKey;
+
+    function placeFeeTokenBet(feeAccounts: { bettorFeeTokenAccount: PublicKey | null; treasuryFeeTokenAccount: PublicKey | null }) {
+      const pdas = getMarketPDAs(marketId);
+      return program.methods
+        .placeBet(0, 0, null)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          leaderboard: pdas.leaderboard,
+          bettorTokenAccount: bettor.tokenAccount,
+          treasuryTokenAccount: treasuryTokenAccount,
+          ...feeAccounts,
+          selfExclusion: getSelfExclusionPDA(bettor.keypair.publicKey),
+          bettorProfile: null,
+          oddsHistory: null,
+          bettor: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      feeMint = await createMint(provider.connection, authority, authority.publicKey, null, 6);
+      treasuryFeeAccount = await createAccount(provider.connection, authority, feeMint, treasury.publicKey);
+
+      await program.methods
+        .setFeeToken(feeMint, FLAT_FEE)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+
+      bettor = await createFundedBettor();
+      bettorFeeAccount = await createAccount(provider.connection, authority, feeMint, bettor.keypair.publicKey);
+      await mintTo(provider.connection, authority, feeMint, bettorFeeAccount, authority, 1_000_000);
+    });
+
+    it('rejects a fee mint other than the protocol fee mint', async () => {
+      try {
+        await createTestMarket(new BN(150_002), { options: { feeMint: Keypair.generate().publicKey } });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InvalidFeeMint');
+      }
+    });
+
+    it('requires fee-mint token accounts when betting', async () => {
+      await createTestMarket(marketId, { options: { feeMint } });
+      try {
+        await placeFeeTokenBet({ bettorFeeTokenAccount: null, treasuryFeeTokenAccount: null });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('FeeTokenAccountRequired');
+      }
+    });
+
+    it('puts the full bet in the pool and charges the flat fee in the fee mint', async () => {
+      const pdas = getMarketPDAs(marketId);
+      const treasuryBefore = await getAccount(provider.connection, treasuryTokenAccount);
+      const treasuryFeeBefore = await getAccount(provider.connection, treasuryFeeAccount);
+
+      await placeFeeTokenBet({ bettorFeeTokenAccount: bettorFeeAccount, treasuryFeeTokenAccount: treasuryFeeAccount });
+
+      const market = await program.account.market.fetch(pdas.market);
+      expect(market.totalPool.toString()).to.equal(BET_AMOUNT.toString());
+      expect(market.bonusPool.toNumber()).to.equal(0);
+
+      const vault = await getAccount(provider.connection, pdas.marketVault);
+      expect(vault.amount.toString()).to.equal(BET_AMOUNT.toString());
+
+      const treasuryAfter = await getAccount(provider.connection, treasuryTokenAccount);
+      expect(treasuryAfter.amount).to.equal(treasuryBefore.amount);
+
+      const treasuryFeeAfter = await getAccount(provider.connection, treasuryFeeAccount);
+      expect((treasuryFeeAfter.amount - treasuryFeeBefore.amount).toString()).to.equal(FLAT_FEE.toString());
+
+      const bettorFee = await getAccount(provider.connection, bettorFeeAccount);
+      expect(bettorFee.amount.toString()).to.equal(new BN(1_000_000).sub(FLAT_FEE).toString());
+    });
+  });
+
+  describe('creator fee wallet separation', () => {
+    const disallowed: [string, (id: BN) => PublicKey][] = [
+      ['treasury', () => treasury.publicKey],
+      ['market PDA', (id) => getMarketPDAs(id).market],
+      ['market vault', (id) => getMarketPDAs(id).marketVault],
+      ['pool vault', (id) => getMarketPDAs(id).poolVault],
+    ];
+
+    disallowed.forEach(([name, wallet], i) => {
+      it(`rejects the ${name} as creator fee wallet`, async () => {
+        const marketId = new BN(151_001 + i);
+        try {
+          await createTestMarket(marketId, { creatorFeeWallet: wallet(marketId) });
+          expect.fail('Should have thrown an error');
+        } catch (error: any) {
+          expect(error.error?.errorCode.code).to.equal('InvalidCreatorFeeWallet');
+        }
+      });
+    });
+
+    it('rejects switching an existing market to a disallowed fee wallet', async () => {
+      const marketId = new BN(151_010);
+      await createTestMarket(marketId);
+      const pdas = getMarketPDAs(marketId);
+
+      try {
+        await program.methods
+          .updateCreatorFeeWallet()
+          .accounts({
+            protocolState: protocolStatePDA,
+            market: pdas.market,
+            newCreatorFeeWallet: pdas.poolVault,
+            creator: creator.publicKey,
+          })
+          .signers([creator])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InvalidCreatorFeeWallet');
+      }
+    });
+  });
+
+  describe('per-outcome stake cap', () => {
+    const marketId = new BN(152_001);
+
+    before(async () => {
+      // One net bet fits under the cap, a second on the same outcome does not
+      await createTestMarket(marketId, { options: { maxPerOutcome: BET_AMOUNT } });
+      await placeTestBet(marketId, await createFundedBettor(), 0);
+    });
+
+    it('rejects a bet that would push an outcome over its cap', async () => {
+      try {
+        await placeTestBet(marketId, await createFundedBettor(), 0);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('OutcomeCapReached');
+      }
+    });
+
+    it('still accepts bets on an outcome below its cap', async () => {
+      await placeTestBet(marketId, await createFundedBettor(), 1);
+
+      const market = await program.account.market.fetch(getMarketPDAs(marketId).market);
+      expect(market.outcomes[0].bettorCount).to.equal(1);
+      expect(market.outcomes[1].bettorCount).to.equal(1);
+      expect(market.outcomes[0].totalAmount.lte(BET_AMOUNT)).to.be.true;
+    });
+  });
+
+  describe('creator fee escrow', () => {
+    const cancelledMarketId = new BN(153_001);
+    const resolvedMarketId = new BN(153_002);
+    let bettorA: { keypair: Keypair; tokenAccount: PublicKey };
+    let bettorB: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function claimCreatorFees(marketId: BN) {
+      const pdas = getMarketPDAs(marketId);
+      return program.methods
+        .claimCreatorFees()
+        .accounts({
+          market: pdas.market,
+          marketVault: pdas.marketVault,
+          creatorTokenAccount: creatorTokenAccount,
+          creator: creator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([creator])
+        .rpc();
+    }
+
+    before(async () => {
+      bettorA = await createFundedBettor();
+      bettorB = await createFundedBettor();
+      await createTestMarket(cancelledMarketId);
+      await createTestMarket(resolvedMarketId, { options: { allowEarlyResolution: true } });
+      for (const marketId of [cancelledMarketId, resolvedMarketId]) {
+        await placeTestBet(marketId, bettorA, 0);
+        await placeTestBet(marketId, bettorB, 1);
+      }
+    });
+
+    it('returns escrowed creator fees to bettors when the market is cancelled', async () => {
+      const pdas = getMarketPDAs(cancelledMarketId);
+      await program.methods
+        .cancelMarket()
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, authority: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      try {
+        await claimCreatorFees(cancelledMarketId);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('MarketNotResolved');
+      }
+
+      const creatorBefore = await getAccount(provider.connection, creatorTokenAccount);
+      for (const bettor of [bettorA, bettorB]) {
+        const betPDA = getBetPDA(pdas.market, bettor.keypair.publicKey);
+        const bet = await program.account.bet.fetch(betPDA);
+        expect(bet.creatorFee.toNumber()).to.be.greaterThan(0);
+
+        const balanceBefore = await getAccount(provider.connection, bettor.tokenAccount);
+        await program.methods
+          .claimRefund()
+          .accounts({
+            market: pdas.market,
+            bet: betPDA,
+            marketVault: pdas.marketVault,
+            claimerTokenAccount: bettor.tokenAccount,
+            claimer: bettor.keypair.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([bettor.keypair])
+          .rpc();
+        const balanceAfter = await getAccount(provider.connection, bettor.tokenAccount);
+        expect(Number(balanceAfter.amount - balanceBefore.amount)).to.equal(
+          bet.poolAmount.toNumber() + bet.creatorFee.toNumber()
+        );
+      }
+
+      const market = await program.account.market.fetch(pdas.market);
+      expect(market.escrowedCreatorFees.toNumber()).to.equal(0);
+      const vault = await getAccount(provider.connection, pdas.marketVault);
+      expect(Number(vault.amount)).to.equal(0);
+      const creatorAfter = await getAccount(provider.connection, creatorTokenAccount);
+      expect(creatorAfter.amount).to.equal(creatorBefore.amount);
+    });
+
+    it('releases escrowed creator fees once the market resolves', async () => {
+      const pdas = getMarketPDAs(resolvedMarketId);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const escrowed = (await program.account.market.fetch(pdas.market)).escrowedCreatorFees;
+      expect(escrowed.toNumber()).to.be.greaterThan(0);
+
+      const creatorBefore = await getAccount(provider.connection, creatorTokenAccount);
+      await claimCreatorFees(resolvedMarketId);
+      const creatorAfter = await getAccount(provider.connection, creatorTokenAccount);
+      expect((creatorAfter.amount - creatorBefore.amount).toString()).to.equal(escrowed.toString());
+
+      try {
+        await claimCreatorFees(resolvedMarketId);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('NoEscrowedCreatorFees');
+      }
+    });
+  });
+
+  describe('license status view', () => {
+    const licenseKey = Array.from(Keypair.generate().publicKey.toBytes());
+    const license = getLicensePDA(licenseKey);
+    const expiresAt = new BN(Math.floor(Date.now() / 1000) + 86400);
+
+    async function setRequireLicense(required: boolean) {
+      await program.methods
+        .setRequireLicense(required)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    before(async () => {
+      await program.methods
+        .issueLicense(licenseKey, 1, [], [], 3, false, expiresAt)
+        .accounts({
+          protocolState: protocolStatePDA,
+          license,
+          holder: creator.publicKey,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      await setRequireLicense(true);
+      await createTestMarket(new BN(155_001), { license });
+    });
+
+    after(async () => {
+      await setRequireLicense(false);
+    });
+
+    it('reports remaining quota and validity for a partially used license', async () => {
+      const status = await program.methods
+        .licenseStatusView()
+        .accounts({ license })
+        .view();
+
+      expect(status.remainingMarkets).to.equal(2);
+      expect(status.isValid).to.be.true;
+      expect(status.isActive).to.be.true;
+      expect(status.expiresAt.toString()).to.equal(expiresAt.toString());
+      expect(status.features.canCreateMarkets).to.be.true;
+    });
+  });
+
+  describe('market bet limit', () => {
+    const marketId = new BN(157_001);
+    let marketPDA: PublicKey;
+    let marketVault: PublicKey;
+    let firstBettor: { keypair: Keypair; tokenAccount: PublicKey };
+
+    before(async () => {
+      ({ market: marketPDA, marketVault } = await createTestMarket(marketId, { options: { maxBets: 2 } }));
+      firstBettor = await createFundedBettor();
+      await placeTestBet(marketId, firstBettor, 0);
+      await placeTestBet(marketId, await createFundedBettor(), 1);
+    });
+
+    it('counts bets up to the limit', async () => {
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.maxBets).to.equal(2);
+      expect(market.betCount).to.equal(2);
+    });
+
+    it('rejects a bet beyond the limit', async () => {
+      try {
+        await placeTestBet(marketId, await createFundedBettor(), 0);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('MarketBetLimitReached');
+      }
+    });
+
+    it('frees a slot when a bet is withdrawn', async () => {
+      await program.methods
+        .withdrawBet()
+        .accounts({
+          market: marketPDA,
+          bet: getBetPDA(marketPDA, firstBettor.keypair.publicKey),
+          marketVault,
+          leaderboard: getLeaderboardPDA(marketPDA),
+          bettorTokenAccount: firstBettor.tokenAccount,
+          bettor: firstBettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([firstBettor.keypair])
+        .rpc();
+
+      let market = await program.account.market.fetch(marketPDA);
+      expect(market.betCount).to.equal(1);
+
+      await placeTestBet(marketId, await createFundedBettor(), 0);
+      market = await program.account.market.fetch(marketPDA);
+      expect(market.betCount).to.equal(2);
+    });
+  });
+
+  describe('bet lifecycle states', () => {
+    const marketId = new BN(158_001);
+
+    it('marks a refunded bet as refunded and rejects a second refund', async () => {
+      const pdas = await createTestMarket(marketId);
+      const bettor = await createFundedBettor();
+      const betPDA = await placeTestBet(marketId, bettor, 0);
+
+      await program.methods
+        .cancelMarket()
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, authority: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const refund = () =>
+        program.methods
+          .claimRefund()
+          .accounts({
+            market: pdas.market,
+            bet: betPDA,
+            marketVault: pdas.marketVault,
+            claimerTokenAccount: bettor.tokenAccount,
+            claimer: bettor.keypair.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([bettor.keypair])
+          .rpc();
+
+      await refund();
+      const bet = await program.account.bet.fetch(betPDA);
+      expect(bet.betState).to.deep.equal({ refunded: {} });
+
+      try {
+        await refund();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('AlreadyClaimed');
+      }
+    });
+  });
+
+  describe('fixed payout multipliers', () => {
+    const coveredMarketId = new BN(159_001);
+    const uncoveredMarketId = new BN(159_002);
+    const liquidity = BET_AMOUNT.toNumber();
+    let bettorA: { keypair: Keypair; tokenAccount: PublicKey };
+    let bettorB: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function multiplierOptions(multipliers: number[]) {
+      return {
+        payoutMode: { fixedMultiplier: {} },
+        payoutMultipliers: multipliers,
+        allowEarlyResolution: true,
+      };
+    }
+
+    before(async () => {
+      bettorA = await createFundedBettor();
+      bettorB = await createFundedBettor();
+      await mintTo(provider.connection, authority, tokenMint, creatorTokenAccount, authority, liquidity);
+      await createTestMarket(coveredMarketId, { options: multiplierOptions([20_000, 20_000]) });
+      await createTestMarket(uncoveredMarketId, { options: multiplierOptions([30_000, 30_000]) });
+      for (const marketId of [coveredMarketId, uncoveredMarketId]) {
+        await placeTestBet(marketId, bettorA, 0);
+        await placeTestBet(marketId, bettorB, 1);
+      }
+
+      const pdas = getMarketPDAs(coveredMarketId);
+      await program.methods
+        .depositMarketLiquidity(new BN(liquidity))
+        .accounts({
+          market: pdas.market,
+          marketVault: pdas.marketVault,
+          creatorTokenAccount: creatorTokenAccount,
+          creator: creator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([creator])
+        .rpc();
+    });
+
+    it('rejects a multiplier table that does not match the outcomes', async () => {
+      try {
+        await createTestMarket(new BN(159_003), { options: multiplierOptions([20_000]) });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InvalidPayoutMultipliers');
+      }
+    });
+
+    it('refuses to resolve when liquidity cannot cover the winning multiplier', async () => {
+      try {
+        await program.methods
+          .resolveMarket(0)
+          .accounts({
+            protocolState: protocolStatePDA,
+            market: getMarketPDAs(uncoveredMarketId).market,
+            resolver: creator.publicKey,
+          })
+          .signers([creator])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InsufficientLiquidity');
+      }
+    });
+
+    it('pays the winning bet twice its stake and returns the liquidity', async () => {
+      const pdas = getMarketPDAs(coveredMarketId);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const betPDA = getBetPDA(pdas.market, bettorA.keypair.publicKey);
+      const bet = await program.account.bet.fetch(betPDA);
+      const bonus = (await getAccount(provider.connection, pdas.poolVault)).amount;
+      const balanceBefore = await getAccount(provider.connection, bettorA.tokenAccount);
+      await program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: betPDA,
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: bettorA.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: bettorA.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bettorA.keypair])
+        .rpc();
+      const balanceAfter = await getAccount(provider.connection, bettorA.tokenAccount);
+      expect((balanceAfter.amount - balanceBefore.amount).toString()).to.equal(
+        (BigInt(bet.poolAmount.toNumber() * 2) + bonus).toString()
+      );
+
+      const creatorBefore = await getAccount(provider.connection, creatorTokenAccount);
+      await program.methods
+        .withdrawMarketLiquidity()
+        .accounts({
+          market: pdas.market,
+          marketVault: pdas.marketVault,
+          creatorTokenAccount: creatorTokenAccount,
+          creator: creator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([creator])
+        .rpc();
+      const creatorAfter = await getAccount(provider.connection, creatorTokenAccount);
+      expect(Number(creatorAfter.amount - creatorBefore.amount)).to.equal(liquidity);
+    });
+  });
+
+  describe('claim and bet', () => {
+    const sourceMarketId = new BN(160_001);
+    const targetMarketId = new BN(160_002);
+    const expensiveMarketId = new BN(160_003);
+    let winner: { keypair: Keypair; tokenAccount: PublicKey };
+    let loser: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function claimAndBet(targetId: BN, outcomeIndex: number) {
+      const source = getMarketPDAs(sourceMarketId);
+      const target = getMarketPDAs(targetId);
+      return program.methods
+        .claimAndBet(outcomeIndex, 0, null)
+        .accounts({
+          claim: {
+            protocolState: protocolStatePDA,
+            market: source.market,
+            bet: getBetPDA(source.market, winner.keypair.publicKey),
+            marketVault: source.marketVault,
+            poolVault: source.poolVault,
+            claimerTokenAccount: winner.tokenAccount,
+            claimEscrow: null,
+            escrowVault: null,
+            memoProgram: null,
+            claimer: winner.keypair.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          },
+          place: {
+            protocolState: protocolStatePDA,
+            market: target.market,
+            bet: getBetPDA(target.market, winner.keypair.publicKey),
+            marketVault: target.marketVault,
+            poolVault: target.poolVault,
+            leaderboard: target.leaderboard,
+            bettorTokenAccount: winner.tokenAccount,
+            treasuryTokenAccount: treasuryTokenAccount,
+            bettorFeeTokenAccount: null,
+            treasuryFeeTokenAccount: null,
+            selfExclusion: getSelfExclusionPDA(winner.keypair.publicKey),
+            bettorProfile: null,
+            oddsHistory: null,
+            bettor: winner.keypair.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          },
+        })
+        .signers([winner.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      winner = await createFundedBettor();
+      loser = await createFundedBettor();
+      await createTestMarket(sourceMarketId, { options: { allowEarlyResolution: true } });
+      await createTestMarket(targetMarketId);
+      await createTestMarket(expensiveMarketId, { betAmount: BET_AMOUNT.muln(5) });
+      await placeTestBet(sourceMarketId, winner, 0);
+      await placeTestBet(sourceMarketId, loser, 1);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: getMarketPDAs(sourceMarketId).market,
+          resolver: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+    });
+
+    it('rejects compounding when winnings do not cover the target bet', async () => {
+      try {
+        await claimAndBet(expensiveMarketId, 0);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InsufficientWinnings');
+      }
+      const bet = await program.account.bet.fetch(
+        getBetPDA(getMarketPDAs(sourceMarketId).market, winner.keypair.publicKey)
+      );
+      expect(bet.betState).to.deep.equal({ active: {} });
+    });
+
+    it('claims a win and places the proceeds on another market', async () => {
+      const source = getMarketPDAs(sourceMarketId);
+      const target = getMarketPDAs(targetMarketId);
+      const balanceBefore = await getAccount(provider.connection, winner.tokenAccount);
+      await claimAndBet(targetMarketId, 1);
+      const balanceAfter = await getAccount(provider.connection, winner.tokenAccount);
+
+      const claimed = await program.account.bet.fetch(getBetPDA(source.market, winner.keypair.publicKey));
+      expect(claimed.betState).to.deep.equal({ claimedWin: {} });
+
+      const placed = await program.account.bet.fetch(getBetPDA(target.market, winner.keypair.publicKey));
+      expect(placed.betState).to.deep.equal({ active: {} });
+      expect(placed.outcomeIndex).to.equal(1);
+      expect(placed.originalAmount.toString()).to.equal(BET_AMOUNT.toString());
+
+      // The winnings exceeded the new stake, so the wallet ends up ahead
+      expect(balanceAfter.amount > balanceBefore.amount).to.equal(true);
+      const targetMarket = await program.account.market.fetch(target.market);
+      expect(targetMarket.outcomes[1].totalAmount.toString()).to.equal(placed.poolAmount.toString());
+    });
+  });
+
+  describe('category outcome bounds', () => {
+    const SPORTS = 1;
+    const CRYPTO = 3;
+
+    function setOutcomeBounds(category: number, min: number, max: number) {
+      return program.methods
+        .setCategoryOutcomeBounds(category, min, max)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    before(async () => {
+      await setOutcomeBounds(CRYPTO, 2, 2);
+      await setOutcomeBounds(SPORTS, 3, 0);
+    });
+
+    after(async () => {
+      await setOutcomeBounds(CRYPTO, 0, 0);
+      await setOutcomeBounds(SPORTS, 0, 0);
+    });
+
+    it('rejects bounds outside the global range or with min above max', async () => {
+      for (const [min, max] of [[1, 0], [0, 11], [5, 3]]) {
+        try {
+          await setOutcomeBounds(CRYPTO, min, max);
+          expect.fail('Should have thrown an error');
+        } catch (error: any) {
+          expect(error.error?.errorCode.code).to.equal('InvalidOutcomeBounds');
+        }
+      }
+    });
+
+    it('limits a binary category to exactly two outcomes', async () => {
+      try {
+        await createTestMarket(new BN(161_001), { category: CRYPTO, outcomes: ['Up', 'Down', 'Flat'] });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('TooManyOutcomes');
+      }
+      await createTestMarket(new BN(161_002), { category: CRYPTO, outcomes: ['Up', 'Down'] });
+    });
+
+    it('requires the configured minimum and falls back to the global maximum', async () => {
+      try {
+        await createTestMarket(new BN(161_003), { category: SPORTS, outcomes: ['Home', 'Away'] });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('TooFewOutcomes');
+      }
+      const outcomes = Array.from({ length: 10 }, (_, i) => `Team ${i}`);
+      await createTestMarket(new BN(161_004), { category: SPORTS, outcomes });
+      const market = await program.account.market.fetch(getMarketPDAs(new BN(161_004)).market);
+      expect(market.outcomes.length).to.equal(10);
+    });
+  });
+
+  describe('market leaderboard', () => {
+    const marketId = new BN(162_001);
+
+    it('ranks the largest bettors and caps the list at ten', async () => {
+      await createTestMarket(marketId, { outcomes: ['A', 'B', 'C'] });
+      const pdas = getMarketPDAs(marketId);
+
+      const bettors = [];
+      for (let i = 0; i < 12; i++) {
+        const bettor = await createFundedBettor();
+        await placeTestBet(marketId, bettor, i % 3);
+        bettors.push(bettor.keypair.publicKey);
+      }
+
+      const leaderboard = await program.account.marketLeaderboard.fetch(pdas.leaderboard);
+      expect(leaderboard.market.toBase58()).to.equal(pdas.market.toBase58());
+      expect(leaderboard.entries.length).to.equal(10);
+
+      // Fixed-amount bets tie, so the earliest ten bettors hold the board in order
+      leaderboard.entries.forEach((entry: any, i: number) => {
+        expect(entry.bettor.toBase58()).to.equal(bettors[i].toBase58());
+      });
+      const bet = await program.account.bet.fetch(getBetPDA(pdas.market, bettors[0]));
+      expect(leaderboard.entries[0].poolAmount.toString()).to.equal(bet.poolAmount.toString());
+    });
+
+    it('orders entries by stake when amounts differ', async () => {
+      const mixedMarketId = new BN(162_002);
+      await createTestMarket(mixedMarketId);
+      const small = await createFundedBettor();
+      await placeTestBet(mixedMarketId, small, 0);
+
+      const pdas = getMarketPDAs(mixedMarketId);
+      await program.methods
+        .setMarketFeeExempt(true)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+      const large = await createFundedBettor();
+      await placeTestBet(mixedMarketId, large, 1);
+
+      const leaderboard = await program.account.marketLeaderboard.fetch(pdas.leaderboard);
+      expect(leaderboard.entries.map((e: any) => e.bettor.toBase58())).to.deep.equal([
+        large.keypair.publicKey.toBase58(),
+        small.keypair.publicKey.toBase58(),
+      ]);
+      expect(leaderboard.entries[0].poolAmount.gt(leaderboard.entries[1].poolAmount)).to.equal(true);
+    });
+
+    it('drops a bettor who withdraws', async () => {
+      const withdrawMarketId = new BN(162_003);
+      await createTestMarket(withdrawMarketId);
+      const pdas = getMarketPDAs(withdrawMarketId);
+      const stayer = await createFundedBettor();
+      const quitter = await createFundedBettor();
+      await placeTestBet(withdrawMarketId, stayer, 0);
+      await placeTestBet(withdrawMarketId, quitter, 1);
+
+      await program.methods
+        .withdrawBet()
+        .accounts({
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, quitter.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          leaderboard: pdas.leaderboard,
+          bettorTokenAccount: quitter.tokenAccount,
+          bettor: quitter.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([quitter.keypair])
+        .rpc();
+
+      const leaderboard = await program.account.marketLeaderboard.fetch(pdas.leaderboard);
+      expect(leaderboard.entries.map((e: any) => e.bettor.toBase58())).to.deep.equal([
+        stayer.keypair.publicKey.toBase58(),
+      ]);
+    });
+  });
+
+  describe('minimum bet hold', () => {
+    const marketId = new BN(163_001);
+    let bettor: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function withdraw() {
+      const pdas = getMarketPDAs(marketId);
+      return program.methods
+        .withdrawBet()
+        .accounts({
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          leaderboard: pdas.leaderboard,
+          bettorTokenAccount: bettor.tokenAccount,
+          bettor: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      bettor = await createFundedBettor();
+      await createTestMarket(marketId, { options: { minHoldSecs: new BN(2) } });
+      await placeTestBet(marketId, bettor, 0);
+    });
+
+    it('rejects a withdrawal before the hold period elapses', async () => {
+      try {
+        await withdraw();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('BetStillLocked');
+      }
+    });
+
+    it('allows the withdrawal once the hold period has passed', async () => {
+      await sleep(3000);
+      await withdraw();
+      const bet = await program.account.bet.fetch(getBetPDA(getMarketPDAs(marketId).market, bettor.keypair.publicKey));
+      expect(bet.betState).to.deep.equal({ withdrawn: {} });
+    });
+  });
+
+  describe('creator fee redirect', () => {
+    const redirectedMarketId = new BN(165_001);
+    const defaultMarketId = new BN(165_002);
+
+    function setRedirect(redirect: boolean) {
+      return program.methods
+        .setRedirectCreatorFeesToTreasury(redirect)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    async function betAndMeasureTreasury(marketId: BN) {
+      const bettor = await createFundedBettor();
+      const before = await getAccount(provider.connection, treasuryTokenAccount);
+      const betPDA = await placeTestBet(marketId, bettor, 0);
+      const after = await getAccount(provider.connection, treasuryTokenAccount);
+      const bet = await program.account.bet.fetch(betPDA);
+      const market = await program.account.market.fetch(getMarketPDAs(marketId).market);
+      return { treasuryDelta: Number(after.amount - before.amount), bet, market };
+    }
+
+    before(async () => {
+      await createTestMarket(redirectedMarketId);
+      await createTestMarket(defaultMarketId);
+    });
+
+    after(async () => {
+      await setRedirect(false);
+    });
+
+    it('sends the creator fee to the treasury when redirected', async () => {
+      await setRedirect(true);
+      const redirected = await betAndMeasureTreasury(redirectedMarketId);
+      expect(redirected.bet.creatorFee.toNumber()).to.equal(0);
+      expect(redirected.market.escrowedCreatorFees.toNumber()).to.equal(0);
+
+      await setRedirect(false);
+      const unchanged = await betAndMeasureTreasury(defaultMarketId);
+      expect(unchanged.bet.creatorFee.toNumber()).to.be.greaterThan(0);
+      expect(unchanged.market.escrowedCreatorFees.toString()).to.equal(unchanged.bet.creatorFee.toString());
+
+      // Same stake and fee split: the treasury gains exactly the creator fee on top
+      expect(redirected.treasuryDelta).to.equal(unchanged.treasuryDelta + unchanged.bet.creatorFee.toNumber());
+      expect(redirected.bet.poolAmount.toString()).to.equal(unchanged.bet.poolAmount.toString());
+    });
+  });
+
+  describe('init front-running protection', () => {
+    const contestedMarketId = new BN(166_001);
+    let attacker: { keypair: Keypair; tokenAccount: PublicKey };
+
+    before(async () => {
+      attacker = await createFundedBettor();
+    });
+
+    it('keeps a creator\'s market ID usable after someone else takes the same ID', async () => {
+      await createTestMarket(contestedMarketId, { marketCreator: attacker.keypair });
+      const { market } = await createTestMarket(contestedMarketId);
+
+      const legit = await program.account.market.fetch(market);
+      expect(legit.creator.toBase58()).to.equal(creator.publicKey.toBase58());
+      const squatted = await program.account.market.fetch(getMarketPDAs(contestedMarketId, attacker.keypair.publicKey).market);
+      expect(squatted.creator.toBase58()).to.equal(attacker.keypair.publicKey.toBase58());
+      expect(market.toBase58()).to.not.equal(getMarketPDAs(contestedMarketId, attacker.keypair.publicKey).market.toBase58());
+    });
+
+    it('rejects initializing a market at another creator\'s address', async () => {
+      const marketId = new BN(166_002);
+      const pdas = getMarketPDAs(marketId);
+      const now = Math.floor(Date.now() / 1000);
+      try {
+        await program.methods
+          .createMarket(
+            marketId,
+            3,
+            'Squatted market',
+            '',
+            BET_AMOUNT,
+            new BN(now + 2 * 86400),
+            new BN(now + 86400),
+            ['Yes', 'No'],
+            '',
+            {
+              payoutMode: { parimutuel: {} },
+              resolutionMode: { standard: {} },
+              maxPerOutcome: new BN(0),
+              maxBets: 0,
+              minHoldSecs: new BN(0),
+              isHouseMarket: false,
+              nativeSol: false,
+              multiCollateral: false,
+              collateralMints: [],
+              resultCommitment: new Array(32).fill(0),
+              requireAta: false,
+              allowEarlyResolution: false,
+              feeMint: PublicKey.default,
+              payoutMultipliers: [],
+              feeWaiverCount: 0,
+              twoSidedFees: false,
+              minBettorsForCreatorFee: 0,
+              claimExpirySecs: new BN(0),
+              requireEstablishedWallet: false,
+              trackOdds: false,
+              payoutMemo: false,
+              creatorLpShareBps: 0,
+              creatorLpShareWindowSecs: new BN(0),
+              seedBonus: new BN(0),
+              visibilityFlags: 0,
+              bettingPhases: [],
+            }
+          )
+          .accounts({
+            protocolState: protocolStatePDA,
+            market: pdas.market,
+            tokenMint: tokenMint,
+            marketVault: pdas.marketVault,
+            poolVault: pdas.poolVault,
+            leaderboard: pdas.leaderboard,
+            eventIndex: null,
+            oddsHistory: null,
+            license: null,
+            categoryConfig: getCategoryConfigPDA(3),
+            creatorTokenAccount: null,
+            creator: attacker.keypair.publicKey,
+            creatorFeeWallet: attacker.keypair.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            rent: SYSVAR_RENT_PUBKEY,
+          })
+          .signers([attacker.keypair])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('ConstraintSeeds');
+      }
+    });
+
+    it('rejects oracle and license initialization by anyone but the protocol authority', async () => {
+      const oracleId = 166_003;
+      try {
+        await program.methods
+          .registerOracle(oracleId, 'Squatted Oracle', new Array(12).fill(true), 'https://example.com')
+          .accounts({
+            protocolState: protocolStatePDA,
+            oracle: getOraclePDA(oracleId),
+            oracleAuthority: attacker.keypair.publicKey,
+            bondMint: null,
+            bondVault: null,
+            oracleBondSource: null,
+            authority: attacker.keypair.publicKey,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([attacker.keypair])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('Unauthorized');
+      }
+
+      const licenseKey = Array.from(Keypair.generate().publicKey.toBytes());
+      try {
+        await program.methods
+          .issueLicense(licenseKey, 1, [], [], 10, true, new BN(0))
+          .accounts({
+            protocolState: protocolStatePDA,
+            license: getLicensePDA(licenseKey),
+            holder: attacker.keypair.publicKey,
+            authority: attacker.keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([attacker.keypair])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+  });
+
+  describe('bet reservations', () => {
+    const RESERVATION_SEED = Buffer.from('reservation');
+    const RESERVATION_VAULT_SEED = Buffer.from('reservation_vault');
+    const marketId = new BN(167_001);
+    let reservationVault: PublicKey;
+
+    function getReservationPDA(market: PublicKey, bettor: PublicKey): PublicKey {
+      const [pda] = PublicKey.findProgramAddressSync(
+        [RESERVATION_SEED, market.toBuffer(), bettor.toBuffer()],
+        program.programId
+      );
+      return pda;
+    }
+
+    async function setReservationWindow(secs: number) {
+      await program.methods
+        .setReservationWindow(new BN(secs))
+        .accounts({
+          protocolState: protocolStatePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    async function reserve(bettor: { keypair: Keypair; tokenAccount: PublicKey }, outcomeIndex: number) {
+      const { market } = getMarketPDAs(marketId);
+      await program.methods
+        .reserveBet(outcomeIndex)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market,
+          reservation: getReservationPDA(market, bettor.keypair.publicKey),
+          tokenMint: tokenMint,
+          reservationVault,
+          bettorTokenAccount: bettor.tokenAccount,
+          bettor: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    function confirm(bettor: { keypair: Keypair; tokenAccount: PublicKey }, outcomeIndex: number) {
+      const pdas = getMarketPDAs(marketId);
+      return program.methods
+        .confirmBet(outcomeIndex, 0, null)
+        .accounts({
+          place: {
+            protocolState: protocolStatePDA,
+            market: pdas.market,
+            bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+            marketVault: pdas.marketVault,
+            poolVault: pdas.poolVault,
+            leaderboard: pdas.leaderboard,
+            bettorTokenAccount: bettor.tokenAccount,
+            treasuryTokenAccount: treasuryTokenAccount,
+            bettorFeeTokenAccount: null,
+            treasuryFeeTokenAccount: null,
+            selfExclusion: getSelfExclusionPDA(bettor.keypair.publicKey),
+            bettorProfile: null,
+            oddsHistory: null,
+            bettor: bettor.keypair.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          },
+          reservation: getReservationPDA(pdas.market, bettor.keypair.publicKey),
+          reservationVault,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    function cancel(bettor: { keypair: Keypair; tokenAccount: PublicKey }, caller: Keypair) {
+      const { market } = getMarketPDAs(marketId);
+      return program.methods
+        .cancelReservation()
+        .accounts({
+          protocolState: protocolStatePDA,
+          reservation: getReservationPDA(market, bettor.keypair.publicKey),
+          reservationVault,
+          bettorTokenAccount: bettor.tokenAccount,
+          bettor: bettor.keypair.publicKey,
+          caller: caller.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([caller])
+        .rpc();
+    }
+
+    before(async () => {
+      [reservationVault] = PublicKey.findProgramAddressSync(
+        [RESERVATION_VAULT_SEED, tokenMint.toBuffer()],
+        program.programId
+      );
+      await createTestMarket(marketId);
+    });
+
+    it('escrows a reservation outside the pool until it is confirmed', async () => {
+      const bettor = await createFundedBettor();
+      const { market } = getMarketPDAs(marketId);
+      const balanceBefore = await getAccount(provider.connection, bettor.tokenAccount);
+
+      await reserve(bettor, 1);
+      const reservation = await program.account.betReservation.fetch(
+        getReservationPDA(market, bettor.keypair.publicKey)
+      );
+      expect(reservation.amount.toString()).to.equal(BET_AMOUNT.toString());
+      expect(reservation.outcomeIndex).to.equal(1);
+      let marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.totalPool.toNumber()).to.equal(0);
+
+      await confirm(bettor, 1);
+      const bet = await program.account.bet.fetch(getBetPDA(market, bettor.keypair.publicKey));
+      expect(bet.outcomeIndex).to.equal(1);
+      expect(bet.originalAmount.toString()).to.equal(BET_AMOUNT.toString());
+      marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.totalPool.toString()).to.equal(bet.poolAmount.toString());
+
+      const balanceAfter = await getAccount(provider.connection, bettor.tokenAccount);
+      expect((balanceBefore.amount - balanceAfter.amount).toString()).to.equal(BET_AMOUNT.toString());
+      const closed = await provider.connection.getAccountInfo(getReservationPDA(market, bettor.keypair.publicKey));
+      expect(closed).to.equal(null);
+    });
+
+    it('refunds a reservation the bettor cancels', async () => {
+      const bettor = await createFundedBettor();
+      const other = await createFundedBettor();
+      const { market } = getMarketPDAs(marketId);
+      const balanceBefore = await getAccount(provider.connection, bettor.tokenAccount);
+
+      await reserve(bettor, 0);
+      try {
+        await cancel(bettor, other.keypair);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('ReservationNotExpired');
+      }
+      await cancel(bettor, bettor.keypair);
+
+      const balanceAfter = await getAccount(provider.connection, bettor.tokenAccount);
+      expect(balanceAfter.amount.toString()).to.equal(balanceBefore.amount.toString());
+      const closed = await provider.connection.getAccountInfo(getReservationPDA(market, bettor.keypair.publicKey));
+      expect(closed).to.equal(null);
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.betCount).to.equal(1);
+    });
+
+    it('refunds an expired reservation to the bettor and rejects confirming it', async () => {
+      const bettor = await createFundedBettor();
+      const cranker = await createFundedBettor();
+      const balanceBefore = await getAccount(provider.connection, bettor.tokenAccount);
+
+      await setReservationWindow(1);
+      try {
+        await reserve(bettor, 0);
+        await sleep(2500);
+
+        try {
+          await confirm(bettor, 0);
+          expect.fail('Should have thrown an error');
+        } catch (error: any) {
+          expect(error.error?.errorCode.code).to.equal('ReservationExpired');
+        }
+
+        // Once expired, anyone can trigger the refund
+        await cancel(bettor, cranker.keypair);
+      } finally {
+        await setReservationWindow(300);
+      }
+
+      const balanceAfter = await getAccount(provider.connection, bettor.tokenAccount);
+      expect(balanceAfter.amount.toString()).to.equal(balanceBefore.amount.toString());
+    });
+  });
+
+  describe('close market', () => {
+    const marketId = new BN(168_001);
+    let winnerA: { keypair: Keypair; tokenAccount: PublicKey };
+    let winnerB: { keypair: Keypair; tokenAccount: PublicKey };
+    let loser: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function claim(bettor: { keypair: Keypair; tokenAccount: PublicKey }, id = marketId) {
+      const pdas = getMarketPDAs(id);
+      return program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: bettor.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    function closeMarket(id = marketId) {
+      const pdas = getMarketPDAs(id);
+      return program.methods
+        .closeMarket()
+        .accounts({
+          market: pdas.market,
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          leaderboard: pdas.leaderboard,
+          creatorTokenAccount: creatorTokenAccount,
+          creator: creator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([creator])
+        .rpc();
+    }
+
+    function claimCreatorFees(id: BN) {
+      const pdas = getMarketPDAs(id);
+      return program.methods
+        .claimCreatorFees()
+        .accounts({
+          market: pdas.market,
+          marketVault: pdas.marketVault,
+          creatorTokenAccount: creatorTokenAccount,
+          creator: creator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([creator])
+        .rpc();
+    }
+
+    function claimRefund(id: BN, bettor: { keypair: Keypair; tokenAccount: PublicKey }) {
+      const pdas = getMarketPDAs(id);
+      return program.methods
+        .claimRefund()
+        .accounts({
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          claimerTokenAccount: bettor.tokenAccount,
+          claimer: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      winnerA = await createFundedBettor();
+      winnerB = await createFundedBettor();
+      loser = await createFundedBettor();
+      const pdas = await createTestMarket(marketId, { options: { allowEarlyResolution: true } });
+      await placeTestBet(marketId, winnerA, 0);
+      await placeTestBet(marketId, winnerB, 0);
+      await placeTestBet(marketId, loser, 1);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          resolver: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+      await claimCreatorFees(marketId);
+    });
+
+    it('records how many winning bets are owed a payout at resolution', async () => {
+      const market = await program.account.market.fetch(getMarketPDAs(marketId).market);
+      expect(market.winningBetsTotal).to.equal(2);
+      expect(market.winningBetsClaimed).to.equal(0);
+    });
+
+    it('rejects closing while a winner has not claimed', async () => {
+      await claim(winnerA);
+      try {
+        await closeMarket();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('MarketHasUnclaimedFunds');
+      }
+    });
+
+    it('closes the market and its vaults after the last winner claims', async () => {
+      const pdas = getMarketPDAs(marketId);
+      await claim(winnerB);
+      let market = await program.account.market.fetch(pdas.market);
+      expect(market.winningBetsClaimed).to.equal(market.winningBetsTotal);
+
+      const lamportsBefore = await provider.connection.getBalance(creator.publicKey);
+      await closeMarket();
+      const lamportsAfter = await provider.connection.getBalance(creator.publicKey);
+      expect(lamportsAfter).to.be.greaterThan(lamportsBefore);
+
+      for (const account of [pdas.market, pdas.marketVault, pdas.poolVault, pdas.leaderboard]) {
+        expect(await provider.connection.getAccountInfo(account)).to.equal(null);
+      }
+    });
+
+    it('closes once a losing protected bet has claimed its loss protection', async () => {
+      const protectedMarketId = new BN(168_002);
+      const winner = await createFundedBettor();
+      const protectedLoser = await createFundedBettor();
+      const pdas = await createTestMarket(protectedMarketId, { options: { allowEarlyResolution: true } });
+
+      await program.methods
+        .setLossProtectionPremium(1000)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+      try {
+        await placeTestBet(protectedMarketId, winner, 0);
+        await placeTestBet(protectedMarketId, protectedLoser, 1, 5000);
+      } finally {
+        await program.methods
+          .setLossProtectionPremium(0)
+          .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+          .signers([authority])
+          .rpc();
+      }
+
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+      await claimCreatorFees(protectedMarketId);
+      await claim(winner, protectedMarketId);
+
+      try {
+        await closeMarket(protectedMarketId);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('MarketHasUnclaimedFunds');
+      }
+
+      const bet = await program.account.bet.fetch(getBetPDA(pdas.market, protectedLoser.keypair.publicKey));
+      await program.methods
+        .claimLossProtection()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, protectedLoser.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          claimerTokenAccount: protectedLoser.tokenAccount,
+          claimer: protectedLoser.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([protectedLoser.keypair])
+        .rpc();
+      const market = await program.account.market.fetch(pdas.market);
+      expect(market.lossProtectionClaimed.toString()).to.equal(bet.protectedAmount.toString());
+
+      await closeMarket(protectedMarketId);
+      expect(await provider.connection.getAccountInfo(pdas.market)).to.equal(null);
+    });
+
+    it('closes a cancelled market once every bet is refunded', async () => {
+      const cancelledMarketId = new BN(168_003);
+      const first = await createFundedBettor();
+      const second = await createFundedBettor();
+      const pdas = await createTestMarket(cancelledMarketId);
+      await placeTestBet(cancelledMarketId, first, 0);
+      await placeTestBet(cancelledMarketId, second, 1);
+      await program.methods
+        .cancelMarket()
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, authority: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      await claimRefund(cancelledMarketId, first);
+      try {
+        await closeMarket(cancelledMarketId);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('MarketHasUnclaimedFunds');
+      }
+
+      await claimRefund(cancelledMarketId, second);
+      const market = await program.account.market.fetch(pdas.market);
+      expect(market.refundsClaimed).to.equal(2);
+
+      await closeMarket(cancelledMarketId);
+      for (const account of [pdas.market, pdas.marketVault, pdas.poolVault, pdas.leaderboard]) {
+        expect(await provider.connection.getAccountInfo(account)).to.equal(null);
+      }
+    });
+  });
+
+  describe('category protocol fees', () => {
+    const SPORTS = 1;
+    const SPORTS_PROTOCOL_FEE_BPS = 10;
+    const sportsMarketId = new BN(169_001);
+    const cryptoMarketId = new BN(169_002);
+
+    function setCategoryProtocolFee(category: number, feeBps: number) {
+      return program.methods
+        .setCategoryProtocolFee(category, feeBps)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    async function treasuryDeltaForBet(marketId: BN) {
+      const bettor = await createFundedBettor();
+      const before = await getAccount(provider.connection, treasuryTokenAccount);
+      await placeTestBet(marketId, bettor, 0);
+      const after = await getAccount(provider.connection, treasuryTokenAccount);
+      return Number(after.amount - before.amount);
+    }
+
+    before(async () => {
+      await createTestMarket(sportsMarketId, { category: SPORTS });
+      await createTestMarket(cryptoMarketId);
+    });
+
+    after(async () => {
+      await setCategoryProtocolFee(SPORTS, 0);
+    });
+
+    it('charges the category override instead of the global protocol fee', async () => {
+      await setCategoryProtocolFee(SPORTS, SPORTS_PROTOCOL_FEE_BPS);
+      const protocolState = await program.account.protocolState.fetch(protocolStatePDA);
+      expect(protocolState.protocolFeeBpsPerCategory[SPORTS]).to.equal(SPORTS_PROTOCOL_FEE_BPS);
+
+      const sportsDelta = await treasuryDeltaForBet(sportsMarketId);
+      const cryptoDelta = await treasuryDeltaForBet(cryptoMarketId);
+      expect(sportsDelta).to.equal(BET_AMOUNT.muln(SPORTS_PROTOCOL_FEE_BPS).divn(10000).toNumber());
+      expect(cryptoDelta).to.equal(BET_AMOUNT.muln(protocolState.protocolFeeBps).divn(10000).toNumber());
+    });
+
+    it('rejects overrides that push total fees over the maximum', async () => {
+      const protocolState = await program.account.protocolState.fetch(protocolStatePDA);
+      const headroom = 1000 - protocolState.creatorFeeBps - protocolState.poolFeeBps;
+      try {
+        await setCategoryProtocolFee(SPORTS, headroom + 1);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InvalidFeeConfig');
+      }
+
+      // Raising another fee must also leave room for the largest override
+      await setCategoryProtocolFee(SPORTS, headroom);
+      try {
+        await program.methods
+          .updateProtocol(null, null, null, protocolState.poolFeeBps + 1)
+          .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+          .signers([authority])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InvalidFeeConfig');
+      }
+    });
+  });
+
+  describe('self-exclusion', () => {
+    const marketId = new BN(170_001);
+    let bettor: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function selfExclude(wallet: Keypair, until: number) {
+      return program.methods
+        .selfExclude(new BN(until))
+        .accounts({
+          selfExclusion: getSelfExclusionPDA(wallet.publicKey),
+          wallet: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    before(async () => {
+      bettor = await createFundedBettor();
+      await createTestMarket(marketId);
+    });
+
+    it('blocks bets from an excluded wallet until the exclusion expires', async () => {
+      const until = Math.floor(Date.now() / 1000) + 3;
+      await selfExclude(bettor.keypair, until);
+      const exclusion = await program.account.selfExclusion.fetch(getSelfExclusionPDA(bettor.keypair.publicKey));
+      expect(exclusion.until.toNumber()).to.equal(until);
+
+      try {
+        await placeTestBet(marketId, bettor, 0);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('SelfExcluded');
+      }
+
+      await sleep(4500);
+      await placeTestBet(marketId, bettor, 0);
+      const market = await program.account.market.fetch(getMarketPDAs(marketId).market);
+      expect(market.betCount).to.equal(1);
+    });
+
+    it('extends an exclusion but refuses to shorten it', async () => {
+      const wallet = (await createFundedBettor()).keypair;
+      const now = Math.floor(Date.now() / 1000);
+      await selfExclude(wallet, now + 86400);
+      await selfExclude(wallet, now + 2 * 86400);
+
+      try {
+        await selfExclude(wallet, now + 3600);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('SelfExclusionShortened');
+      }
+      const exclusion = await program.account.selfExclusion.fetch(getSelfExclusionPDA(wallet.publicKey));
+      expect(exclusion.until.toNumber()).to.equal(now + 2 * 86400);
+    });
+  });
+
+  describe('oracle bonds', () => {
+    const ORACLE_BOND_VAULT_SEED = Buffer.from('oracle_bond_vault');
+    const BOND_AMOUNT = new BN(5_000_000);
+    const COOLDOWN_SECS = 4;
+    const slashedOracleId = 171_001;
+    const retiringOracleId = 171_002;
+    let slashedAuthority: { keypair: Keypair; tokenAccount: PublicKey };
+    let retiringAuthority: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function getBondVaultPDA(oracleId: number): PublicKey {
+      const [vault] = PublicKey.findProgramAddressSync(
+        [ORACLE_BOND_VAULT_SEED, getOraclePDA(oracleId).toBuffer()],
+        program.programId
+      );
+      return vault;
+    }
+
+    function setOracleBond(amount: BN, cooldownSecs: number) {
+      return program.methods
+        .setOracleBond(amount, new BN(cooldownSecs))
+        .accounts({
+          protocolState: protocolStatePDA,
+          bondMint: tokenMint,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    function registerBondedOracle(
+      oracleId: number,
+      oracleAuthority: { keypair: Keypair; tokenAccount: PublicKey },
+      signers: Keypair[]
+    ) {
+      return program.methods
+        .registerOracle(oracleId, 'Bonded Oracle', new Array(12).fill(true), 'https://example.com')
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: getOraclePDA(oracleId),
+          oracleAuthority: oracleAuthority.keypair.publicKey,
+          bondMint: tokenMint,
+          bondVault: getBondVaultPDA(oracleId),
+          oracleBondSource: oracleAuthority.tokenAccount,
+          authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers(signers)
+        .rpc();
+    }
+
+    function deactivateOracle(oracleId: number) {
+      return program.methods
+        .updateOracle(null, null, null, false)
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: getOraclePDA(oracleId),
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    function slash(oracleId: number, amount: BN, signer: Keypair = authority) {
+      return program.methods
+        .slashOracle(amount)
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: getOraclePDA(oracleId),
+          bondVault: getBondVaultPDA(oracleId),
+          treasuryTokenAccount: treasuryTokenAccount,
+          authority: signer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([signer])
+        .rpc();
+    }
+
+    function withdrawBond(oracleId: number, oracleAuthority: { keypair: Keypair; tokenAccount: PublicKey }) {
+      return program.methods
+        .withdrawOracleBond()
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: getOraclePDA(oracleId),
+          bondVault: getBondVaultPDA(oracleId),
+          oracleTokenAccount: oracleAuthority.tokenAccount,
+          oracleAuthority: oracleAuthority.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([oracleAuthority.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      slashedAuthority = await createFundedBettor();
+      retiringAuthority = await createFundedBettor();
+      await setOracleBond(BOND_AMOUNT, COOLDOWN_SECS);
+    });
+
+    after(async () => {
+      await setOracleBond(new BN(0), 0);
+    });
+
+    it('requires the oracle authority to post the bond at registration', async () => {
+      try {
+        await registerBondedOracle(slashedOracleId, slashedAuthority, [authority]);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('OracleBondRequired');
+      }
+
+      const balanceBefore = await getAccount(provider.connection, slashedAuthority.tokenAccount);
+      await registerBondedOracle(slashedOracleId, slashedAuthority, [authority, slashedAuthority.keypair]);
+      const balanceAfter = await getAccount(provider.connection, slashedAuthority.tokenAccount);
+
+      expect((balanceBefore.amount - balanceAfter.amount).toString()).to.equal(BOND_AMOUNT.toString());
+      const vault = await getAccount(provider.connection, getBondVaultPDA(slashedOracleId));
+      expect(vault.amount.toString()).to.equal(BOND_AMOUNT.toString());
+      const oracle = await program.account.oracle.fetch(getOraclePDA(slashedOracleId));
+      expect(oracle.bondAmount.toString()).to.equal(BOND_AMOUNT.toString());
+    });
+
+    it('lets only the protocol authority slash a bond into the treasury', async () => {
+      const slashAmount = new BN(2_000_000);
+      try {
+        await slash(slashedOracleId, slashAmount, slashedAuthority.keypair);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('Unauthorized');
+      }
+      try {
+        await slash(slashedOracleId, BOND_AMOUNT.addn(1));
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InvalidSlashAmount');
+      }
+
+      const treasuryBefore = await getAccount(provider.connection, treasuryTokenAccount);
+      await slash(slashedOracleId, slashAmount);
+      const treasuryAfter = await getAccount(provider.connection, treasuryTokenAccount);
+
+      expect((treasuryAfter.amount - treasuryBefore.amount).toString()).to.equal(slashAmount.toString());
+      const oracle = await program.account.oracle.fetch(getOraclePDA(slashedOracleId));
+      expect(oracle.bondAmount.toString()).to.equal(BOND_AMOUNT.sub(slashAmount).toString());
+    });
+
+    it('keeps the bond locked while the oracle is active and blocks closing a bonded oracle', async () => {
+      try {
+        await withdrawBond(slashedOracleId, slashedAuthority);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('OracleBondLocked');
+      }
+
+      await deactivateOracle(slashedOracleId);
+      try {
+        await program.methods
+          .closeOracle()
+          .accounts({
+            protocolState: protocolStatePDA,
+            oracle: getOraclePDA(slashedOracleId),
+            authority: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('OracleBondOutstanding');
+      }
+    });
+
+    it('returns the bond to a deactivated oracle after the cooldown', async () => {
+      await registerBondedOracle(retiringOracleId, retiringAuthority, [authority, retiringAuthority.keypair]);
+      await deactivateOracle(retiringOracleId);
+      try {
+        await withdrawBond(retiringOracleId, retiringAuthority);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('OracleBondLocked');
+      }
+
+      await sleep((COOLDOWN_SECS + 1) * 1000);
+      const balanceBefore = await getAccount(provider.connection, retiringAuthority.tokenAccount);
+      await withdrawBond(retiringOracleId, retiringAuthority);
+      const balanceAfter = await getAccount(provider.connection, retiringAuthority.tokenAccount);
+
+      expect((balanceAfter.amount - balanceBefore.amount).toString()).to.equal(BOND_AMOUNT.toString());
+      const oracle = await program.account.oracle.fetch(getOraclePDA(retiringOracleId));
+      expect(oracle.bondAmount.toNumber()).to.equal(0);
+    });
+  });
+
+  describe('event index', () => {
+    const EVENT_ID = 'EPL-2026-ARS-CHE';
+
+    it('finds every market created for an oracle event id', async () => {
+      const first = await createTestMarket(new BN(172_001), { oracleEventId: EVENT_ID });
+      const second = await createTestMarket(new BN(172_002), { oracleEventId: EVENT_ID });
+      await createTestMarket(new BN(172_003), { oracleEventId: 'NBA-2026-LAL-BOS' });
+
+      const eventIndex = await program.account.eventIndex.fetch(getEventIndexPDA(EVENT_ID));
+      expect(eventIndex.markets.map((m) => m.toBase58())).to.deep.equal([
+        first.market.toBase58(),
+        second.market.toBase58(),
+      ]);
+
+      const market = await program.account.market.fetch(eventIndex.markets[0]);
+      expect(market.oracleEventId).to.equal(EVENT_ID);
+    });
+  });
+
+  describe('oracle assignment cap', () => {
+    const oracleId = 173_001;
+    const marketIds = [new BN(173_001), new BN(173_002), new BN(173_003)];
+    const oracleAuthority = Keypair.generate();
+    let oraclePDA: PublicKey;
+
+    function setMaxMarketsPerOracle(max: number) {
+      return program.methods
+        .setMaxMarketsPerOracle(max)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    function assign(marketId: BN) {
+      return program.methods
+        .assignOracle()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: getMarketPDAs(marketId).market,
+          oracle: oraclePDA,
+          creator: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+    }
+
+    async function assignedOpenMarkets() {
+      return (await program.account.oracle.fetch(oraclePDA)).assignedOpenMarkets;
+    }
+
+    before(async () => {
+      oraclePDA = getOraclePDA(oracleId);
+      await program.methods
+        .registerOracle(oracleId, 'Capped Oracle', new Array(12).fill(true), 'https://example.com')
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: oraclePDA,
+          oracleAuthority: oracleAuthority.publicKey,
+          bondMint: null,
+          bondVault: null,
+          oracleBondSource: null,
+          authority: authority.publicKey,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      for (const marketId of marketIds) {
+        await createTestMarket(marketId, { options: { allowEarlyResolution: true } });
+      }
+      await setMaxMarketsPerOracle(2);
+    });
+
+    after(async () => {
+      await setMaxMarketsPerOracle(0);
+    });
+
+    it('assigns up to the cap and rejects the next market', async () => {
+      await assign(marketIds[0]);
+      await assign(marketIds[1]);
+      expect(await assignedOpenMarkets()).to.equal(2);
+
+      try {
+        await assign(marketIds[2]);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('OracleAssignmentLimitReached');
+      }
+    });
+
+    it('frees a slot when the oracle resolves one of its markets', async () => {
+      await program.methods
+        .oracleResolveMarket(0)
+        .accounts({
+          market: getMarketPDAs(marketIds[0]).market,
+          oracle: oraclePDA,
+          oracleAuthority: oracleAuthority.publicKey,
+        })
+        .signers([oracleAuthority])
+        .rpc();
+      expect(await assignedOpenMarkets()).to.equal(1);
+
+      await assign(marketIds[2]);
+      expect(await assignedOpenMarkets()).to.equal(2);
+    });
+
+    it('releases the slot of a market that was cancelled instead', async () => {
+      const market = getMarketPDAs(marketIds[1]).market;
+      await program.methods
+        .cancelMarket()
+        .accounts({ protocolState: protocolStatePDA, market, authority: creator.publicKey })
+        .signers([creator])
+        .rpc();
+      await program.methods
+        .releaseOracleAssignment()
+        .accounts({ market, oracle: oraclePDA })
+        .rpc();
+
+      expect(await assignedOpenMarkets()).to.equal(1);
+      const marketAccount = await program.account.market.fetch(market);
+      expect(marketAccount.oracle.toBase58()).to.equal(PublicKey.default.toBase58());
+    });
+  });
+
+  describe('fee waiver for early bettors', () => {
+    const marketId = new BN(174_001);
+
+    async function betAndMeasureFees() {
+      const bettor = await createFundedBettor();
+      const treasuryBefore = await getAccount(provider.connection, treasuryTokenAccount);
+      const betPDA = await placeTestBet(marketId, bettor, 0);
+      const treasuryAfter = await getAccount(provider.connection, treasuryTokenAccount);
+      const bet = await program.account.bet.fetch(betPDA);
+      return { bet, treasuryDelta: Number(treasuryAfter.amount - treasuryBefore.amount) };
+    }
+
+    before(async () => {
+      await createTestMarket(marketId, { options: { feeWaiverCount: 2 } });
+    });
+
+    it('puts the whole stake of the first bettors in the pool', async () => {
+      for (let i = 0; i < 2; i++) {
+        const { bet, treasuryDelta } = await betAndMeasureFees();
+        expect(bet.poolAmount.toString()).to.equal(BET_AMOUNT.toString());
+        expect(bet.creatorFee.toNumber()).to.equal(0);
+        expect(treasuryDelta).to.equal(0);
+      }
+      const market = await program.account.market.fetch(getMarketPDAs(marketId).market);
+      expect(market.totalPool.toString()).to.equal(BET_AMOUNT.muln(2).toString());
+      expect(market.bonusPool.toNumber()).to.equal(0);
+    });
+
+    it('charges normal fees once the waiver is used up', async () => {
+      const { bet, treasuryDelta } = await betAndMeasureFees();
+      expect(bet.poolAmount.lt(BET_AMOUNT)).to.equal(true);
+      expect(bet.creatorFee.toNumber()).to.be.greaterThan(0);
+      expect(treasuryDelta).to.be.greaterThan(0);
+    });
+  });
+
+  describe('winnings claimed event', () => {
+    const marketId = new BN(175_001);
+
+    it('includes the winning outcome label', async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const pdas = await createTestMarket(marketId, {
+        outcomes: ['Home', 'Away'],
+        bettingDeadline: new BN(now + 6),
+      });
+      const winner = await createFundedBettor();
+      await placeTestBet(marketId, winner, 0);
+      await placeTestBet(marketId, await createFundedBettor(), 1);
+
+      await sleep(8000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const sig = await program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, winner.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: winner.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([winner.keypair])
+        .rpc({ commitment: 'confirmed' });
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const event = Array.from(parser.parseLogs(tx!.meta!.logMessages!)).find(
+        (e: any) => e.name === 'WinningsClaimed'
+      ) as any;
+
+      expect(event).to.not.be.undefined;
+      expect(event.data.bettor.toBase58()).to.equal(winner.keypair.publicKey.toBase58());
+      expect(event.data.winningOutcome).to.equal(0);
+      expect(event.data.winningOutcomeLabel).to.equal('Home');
+      expect(event.data.amount.toNumber()).to.be.greaterThan(0);
+    });
+  });
+
+  describe('low-participation creator fee redirect', () => {
+    const thinMarketId = new BN(176_001);
+    const busyMarketId = new BN(176_002);
+    let thin: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let busy: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let winner: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function redirectCreatorFees(pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey }) {
+      return program.methods
+        .redirectCreatorFees()
+        .accounts({
+          market: pdas.market,
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          caller: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    function claimWinnings() {
+      return program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: thin.market,
+          bet: getBetPDA(thin.market, winner.keypair.publicKey),
+          marketVault: thin.marketVault,
+          poolVault: thin.poolVault,
+          claimerTokenAccount: winner.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([winner.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const bettingDeadline = new BN(now + 6);
+      thin = await createTestMarket(thinMarketId, { bettingDeadline, options: { minBettorsForCreatorFee: 3 } });
+      busy = await createTestMarket(busyMarketId, { bettingDeadline, options: { minBettorsForCreatorFee: 2 } });
+
+      winner = await createFundedBettor();
+      await placeTestBet(thinMarketId, winner, 0);
+      await placeTestBet(thinMarketId, await createFundedBettor(), 1);
+      await placeTestBet(busyMarketId, await createFundedBettor(), 0);
+      await placeTestBet(busyMarketId, await createFundedBettor(), 1);
+
+      await sleep(8000);
+      for (const market of [thin.market, busy.market]) {
+        await program.methods
+          .resolveMarket(0)
+          .accounts({ protocolState: protocolStatePDA, market, resolver: creator.publicKey })
+          .signers([creator])
+          .rpc();
+      }
+    });
+
+    it('blocks winner claims until the forfeited fees reach the bonus pool', async () => {
+      try {
+        await claimWinnings();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('CreatorFeeRedirectPending');
+      }
+    });
+
+    it('rejects the creator claiming forfeited fees', async () => {
+      try {
+        await program.methods
+          .claimCreatorFees()
+          .accounts({
+            market: thin.market,
+            marketVault: thin.marketVault,
+            creatorTokenAccount: creatorTokenAccount,
+            creator: creator.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([creator])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('CreatorFeesForfeited');
+      }
+    });
+
+    it('moves the escrowed creator fees into the bonus pool', async () => {
+      const before = await program.account.market.fetch(thin.market);
+      const poolVaultBefore = await getAccount(provider.connection, thin.poolVault);
+      expect(before.escrowedCreatorFees.toNumber()).to.be.greaterThan(0);
+
+      await redirectCreatorFees(thin);
+
+      const after = await program.account.market.fetch(thin.market);
+      const poolVaultAfter = await getAccount(provider.connection, thin.poolVault);
+      expect(after.escrowedCreatorFees.toNumber()).to.equal(0);
+      expect(after.bonusPool.toString()).to.equal(before.bonusPool.add(before.escrowedCreatorFees).toString());
+      expect(Number(poolVaultAfter.amount - poolVaultBefore.amount)).to.equal(before.escrowedCreatorFees.toNumber());
+    });
+
+    it('pays the redirected fees to the winner', async () => {
+      const market = await program.account.market.fetch(thin.market);
+      const balanceBefore = await getAccount(provider.connection, winner.tokenAccount);
+      await claimWinnings();
+      const balanceAfter = await getAccount(provider.connection, winner.tokenAccount);
+      expect(Number(balanceAfter.amount - balanceBefore.amount)).to.equal(
+        market.totalPool.add(market.bonusPool).toNumber()
+      );
+    });
+
+    it('leaves creator fees alone once the threshold is met', async () => {
+      try {
+        await redirectCreatorFees(busy);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('CreatorFeesNotForfeited');
+      }
+    });
+  });
+
+  describe('instruction return data', () => {
+    const marketId = new BN(177_001);
+    let pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey; leaderboard: PublicKey };
+    let winner: { keypair: Keypair; tokenAccount: PublicKey };
+
+    // Borsh layout of InstructionResult
+    async function readInstructionResult(sig: string) {
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      const returnData = (tx!.meta as any).returnData;
+      expect(returnData.programId).to.equal(program.programId.toBase58());
+      const data = Buffer.from(returnData.data[0], 'base64');
+      return {
+        market: new PublicKey(data.subarray(0, 32)),
+        totalPool: new BN(data.subarray(32, 40), 'le'),
+        bonusPool: new BN(data.subarray(40, 48), 'le'),
+        outcomeIndex: data[48],
+        outcomeTotal: new BN(data.subarray(49, 57), 'le'),
+        position: new BN(data.subarray(57, 65), 'le'),
+        payout: new BN(data.subarray(65, 73), 'le'),
+      };
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      await createTestMarket(marketId, { bettingDeadline: new BN(now + 6) });
+      pdas = getMarketPDAs(marketId);
+      winner = await createFundedBettor();
+    });
+
+    it('returns the pool totals and position from place_bet', async () => {
+      const bet = getBetPDA(pdas.market, winner.keypair.publicKey);
+      const sig = await program.methods
+        .placeBet(0, 0, null)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet,
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          leaderboard: pdas.leaderboard,
+          bettorTokenAccount: winner.tokenAccount,
+          treasuryTokenAccount: treasuryTokenAccount,
+          bettorFeeTokenAccount: null,
+          treasuryFeeTokenAccount: null,
+          selfExclusion: getSelfExclusionPDA(winner.keypair.publicKey),
+          bettorProfile: null,
+          oddsHistory: null,
+          bettor: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([winner.keypair])
+        .rpc({ commitment: 'confirmed' });
+
+      const result = await readInstructionResult(sig);
+      const market = await program.account.market.fetch(pdas.market);
+      const betAccount = await program.account.bet.fetch(bet);
+      expect(result.market.toBase58()).to.equal(pdas.market.toBase58());
+      expect(result.totalPool.toString()).to.equal(market.totalPool.toString());
+      expect(result.bonusPool.toString()).to.equal(market.bonusPool.toString());
+      expect(result.outcomeIndex).to.equal(0);
+      expect(result.outcomeTotal.toString()).to.equal(market.outcomes[0].totalAmount.toString());
+      expect(result.position.toString()).to.equal(betAccount.poolAmount.toString());
+      expect(result.payout.toNumber()).to.equal(0);
+    });
+
+    it('returns the payout from claim_winnings', async () => {
+      await placeTestBet(marketId, await createFundedBettor(), 1);
+      await sleep(8000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const balanceBefore = await getAccount(provider.connection, winner.tokenAccount);
+      const sig = await program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, winner.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: winner.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([winner.keypair])
+        .rpc({ commitment: 'confirmed' });
+      const balanceAfter = await getAccount(provider.connection, winner.tokenAccount);
+
+      const result = await readInstructionResult(sig);
+      expect(result.payout.toNumber()).to.be.greaterThan(0);
+      expect(result.payout.toNumber()).to.equal(Number(balanceAfter.amount - balanceBefore.amount));
+    });
+  });
+
+  describe('market vault reuse', () => {
+    const existingMarketId = new BN(178_001);
+    let existing: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+
+    before(async () => {
+      existing = await createTestMarket(existingMarketId);
+    });
+
+    it("rejects another market's market vault", async () => {
+      try {
+        await createTestMarket(new BN(178_002), { marketVault: existing.marketVault });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('ConstraintSeeds');
+      }
+    });
+
+    it("rejects another market's pool vault", async () => {
+      try {
+        await createTestMarket(new BN(178_003), { poolVault: existing.poolVault });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('ConstraintSeeds');
+      }
+    });
+
+    it('rejects using one vault for both roles', async () => {
+      const own = getMarketPDAs(new BN(178_004));
+      try {
+        await createTestMarket(new BN(178_004), { poolVault: own.marketVault });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('ConstraintSeeds');
+      }
+    });
+
+    it('leaves the existing vaults untouched', async () => {
+      const marketVault = await getAccount(provider.connection, existing.marketVault);
+      const poolVault = await getAccount(provider.connection, existing.poolVault);
+      expect(marketVault.owner.toBase58()).to.equal(existing.market.toBase58());
+      expect(poolVault.owner.toBase58()).to.equal(existing.market.toBase58());
+    });
+  });
+
+  describe('fee taper schedule', () => {
+    const marketId = new BN(179_001);
+
+    function setFeeTaperSchedule(schedule: { poolThreshold: BN; feeMultiplierBps: number }[]) {
+      return program.methods
+        .setFeeTaperSchedule(schedule)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    before(async () => {
+      await setFeeTaperSchedule([{ poolThreshold: new BN(1), feeMultiplierBps: 5000 }]);
+      await createTestMarket(marketId);
+    });
+
+    after(async () => {
+      await setFeeTaperSchedule([]);
+    });
+
+    // The multiplier scales the fee rate; the scaled fee is then floored at one unit like any other
+    function taperedFee(amount: number, feeBps: number, multiplierBps: number) {
+      const bps = Math.floor((feeBps * multiplierBps) / 10000);
+      return bps === 0 ? 0 : Math.max(1, Math.floor((amount * bps) / 10000));
+    }
+
+    it('halves the fees once the pool crosses the threshold', async () => {
+      const protocolState = await program.account.protocolState.fetch(protocolStatePDA);
+      const first = await program.account.bet.fetch(await placeTestBet(marketId, await createFundedBettor(), 0));
+      const second = await program.account.bet.fetch(await placeTestBet(marketId, await createFundedBettor(), 0));
+
+      expect(first.creatorFee.toNumber()).to.be.greaterThan(0);
+      expect(second.creatorFee.toNumber()).to.equal(
+        taperedFee(BET_AMOUNT.toNumber(), protocolState.creatorFeeBps, 5000)
+      );
+      expect(second.poolAmount.gt(first.poolAmount)).to.equal(true);
+    });
+
+    it('keeps the one-unit floor on tapered fees for tiny bets', async () => {
+      const tinyMarketId = new BN(179_002);
+      const TINY_BET = new BN(10);
+      await createTestMarket(tinyMarketId, { betAmount: TINY_BET });
+      await placeTestBet(tinyMarketId, await createFundedBettor(), 0);
+
+      const protocolState = await program.account.protocolState.fetch(protocolStatePDA);
+      const bet = await program.account.bet.fetch(await placeTestBet(tinyMarketId, await createFundedBettor(), 0));
+      expect(bet.creatorFee.toNumber()).to.equal(
+        taperedFee(TINY_BET.toNumber(), protocolState.creatorFeeBps, 5000)
+      );
+      expect(bet.creatorFee.toNumber()).to.equal(1);
+    });
+
+    it('applies the highest tier the pool has reached', async () => {
+      const tieredMarketId = new BN(179_003);
+      // The deeper tier charges more than the shallow one, so taking the smallest multiplier would undercharge
+      await setFeeTaperSchedule([
+        { poolThreshold: new BN(1), feeMultiplierBps: 2000 },
+        { poolThreshold: new BN(2), feeMultiplierBps: 8000 },
+      ]);
+      await createTestMarket(tieredMarketId);
+      await placeTestBet(tieredMarketId, await createFundedBettor(), 0);
+
+      const protocolState = await program.account.protocolState.fetch(protocolStatePDA);
+      const bet = await program.account.bet.fetch(await placeTestBet(tieredMarketId, await createFundedBettor(), 0));
+      expect(bet.creatorFee.toNumber()).to.equal(
+        taperedFee(BET_AMOUNT.toNumber(), protocolState.creatorFeeBps, 8000)
+      );
+    });
+
+    it('rejects unsorted tiers', async () => {
+      try {
+        await setFeeTaperSchedule([
+          { poolThreshold: new BN(2_000), feeMultiplierBps: 5000 },
+          { poolThreshold: new BN(1_000), feeMultiplierBps: 8000 },
+        ]);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InvalidFeeTaperSchedule');
+      }
+    });
+
+    it('rejects multipliers above 100%', async () => {
+      try {
+        await setFeeTaperSchedule([{ poolThreshold: new BN(1), feeMultiplierBps: 10_001 }]);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InvalidFeeTaperSchedule');
+      }
+    });
+  });
+
+  describe('recover excess lamports', () => {
+    const marketId = new BN(180_001);
+    let marketPDA: PublicKey;
+
+    function recoverExcessLamports(signer: Keypair = authority) {
+      return program.methods
+        .recoverExcessLamports()
+        .accounts({
+          protocolState: protocolStatePDA,
+          target: marketPDA,
+          treasury: treasury.publicKey,
+          authority: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+    }
+
+    before(async () => {
+      ({ market: marketPDA } = await createTestMarket(marketId));
+      const sig = await provider.connection.requestAirdrop(marketPDA, LAMPORTS_PER_SOL / 2);
+      await provider.connection.confirmTransaction(sig);
+    });
+
+    it('rejects non-admin callers', async () => {
+      try {
+        await recoverExcessLamports(creator);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+
+    it('sends the excess to the treasury and keeps the market rent-exempt', async () => {
+      const treasuryBefore = await provider.connection.getBalance(treasury.publicKey);
+
+      await recoverExcessLamports();
+
+      const marketInfo = await provider.connection.getAccountInfo(marketPDA);
+      const rentExemptMinimum = await provider.connection.getMinimumBalanceForRentExemption(
+        marketInfo!.data.length
+      );
+      expect(marketInfo!.lamports).to.equal(rentExemptMinimum);
+
+      const treasuryAfter = await provider.connection.getBalance(treasury.publicKey);
+      expect(treasuryAfter - treasuryBefore).to.equal(LAMPORTS_PER_SOL / 2);
+
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.marketId.toString()).to.equal(marketId.toString());
+    });
+
+    it('rejects accounts with nothing to recover', async () => {
+      try {
+        await recoverExcessLamports();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('NoExcessLamports');
+      }
+    });
+  });
+
+  describe('claim expiry', () => {
+    const marketId = new BN(181_001);
+    let pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let winner: { keypair: Keypair; tokenAccount: PublicKey };
+    let loser: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function sweepExpiredClaims(bets: PublicKey[]) {
+      return program.methods
+        .sweepExpiredClaims()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          treasuryTokenAccount: treasuryTokenAccount,
+          authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(bets.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })))
+        .signers([authority])
+        .rpc();
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      pdas = await createTestMarket(marketId, {
+        bettingDeadline: new BN(now + 6),
+        options: { claimExpirySecs: new BN(5) },
+      });
+      winner = await createFundedBettor();
+      loser = await createFundedBettor();
+      await placeTestBet(marketId, winner, 0);
+      await placeTestBet(marketId, loser, 1);
+
+      await sleep(8000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+    });
+
+    it('rejects sweeping before the claim window closes', async () => {
+      try {
+        await sweepExpiredClaims([getBetPDA(pdas.market, winner.keypair.publicKey)]);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('ClaimsNotExpired');
+      }
+    });
+
+    it('rejects sweeping a losing bet', async () => {
+      await sleep(6000);
+      try {
+        await sweepExpiredClaims([getBetPDA(pdas.market, loser.keypair.publicKey)]);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('LostBet');
+      }
+    });
+
+    it('sends unclaimed winnings to the treasury and marks the bet claimed', async () => {
+      const betPDA = getBetPDA(pdas.market, winner.keypair.publicKey);
+      const market = await program.account.market.fetch(pdas.market);
+      const treasuryBefore = await getAccount(provider.connection, treasuryTokenAccount);
+
+      await sweepExpiredClaims([betPDA]);
+
+      const treasuryAfter = await getAccount(provider.connection, treasuryTokenAccount);
+      expect(Number(treasuryAfter.amount - treasuryBefore.amount)).to.equal(
+        market.totalPool.add(market.bonusPool).toNumber()
+      );
+      const bet = await program.account.bet.fetch(betPDA);
+      expect(bet.betState).to.deep.equal({ claimedWin: {} });
+      const settled = await program.account.market.fetch(pdas.market);
+      expect(settled.winningBetsClaimed).to.equal(1);
+    });
+
+    it('stops the winner claiming afterwards', async () => {
+      try {
+        await program.methods
+          .claimWinnings()
+          .accounts({
+            protocolState: protocolStatePDA,
+            market: pdas.market,
+            bet: getBetPDA(pdas.market, winner.keypair.publicKey),
+            marketVault: pdas.marketVault,
+            poolVault: pdas.poolVault,
+            claimerTokenAccount: winner.tokenAccount,
+            claimEscrow: null,
+            escrowVault: null,
+            memoProgram: null,
+            claimer: winner.keypair.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([winner.keypair])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('AlreadyClaimed');
+      }
+    });
+  });
+
+  describe('established wallet requirement', () => {
+    const marketId = new BN(182_001);
+    let oldBettor: { keypair: Keypair; tokenAccount: PublicKey };
+    let newBettor: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function createBettorProfile(wallet: Keypair) {
+      return program.methods
+        .createBettorProfile()
+        .accounts({
+          bettorProfile: getBettorProfilePDA(wallet.publicKey),
+          wallet: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    function placeBet(bettor: { keypair: Keypair; tokenAccount: PublicKey }, bettorProfile: PublicKey | null) {
+      const pdas = getMarketPDAs(marketId);
+      return program.methods
+        .placeBet(0, 0, null)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          leaderboard: pdas.leaderboard,
+          bettorTokenAccount: bettor.tokenAccount,
+          treasuryTokenAccount: treasuryTokenAccount,
+          bettorFeeTokenAccount: null,
+          treasuryFeeTokenAccount: null,
+          selfExclusion: getSelfExclusionPDA(bettor.keypair.publicKey),
+          bettorProfile,
+          oddsHistory: null,
+          bettor: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      oldBettor = await createFundedBettor();
+      newBettor = await createFundedBettor();
+      await createBettorProfile(oldBettor.keypair);
+      await sleep(2000);
+      await createTestMarket(marketId, { options: { requireEstablishedWallet: true } });
+      await createBettorProfile(newBettor.keypair);
+    });
+
+    it('rejects a profile created after the market', async () => {
+      try {
+        await placeBet(newBettor, getBettorProfilePDA(newBettor.keypair.publicKey));
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('WalletTooNew');
+      }
+    });
+
+    it('rejects a bettor without a profile', async () => {
+      try {
+        await placeBet(newBettor, null);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('WalletTooNew');
+      }
+    });
+
+    it('accepts a profile that predates the market', async () => {
+      await placeBet(oldBettor, getBettorProfilePDA(oldBettor.keypair.publicKey));
+      const bet = await program.account.bet.fetch(getBetPDA(getMarketPDAs(marketId).market, oldBettor.keypair.publicKey));
+      expect(bet.bettor.toBase58()).to.equal(oldBettor.keypair.publicKey.toBase58());
+    });
+  });
+
+  describe('odds history', () => {
+    const marketId = new BN(183_001);
+    let pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey; leaderboard: PublicKey };
+    let oddsHistory: PublicKey;
+
+    function placeBet(outcomeIndex: number, bettor: { keypair: Keypair; tokenAccount: PublicKey }, history: PublicKey | null) {
+      return program.methods
+        .placeBet(outcomeIndex, 0, null)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          leaderboard: pdas.leaderboard,
+          bettorTokenAccount: bettor.tokenAccount,
+          treasuryTokenAccount: treasuryTokenAccount,
+          bettorFeeTokenAccount: null,
+          treasuryFeeTokenAccount: null,
+          selfExclusion: getSelfExclusionPDA(bettor.keypair.publicKey),
+          bettorProfile: null,
+          oddsHistory: history,
+          bettor: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      await createTestMarket(marketId, { options: { trackOdds: true } });
+      pdas = getMarketPDAs(marketId);
+      oddsHistory = getOddsHistoryPDA(pdas.market);
+    });
+
+    it('starts empty', async () => {
+      const history = await program.account.marketOddsHistory.fetch(oddsHistory);
+      expect(history.market.toBase58()).to.equal(pdas.market.toBase58());
+      expect(history.snapshots).to.have.length(0);
+    });
+
+    it('requires the history account on odds-tracking markets', async () => {
+      try {
+        await placeBet(0, await createFundedBettor(), null);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('OddsHistoryRequired');
+      }
+    });
+
+    it('records the implied odds after each bet', async () => {
+      for (const outcomeIndex of [0, 0, 1]) {
+        await placeBet(outcomeIndex, await createFundedBettor(), oddsHistory);
+      }
+
+      const history = await program.account.marketOddsHistory.fetch(oddsHistory);
+      expect(history.snapshots).to.have.length(3);
+      expect(history.nextIndex).to.equal(3);
+
+      const [first, second, third] = history.snapshots;
+      expect(first.impliedOddsBps.slice(0, 2)).to.deep.equal([10000, 0]);
+      expect(second.impliedOddsBps.slice(0, 2)).to.deep.equal([10000, 0]);
+      expect(third.impliedOddsBps[0]).to.equal(6666);
+      expect(third.impliedOddsBps[1]).to.equal(3333);
+      expect(second.totalPool.gt(first.totalPool)).to.equal(true);
+
+      const market = await program.account.market.fetch(pdas.market);
+      expect(third.totalPool.toString()).to.equal(market.totalPool.toString());
+    });
+  });
+
+  describe('claim escrow for contested categories', () => {
+    const POLITICS = 0;
+    const ESCROW_DELAY_SECS = 3;
+    const marketId = new BN(184_001);
+    let pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let winner: { keypair: Keypair; tokenAccount: PublicKey };
+    let claimEscrow: PublicKey;
+    let escrowVault: PublicKey;
+
+    function setCategoryEscrowClaims(escrowClaims: boolean) {
+      return program.methods
+        .setCategoryEscrowClaims(POLITICS, escrowClaims)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    function setClaimDelay(delay: number) {
+      return program.methods
+        .setClaimDelay(POLITICS, new BN(delay))
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    function claim(escrow: { claimEscrow: PublicKey | null; escrowVault: PublicKey | null }) {
+      return program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, winner.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: winner.tokenAccount,
+          ...escrow,
+          memoProgram: null,
+          claimer: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([winner.keypair])
+        .rpc();
+    }
+
+    function release() {
+      return program.methods
+        .releaseClaimEscrow()
+        .accounts({
+          protocolState: protocolStatePDA,
+          claimEscrow,
+          escrowVault,
+          bettorTokenAccount: winner.tokenAccount,
+          bettor: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([winner.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      await setCategoryEscrowClaims(true);
+      await setClaimDelay(ESCROW_DELAY_SECS);
+
+      winner = await createFundedBettor();
+      [claimEscrow] = PublicKey.findProgramAddressSync(
+        [Buffer.from('claim_escrow'), winner.keypair.publicKey.toBuffer(), tokenMint.toBuffer()],
+        program.programId
+      );
+      [escrowVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('claim_escrow_vault'), tokenMint.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .openClaimEscrow()
+        .accounts({
+          protocolState: protocolStatePDA,
+          tokenMint,
+          claimEscrow,
+          escrowVault,
+          bettor: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([winner.keypair])
+        .rpc();
+
+      const now = Math.floor(Date.now() / 1000);
+      pdas = await createTestMarket(marketId, { category: POLITICS, bettingDeadline: new BN(now + 6) });
+      await placeTestBet(marketId, winner, 0);
+      await placeTestBet(marketId, await createFundedBettor(), 1);
+
+      await sleep(8000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+      await sleep((ESCROW_DELAY_SECS + 1) * 1000);
+    });
+
+    after(async () => {
+      await setCategoryEscrowClaims(false);
+      await setClaimDelay(0);
+    });
+
+    it('requires the escrow accounts', async () => {
+      try {
+        await claim({ claimEscrow: null, escrowVault: null });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('ClaimEscrowRequired');
+      }
+    });
+
+    it('settles winnings into the escrow instead of the claimer', async () => {
+      const balanceBefore = await getAccount(provider.connection, winner.tokenAccount);
+      const market = await program.account.market.fetch(pdas.market);
+
+      await claim({ claimEscrow, escrowVault });
+
+      const balanceAfter = await getAccount(provider.connection, winner.tokenAccount);
+      expect(balanceAfter.amount).to.equal(balanceBefore.amount);
+      const escrow = await program.account.claimEscrow.fetch(claimEscrow);
+      expect(escrow.amount.toString()).to.equal(market.totalPool.add(market.bonusPool).toString());
+      expect(escrow.releaseAt.toNumber()).to.be.greaterThan(Math.floor(Date.now() / 1000));
+    });
+
+    it('keeps the escrow locked until the category delay passes', async () => {
+      try {
+        await release();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('ClaimEscrowLocked');
+      }
+    });
+
+    it('releases the escrowed winnings after the delay', async () => {
+      await sleep((ESCROW_DELAY_SECS + 1) * 1000);
+      const escrowBefore = await program.account.claimEscrow.fetch(claimEscrow);
+      const balanceBefore = await getAccount(provider.connection, winner.tokenAccount);
+
+      await release();
+
+      const balanceAfter = await getAccount(provider.connection, winner.tokenAccount);
+      expect(Number(balanceAfter.amount - balanceBefore.amount)).to.equal(escrowBefore.amount.toNumber());
+      const escrow = await program.account.claimEscrow.fetch(claimEscrow);
+      expect(escrow.amount.toNumber()).to.equal(0);
+    });
+  });
+
+  describe('vault mint consistency', () => {
+    const marketId = new BN(185_001);
+    const otherMarketId = new BN(185_002);
+    let pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey; leaderboard: PublicKey };
+    let other: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let bettor: { keypair: Keypair; tokenAccount: PublicKey };
+
+    // Vault seeds are checked before the mint constraint, so either error means the vault was refused
+    const VAULT_REJECTIONS = ['ConstraintSeeds', 'VaultMintMismatch'];
+
+    before(async () => {
+      const otherMint = await createMint(provider.connection, authority, authority.publicKey, null, 6);
+      await createTestMarket(marketId);
+      pdas = getMarketPDAs(marketId);
+      other = await createTestMarket(otherMarketId, { tokenMint: otherMint });
+      bettor = await createFundedBettor();
+    });
+
+    it('rejects bets routed to a vault of another mint', async () => {
+      for (const vaults of [
+        { marketVault: other.marketVault, poolVault: pdas.poolVault },
+        { marketVault: pdas.marketVault, poolVault: other.poolVault },
+      ]) {
+        try {
+          await program.methods
+            .placeBet(0, 0, null)
+            .accounts({
+              protocolState: protocolStatePDA,
+              market: pdas.market,
+              bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+              ...vaults,
+              leaderboard: pdas.leaderboard,
+              bettorTokenAccount: bettor.tokenAccount,
+              treasuryTokenAccount: treasuryTokenAccount,
+              bettorFeeTokenAccount: null,
+              treasuryFeeTokenAccount: null,
+              selfExclusion: getSelfExclusionPDA(bettor.keypair.publicKey),
+              bettorProfile: null,
+              oddsHistory: null,
+              bettor: bettor.keypair.publicKey,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([bettor.keypair])
+            .rpc();
+          expect.fail('Should have thrown an error');
+        } catch (error: any) {
+          expect(VAULT_REJECTIONS).to.include(error.error?.errorCode.code);
+        }
+      }
+    });
+
+    it('rejects withdrawals from a vault of another mint', async () => {
+      await placeTestBet(marketId, bettor, 0);
+      try {
+        await program.methods
+          .withdrawBet()
+          .accounts({
+            market: pdas.market,
+            bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+            marketVault: other.marketVault,
+            leaderboard: pdas.leaderboard,
+            bettorTokenAccount: bettor.tokenAccount,
+            bettor: bettor.keypair.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([bettor.keypair])
+          .rpc();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(VAULT_REJECTIONS).to.include(error.error?.errorCode.code);
+      }
+    });
+  });
+
+  describe('resolution requests', () => {
+    const marketId = new BN(186_001);
+    let marketPDA: PublicKey;
+    let bettorA: { keypair: Keypair; tokenAccount: PublicKey };
+    let bettorB: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function requestResolution(bettor: { keypair: Keypair; tokenAccount: PublicKey }) {
+      return program.methods
+        .requestResolution()
+        .accounts({
+          market: marketPDA,
+          bet: getBetPDA(marketPDA, bettor.keypair.publicKey),
+          bettor: bettor.keypair.publicKey,
+        })
+        .signers([bettor.keypair])
+        .rpc({ commitment: 'confirmed' });
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      ({ market: marketPDA } = await createTestMarket(marketId, { bettingDeadline: new BN(now + 6) }));
+      bettorA = await createFundedBettor();
+      bettorB = await createFundedBettor();
+      await placeTestBet(marketId, bettorA, 0);
+      await placeTestBet(marketId, bettorB, 1);
+    });
+
+    it('rejects requests while betting is open', async () => {
+      try {
+        await requestResolution(bettorA);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('ResolutionRequestTooEarly');
+      }
+    });
+
+    it('counts each bettor once and emits an event', async () => {
+      await sleep(8000);
+      const sig = await requestResolution(bettorA);
+
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const event = Array.from(parser.parseLogs(tx!.meta!.logMessages!)).find(
+        (e: any) => e.name === 'ResolutionRequested'
+      ) as any;
+      expect(event).to.not.be.undefined;
+      expect(event.data.bettor.toBase58()).to.equal(bettorA.keypair.publicKey.toBase58());
+      expect(event.data.resolutionRequests).to.equal(1);
+
+      await requestResolution(bettorB);
+      try {
+        await requestResolution(bettorA);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('ResolutionAlreadyRequested');
+      }
+
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.resolutionRequests).to.equal(2);
+      expect(market.status).to.deep.equal({ open: {} });
+    });
+  });
+
+  describe('betting phases', () => {
+    const marketId = new BN(189_001);
+
+    it('rejects phases out of order or past the betting deadline', async () => {
+      const now = Math.floor(Date.now() / 1000);
+      for (const [id, phases] of [
+        [189_002, [
+          { phaseEnd: new BN(now + 600), poolFeeBps: 100, creatorFeeBps: 0 },
+          { phaseEnd: new BN(now + 300), poolFeeBps: 100, creatorFeeBps: 0 },
+        ]],
+        [189_003, [{ phaseEnd: new BN(now + 7200), poolFeeBps: 100, creatorFeeBps: 0 }]],
+      ] as [number, any[]][]) {
+        try {
+          await createTestMarket(new BN(id), {
+            bettingDeadline: new BN(now + 3600),
+            options: { bettingPhases: phases },
+          });
+          expect.fail('Should have thrown an error');
+        } catch (error: any) {
+          expect(error.error?.errorCode.code).to.equal('InvalidBettingPhases');
+        }
+      }
+    });
+
+    it('charges each bet the fees of its phase', async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const pdas = await createTestMarket(marketId, {
+        bettingDeadline: new BN(now + 60),
+        options: {
+          bettingPhases: [
+            { phaseEnd: new BN(now + 8), poolFeeBps: 100, creatorFeeBps: 0 },
+            { phaseEnd: new BN(now + 60), poolFeeBps: 400, creatorFeeBps: 100 },
+          ],
+        },
+      });
+
+      await placeTestBet(marketId, await createFundedBettor(), 0);
+      let market = await program.account.market.fetch(pdas.market);
+      expect(market.bonusPool.toNumber()).to.equal(BET_AMOUNT.toNumber() / 100);
+      expect(market.escrowedCreatorFees.toNumber()).to.equal(0);
+
+      await sleep(10000);
+      await placeTestBet(marketId, await createFundedBettor(), 1);
+      market = await program.account.market.fetch(pdas.market);
+      expect(market.bonusPool.toNumber()).to.equal(BET_AMOUNT.toNumber() / 100 + BET_AMOUNT.toNumber() * 4 / 100);
+      expect(market.escrowedCreatorFees.toNumber()).to.equal(BET_AMOUNT.toNumber() / 100);
+    });
+  });
+
+  describe('treasury change mid-market', () => {
+    const marketId = new BN(190_001);
+    let pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey; leaderboard: PublicKey };
+    let newTreasury: Keypair;
+    let newTreasuryTokenAccount: PublicKey;
+
+    function placeBetPayingTreasury(
+      bettor: { keypair: Keypair; tokenAccount: PublicKey },
+      treasuryAccount: PublicKey
+    ) {
+      return program.methods
+        .placeBet(0, 0, null)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          leaderboard: pdas.leaderboard,
+          bettorTokenAccount: bettor.tokenAccount,
+          treasuryTokenAccount: treasuryAccount,
+          bettorFeeTokenAccount: null,
+          treasuryFeeTokenAccount: null,
+          selfExclusion: getSelfExclusionPDA(bettor.keypair.publicKey),
+          bettorProfile: null,
+          oddsHistory: null,
+          bettor: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    function setTreasury(treasuryKey: PublicKey) {
+      return program.methods
+        .updateProtocol(treasuryKey, null, null, null)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    before(async () => {
+      pdas = await createTestMarket(marketId);
+      newTreasury = Keypair.generate();
+      newTreasuryTokenAccount = await createAccount(
+        provider.connection,
+        authority,
+        tokenMint,
+        newTreasury.publicKey
+      );
+    });
+
+    after(async () => {
+      await setTreasury(treasury.publicKey);
+    });
+
+    it('pays each bet\'s protocol fee to the treasury current at bet time', async () => {
+      const oldBefore = Number((await getAccount(provider.connection, treasuryTokenAccount)).amount);
+      await placeBetPayingTreasury(await createFundedBettor(), treasuryTokenAccount);
+      const oldAfterFirst = Number((await getAccount(provider.connection, treasuryTokenAccount)).amount);
+      const protocolFee = oldAfterFirst - oldBefore;
+      expect(protocolFee).to.be.greaterThan(0);
+
+      await setTreasury(newTreasury.publicKey);
+
+      // The old treasury no longer qualifies to receive fees
+      try {
+        await placeBetPayingTreasury(await createFundedBettor(), treasuryTokenAccount);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('ConstraintRaw');
+      }
+
+      await placeBetPayingTreasury(await createFundedBettor(), newTreasuryTokenAccount);
+      expect(Number((await getAccount(provider.connection, newTreasuryTokenAccount)).amount)).to.equal(protocolFee);
+      expect(Number((await getAccount(provider.connection, treasuryTokenAccount)).amount)).to.equal(oldAfterFirst);
+    });
+  });
+
+  describe('oracle category changes after assignment', () => {
+    const oracleId = 191_001;
+    const marketId = new BN(191_001);
+    const oracleAuthority = Keypair.generate();
+    let oraclePDA: PublicKey;
+    let marketPDA: PublicKey;
+
+    before(async () => {
+      oraclePDA = getOraclePDA(oracleId);
+      const categories = new Array(12).fill(false);
+      categories[3] = true; // Crypto
+      categories[1] = true; // Sports
+      await program.methods
+        .registerOracle(oracleId, 'Narrowing Oracle', categories, 'https://example.com')
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: oraclePDA,
+          oracleAuthority: oracleAuthority.publicKey,
+          bondMint: null,
+          bondVault: null,
+          oracleBondSource: null,
+          authority: authority.publicKey,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      ({ market: marketPDA } = await createTestMarket(marketId, { options: { allowEarlyResolution: true } }));
+      await program.methods
+        .assignOracle()
+        .accounts({ protocolState: protocolStatePDA, market: marketPDA, oracle: oraclePDA, creator: creator.publicKey })
+        .signers([creator])
+        .rpc();
+    });
+
+    it('lets the assigned oracle resolve after its category is disabled', async () => {
+      let market = await program.account.market.fetch(marketPDA);
+      expect(market.authorizedAtAssignment).to.equal(true);
+
+      const sportsOnly = new Array(12).fill(false);
+      sportsOnly[1] = true;
+      await program.methods
+        .updateOracle(null, sportsOnly, null, null)
+        .accounts({ protocolState: protocolStatePDA, oracle: oraclePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+
+      await program.methods
+        .oracleResolveMarket(0)
+        .accounts({ market: marketPDA, oracle: oraclePDA, oracleAuthority: oracleAuthority.publicKey })
+        .signers([oracleAuthority])
+        .rpc();
+
+      market = await program.account.market.fetch(marketPDA);
+      expect(market.status).to.deep.equal({ resolved: {} });
+      expect(market.resolvedByOracle).to.equal(true);
+    });
+  });
+
+  describe('disable outcome', () => {
+    const marketId = new BN(192_001);
+    let marketPDA: PublicKey;
+    let earlyBettor: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function disableOutcome(outcomeIndex: number, signer: Keypair = creator) {
+      return program.methods
+        .disableOutcome(outcomeIndex)
+        .accounts({ market: marketPDA, creator: signer.publicKey })
+        .signers([signer])
+        .rpc();
+    }
+
+    before(async () => {
+      ({ market: marketPDA } = await createTestMarket(marketId, { outcomes: ['Alice', 'Bob', 'Carol'] }));
+      earlyBettor = await createFundedBettor();
+      await placeTestBet(marketId, earlyBettor, 1);
+    });
+
+    it('only lets the creator disable an outcome', async () => {
+      try {
+        await disableOutcome(1, bettor1);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+
+    it('rejects new bets on a disabled outcome and keeps existing ones', async () => {
+      await disableOutcome(1);
+
+      try {
+        await placeTestBet(marketId, await createFundedBettor(), 1);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('OutcomeClosed');
+      }
+      await placeTestBet(marketId, await createFundedBettor(), 2);
+
+      const market = await program.account.market.fetch(marketPDA);
+      expect(market.outcomes.length).to.equal(3);
+      expect(market.outcomes[1].isOpen).to.equal(false);
+      expect(market.outcomes[1].bettorCount).to.equal(1);
+      expect(market.outcomes[2].isOpen).to.equal(true);
+      const bet = await program.account.bet.fetch(getBetPDA(marketPDA, earlyBettor.keypair.publicKey));
+      expect(bet.betState).to.deep.equal({ active: {} });
+    });
+
+    it('rejects disabling an outcome twice', async () => {
+      try {
+        await disableOutcome(1);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('OutcomeClosed');
+      }
+    });
+  });
+
+  describe('payout memo', () => {
+    const MEMO_PROGRAM_ID = new PublicKey('MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr');
+    const marketId = new BN(193_001);
+    let pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let winner: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function claim(memoProgram: PublicKey | null) {
+      return program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, winner.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: winner.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram,
+          claimer: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([winner.keypair])
+        .rpc({ commitment: 'confirmed' });
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      pdas = await createTestMarket(marketId, {
+        bettingDeadline: new BN(now + 6),
+        options: { payoutMemo: true },
+      });
+      winner = await createFundedBettor();
+      await placeTestBet(marketId, winner, 0);
+      await placeTestBet(marketId, await createFundedBettor(), 1);
+
+      await sleep(8000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+    });
+
+    it('requires the memo program on markets with payout memos', async () => {
+      try {
+        await claim(null);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('MemoProgramRequired');
+      }
+    });
+
+    it('writes a memo naming the market with the payout', async () => {
+      const sig = await claim(MEMO_PROGRAM_ID);
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      const logs = tx!.meta!.logMessages!;
+      expect(logs.some((log) => log.startsWith(`Program ${MEMO_PROGRAM_ID.toBase58()} invoke`))).to.equal(true);
+      expect(logs.some((log) => log.includes(`Fortuna payout market ${marketId.toString()}`))).to.equal(true);
+    });
+  });
+
+  describe('terminal market states', () => {
+    const resolvedMarketId = new BN(194_001);
+    const cancelledMarketId = new BN(194_002);
+    let resolved: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let cancelled: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let resolvedBettor: { keypair: Keypair; tokenAccount: PublicKey };
+    let cancelledBettor: { keypair: Keypair; tokenAccount: PublicKey };
+
+    async function expectError(call: () => Promise<unknown>, code: string) {
+      try {
+        await call();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal(code);
+      }
+    }
+
+    const resolve = (market: PublicKey) => () =>
+      program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+    const cancel = (market: PublicKey) => () =>
+      program.methods
+        .cancelMarket()
+        .accounts({ protocolState: protocolStatePDA, market, authority: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      resolved = await createTestMarket(resolvedMarketId, { bettingDeadline: new BN(now + 6) });
+      cancelled = await createTestMarket(cancelledMarketId);
+      resolvedBettor = await createFundedBettor();
+      cancelledBettor = await createFundedBettor();
+      await placeTestBet(resolvedMarketId, resolvedBettor, 0);
+      await placeTestBet(cancelledMarketId, cancelledBettor, 0);
+
+      await sleep(8000);
+      await resolve(resolved.market)();
+      await cancel(cancelled.market)();
+    });
+
+    it('blocks resolving, cancelling, betting and refunds on a resolved market', async () => {
+      await expectError(resolve(resolved.market), 'MarketAlreadyResolved');
+      await expectError(cancel(resolved.market), 'MarketAlreadyResolved');
+      await expectError(() => placeTestBet(resolvedMarketId, await createFundedBettor(), 0), 'MarketAlreadyResolved');
+      await expectError(
+        () =>
+          program.methods
+            .claimRefund()
+            .accounts({
+              market: resolved.market,
+              bet: getBetPDA(resolved.market, resolvedBettor.keypair.publicKey),
+              marketVault: resolved.marketVault,
+              claimerTokenAccount: resolvedBettor.tokenAccount,
+              claimer: resolvedBettor.keypair.publicKey,
+              tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([resolvedBettor.keypair])
+            .rpc(),
+        'MarketNotCancelled'
+      );
+    });
+
+    it('blocks resolving, cancelling, betting, withdrawals and winnings on a cancelled market', async () => {
+      await expectError(resolve(cancelled.market), 'MarketIsCancelled');
+      await expectError(cancel(cancelled.market), 'MarketIsCancelled');
+      await expectError(() => placeTestBet(cancelledMarketId, await createFundedBettor(), 0), 'MarketIsCancelled');
+      await expectError(
+        () =>
+          program.methods
+            .withdrawBet()
+            .accounts({
+              market: cancelled.market,
+              bet: getBetPDA(cancelled.market, cancelledBettor.keypair.publicKey),
+              marketVault: cancelled.marketVault,
+              leaderboard: getLeaderboardPDA(cancelled.market),
+              bettorTokenAccount: cancelledBettor.tokenAccount,
+              bettor: cancelledBettor.keypair.publicKey,
+              tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([cancelledBettor.keypair])
+            .rpc(),
+        'MarketIsCancelled'
+      );
+      await expectError(
+        () =>
+          program.methods
+            .claimWinnings()
+            .accounts({
+              protocolState: protocolStatePDA,
+              market: cancelled.market,
+              bet: getBetPDA(cancelled.market, cancelledBettor.keypair.publicKey),
+              marketVault: cancelled.marketVault,
+              poolVault: cancelled.poolVault,
+              claimerTokenAccount: cancelledBettor.tokenAccount,
+              claimEscrow: null,
+              escrowVault: null,
+              memoProgram: null,
+              claimer: cancelledBettor.keypair.publicKey,
+              tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([cancelledBettor.keypair])
+            .rpc(),
+        'MarketNotResolved'
+      );
+    });
+  });
+
+  describe('creator LP share', () => {
+    const marketId = new BN(195_001);
+
+    it('rejects a share without a window', async () => {
+      try {
+        await createTestMarket(new BN(195_002), { options: { creatorLpShareBps: 5000 } });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InvalidCreatorLpShare');
+      }
+    });
+
+    it('moves part of the creator fee into the bonus pool for early bets only', async () => {
+      const pdas = await createTestMarket(marketId, {
+        options: { creatorLpShareBps: 5000, creatorLpShareWindowSecs: new BN(6) },
+      });
+      const protocolState = await program.account.protocolState.fetch(protocolStatePDA);
+      const creatorFee = Math.floor((BET_AMOUNT.toNumber() * protocolState.creatorFeeBps) / 10000);
+      const poolFee = Math.floor((BET_AMOUNT.toNumber() * protocolState.poolFeeBps) / 10000);
+      const share = Math.floor(creatorFee / 2);
+
+      await placeTestBet(marketId, await createFundedBettor(), 0);
+      let market = await program.account.market.fetch(pdas.market);
+      expect(market.bonusPool.toNumber()).to.equal(poolFee + share);
+      expect(market.escrowedCreatorFees.toNumber()).to.equal(creatorFee - share);
+
+      await sleep(8000);
+      await placeTestBet(marketId, await createFundedBettor(), 1);
+      market = await program.account.market.fetch(pdas.market);
+      expect(market.bonusPool.toNumber()).to.equal(2 * poolFee + share);
+      expect(market.escrowedCreatorFees.toNumber()).to.equal(2 * creatorFee - share);
+    });
+  });
+
+  describe('batch market cancellation', () => {
+    const emptyIds = [new BN(196_001), new BN(196_002)];
+    const bettedId = new BN(196_003);
+    let empty: PublicKey[];
+    let betted: PublicKey;
+
+    function cancelBatch(markets: PublicKey[], signer: Keypair = creator) {
+      return program.methods
+        .cancelMarketsBatch()
+        .accounts({ creator: signer.publicKey })
+        .remainingAccounts(markets.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })))
+        .signers([signer])
+        .rpc();
+    }
+
+    before(async () => {
+      empty = [];
+      for (const id of emptyIds) {
+        empty.push((await createTestMarket(id)).market);
+      }
+      ({ market: betted } = await createTestMarket(bettedId));
+      await placeTestBet(bettedId, await createFundedBettor(), 0);
+    });
+
+    it('rejects markets the signer did not create', async () => {
+      try {
+        await cancelBatch(empty, bettor1);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+
+    it('cancels only the open markets without bets', async () => {
+      await cancelBatch([empty[0], betted, empty[1]]);
+
+      for (const market of empty) {
+        const account = await program.account.market.fetch(market);
+        expect(account.status).to.deep.equal({ cancelled: {} });
+        expect(account.cancelReason).to.deep.equal({ creatorCancelled: {} });
+      }
+      expect((await program.account.market.fetch(betted)).status).to.deep.equal({ open: {} });
+
+      // Already-cancelled markets are skipped rather than failing the batch
+      await cancelBatch([empty[0], betted]);
+    });
+  });
+
+  describe('vault collateralization', () => {
+    const marketId = new BN(197_001);
+    let pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let winners: { keypair: Keypair; tokenAccount: PublicKey }[];
+
+    async function claim(bettor: { keypair: Keypair; tokenAccount: PublicKey }) {
+      await program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: bettor.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      pdas = await createTestMarket(marketId, { bettingDeadline: new BN(now + 6) });
+      winners = [await createFundedBettor(), await createFundedBettor()];
+      for (const winner of winners) {
+        await placeTestBet(marketId, winner, 0);
+      }
+      await placeTestBet(marketId, await createFundedBettor(), 1);
+
+      await sleep(8000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+    });
+
+    it('records each payout against the pool it was paid from', async () => {
+      const before = await getAccount(provider.connection, winners[0].tokenAccount);
+      await claim(winners[0]);
+      const after = await getAccount(provider.connection, winners[0].tokenAccount);
+
+      const market = await program.account.market.fetch(pdas.market);
+      expect(Number(market.poolPaid) + Number(market.bonusPaid)).to.equal(
+        Number(after.amount) - Number(before.amount)
+      );
+      expect(Number(market.poolPaid)).to.be.greaterThan(0);
+    });
+
+    it('keeps paying winners while the vaults cover what is still owed', async () => {
+      await claim(winners[1]);
+
+      const market = await program.account.market.fetch(pdas.market);
+      expect(market.winningBetsClaimed).to.equal(2);
+      expect(Number(market.poolPaid)).to.be.at.most(Number(market.totalPool));
+      expect(Number(market.bonusPaid)).to.be.at.least(Number(market.bonusPool));
+    });
+  });
+
+  describe('two-sided fees', () => {
+    const marketId = new BN(198_001);
+
+    async function betAndMeasureFees(outcomeIndex: number) {
+      const bettor = await createFundedBettor();
+      const treasuryBefore = await getAccount(provider.connection, treasuryTokenAccount);
+      const betPDA = await placeTestBet(marketId, bettor, outcomeIndex);
+      const treasuryAfter = await getAccount(provider.connection, treasuryTokenAccount);
+      const bet = await program.account.bet.fetch(betPDA);
+      return { bet, treasuryDelta: Number(treasuryAfter.amount) - Number(treasuryBefore.amount) };
+    }
+
+    before(async () => {
+      await createTestMarket(marketId, { options: { twoSidedFees: true } });
+    });
+
+    it('charges no fees while every bet is on one outcome', async () => {
+      for (let i = 0; i < 2; i++) {
+        const { bet, treasuryDelta } = await betAndMeasureFees(0);
+        expect(bet.poolAmount.toString()).to.equal(BET_AMOUNT.toString());
+        expect(bet.creatorFee.toNumber()).to.equal(0);
+        expect(treasuryDelta).to.equal(0);
+      }
+    });
+
+    it('waives fees on the bet that opens a second outcome', async () => {
+      const { bet, treasuryDelta } = await betAndMeasureFees(1);
+      expect(bet.poolAmount.toString()).to.equal(BET_AMOUNT.toString());
+      expect(treasuryDelta).to.equal(0);
+
+      const market = await program.account.market.fetch(getMarketPDAs(marketId).market);
+      expect(market.totalPool.toString()).to.equal(BET_AMOUNT.muln(3).toString());
+      expect(market.bonusPool.toNumber()).to.equal(0);
+    });
+
+    it('charges normal fees once the market is two-sided', async () => {
+      for (const outcomeIndex of [0, 1]) {
+        const { bet, treasuryDelta } = await betAndMeasureFees(outcomeIndex);
+        expect(bet.poolAmount.lt(BET_AMOUNT)).to.equal(true);
+        expect(bet.creatorFee.toNumber()).to.be.greaterThan(0);
+        expect(treasuryDelta).to.be.greaterThan(0);
+      }
+    });
+  });
+
+  describe('zero-amount bet transfers', () => {
+    async function transfersIn(bet: PublicKey) {
+      const [{ signature }] = await provider.connection.getSignaturesForAddress(bet, {}, 'confirmed');
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: 'confirmed',
+        maxSupportedTransactionVersion: 0,
+      });
+      return tx!.meta!.logMessages!.filter((log) => log === 'Program log: Instruction: Transfer').length;
+    }
+
+    it('skips the pool transfer when a bet pays no creator or pool fee', async () => {
+      const marketId = new BN(199_001);
+      const now = Math.floor(Date.now() / 1000);
+      const pdas = await createTestMarket(marketId, {
+        bettingDeadline: new BN(now + 3600),
+        options: {
+          bettingPhases: [{ phaseEnd: new BN(now + 3600), poolFeeBps: 0, creatorFeeBps: 0 }],
+        },
+      });
+      const poolBefore = await getAccount(provider.connection, pdas.poolVault);
+
+      const bet = await placeTestBet(marketId, await createFundedBettor(), 0);
+
+      const market = await program.account.market.fetch(pdas.market);
+      expect(market.escrowedCreatorFees.toNumber()).to.equal(0);
+      expect(market.bonusPool.toNumber()).to.equal(0);
+      const poolAfter = await getAccount(provider.connection, pdas.poolVault);
+      expect(Number(poolAfter.amount)).to.equal(Number(poolBefore.amount));
+      // Stake to the market vault and protocol fee to the treasury only
+      expect(await transfersIn(bet)).to.equal(2);
+    });
+
+    it('makes a single transfer when every fee is waived', async () => {
+      const marketId = new BN(199_002);
+      await createTestMarket(marketId, { options: { feeWaiverCount: 1 } });
+
+      const bet = await placeTestBet(marketId, await createFundedBettor(), 0);
+
+      expect(await transfersIn(bet)).to.equal(1);
+    });
+  });
+
+  describe('license rate limit', () => {
+    const licenseKey = Array.from(Keypair.generate().publicKey.toBytes());
+    const license = getLicensePDA(licenseKey);
+    const marketIds = [new BN(200_001), new BN(200_002), new BN(200_003)];
+
+    function setLicenseRateLimit(limit: number, windowSecs: number) {
+      return program.methods
+        .setLicenseRateLimit(limit, new BN(windowSecs))
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    async function setRequireLicense(required: boolean) {
+      await program.methods
+        .setRequireLicense(required)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    before(async () => {
+      await program.methods
+        .issueLicense(licenseKey, 1, [], [], 10, false, new BN(0))
+        .accounts({
+          protocolState: protocolStatePDA,
+          license,
+          holder: creator.publicKey,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      await setLicenseRateLimit(2, 6);
+      await setRequireLicense(true);
+    });
+
+    after(async () => {
+      await setRequireLicense(false);
+      await setLicenseRateLimit(0, 0);
+    });
+
+    it('rejects a cap without a window', async () => {
+      try {
+        await setLicenseRateLimit(2, 0);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InvalidLicenseRateLimit');
+      }
+    });
+
+    it('rejects creation past the cap within the window', async () => {
+      await createTestMarket(marketIds[0], { license });
+      await createTestMarket(marketIds[1], { license });
+
+      try {
+        await createTestMarket(marketIds[2], { license });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('LicenseRateLimited');
+      }
+      const account = await program.account.license.fetch(license);
+      expect(account.createdInWindow).to.equal(2);
+      expect(account.marketsCreated).to.equal(2);
+    });
+
+    it('allows creation again once the window rolls over', async () => {
+      await sleep(7000);
+      await createTestMarket(marketIds[2], { license });
+
+      const account = await program.account.license.fetch(license);
+      expect(account.createdInWindow).to.equal(1);
+      expect(account.marketsCreated).to.equal(3);
+    });
+  });
+
+  describe('inline bettor profile', () => {
+    const marketIds = [new BN(201_001), new BN(201_002)];
+    const establishedMarketId = new BN(201_003);
+
+    function placeBet(
+      marketId: BN,
+      bettor: { keypair: Keypair; tokenAccount: PublicKey },
+      bettorProfile: PublicKey | null
+    ) {
+      const pdas = getMarketPDAs(marketId);
+      return program.methods
+        .placeBet(0, 0, null)
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, bettor.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          leaderboard: pdas.leaderboard,
+          bettorTokenAccount: bettor.tokenAccount,
+          treasuryTokenAccount: treasuryTokenAccount,
+          bettorFeeTokenAccount: null,
+          treasuryFeeTokenAccount: null,
+          selfExclusion: getSelfExclusionPDA(bettor.keypair.publicKey),
+          bettorProfile,
+          oddsHistory: null,
+          bettor: bettor.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bettor.keypair])
+        .rpc();
+    }
+
+    before(async () => {
+      for (const marketId of marketIds) {
+        await createTestMarket(marketId);
+      }
+      await createTestMarket(establishedMarketId, { options: { requireEstablishedWallet: true } });
+    });
+
+    it("creates a first-time bettor's profile within the bet transaction", async () => {
+      const bettor = await createFundedBettor();
+      const profilePDA = getBettorProfilePDA(bettor.keypair.publicKey);
+      expect(await provider.connection.getAccountInfo(profilePDA)).to.be.null;
+
+      await placeBet(marketIds[0], bettor, profilePDA);
+
+      const profile = await program.account.bettorProfile.fetch(profilePDA);
+      expect(profile.wallet.toBase58()).to.equal(bettor.keypair.publicKey.toBase58());
+      expect(profile.createdAt.toNumber()).to.be.greaterThan(0);
+    });
+
+    it('keeps the original first-seen time on later bets', async () => {
+      const bettor = await createFundedBettor();
+      const profilePDA = getBettorProfilePDA(bettor.keypair.publicKey);
+      await placeBet(marketIds[0], bettor, profilePDA);
+      const createdAt = (await program.account.bettorProfile.fetch(profilePDA)).createdAt;
+
+      await sleep(2000);
+      await placeBet(marketIds[1], bettor, profilePDA);
+
+      const profile = await program.account.bettorProfile.fetch(profilePDA);
+      expect(profile.createdAt.toString()).to.equal(createdAt.toString());
+    });
+
+    it('still treats a profile created by the bet itself as too new', async () => {
+      const bettor = await createFundedBettor();
+      const profilePDA = getBettorProfilePDA(bettor.keypair.publicKey);
+      try {
+        await placeBet(establishedMarketId, bettor, profilePDA);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('WalletTooNew');
+      }
+      expect(await provider.connection.getAccountInfo(profilePDA)).to.be.null;
+    });
+  });
+
+  describe('reserved account space', () => {
+    const BET_RESERVED_LEN = 16;
+    const LICENSE_RESERVED_LEN = 32;
+    const MAX_LICENSE_DOMAINS = 5;
+    const MAX_DOMAIN_NAME_LEN = 64;
+    const MAX_LICENSE_WALLETS = 10;
+
+    // Bytes the account holds beyond its serialized data
+    async function unusedSpace(name: 'Bet' | 'License', address: PublicKey, account: any) {
+      const info = await provider.connection.getAccountInfo(address);
+      const encoded = await program.coder.accounts.encode(name, account);
+      return info!.data.length - encoded.length;
+    }
+
+    it('sizes a bet for its memo plus the empty reserved capacity', async () => {
+      const marketId = new BN(202_001);
+      await createTestMarket(marketId);
+      const betPDA = await placeTestBet(marketId, await createFundedBettor(), 0, 0, 'reserved');
+
+      const bet = await program.account.bet.fetch(betPDA);
+      expect(bet.reserved).to.have.length(0);
+      expect(await unusedSpace('Bet', betPDA, bet)).to.equal(BET_RESERVED_LEN);
+    });
+
+    it('sizes a license for its list capacities plus the empty reserved capacity', async () => {
+      const licenseKey = Array.from(Keypair.generate().publicKey.toBytes());
+      const licensePDA = getLicensePDA(licenseKey);
+      await program.methods
+        .issueLicense(licenseKey, 1, [], [], 10, false, new BN(0))
+        .accounts({
+          protocolState: protocolStatePDA,
+          license: licensePDA,
+          holder: creator.publicKey,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      const license = await program.account.license.fetch(licensePDA);
+      expect(license.reserved).to.have.length(0);
+      expect(await unusedSpace('License', licensePDA, license)).to.equal(
+        MAX_LICENSE_DOMAINS * (4 + MAX_DOMAIN_NAME_LEN) + MAX_LICENSE_WALLETS * 32 + LICENSE_RESERVED_LEN
+      );
+    });
+  });
+
+  describe('default token mint', () => {
+    const marketId = new BN(203_001);
+
+    function setDefaultTokenMint(mint: PublicKey | null, signer: Keypair = authority) {
+      return program.methods
+        .setDefaultTokenMint()
+        .accounts({ protocolState: protocolStatePDA, defaultTokenMint: mint, authority: signer.publicKey })
+        .signers([signer])
+        .rpc();
+    }
+
+    after(async () => {
+      await setDefaultTokenMint(null);
+    });
+
+    it('only lets the admin set the default', async () => {
+      try {
+        await setDefaultTokenMint(tokenMint, creator);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+
+    it('creates a market in the default mint when none is named', async () => {
+      await setDefaultTokenMint(tokenMint);
+      const { defaultTokenMint } = await program.account.protocolState.fetch(protocolStatePDA);
+      expect(defaultTokenMint.toBase58()).to.equal(tokenMint.toBase58());
+
+      const pdas = await createTestMarket(marketId, { tokenMint: defaultTokenMint });
+
+      const market = await program.account.market.fetch(pdas.market);
+      expect(market.tokenMint.toBase58()).to.equal(tokenMint.toBase58());
+      const vault = await getAccount(provider.connection, pdas.marketVault);
+      expect(vault.mint.toBase58()).to.equal(tokenMint.toBase58());
+    });
+
+    it('clears the default', async () => {
+      await setDefaultTokenMint(null);
+      const { defaultTokenMint } = await program.account.protocolState.fetch(protocolStatePDA);
+      expect(defaultTokenMint.toBase58()).to.equal(PublicKey.default.toBase58());
+    });
+  });
+
+  describe('dormant authority recovery', () => {
+    const recovery = Keypair.generate();
+
+    function setRecoveryAuthority(signer: Keypair, recoveryAuthority: PublicKey, dormancySecs: number) {
+      return program.methods
+        .setRecoveryAuthority(recoveryAuthority, new BN(dormancySecs))
+        .accounts({ protocolState: protocolStatePDA, authority: signer.publicKey })
+        .signers([signer])
+        .rpc();
+    }
+
+    function claimDormantAuthority(signer: Keypair) {
+      return program.methods
+        .claimDormantAuthority()
+        .accounts({ protocolState: protocolStatePDA, recoveryAuthority: signer.publicKey })
+        .signers([signer])
+        .rpc();
+    }
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(recovery.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+    });
+
+    it('rejects a recovery key without a dormancy period', async () => {
+      try {
+        await setRecoveryAuthority(authority, recovery.publicKey, 0);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('InvalidRecoveryAuthority');
+      }
+    });
+
+    it('rejects a claim while the authority keeps acting', async () => {
+      await setRecoveryAuthority(authority, recovery.publicKey, 4);
+      await sleep(3000);
+      // Any admin action resets the dormancy clock
+      await program.methods
+        .setMaxMarketsPerOracle(0)
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+      await sleep(2000);
+
+      try {
+        await claimDormantAuthority(recovery);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('AuthorityNotDormant');
+      }
+    });
+
+    it('only lets the recovery key claim', async () => {
+      try {
+        await claimDormantAuthority(creator);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+
+    it('hands the authority to the recovery key after the dormancy period', async () => {
+      await sleep(5000);
+      await claimDormantAuthority(recovery);
+
+      const state = await program.account.protocolState.fetch(protocolStatePDA);
+      expect(state.authority.toBase58()).to.equal(recovery.publicKey.toBase58());
+      expect(state.recoveryAuthority.toBase58()).to.equal(PublicKey.default.toBase58());
+
+      // The old key is locked out of admin actions
+      try {
+        await setRecoveryAuthority(authority, authority.publicKey, 1);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+
+    after(async () => {
+      // Hand the authority back so later suites keep their admin
+      await setRecoveryAuthority(recovery, authority.publicKey, 1);
+      await sleep(2000);
+      await claimDormantAuthority(authority);
+    });
+  });
+
+  describe('seeded bonus pool', () => {
+    const marketId = new BN(207_001);
+    const SEED_BONUS = new BN(25_000_000);
+    let pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let winner: { keypair: Keypair; tokenAccount: PublicKey };
+
+    before(async () => {
+      await mintTo(provider.connection, authority, tokenMint, creatorTokenAccount, authority, SEED_BONUS.toNumber());
+      const now = Math.floor(Date.now() / 1000);
+      pdas = await createTestMarket(marketId, {
+        bettingDeadline: new BN(now + 6),
+        creatorTokenAccount,
+        options: { seedBonus: SEED_BONUS },
+      });
+      winner = await createFundedBettor();
+      await placeTestBet(marketId, winner, 0);
+      await placeTestBet(marketId, await createFundedBettor(), 1);
+    });
+
+    it('requires the creator token account to fund the seed', async () => {
+      try {
+        await createTestMarket(new BN(207_002), { options: { seedBonus: SEED_BONUS } });
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('CreatorTokenAccountRequired');
+      }
+    });
+
+    it('pays the seed into the bonus pool at creation', async () => {
+      const market = await program.account.market.fetch(pdas.market);
+      const poolVault = await getAccount(provider.connection, pdas.poolVault);
+      expect(market.bonusPool.gte(SEED_BONUS)).to.equal(true);
+      expect(Number(poolVault.amount)).to.equal(market.bonusPool.toNumber());
+    });
+
+    it('pays the seeded bonus to the winner', async () => {
+      await sleep(8000);
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ protocolState: protocolStatePDA, market: pdas.market, resolver: creator.publicKey })
+        .signers([creator])
+        .rpc();
+      const market = await program.account.market.fetch(pdas.market);
+
+      const before = await getAccount(provider.connection, winner.tokenAccount);
+      await program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, winner.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: winner.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([winner.keypair])
+        .rpc();
+      const after = await getAccount(provider.connection, winner.tokenAccount);
+
+      // The only winner takes the whole pool and the whole bonus pool, seed included
+      expect(Number(after.amount) - Number(before.amount)).to.equal(
+        market.totalPool.toNumber() + market.bonusPool.toNumber()
+      );
+    });
+  });
+
+  describe('claim timing errors', () => {
+    const CULTURE = 7;
+    const oracleId = 208_001;
+    const marketId = new BN(208_001);
+    const oracleAuthority = Keypair.generate();
+    let pdas: { market: PublicKey; marketVault: PublicKey; poolVault: PublicKey };
+    let winner: { keypair: Keypair; tokenAccount: PublicKey };
+
+    function setClaimDelay(delay: number) {
+      return program.methods
+        .setClaimDelay(CULTURE, new BN(delay))
+        .accounts({ protocolState: protocolStatePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    }
+
+    function claim() {
+      return program.methods
+        .claimWinnings()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          bet: getBetPDA(pdas.market, winner.keypair.publicKey),
+          marketVault: pdas.marketVault,
+          poolVault: pdas.poolVault,
+          claimerTokenAccount: winner.tokenAccount,
+          claimEscrow: null,
+          escrowVault: null,
+          memoProgram: null,
+          claimer: winner.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([winner.keypair])
+        .rpc();
+    }
+
+    async function expectClaimError(code: string) {
+      try {
+        await claim();
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal(code);
+      }
+    }
+
+    before(async () => {
+      await program.methods
+        .registerOracle(oracleId, 'Early Oracle', new Array(12).fill(true), 'https://example.com')
+        .accounts({
+          protocolState: protocolStatePDA,
+          oracle: getOraclePDA(oracleId),
+          oracleAuthority: oracleAuthority.publicKey,
+          bondMint: null,
+          bondVault: null,
+          oracleBondSource: null,
+          authority: authority.publicKey,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      pdas = await createTestMarket(marketId, {
+        category: CULTURE,
+        options: { allowEarlyResolution: true },
+      });
+      await program.methods
+        .assignOracle()
+        .accounts({
+          protocolState: protocolStatePDA,
+          market: pdas.market,
+          oracle: getOraclePDA(oracleId),
+          creator: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+
+      winner = await createFundedBettor();
+      await placeTestBet(marketId, winner, 0);
+      await setClaimDelay(3600);
+    });
+
+    after(async () => {
+      await setClaimDelay(0);
+    });
+
+    it('reports an unresolved market as not resolved', async () => {
+      await expectClaimError('MarketNotResolved');
+    });
+
+    it('reports an early oracle resolution still inside the claim delay as delayed', async () => {
+      await program.methods
+        .oracleResolveMarket(0)
+        .accounts({
+          market: pdas.market,
+          oracle: getOraclePDA(oracleId),
+          oracleAuthority: oracleAuthority.publicKey,
+        })
+        .signers([oracleAuthority])
+        .rpc();
+
+      const market = await program.account.market.fetch(pdas.market);
+      expect(market.status).to.deep.equal({ resolved: {} });
+      await expectClaimError('ClaimDelayNotElapsed');
+    });
+
+    it('pays out once the delay no longer applies', async () => {
+      await setClaimDelay(0);
+      await claim();
+
+      const bet = await program.account.bet.fetch(getBetPDA(pdas.market, winner.keypair.publicKey));
+      expect(bet.betState).to.deep.equal({ claimedWin: {} });
+    });
+  });
+
+  describe('market visibility flags', () => {
+    const marketId = new BN(209_001);
+    const MOBILE = 1 << 0;
+    const PARTNER_SITE = 1 << 3;
+    let market: PublicKey;
+
+    function setVisibilityFlags(flags: number, signer: Keypair = creator) {
+      return program.methods
+        .setVisibilityFlags(flags)
+        .accounts({ market, creator: signer.publicKey })
+        .signers([signer])
+        .rpc();
+    }
+
+    before(async () => {
+      ({ market } = await createTestMarket(marketId, {
+        options: { visibilityFlags: MOBILE | PARTNER_SITE },
+      }));
+    });
+
+    it('stores the flags given at creation', async () => {
+      const account = await program.account.market.fetch(market);
+      expect(account.visibilityFlags).to.equal(MOBILE | PARTNER_SITE);
+    });
+
+    it('lets the creator retag an open market', async () => {
+      await setVisibilityFlags(PARTNER_SITE);
+      const account = await program.account.market.fetch(market);
+      expect(account.visibilityFlags).to.equal(PARTNER_SITE);
+    });
+
+    it('rejects anyone but the creator', async () => {
+      try {
+        await setVisibilityFlags(MOBILE, Keypair.generate());
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('Unauthorized');
+      }
+    });
+
+    it('rejects retagging once the market is no longer open', async () => {
+      await program.methods
+        .cancelMarket()
+        .accounts({ protocolState: protocolStatePDA, market, authority: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      try {
+        await setVisibilityFlags(MOBILE);
+        expect.fail('Should have thrown an error');
+      } catch (error: any) {
+        expect(error.error?.errorCode.code).to.equal('MarketIsCancelled');
       }
+      const account = await program.account.market.fetch(market);
+      expect(account.visibilityFlags).to.equal(PARTNER_SITE);
     });
   });
 });